@@ -0,0 +1,114 @@
+//! The within-run population size controller enabled by
+//! [`CMAESOptions::auto_population`][crate::CMAESOptions::auto_population].
+
+/// The weight of the latest generation in the success rate (so the rate covers roughly the last
+/// `1 / SUCCESS_RATE_WEIGHT` generations)
+const SUCCESS_RATE_WEIGHT: f64 = 0.2;
+/// The population size is doubled if the success rate drops below this value
+const GROW_THRESHOLD: f64 = 0.2;
+/// The population size is halved if the success rate rises above this value
+const SHRINK_THRESHOLD: f64 = 0.6;
+/// The minimum number of generations between changes of the population size, so that the success
+/// rate reflects the current population size before it is changed again
+const MIN_GENERATIONS_BETWEEN_CHANGES: usize = 10;
+/// The largest population size as a multiple of the initial population size
+const MAX_POPULATION_FACTOR: usize = 16;
+
+/// Tracks the success rate of the mean update and decides when to grow or shrink the population
+#[derive(Clone, Debug)]
+pub struct PopulationController {
+    /// The smallest population size (the initial one)
+    min: usize,
+    /// The largest population size
+    max: usize,
+    /// The exponentially weighted average of the successes of the latest generations
+    success_rate: f64,
+    /// The generation in which the population size was last changed
+    last_change: usize,
+}
+
+impl PopulationController {
+    /// Returns a new `PopulationController` for the initial population size `lambda`
+    pub fn new(lambda: usize) -> Self {
+        Self {
+            min: lambda,
+            max: lambda * MAX_POPULATION_FACTOR,
+            success_rate: neutral_success_rate(),
+            last_change: 0,
+        }
+    }
+
+    /// Records whether the mean update of `generation` was successful and returns the new
+    /// population size if the current population size `lambda` should be changed
+    pub fn update(&mut self, success: bool, generation: usize, lambda: usize) -> Option<usize> {
+        let success = if success { 1.0 } else { 0.0 };
+        self.success_rate += SUCCESS_RATE_WEIGHT * (success - self.success_rate);
+
+        if generation < self.last_change + MIN_GENERATIONS_BETWEEN_CHANGES {
+            return None;
+        }
+
+        let new_lambda = if self.success_rate < GROW_THRESHOLD {
+            (2 * lambda).min(self.max)
+        } else if self.success_rate > SHRINK_THRESHOLD {
+            (lambda / 2).max(self.min)
+        } else {
+            lambda
+        };
+
+        (new_lambda != lambda).then(|| {
+            // Start over so that the next decision is based on the new population size
+            self.success_rate = neutral_success_rate();
+            self.last_change = generation;
+            new_lambda
+        })
+    }
+}
+
+/// Returns the success rate that is between the thresholds for growing and shrinking
+fn neutral_success_rate() -> f64 {
+    (GROW_THRESHOLD + SHRINK_THRESHOLD) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_population_controller() {
+        let mut controller = PopulationController::new(8);
+        let mut lambda = 8;
+        let mut changes = Vec::new();
+
+        // Repeated failures grow the population up to the limit, waiting between changes
+        for generation in 1..=100 {
+            if let Some(new_lambda) = controller.update(false, generation, lambda) {
+                changes.push((generation, new_lambda));
+                lambda = new_lambda;
+            }
+        }
+        assert_eq!(
+            vec![(10, 16), (20, 32), (30, 64), (40, 128)],
+            changes.as_slice(),
+        );
+
+        // Repeated successes shrink it back down to the initial size
+        changes.clear();
+        for generation in 101..=200 {
+            if let Some(new_lambda) = controller.update(true, generation, lambda) {
+                changes.push((generation, new_lambda));
+                lambda = new_lambda;
+            }
+        }
+        assert_eq!(
+            vec![(105, 64), (115, 32), (125, 16), (135, 8)],
+            changes.as_slice(),
+        );
+
+        // A moderate success rate leaves it unchanged
+        for generation in 201..=300 {
+            let success = generation % 5 < 2;
+            assert_eq!(None, controller.update(success, generation, lambda));
+        }
+    }
+}
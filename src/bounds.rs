@@ -0,0 +1,323 @@
+//! Types related to box bounds on the search space. See [`Bounds`] for full documentation.
+
+use nalgebra::DVector;
+
+/// The largest magnitude allowed for the normalized argument of the inverse transform, used to keep
+/// points lying exactly on a bound at a finite internal coordinate
+const MAX_INVERSE_TRANSFORM_ARG: f64 = 1.0 - 1e-12;
+
+/// The fraction of the smallest box width used by [`Bounds::suggested_step_size`]
+const SUGGESTED_STEP_SIZE_WIDTH_FRACTION: f64 = 0.25;
+
+/// The method used to keep evaluated points inside the [`Bounds`]. The default value is
+/// `Transform`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundaryHandling {
+    /// Maps each coordinate of the unbounded internal search space smoothly into the feasible box
+    /// using `lower + (upper - lower) * 0.5 * (1 + tanh(z))`. The objective function never sees an
+    /// infeasible point and never sees a hard wall, so no information is lost to clamping.
+    ///
+    /// The distribution (mean, step size, covariance matrix) is adapted in the internal space,
+    /// while the mean and individuals reported by [`CMAES`][crate::CMAES] and
+    /// [`TerminationData`][crate::TerminationData] are mapped into the feasible box. The initial
+    /// mean is given in feasible coordinates and mapped into the internal space (points exactly on
+    /// a bound are moved slightly inward, and points outside of the bounds are clamped first). The
+    /// initial step size is measured in the internal space, where a value of `1.0` covers most of
+    /// the box.
+    ///
+    /// The mapping is nearly linear at the center of the box but is increasingly compressed towards
+    /// the bounds, so steps of the same size in the internal space produce smaller and smaller
+    /// changes in the feasible space. Optima lying on or very close to a bound are therefore only
+    /// approached asymptotically and may require many generations (and a growing internal mean) to
+    /// resolve precisely.
+    ///
+    /// Coordinates with an infinite lower or upper bound are not transformed.
+    #[default]
+    Transform,
+    /// Repairs each sampled point by clamping it into the feasible box and evaluates the objective
+    /// function at the repaired point, then adds a penalty to the function value that grows with
+    /// the squared distance between the sampled and the repaired point: `sum(w[i] * d[i]^2)` for
+    /// the distance `d[i]` outside of the bounds in coordinate `i` (the penalty is subtracted
+    /// when maximizing). The penalty pulls the distribution back towards the box without
+    /// distorting the search space, so optima on or near a bound can be located precisely.
+    ///
+    /// The search space is not transformed, so the initial mean, the step size, and the reported
+    /// mean (which is clamped into the box) are all given in the feasible coordinates. An initial
+    /// mean outside of the bounds is clamped into the box. The
+    /// reported individuals are the repaired points, while their values include the penalty.
+    ///
+    /// By default, the penalty weights are adapted in each generation to `w[i] = iqr /
+    /// (sigma^2 * C[i][i])`, where `iqr` is the interquartile range of the function values of
+    /// the generation (or `1.0` if it is zero) and `sigma^2 * C[i][i]` is the current variance of
+    /// the distribution in coordinate `i`. Stepping one standard deviation outside of the bounds
+    /// therefore costs about as much as the spread of the function values, regardless of the
+    /// scale of each coordinate. Fixed weights can be set using
+    /// [`CMAESOptions::penalty_weights`][crate::CMAESOptions::penalty_weights].
+    Penalty,
+}
+
+/// Box bounds on the search space, along with the method used to handle them. Set using
+/// [`CMAESOptions::bounds`][crate::CMAESOptions::bounds].
+///
+/// Each of `lower` and `upper` must have a length equal to the problem dimension, and
+/// `lower[i] < upper[i]` must hold for every coordinate. Bounds may be infinite.
+#[derive(Clone, Debug)]
+pub struct Bounds {
+    /// The lower bound for each coordinate
+    lower: DVector<f64>,
+    /// The upper bound for each coordinate
+    upper: DVector<f64>,
+    /// The method used to handle the bounds
+    handling: BoundaryHandling,
+}
+
+impl Bounds {
+    /// Creates a new `Bounds` from the lower and upper bound of each coordinate.
+    pub fn new<V: Into<DVector<f64>>>(lower: V, upper: V, handling: BoundaryHandling) -> Self {
+        Self {
+            lower: lower.into(),
+            upper: upper.into(),
+            handling,
+        }
+    }
+
+    /// Returns the lower bound of each coordinate.
+    pub fn lower(&self) -> &DVector<f64> {
+        &self.lower
+    }
+
+    /// Returns the upper bound of each coordinate.
+    pub fn upper(&self) -> &DVector<f64> {
+        &self.upper
+    }
+
+    /// Returns the method used to handle the bounds.
+    pub fn handling(&self) -> BoundaryHandling {
+        self.handling
+    }
+
+    /// Returns a suggested initial step size for these bounds, or `None` if no coordinate has both
+    /// a finite lower and upper bound. Used by
+    /// [`initial_step_size_from_bounds`][crate::CMAESOptions::initial_step_size_from_bounds].
+    ///
+    /// With [`BoundaryHandling::Penalty`], the step size is measured in the feasible space and the
+    /// suggestion is a quarter of the smallest width `upper[i] - lower[i]` among the finite
+    /// coordinates. With [`BoundaryHandling::Transform`], the step size is measured in the
+    /// internal space, where a step of `1.0` at the center of the box corresponds to half of the
+    /// box width in every bounded coordinate, so the suggestion is always `0.5` (a quarter of each
+    /// box width).
+    pub fn suggested_step_size(&self) -> Option<f64> {
+        let smallest_width = self
+            .lower
+            .iter()
+            .zip(self.upper.iter())
+            .filter(|(l, u)| l.is_finite() && u.is_finite())
+            .map(|(&lower, &upper)| upper - lower)
+            .reduce(f64::min)?;
+
+        Some(match self.handling {
+            // The derivative of the transform at the center of the box is `width / 2`, so a
+            // quarter of any width is `0.5` in the internal space
+            BoundaryHandling::Transform => 0.5,
+            BoundaryHandling::Penalty => SUGGESTED_STEP_SIZE_WIDTH_FRACTION * smallest_width,
+        })
+    }
+
+    /// Returns whether the bounds are valid for the given problem dimension
+    pub(crate) fn is_valid(&self, dim: usize) -> bool {
+        self.lower.len() == dim
+            && self.upper.len() == dim
+            && self.lower.iter().zip(self.upper.iter()).all(|(l, u)| l < u)
+    }
+
+    /// Maps a point in the internal search space to the feasible space
+    pub(crate) fn to_feasible(&self, point: &DVector<f64>) -> DVector<f64> {
+        match self.handling {
+            BoundaryHandling::Transform => self.map_finite(point, |z, lower, upper| {
+                lower + (upper - lower) * 0.5 * (1.0 + z.tanh())
+            }),
+            BoundaryHandling::Penalty => self.clamp(point),
+        }
+    }
+
+    /// Maps a point in the feasible space to the internal search space
+    pub(crate) fn to_internal(&self, point: &DVector<f64>) -> DVector<f64> {
+        match self.handling {
+            BoundaryHandling::Transform => self.map_finite(point, |x, lower, upper| {
+                (2.0 * (x - lower) / (upper - lower) - 1.0)
+                    .clamp(-MAX_INVERSE_TRANSFORM_ARG, MAX_INVERSE_TRANSFORM_ARG)
+                    .atanh()
+            }),
+            BoundaryHandling::Penalty => point.clone(),
+        }
+    }
+
+    /// Returns the total distance by which `point` lies outside of the bounds, summed over all
+    /// coordinates (`0.0` if the point is feasible)
+    pub(crate) fn violation(&self, point: &DVector<f64>) -> f64 {
+        point
+            .iter()
+            .zip(self.lower.iter().zip(self.upper.iter()))
+            .map(|(&x, (&lower, &upper))| (lower - x).max(0.0) + (x - upper).max(0.0))
+            .sum()
+    }
+
+    /// Returns the penalty for `point` lying outside of the bounds, `sum(w[i] * d[i]^2)` for the
+    /// distance `d[i]` outside of the bounds in each coordinate (`0.0` if the point is feasible)
+    pub(crate) fn penalty(&self, point: &DVector<f64>, weights: &DVector<f64>) -> f64 {
+        (point - self.clamp(point))
+            .iter()
+            .zip(weights.iter())
+            .map(|(distance, weight)| weight * distance.powi(2))
+            .sum()
+    }
+
+    /// Clamps each coordinate of `point` into the bounds
+    pub(crate) fn clamp(&self, point: &DVector<f64>) -> DVector<f64> {
+        DVector::from_iterator(
+            point.len(),
+            point
+                .iter()
+                .zip(self.lower.iter().zip(self.upper.iter()))
+                .map(|(&x, (&lower, &upper))| x.clamp(lower, upper)),
+        )
+    }
+
+    /// Applies `f` to each coordinate of `point` that has finite bounds, leaving the others
+    /// unchanged
+    fn map_finite<M: Fn(f64, f64, f64) -> f64>(&self, point: &DVector<f64>, f: M) -> DVector<f64> {
+        DVector::from_iterator(
+            point.len(),
+            point
+                .iter()
+                .zip(self.lower.iter().zip(self.upper.iter()))
+                .map(|(&x, (&lower, &upper))| {
+                    if lower.is_finite() && upper.is_finite() {
+                        f(x, lower, upper)
+                    } else {
+                        x
+                    }
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_is_valid() {
+        let bounds = Bounds::new(vec![0.0, -1.0], vec![1.0, 1.0], BoundaryHandling::Transform);
+        assert!(bounds.is_valid(2));
+        assert!(!bounds.is_valid(3));
+
+        let bounds = Bounds::new(
+            vec![f64::NEG_INFINITY, 0.0],
+            vec![f64::INFINITY, 1.0],
+            BoundaryHandling::Transform,
+        );
+        assert!(bounds.is_valid(2));
+
+        let bounds = Bounds::new(vec![1.0, 0.0], vec![1.0, 1.0], BoundaryHandling::Transform);
+        assert!(!bounds.is_valid(2));
+
+        let bounds = Bounds::new(vec![f64::NAN], vec![1.0], BoundaryHandling::Transform);
+        assert!(!bounds.is_valid(1));
+    }
+
+    #[test]
+    fn test_transform() {
+        let bounds = Bounds::new(
+            vec![-1.0, 2.0, f64::NEG_INFINITY],
+            vec![1.0, 6.0, 0.0],
+            BoundaryHandling::Transform,
+        );
+
+        let center = bounds.to_feasible(&DVector::from(vec![0.0, 0.0, 5.0]));
+        assert_eq!(center, DVector::from(vec![0.0, 4.0, 5.0]));
+
+        let far = bounds.to_feasible(&DVector::from(vec![100.0, -100.0, -100.0]));
+        assert_eq!(far, DVector::from(vec![1.0, 2.0, -100.0]));
+
+        let point = DVector::from(vec![0.3, 5.5, 7.0]);
+        let round_trip = bounds.to_feasible(&bounds.to_internal(&point));
+        for i in 0..point.len() {
+            assert_approx_eq!(point[i], round_trip[i], 1e-12);
+        }
+
+        // Points on a bound are mapped to a finite internal coordinate
+        let internal = bounds.to_internal(&DVector::from(vec![1.0, 2.0, 0.0]));
+        assert!(internal.iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn test_suggested_step_size() {
+        let bounds = Bounds::new(
+            vec![-1.0, 2.0, f64::NEG_INFINITY],
+            vec![1.0, 100.0, 0.0],
+            BoundaryHandling::Transform,
+        );
+        assert_eq!(Some(0.5), bounds.suggested_step_size());
+
+        // At the center of the box, the suggested step size corresponds to a quarter of the width
+        // of each bounded coordinate in the feasible space
+        let center = bounds.to_feasible(&DVector::zeros(3));
+        let moved = bounds.to_feasible(&DVector::from(vec![1e-6; 3]));
+        let slope = (moved - center) / 1e-6;
+        assert_approx_eq!(0.5 * slope[0], 2.0 / 4.0, 1e-6);
+        assert_approx_eq!(0.5 * slope[1], 98.0 / 4.0, 1e-6);
+
+        let unbounded = Bounds::new(
+            vec![f64::NEG_INFINITY, 0.0],
+            vec![0.0, f64::INFINITY],
+            BoundaryHandling::Transform,
+        );
+        assert_eq!(None, unbounded.suggested_step_size());
+    }
+
+    #[test]
+    fn test_violation() {
+        let bounds = Bounds::new(
+            vec![-1.0, 2.0, f64::NEG_INFINITY],
+            vec![1.0, 6.0, 0.0],
+            BoundaryHandling::Transform,
+        );
+
+        assert_eq!(0.0, bounds.violation(&DVector::from(vec![0.0, 2.0, -1e9])));
+        assert_eq!(0.0, bounds.violation(&DVector::from(vec![1.0, 6.0, 0.0])));
+        assert_eq!(3.5, bounds.violation(&DVector::from(vec![-2.0, 7.5, 1.0])));
+
+        // Points mapped into the feasible space never violate the bounds
+        let far = bounds.to_feasible(&DVector::from(vec![100.0, -100.0, -100.0]));
+        assert_eq!(0.0, bounds.violation(&far));
+    }
+
+    #[test]
+    fn test_penalty() {
+        let bounds = Bounds::new(
+            vec![-1.0, 2.0, f64::NEG_INFINITY],
+            vec![1.0, 6.0, 0.0],
+            BoundaryHandling::Penalty,
+        );
+
+        let point = DVector::from(vec![-2.0, 7.5, 1.0]);
+        assert_eq!(
+            DVector::from(vec![-1.0, 6.0, 0.0]),
+            bounds.to_feasible(&point)
+        );
+        assert_eq!(point, bounds.to_internal(&point));
+
+        let weights = DVector::from(vec![1.0, 2.0, 4.0]);
+        assert_eq!(1.0 + 2.0 * 2.25 + 4.0, bounds.penalty(&point, &weights));
+        assert_eq!(
+            0.0,
+            bounds.penalty(&DVector::from(vec![0.0, 2.0, -1e9]), &weights)
+        );
+
+        assert_eq!(Some(0.5), bounds.suggested_step_size());
+    }
+}
@@ -0,0 +1,141 @@
+//! Caching of objective function values for repeatedly evaluated points. See
+//! [`CMAESOptions::cache_evaluations`][crate::CMAESOptions::cache_evaluations] for full
+//! documentation.
+
+use nalgebra::DVector;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// The number of low mantissa bits that are rounded away from each coordinate to form the cache
+/// key (leaving 40 bits, a relative precision of about `1e-12`)
+const QUANTIZATION_BITS: u32 = 12;
+
+/// Statistics of the evaluation cache. Obtained from
+/// [`CMAES::cache_stats`][crate::CMAES::cache_stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of evaluations that were answered from the cache (i.e., the number of duplicate
+    /// evaluations that were avoided).
+    pub hits: usize,
+    /// The number of evaluations that were not found in the cache and were passed to the objective
+    /// function.
+    pub misses: usize,
+    /// The number of distinct points currently stored in the cache.
+    pub entries: usize,
+}
+
+/// A cache of objective function values keyed by quantized points
+#[derive(Debug, Default)]
+pub(crate) struct EvaluationCache {
+    inner: Mutex<CacheInner>,
+}
+
+#[derive(Debug, Default)]
+struct CacheInner {
+    values: HashMap<Vec<u64>, f64>,
+    hits: usize,
+    misses: usize,
+}
+
+impl EvaluationCache {
+    /// Returns the cached value at `point` or evaluates it using `evaluate` and stores the result
+    ///
+    /// The cache is not locked during evaluation, so the same point may be evaluated more than
+    /// once if it is evaluated concurrently on multiple threads
+    pub fn get_or_evaluate<E: FnOnce(&DVector<f64>) -> f64>(
+        &self,
+        point: &DVector<f64>,
+        evaluate: E,
+    ) -> f64 {
+        let key = quantize(point);
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(&value) = inner.values.get(&key) {
+                inner.hits += 1;
+                return value;
+            }
+            inner.misses += 1;
+        }
+
+        let value = evaluate(point);
+        // Invalid values are not cached so that they are reported every time
+        if !value.is_nan() {
+            self.inner.lock().unwrap().values.insert(key, value);
+        }
+        value
+    }
+
+    /// Returns the current statistics of the cache
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.inner.lock().unwrap();
+        CacheStats {
+            hits: inner.hits,
+            misses: inner.misses,
+            entries: inner.values.len(),
+        }
+    }
+}
+
+/// Returns the cache key of `point`: the bits of each coordinate with the lowest
+/// `QUANTIZATION_BITS` bits of the mantissa rounded away
+fn quantize(point: &DVector<f64>) -> Vec<u64> {
+    let half = 1 << (QUANTIZATION_BITS - 1);
+    let mask = !((1 << QUANTIZATION_BITS) - 1);
+
+    point
+        .iter()
+        // Adding 0.0 maps -0.0 to 0.0
+        .map(|x| ((x + 0.0).to_bits().wrapping_add(half)) & mask)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantize() {
+        let point = DVector::from(vec![1.0, -2.5, 0.0]);
+
+        assert_eq!(
+            quantize(&point),
+            quantize(&point.map(|x| x * (1.0 + 1e-14)))
+        );
+        assert_eq!(
+            quantize(&point),
+            quantize(&DVector::from(vec![1.0, -2.5, -0.0]))
+        );
+        assert_ne!(quantize(&point), quantize(&point.map(|x| x + 1e-6)));
+    }
+
+    #[test]
+    fn test_get_or_evaluate() {
+        let cache = EvaluationCache::default();
+        let mut evals = 0;
+        let mut function = |x: &DVector<f64>| {
+            evals += 1;
+            x.sum()
+        };
+        let a = DVector::from(vec![1.0, 2.0]);
+        let b = DVector::from(vec![3.0, 4.0]);
+
+        assert_eq!(3.0, cache.get_or_evaluate(&a, &mut function));
+        assert_eq!(7.0, cache.get_or_evaluate(&b, &mut function));
+        assert_eq!(3.0, cache.get_or_evaluate(&a, &mut function));
+        assert_eq!(2, evals);
+        assert_eq!(
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                entries: 2,
+            },
+            cache.stats(),
+        );
+
+        // NAN is not cached
+        assert!(cache.get_or_evaluate(&a.scale(2.0), |_| f64::NAN).is_nan());
+        assert_eq!(2, cache.stats().entries);
+    }
+}
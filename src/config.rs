@@ -0,0 +1,572 @@
+//! A serializable form of [`CMAESOptions`] for configuring the algorithm from files. Requires the
+//! `serde` feature. See [`CMAESConfig`] for full documentation.
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::bounds::BoundaryHandling;
+use crate::mode::Mode;
+use crate::options::{CMAESOptions, InvalidOptionsError};
+use crate::parameters::{AdaptivePopulation, AutoKick, RecombinationWeighting, Weights};
+use crate::sampling::Aggregation;
+use crate::termination::{InvalidPolicy, TargetMode, TerminationPreset};
+
+/// All options of [`CMAESOptions`] that do not involve closures, in a form that can be
+/// (de)serialized with [serde][serde] (e.g. from a TOML or JSON file). Requires the `serde`
+/// feature. Convert it into options with [`CMAESOptions::from_config`].
+///
+/// Only `initial_mean` must be given (if missing, it defaults to an empty mean, which is rejected
+/// by validation). `initial_step_size` must also be given unless `bounds` are, in which case it
+/// defaults to [`Bounds::suggested_step_size`][crate::Bounds::suggested_step_size] (see
+/// [`CMAESOptions::initial_step_size_from_bounds`]). If neither is given, it defaults to `0.0`,
+/// and if no coordinate of the bounds has both a finite lower and upper bound, there is no
+/// suggested step size; both are rejected by validation. Every other field is optional, and
+/// missing fields keep the default values of [`CMAESOptions::new`] (which may depend on the number
+/// of dimensions). See the fields of [`CMAESOptions`] for the meaning of each option.
+///
+/// The objective function is supplied when building the [`CMAES`][crate::CMAES], and options that
+/// involve closures, output or recorded data ([`transform`][CMAESOptions::transform],
+/// [`fitness_transform`][CMAESOptions::fitness_transform],
+/// [`cm_schedule`][CMAESOptions::cm_schedule], [`on_improvement`][CMAESOptions::on_improvement],
+/// [`replay_samples`][CMAESOptions::replay_samples], and `plot_options`) can be set on the
+/// returned [`CMAESOptions`] with their builder methods.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::{CMAESConfig, CMAESOptions, DVector};
+///
+/// let json = r#"{
+///     "initial_mean": [1.0, 2.0, 3.0],
+///     "initial_step_size": 0.5,
+///     "population_size": 20,
+///     "bounds": { "lower": [-5.0, -5.0, -5.0], "upper": [5.0, 5.0, 5.0] },
+///     "max_time": 10.0,
+///     "tol_fun": 1e-10
+/// }"#;
+/// let config: CMAESConfig = serde_json::from_str(json).unwrap();
+///
+/// let function = |x: &DVector<f64>| x.magnitude();
+/// let mut cmaes_state = CMAESOptions::from_config(config)
+///     .unwrap()
+///     .build(function)
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CMAESConfig {
+    /// See [`CMAESOptions::initial_mean`]. Must be given.
+    pub initial_mean: Vec<f64>,
+    /// See [`CMAESOptions::initial_step_size`]. Must be given if `bounds` are not; otherwise
+    /// defaults to [`Bounds::suggested_step_size`][crate::Bounds::suggested_step_size].
+    pub initial_step_size: Option<f64>,
+    /// See [`CMAESOptions::initial_covariance_scale`].
+    pub initial_covariance_scale: Option<f64>,
+    /// See [`CMAESOptions::random_initial_mean`].
+    pub random_initial_mean: Option<RegionConfig>,
+    /// See [`CMAESOptions::mode`].
+    pub mode: Option<Mode>,
+    /// See [`CMAESOptions::population_size`]. If `adaptive_population` is also given, this
+    /// overrides its `min` as the initial population size and must lie within `[min, max]`.
+    pub population_size: Option<usize>,
+    /// See [`CMAESOptions::weights`].
+    pub weights: Option<Weights>,
+    /// See [`CMAESOptions::adaptive_population`]. Sets the initial population size to `min`
+    /// unless `population_size` is also given.
+    pub adaptive_population: Option<AdaptivePopulation>,
+    /// See [`CMAESOptions::auto_population`].
+    pub auto_population: Option<bool>,
+    /// See [`CMAESOptions::auto_kick`].
+    pub auto_kick: Option<AutoKick>,
+    /// See [`CMAESOptions::recombination_weighting`].
+    pub recombination_weighting: Option<RecombinationWeighting>,
+    /// See [`CMAESOptions::parallel_update`].
+    pub parallel_update: Option<bool>,
+    /// See [`CMAESOptions::cm`].
+    pub cm: Option<f64>,
+    /// See [`CMAESOptions::max_sigma_change`].
+    pub max_sigma_change: Option<f64>,
+    /// See [`CMAESOptions::min_mu_eff`].
+    pub min_mu_eff: Option<f64>,
+    /// See [`CMAESOptions::min_eigenvalue_ratio`].
+    pub min_eigenvalue_ratio: Option<f64>,
+    /// See [`CMAESOptions::bounds`].
+    pub bounds: Option<BoundsConfig>,
+    /// See [`CMAESOptions::penalty_weights`].
+    pub penalty_weights: Option<Vec<f64>>,
+    /// See [`CMAESOptions::value_scale`].
+    pub value_scale: Option<f64>,
+    /// See [`CMAESOptions::max_recorded_generations`].
+    pub max_recorded_generations: Option<usize>,
+    /// See [`CMAESOptions::cache_evaluations`].
+    pub cache_evaluations: Option<bool>,
+    /// See [`CMAESOptions::evaluations_per_candidate`].
+    pub evaluations_per_candidate: Option<usize>,
+    /// See [`CMAESOptions::evaluation_aggregation`].
+    pub evaluation_aggregation: Option<Aggregation>,
+    /// See [`CMAESOptions::evaluate_mean`].
+    pub evaluate_mean: Option<bool>,
+    /// See [`CMAESOptions::record_samples`].
+    pub record_samples: Option<bool>,
+    /// See [`CMAESOptions::record_covariance`].
+    pub record_covariance: Option<RecordCovarianceConfig>,
+    /// See [`CMAESOptions::termination_preset`]. Applied before the individual termination options
+    /// below, which override the values of the preset.
+    pub termination_preset: Option<TerminationPreset>,
+    /// See [`CMAESOptions::max_function_evals`].
+    pub max_function_evals: Option<usize>,
+    /// See [`CMAESOptions::max_generations`].
+    pub max_generations: Option<usize>,
+    /// See [`CMAESOptions::max_time`]. Given in seconds.
+    pub max_time: Option<f64>,
+    /// See [`CMAESOptions::improvement_timeout`]. Given in seconds.
+    pub improvement_timeout: Option<f64>,
+    /// See [`CMAESOptions::fun_target`].
+    pub fun_target: Option<f64>,
+    /// See [`CMAESOptions::fun_target_mode`].
+    pub fun_target_mode: Option<TargetMode>,
+    /// See [`CMAESOptions::target_values`].
+    pub target_values: Option<Vec<f64>>,
+    /// See [`CMAESOptions::tol_fun`].
+    pub tol_fun: Option<f64>,
+    /// See [`CMAESOptions::adaptive_tol_fun`].
+    pub adaptive_tol_fun: Option<bool>,
+    /// See [`CMAESOptions::tol_fun_rel`].
+    pub tol_fun_rel: Option<f64>,
+    /// See [`CMAESOptions::tol_fun_hist`].
+    pub tol_fun_hist: Option<f64>,
+    /// See [`CMAESOptions::tol_x`].
+    pub tol_x: Option<f64>,
+    /// See [`CMAESOptions::relative_tol_x`].
+    pub relative_tol_x: Option<f64>,
+    /// See [`CMAESOptions::target_sigma`].
+    pub target_sigma: Option<f64>,
+    /// See [`CMAESOptions::tol_diversity`].
+    pub tol_diversity: Option<f64>,
+    /// See [`CMAESOptions::tol_diversity_generations`].
+    pub tol_diversity_generations: Option<usize>,
+    /// See [`CMAESOptions::flat_function_generations`].
+    pub flat_function_generations: Option<usize>,
+    /// See [`CMAESOptions::validate_objective`].
+    pub validate_objective: Option<bool>,
+    /// See [`CMAESOptions::invalid_value_policy`].
+    pub invalid_value_policy: Option<InvalidPolicy>,
+    /// See [`CMAESOptions::mean_region`].
+    pub mean_region: Option<RegionConfig>,
+    /// See [`CMAESOptions::tol_improvement_rate`].
+    pub tol_improvement_rate: Option<f64>,
+    /// See [`CMAESOptions::max_sigma_decay_ratio`].
+    pub max_sigma_decay_ratio: Option<f64>,
+    /// See [`CMAESOptions::tol_stagnation`].
+    pub tol_stagnation: Option<usize>,
+    /// See [`CMAESOptions::tol_x_up`].
+    pub tol_x_up: Option<f64>,
+    /// See [`CMAESOptions::tol_condition_cov`].
+    pub tol_condition_cov: Option<f64>,
+    /// See [`CMAESOptions::min_generations`].
+    pub min_generations: Option<usize>,
+    /// See [`CMAESOptions::seed`].
+    pub seed: Option<u64>,
+    /// See [`CMAESOptions::print_gap_evals`].
+    pub print_gap_evals: Option<usize>,
+    /// See [`CMAESOptions::live_status`].
+    pub live_status: Option<bool>,
+    /// See [`CMAESOptions::metadata`].
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// The serializable form of [`Bounds`][crate::Bounds], used in [`CMAESConfig`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BoundsConfig {
+    /// The lower bound of each coordinate.
+    pub lower: Vec<f64>,
+    /// The upper bound of each coordinate.
+    pub upper: Vec<f64>,
+    /// The method used to handle the bounds. Default value is [`BoundaryHandling::Transform`].
+    #[serde(default)]
+    pub handling: BoundaryHandling,
+}
+
+/// The serializable form of a box-shaped region, used for
+/// [`CMAESConfig::random_initial_mean`] and [`CMAESConfig::mean_region`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegionConfig {
+    /// The lower bound of each coordinate.
+    pub lower: Vec<f64>,
+    /// The upper bound of each coordinate.
+    pub upper: Vec<f64>,
+}
+
+/// The serializable form of the arguments of [`CMAESOptions::record_covariance`], used in
+/// [`CMAESConfig`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordCovarianceConfig {
+    /// The number of generations between recorded matrices.
+    pub every: usize,
+    /// The path of each file, containing `{generation}`.
+    pub path_template: String,
+}
+
+/// Represents an invalid [`CMAESConfig`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum InvalidConfigError {
+    /// The options are invalid in the same way as when building a [`CMAES`][crate::CMAES] from
+    /// them.
+    Options(InvalidOptionsError),
+    /// `max_time` is negative or not finite.
+    MaxTime,
+    /// `improvement_timeout` is negative or not finite.
+    ImprovementTimeout,
+}
+
+impl From<InvalidOptionsError> for InvalidConfigError {
+    fn from(error: InvalidOptionsError) -> Self {
+        Self::Options(error)
+    }
+}
+
+impl CMAESOptions {
+    /// Creates a new `CMAESOptions` from a [`CMAESConfig`], using the default values of
+    /// [`CMAESOptions::new`] for all options missing from the config. Requires the `serde`
+    /// feature.
+    ///
+    /// The resulting options are validated in the same way as by [`build`][Self::build], so
+    /// `Err` is returned for invalid configs before an objective function is supplied.
+    pub fn from_config(config: CMAESConfig) -> Result<Self, InvalidConfigError> {
+        let mut options =
+            CMAESOptions::new(config.initial_mean, config.initial_step_size.unwrap_or(0.0));
+
+        if let Some(initial_covariance_scale) = config.initial_covariance_scale {
+            options = options.initial_covariance_scale(initial_covariance_scale);
+        }
+        if let Some(random_initial_mean) = config.random_initial_mean {
+            options =
+                options.random_initial_mean(random_initial_mean.lower, random_initial_mean.upper);
+        }
+        if let Some(mode) = config.mode {
+            options = options.mode(mode);
+        }
+        // Applied first so that an explicit population size overrides its minimum
+        if let Some(adaptive_population) = config.adaptive_population {
+            options = options.adaptive_population(adaptive_population);
+        }
+        if let Some(population_size) = config.population_size {
+            options = options.population_size(population_size);
+        }
+        if let Some(weights) = config.weights {
+            options = options.weights(weights);
+        }
+        if let Some(auto_population) = config.auto_population {
+            options = options.auto_population(auto_population);
+        }
+        options.auto_kick = config.auto_kick;
+        if let Some(recombination_weighting) = config.recombination_weighting {
+            options = options.recombination_weighting(recombination_weighting);
+        }
+        if let Some(parallel_update) = config.parallel_update {
+            options = options.parallel_update(parallel_update);
+        }
+        if let Some(cm) = config.cm {
+            options = options.cm(cm);
+        }
+        options.max_sigma_change = config.max_sigma_change;
+        options.min_mu_eff = config.min_mu_eff;
+        options.min_eigenvalue_ratio = config.min_eigenvalue_ratio;
+        if let Some(bounds) = config.bounds {
+            options = options.bounds(bounds.lower, bounds.upper, bounds.handling);
+            if config.initial_step_size.is_none() {
+                options = options.initial_step_size_from_bounds();
+            }
+        }
+        if let Some(penalty_weights) = config.penalty_weights {
+            options = options.penalty_weights(penalty_weights);
+        }
+        if let Some(value_scale) = config.value_scale {
+            options = options.value_scale(value_scale);
+        }
+        options.max_recorded_generations = config.max_recorded_generations;
+        if let Some(cache_evaluations) = config.cache_evaluations {
+            options = options.cache_evaluations(cache_evaluations);
+        }
+        if let Some(evaluations_per_candidate) = config.evaluations_per_candidate {
+            options = options.evaluations_per_candidate(evaluations_per_candidate);
+        }
+        if let Some(evaluation_aggregation) = config.evaluation_aggregation {
+            options = options.evaluation_aggregation(evaluation_aggregation);
+        }
+        if let Some(evaluate_mean) = config.evaluate_mean {
+            options = options.evaluate_mean(evaluate_mean);
+        }
+        if let Some(record_samples) = config.record_samples {
+            options = options.record_samples(record_samples);
+        }
+        if let Some(record_covariance) = config.record_covariance {
+            options =
+                options.record_covariance(record_covariance.every, record_covariance.path_template);
+        }
+
+        // Applied first so that individual termination options override the preset
+        if let Some(preset) = config.termination_preset {
+            options = options.termination_preset(preset);
+        }
+        if let Some(max_function_evals) = config.max_function_evals {
+            options = options.max_function_evals(max_function_evals);
+        }
+        if let Some(max_generations) = config.max_generations {
+            options = options.max_generations(max_generations);
+        }
+        if let Some(max_time) = config.max_time {
+            let max_time =
+                Duration::try_from_secs_f64(max_time).map_err(|_| InvalidConfigError::MaxTime)?;
+            options = options.max_time(max_time);
+        }
+        if let Some(improvement_timeout) = config.improvement_timeout {
+            let improvement_timeout = Duration::try_from_secs_f64(improvement_timeout)
+                .map_err(|_| InvalidConfigError::ImprovementTimeout)?;
+            options = options.improvement_timeout(improvement_timeout);
+        }
+        options.fun_target = config.fun_target;
+        if let Some(fun_target_mode) = config.fun_target_mode {
+            options = options.fun_target_mode(fun_target_mode);
+        }
+        if let Some(target_values) = config.target_values {
+            options = options.target_values(target_values);
+        }
+        if let Some(tol_fun) = config.tol_fun {
+            options = options.tol_fun(tol_fun);
+        }
+        if let Some(adaptive_tol_fun) = config.adaptive_tol_fun {
+            options = options.adaptive_tol_fun(adaptive_tol_fun);
+        }
+        if let Some(tol_fun_rel) = config.tol_fun_rel {
+            options = options.tol_fun_rel(tol_fun_rel);
+        }
+        if let Some(tol_fun_hist) = config.tol_fun_hist {
+            options = options.tol_fun_hist(tol_fun_hist);
+        }
+        if let Some(tol_x) = config.tol_x {
+            options = options.tol_x(tol_x);
+        }
+        if let Some(relative_tol_x) = config.relative_tol_x {
+            options.relative_tol_x = relative_tol_x;
+        }
+        if let Some(target_sigma) = config.target_sigma {
+            options = options.target_sigma(target_sigma);
+        }
+        if let Some(tol_diversity) = config.tol_diversity {
+            options = options.tol_diversity(tol_diversity);
+        }
+        if let Some(tol_diversity_generations) = config.tol_diversity_generations {
+            options = options.tol_diversity_generations(tol_diversity_generations);
+        }
+        options.flat_function_generations = config.flat_function_generations;
+        if let Some(validate_objective) = config.validate_objective {
+            options = options.validate_objective(validate_objective);
+        }
+        if let Some(invalid_value_policy) = config.invalid_value_policy {
+            options = options.invalid_value_policy(invalid_value_policy);
+        }
+        if let Some(mean_region) = config.mean_region {
+            options = options.mean_region(mean_region.lower, mean_region.upper);
+        }
+        if let Some(tol_improvement_rate) = config.tol_improvement_rate {
+            options = options.tol_improvement_rate(tol_improvement_rate);
+        }
+        if let Some(max_sigma_decay_ratio) = config.max_sigma_decay_ratio {
+            options = options.max_sigma_decay_ratio(max_sigma_decay_ratio);
+        }
+        if let Some(tol_stagnation) = config.tol_stagnation {
+            options = options.tol_stagnation(tol_stagnation);
+        }
+        if let Some(tol_x_up) = config.tol_x_up {
+            options = options.tol_x_up(tol_x_up);
+        }
+        if let Some(tol_condition_cov) = config.tol_condition_cov {
+            options = options.tol_condition_cov(tol_condition_cov);
+        }
+        if let Some(min_generations) = config.min_generations {
+            options = options.min_generations(min_generations);
+        }
+        options.seed = config.seed;
+        options.print_gap_evals = config.print_gap_evals;
+        if let Some(live_status) = config.live_status {
+            options = options.live_status(live_status);
+        }
+        if let Some(metadata) = config.metadata {
+            options = options.metadata(metadata);
+        }
+
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DVector;
+
+    use super::*;
+
+    #[test]
+    fn test_from_config() {
+        let config: CMAESConfig = serde_json::from_str(
+            r#"{
+                "initial_mean": [1.0, 2.0],
+                "initial_step_size": 0.5,
+                "initial_covariance_scale": 4.0,
+                "random_initial_mean": { "lower": [0.5, 0.5], "upper": [2.5, 2.5] },
+                "mode": "Maximize",
+                "population_size": 12,
+                "auto_population": true,
+                "evaluations_per_candidate": 3,
+                "evaluation_aggregation": "Median",
+                "record_covariance": { "every": 5, "path_template": "cov_{generation}.csv" },
+                "bounds": { "lower": [0.0, 0.0], "upper": [3.0, 3.0], "handling": "Transform" },
+                "termination_preset": "Fast",
+                "mean_region": { "lower": [-1.0, -1.0], "upper": [4.0, 4.0] },
+                "tol_fun": 1e-3,
+                "max_time": 1.5,
+                "improvement_timeout": 0.5,
+                "max_sigma_decay_ratio": 10.0,
+                "seed": 7,
+                "metadata": { "experiment": "test" }
+            }"#,
+        )
+        .unwrap();
+        let options = CMAESOptions::from_config(config).unwrap();
+
+        assert_eq!(vec![1.0, 2.0], options.initial_mean.as_slice());
+        assert_eq!(0.5, options.initial_step_size);
+        assert_eq!(4.0, options.initial_covariance_scale);
+        assert_eq!(
+            Some((DVector::from(vec![0.5; 2]), DVector::from(vec![2.5; 2]))),
+            options.random_initial_mean
+        );
+        assert_eq!(Mode::Maximize, options.mode);
+        assert_eq!(12, options.population_size);
+        assert!(options.auto_population);
+        assert_eq!(3, options.evaluations_per_candidate);
+        assert_eq!(Aggregation::Median, options.evaluation_aggregation);
+        assert_eq!(
+            Some((5, "cov_{generation}.csv".to_string())),
+            options.record_covariance
+        );
+        assert_eq!(
+            BoundaryHandling::Transform,
+            options.bounds.as_ref().unwrap().handling()
+        );
+        // Individual options override the preset
+        assert_eq!(1e-3, options.tol_fun);
+        assert_eq!(1e-10, options.tol_fun_hist);
+        assert_eq!(1e-8, options.relative_tol_x);
+        assert_eq!(Some(Duration::from_secs_f64(1.5)), options.max_time);
+        assert_eq!(
+            Some(Duration::from_secs_f64(0.5)),
+            options.improvement_timeout
+        );
+        assert_eq!(Some(10.0), options.max_sigma_decay_ratio);
+        assert_eq!(Some(7), options.seed);
+        assert_eq!("test", options.metadata["experiment"]);
+        assert_eq!(
+            Some((DVector::from(vec![-1.0; 2]), DVector::from(vec![4.0; 2]))),
+            options.mean_region
+        );
+
+        // Missing options keep their default values
+        let default = CMAESOptions::new(vec![1.0, 2.0], 0.5);
+        assert_eq!(default.cm, options.cm);
+        assert_eq!(default.weights, options.weights);
+        assert_eq!(default.tol_x_up.min(1e4), options.tol_x_up);
+
+        // Round trip
+        let config = CMAESConfig {
+            initial_mean: vec![0.0; 3],
+            initial_step_size: Some(1.0),
+            tol_fun: Some(1e-6),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(config, serde_json::from_str(&json).unwrap());
+
+        // The initial step size is derived from the bounds if missing
+        let config: CMAESConfig = serde_json::from_str(
+            r#"{
+                "initial_mean": [1.0, 2.0],
+                "bounds": { "lower": [0.0, 0.0], "upper": [3.0, 10.0] }
+            }"#,
+        )
+        .unwrap();
+        let options = CMAESOptions::from_config(config).unwrap();
+        assert_eq!(0.5, options.resolved_initial_step_size());
+
+        // An explicit population size overrides the minimum of the adaptive population size
+        let config: CMAESConfig = serde_json::from_str(
+            r#"{
+                "initial_mean": [1.0, 2.0],
+                "initial_step_size": 0.5,
+                "population_size": 8,
+                "adaptive_population": { "min": 4, "max": 16, "growth": 2.0 }
+            }"#,
+        )
+        .unwrap();
+        let options = CMAESOptions::from_config(config).unwrap();
+        assert_eq!(8, options.population_size);
+        assert_eq!(4, options.adaptive_population.unwrap().min);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = |json: &str| -> CMAESConfig { serde_json::from_str(json).unwrap() };
+
+        let error = |json: &str| CMAESOptions::from_config(config(json)).unwrap_err();
+
+        assert_eq!(
+            InvalidConfigError::Options(InvalidOptionsError::Dimensions),
+            error(r#"{ "initial_mean": [], "initial_step_size": 1.0 }"#),
+        );
+        assert_eq!(
+            InvalidConfigError::Options(InvalidOptionsError::InitialStepSize),
+            error(r#"{ "initial_mean": [1.0] }"#),
+        );
+        assert_eq!(
+            InvalidConfigError::Options(InvalidOptionsError::Bounds),
+            error(
+                r#"{
+                    "initial_mean": [1.0],
+                    "initial_step_size": 1.0,
+                    "bounds": { "lower": [1.0], "upper": [0.0] }
+                }"#
+            ),
+        );
+        assert_eq!(
+            InvalidConfigError::Options(InvalidOptionsError::AdaptivePopulation),
+            error(
+                r#"{
+                    "initial_mean": [1.0],
+                    "initial_step_size": 1.0,
+                    "population_size": 32,
+                    "adaptive_population": { "min": 4, "max": 16, "growth": 2.0 }
+                }"#
+            ),
+        );
+        assert_eq!(
+            InvalidConfigError::MaxTime,
+            error(r#"{ "initial_mean": [1.0], "initial_step_size": 1.0, "max_time": -1.0 }"#),
+        );
+        assert_eq!(
+            InvalidConfigError::ImprovementTimeout,
+            error(
+                r#"{ "initial_mean": [1.0], "initial_step_size": 1.0, "improvement_timeout": -1.0 }"#
+            ),
+        );
+        assert!(serde_json::from_str::<CMAESConfig>(
+            r#"{ "initial_mean": [1.0], "initial_step_size": 1.0, "unknown": 1 }"#
+        )
+        .is_err());
+    }
+}
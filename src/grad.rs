@@ -0,0 +1,79 @@
+//! Utilities for estimating gradients of objective functions.
+//!
+//! CMA-ES itself never uses gradients, but gradient estimates can be useful for analyzing a
+//! solution (e.g. checking whether it lies at a stationary point) or for refining it with a
+//! gradient-based local method after optimization.
+
+use nalgebra::DVector;
+
+use crate::objective_function::ObjectiveFunction;
+
+/// Estimates the gradient of `function` at `x` using central finite differences with step size
+/// `h`. The objective function is called `2 * x.len()` times.
+///
+/// The error of each partial derivative is `O(h^2)` for smooth functions, but floating point
+/// cancellation makes the error grow like `O(eps / h)` as `h` becomes small, so `h` should not be
+/// made arbitrarily small. A value around `eps^(1/3) * max(|x_i|, 1)` (roughly `6e-6` for
+/// coordinates of unit scale) balances the two. Noisy or non-smooth objective functions may
+/// require a much larger `h`.
+///
+/// The evaluations are not counted by [`CMAES::function_evals`][crate::CMAES::function_evals] or
+/// by the `MaxFunctionEvals` termination criterion, but if the same function object is used for
+/// optimization, they do affect any state it carries. In particular, an objective function that
+/// tracks its own evaluation budget will have `2 * x.len()` evaluations charged against it.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::{grad, DVector};
+///
+/// let mut function = |x: &DVector<f64>| x[0].powi(2) + 3.0 * x[1];
+/// let gradient = grad::finite_difference(&mut function, &DVector::from(vec![2.0, 1.0]), 1e-5);
+///
+/// assert!((gradient[0] - 4.0).abs() < 1e-6);
+/// assert!((gradient[1] - 3.0).abs() < 1e-6);
+/// ```
+pub fn finite_difference<F: ObjectiveFunction + ?Sized>(
+    function: &mut F,
+    x: &DVector<f64>,
+    h: f64,
+) -> DVector<f64> {
+    let mut point = x.clone();
+
+    DVector::from_iterator(
+        x.len(),
+        (0..x.len()).map(|i| {
+            point[i] = x[i] + h;
+            let forward = function.evaluate(&point);
+            point[i] = x[i] - h;
+            let backward = function.evaluate(&point);
+            point[i] = x[i];
+
+            (forward - backward) / (2.0 * h)
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_finite_difference() {
+        let mut evals = 0;
+        let mut function = |x: &DVector<f64>| {
+            evals += 1;
+            x[0].powi(3) + x[0] * x[1] + x[2].sin()
+        };
+        let x = DVector::from(vec![1.0, -2.0, 0.5]);
+
+        let gradient = finite_difference(&mut function, &x, 1e-5);
+
+        assert_approx_eq!(3.0 - 2.0, gradient[0], 1e-8);
+        assert_approx_eq!(1.0, gradient[1], 1e-8);
+        assert_approx_eq!(0.5f64.cos(), gradient[2], 1e-8);
+        assert_eq!(6, evals);
+    }
+}
@@ -4,13 +4,45 @@ use std::collections::VecDeque;
 
 use crate::mode::Mode;
 use crate::sampling::EvaluatedPoint;
-use crate::Individual;
+use crate::{Individual, Quartiles};
 
 /// The maximum number of elements to store in the objective function value histories.
 pub const MAX_HISTORY_LENGTH: usize = 20_000;
 
+/// The minimum weight of the latest difference between the function values at consecutive means
+/// in the estimate of the objective function noise (so the estimate covers roughly the last
+/// `1 / MEAN_DIFFERENCE_WEIGHT` generations)
+const MEAN_DIFFERENCE_WEIGHT: f64 = 0.1;
+
+/// The minimum weight of the latest generation in the estimate of the improvement probability (so
+/// the estimate covers roughly the last `1 / IMPROVEMENT_FRACTION_WEIGHT` generations)
+const IMPROVEMENT_FRACTION_WEIGHT: f64 = 0.1;
+
+/// The progress of the run in a single generation, as recorded in the trajectory
+#[derive(Clone, Copy, Debug)]
+pub struct TrajectoryEntry {
+    /// The number of the generation (starting at `1`)
+    pub generation: usize,
+    /// The number of function evaluations performed by the end of the generation
+    pub function_evals: usize,
+    /// The overall best function value at the end of the generation
+    pub best_value: f64,
+    /// The median function value of the generation (`NAN` if no individual could be ranked)
+    pub median_value: f64,
+    /// The step size of the distribution that the generation was sampled from
+    pub sigma: f64,
+    /// The axis ratio of the distribution that the generation was sampled from
+    pub axis_ratio: f64,
+    /// The norm of the mean of the distribution that the generation was sampled from
+    pub mean_norm: f64,
+}
+
 /// A type that tracks various histories of the objective function value, as well as the current
 /// and overall best points.
+///
+/// The function value histories (and the first/best median values) are stored divided by the
+/// value scale, while the best individuals keep their original values.
+#[derive(Clone)]
 pub struct History {
     /// A history of the best function values (values at the front are from more recent generations)
     best_function_values: VecDeque<f64>,
@@ -21,21 +53,77 @@ pub struct History {
     current_best_individual: Option<Individual>,
     /// The best individual of any generation
     overall_best_individual: Option<Individual>,
+    /// The quartiles of the function values of the latest generation
+    current_quartiles: Option<Quartiles>,
     /// The median function value of the first generation
     first_median_function_value: Option<f64>,
     /// The best median function value of any generation
     best_median_function_value: Option<f64>,
+    /// The factor that function values are divided by before being stored in the histories
+    value_scale: f64,
+    /// The number of consecutive generations (up to and including the latest) in which the
+    /// population diversity was below `tol_diversity`
+    low_diversity_generations: usize,
+    /// The number of consecutive generations (up to and including the latest) in which every
+    /// individual had the same function value
+    flat_generations: usize,
+    /// The number of consecutive generations (up to and including the latest) in which the
+    /// overall best function value did not improve
+    generations_without_improvement: usize,
+    /// A history of the step sizes that each generation was sampled with (values at the front are
+    /// from more recent generations)
+    sigma_values: VecDeque<f64>,
+    /// The progress of the run in each generation (also used for the convergence curve)
+    trajectory: VecDeque<TrajectoryEntry>,
+    /// A history of the number of function evaluations performed by the end of each generation
+    /// and the overall best function value at that point (values at the front are from more
+    /// recent generations). Unlike `trajectory`, this is not limited by `max_recorded_generations`
+    best_value_evals: VecDeque<(usize, f64)>,
+    /// The largest improvement of the overall best function value over any window of consecutive
+    /// generations so far (see `Self::update_peak_improvement`)
+    peak_improvement: f64,
+    /// The latest valid function value at the mean of the distribution
+    last_mean_value: Option<f64>,
+    /// The number of differences between the function values at consecutive means
+    mean_differences: usize,
+    /// The exponentially weighted average of the differences between the function values at
+    /// consecutive means
+    mean_difference_average: f64,
+    /// The exponentially weighted variance of the differences between the function values at
+    /// consecutive means
+    mean_difference_variance: f64,
+    /// The number of generations whose individuals were compared against a previous overall best
+    /// individual
+    improvement_generations: usize,
+    /// The exponentially weighted average of the fraction of individuals in each generation that
+    /// were better than the overall best individual of the previous generations
+    improvement_fraction: f64,
 }
 
 impl History {
-    pub fn new() -> Self {
+    pub fn new(value_scale: f64) -> Self {
         Self {
             best_function_values: VecDeque::new(),
             median_function_values: VecDeque::new(),
             current_best_individual: None,
             overall_best_individual: None,
+            current_quartiles: None,
             first_median_function_value: None,
             best_median_function_value: None,
+            value_scale,
+            low_diversity_generations: 0,
+            flat_generations: 0,
+            generations_without_improvement: 0,
+            sigma_values: VecDeque::new(),
+            trajectory: VecDeque::new(),
+            best_value_evals: VecDeque::new(),
+            peak_improvement: 0.0,
+            last_mean_value: None,
+            mean_differences: 0,
+            mean_difference_average: 0.0,
+            mean_difference_variance: 0.0,
+            improvement_generations: 0,
+            improvement_fraction: 0.0,
         }
     }
 
@@ -58,9 +146,20 @@ impl History {
     }
 
     /// Always `Some` if `Self::update` has been called at least once
+    ///
+    /// Unlike the histories, these values are not scaled
+    pub fn current_quartiles(&self) -> Option<Quartiles> {
+        self.current_quartiles
+    }
+
+    /// Always `Some` if `Self::update` has been called at least once
+    ///
+    /// Unlike the histories, this value is not scaled
     #[cfg(feature = "plotters")]
     pub fn current_median_function_value(&self) -> Option<f64> {
-        self.median_function_values.get(0).cloned()
+        self.median_function_values
+            .get(0)
+            .map(|value| value * self.value_scale)
     }
 
     /// Always `Some` if `Self::update` has been called at least once
@@ -78,12 +177,13 @@ impl History {
     pub fn update(&mut self, mode: Mode, current_generation: &[EvaluatedPoint]) {
         let best = &current_generation[0];
 
-        self.best_function_values.push_front(best.value());
+        self.best_function_values
+            .push_front(best.value() / self.value_scale);
         if self.best_function_values.len() > MAX_HISTORY_LENGTH {
             self.best_function_values.pop_back();
         }
 
-        let median_value = get_median_value(current_generation);
+        let median_value = get_median_value(current_generation) / self.value_scale;
         self.median_function_values.push_front(median_value);
         if self.median_function_values.len() > MAX_HISTORY_LENGTH {
             self.median_function_values.pop_back();
@@ -91,14 +191,151 @@ impl History {
 
         self.first_median_function_value = self.first_median_function_value.or(Some(median_value));
 
+        self.current_quartiles = Some(Quartiles::new(
+            current_generation.iter().map(|p| p.value()).collect(),
+        ));
+
+        if current_generation
+            .iter()
+            .all(|individual| individual.value() == best.value())
+        {
+            self.flat_generations += 1;
+        } else {
+            self.flat_generations = 0;
+        }
+
         match self.best_median_function_value {
             Some(ref mut value) => *value = mode.choose_best(*value, median_value),
             None => self.best_median_function_value = Some(median_value),
         }
 
+        self.update_improvement_fraction(mode, current_generation);
         self.update_best_individuals(mode, Individual::new(best.point().clone(), best.value()));
     }
 
+    /// Records the step size that the latest generation was sampled with
+    pub fn update_sigma(&mut self, sigma: f64) {
+        self.sigma_values.push_front(sigma);
+        if self.sigma_values.len() > MAX_HISTORY_LENGTH {
+            self.sigma_values.pop_back();
+        }
+    }
+
+    /// Returns the average rate at which the step size decreased per generation over the latest
+    /// `window` generations (`ln(old_sigma / sigma) / window`, negative if it increased), or `None`
+    /// if fewer than `window + 1` generations have been recorded
+    pub fn sigma_decay_rate(&self, window: usize) -> Option<f64> {
+        let latest = self.sigma_values.front()?;
+        let earlier = self.sigma_values.get(window)?;
+        Some((earlier / latest).ln() / window as f64)
+    }
+
+    /// Returns the average rate at which the best function value found so far improved per
+    /// generation over the latest `window` generations, relative to its magnitude and on the
+    /// same logarithmic scale as `Self::sigma_decay_rate` (`ln(old_best / best) / window` for
+    /// positive values when minimizing), or `None` if fewer than `window + 1` generations have
+    /// been recorded
+    ///
+    /// The rate is infinite if the best value improved by more than its own magnitude (e.g.
+    /// crossed zero)
+    pub fn best_value_improvement_rate(&self, mode: Mode, window: usize) -> Option<f64> {
+        if self.best_function_values.len() <= window {
+            return None;
+        }
+
+        let best = |values: &mut dyn Iterator<Item = &f64>| {
+            values.fold(None, |best, &value| match best {
+                Some(best) => Some(mode.choose_best(best, value)),
+                None => Some(value),
+            })
+        };
+        let earlier = best(&mut self.best_function_values.iter().skip(window))?;
+        let latest = mode.choose_best(
+            earlier,
+            best(&mut self.best_function_values.iter().take(window)).unwrap_or(earlier),
+        );
+
+        let improvement = match mode {
+            Mode::Minimize => earlier - latest,
+            Mode::Maximize => latest - earlier,
+        };
+        let relative_improvement = if improvement > 0.0 {
+            improvement / earlier.abs()
+        } else {
+            0.0
+        };
+
+        Some(if relative_improvement < 1.0 {
+            (1.0 / (1.0 - relative_improvement)).ln() / window as f64
+        } else {
+            f64::INFINITY
+        })
+    }
+
+    /// Returns the ratio between `Self::sigma_decay_rate` and `Self::best_value_improvement_rate`
+    /// over the latest `window` generations (`0.0` if the step size did not decrease and infinite
+    /// if it did but the best value did not improve), or `None` if fewer than `window + 1`
+    /// generations have been recorded
+    pub fn sigma_decay_ratio(&self, mode: Mode, window: usize) -> Option<f64> {
+        let sigma_decay_rate = self.sigma_decay_rate(window)?;
+        let improvement_rate = self.best_value_improvement_rate(mode, window)?;
+
+        Some(if sigma_decay_rate > 0.0 {
+            sigma_decay_rate / improvement_rate
+        } else {
+            0.0
+        })
+    }
+
+    /// Records whether the population diversity of the latest generation was below `tol_diversity`
+    pub fn update_diversity(&mut self, below_tol_diversity: bool) {
+        if below_tol_diversity {
+            self.low_diversity_generations += 1;
+        } else {
+            self.low_diversity_generations = 0;
+        }
+    }
+
+    /// Returns the number of consecutive generations in which the population diversity was below
+    /// `tol_diversity`
+    pub fn low_diversity_generations(&self) -> usize {
+        self.low_diversity_generations
+    }
+
+    /// Returns the number of consecutive generations in which every individual had the same
+    /// function value
+    pub fn flat_generations(&self) -> usize {
+        self.flat_generations
+    }
+
+    /// Updates the estimate of the improvement probability with the fraction of individuals in
+    /// `current_generation` that are better than the overall best individual so far. Must be
+    /// called before the best individuals are updated
+    ///
+    /// The fractions are averaged with equal weights at first and with exponentially decaying
+    /// weights once there are enough of them
+    fn update_improvement_fraction(&mut self, mode: Mode, current_generation: &[EvaluatedPoint]) {
+        if let Some(ref overall_best) = self.overall_best_individual {
+            let improved = current_generation
+                .iter()
+                .filter(|individual| mode.is_better(individual.value(), overall_best.value))
+                .count();
+            let fraction = improved as f64 / current_generation.len() as f64;
+
+            self.improvement_generations += 1;
+            let weight =
+                (1.0 / self.improvement_generations as f64).max(IMPROVEMENT_FRACTION_WEIGHT);
+            self.improvement_fraction += weight * (fraction - self.improvement_fraction);
+        }
+    }
+
+    /// Returns the estimated probability that an individual improves on the overall best
+    /// individual, or `None` if fewer than two generations have been compared against a previous
+    /// overall best individual
+    pub fn improvement_probability(&self) -> Option<f64> {
+        (self.improvement_generations >= 2).then_some(self.improvement_fraction)
+    }
+
     /// Updates the current and overall best individuals
     fn update_best_individuals(&mut self, mode: Mode, current_best: Individual) {
         self.current_best_individual = Some(current_best.clone());
@@ -107,17 +344,113 @@ impl History {
             Some(ref mut overall) => {
                 if mode.is_better(current_best.value, overall.value) {
                     *overall = current_best;
+                    self.generations_without_improvement = 0;
+                } else {
+                    self.generations_without_improvement += 1;
                 }
             }
             None => self.overall_best_individual = Some(current_best),
         }
     }
 
+    /// Records the progress of the latest generation, discarding the oldest trajectory entry if
+    /// more than `max_len` entries are stored
+    pub fn update_trajectory(&mut self, entry: TrajectoryEntry, max_len: Option<usize>) {
+        self.best_value_evals
+            .push_front((entry.function_evals, entry.best_value / self.value_scale));
+        if self.best_value_evals.len() > MAX_HISTORY_LENGTH {
+            self.best_value_evals.pop_back();
+        }
+
+        self.trajectory.push_back(entry);
+        if let Some(max_len) = max_len {
+            if self.trajectory.len() > max_len {
+                self.trajectory.pop_front();
+            }
+        }
+    }
+
+    /// Returns the recorded progress of each generation, from oldest to newest
+    pub fn trajectory(&self) -> &VecDeque<TrajectoryEntry> {
+        &self.trajectory
+    }
+
+    /// Returns the number of function evaluations performed by the end of each generation and the
+    /// overall best function value at that point (most recent first)
+    pub fn best_value_evals(&self) -> &VecDeque<(usize, f64)> {
+        &self.best_value_evals
+    }
+
+    /// Updates the peak improvement with the improvement of the overall best function value over
+    /// the latest `window` generations (or all generations recorded so far if there are fewer)
+    ///
+    /// The overall best function value changes monotonically, so this measures actual progress
+    /// and is not affected by how much the best values of individual generations scatter
+    pub fn update_peak_improvement(&mut self, window: usize) {
+        let latest = self.best_value_evals.front().map(|&(_, value)| value);
+        let earlier = self
+            .best_value_evals
+            .get(window)
+            .or_else(|| self.best_value_evals.back())
+            .map(|&(_, value)| value);
+
+        if let (Some(latest), Some(earlier)) = (latest, earlier) {
+            self.peak_improvement = self.peak_improvement.max((latest - earlier).abs());
+        }
+    }
+
+    /// Returns the largest improvement of the overall best function value over any window of
+    /// consecutive generations so far, which is never forgotten in long runs (`0.0` before the
+    /// first generation)
+    pub fn peak_improvement(&self) -> f64 {
+        self.peak_improvement
+    }
+
+    /// Returns the number of consecutive generations in which the overall best function value did
+    /// not improve
+    pub fn generations_without_improvement(&self) -> usize {
+        self.generations_without_improvement
+    }
+
+    /// Updates the estimate of the objective function noise with the function value at the mean
+    /// of the latest generation
+    ///
+    /// The differences between consecutive values are averaged with equal weights at first and
+    /// with exponentially decaying weights once there are enough of them
+    pub fn update_mean_value(&mut self, value: f64) {
+        if let Some(last_value) = self.last_mean_value {
+            self.mean_differences += 1;
+            let weight = (1.0 / self.mean_differences as f64).max(MEAN_DIFFERENCE_WEIGHT);
+
+            let deviation = (value - last_value) - self.mean_difference_average;
+            let increment = weight * deviation;
+            self.mean_difference_average += increment;
+            self.mean_difference_variance =
+                (1.0 - weight) * (self.mean_difference_variance + deviation * increment);
+        }
+
+        self.last_mean_value = Some(value);
+    }
+
+    /// Returns the estimated variance of the objective function noise, or `None` if fewer than
+    /// two differences between the function values at consecutive means have been recorded
+    ///
+    /// Unlike the histories, this value is not scaled
+    pub fn objective_noise(&self) -> Option<f64> {
+        // The difference of two independent noisy values has twice the variance of the noise
+        (self.mean_differences >= 2).then_some(self.mean_difference_variance / 2.0)
+    }
+
     #[cfg(test)]
     pub fn mut_best_function_values(&mut self) -> &mut VecDeque<f64> {
         &mut self.best_function_values
     }
 
+    #[cfg(test)]
+    pub fn mut_sigma_values(&mut self) -> &mut VecDeque<f64> {
+        &mut self.sigma_values
+    }
+
     #[cfg(test)]
     pub fn mut_median_function_values(&mut self) -> &mut VecDeque<f64> {
         &mut self.median_function_values
@@ -132,6 +465,16 @@ impl History {
     pub fn mut_best_median_function_value(&mut self) -> &mut Option<f64> {
         &mut self.best_median_function_value
     }
+
+    #[cfg(test)]
+    pub fn mut_flat_generations(&mut self) -> &mut usize {
+        &mut self.flat_generations
+    }
+
+    #[cfg(test)]
+    pub fn mut_peak_improvement(&mut self) -> &mut f64 {
+        &mut self.peak_improvement
+    }
 }
 
 /// Returns the median function value of the generation
@@ -148,16 +491,167 @@ fn get_median_value(individuals: &[EvaluatedPoint]) -> f64 {
 
 #[cfg(test)]
 mod tests {
+    use assert_approx_eq::assert_approx_eq;
     use nalgebra::DVector;
 
     use super::*;
+    use crate::sampling::PointMapping;
+
+    #[test]
+    fn test_objective_noise() {
+        let mut history = History::new(1.0);
+        assert!(history.objective_noise().is_none());
+
+        history.update_mean_value(1.0);
+        history.update_mean_value(2.0);
+        assert!(history.objective_noise().is_none());
+
+        // A steady trend does not count as noise
+        history.update_mean_value(3.0);
+        assert_eq!(Some(0.0), history.objective_noise());
+
+        // The differences are 1, 1, -1, and 1, with a variance of 0.75
+        history.update_mean_value(2.0);
+        history.update_mean_value(3.0);
+        assert_approx_eq!(0.375, history.objective_noise().unwrap(), 1e-12);
+    }
+
+    #[test]
+    fn test_sigma_decay_ratio() {
+        let mut history = History::new(1.0);
+        let generation = |value: f64| {
+            vec![EvaluatedPoint::new(
+                DVector::zeros(1),
+                &DVector::zeros(1),
+                1.0,
+                PointMapping::default(),
+                &mut |_: &DVector<f64>| value,
+            )
+            .unwrap()]
+        };
+
+        // The step size halves while the best value quarters in each generation
+        for i in 0..3 {
+            history.update(Mode::Minimize, &generation(0.25f64.powi(i)));
+            history.update_sigma(0.5f64.powi(i));
+        }
+        assert!(history.sigma_decay_rate(3).is_none());
+        assert!(history.sigma_decay_ratio(Mode::Minimize, 3).is_none());
+        assert_approx_eq!(2f64.ln(), history.sigma_decay_rate(2).unwrap(), 1e-12);
+        assert_approx_eq!(
+            4f64.ln(),
+            history
+                .best_value_improvement_rate(Mode::Minimize, 2)
+                .unwrap(),
+            1e-12
+        );
+        assert_approx_eq!(
+            0.5,
+            history.sigma_decay_ratio(Mode::Minimize, 2).unwrap(),
+            1e-12
+        );
+
+        // A worse value does not count as progress
+        history.update(Mode::Minimize, &generation(2.0));
+        history.update_sigma(0.0625);
+        assert_approx_eq!(
+            4f64.ln() / 2.0,
+            history
+                .best_value_improvement_rate(Mode::Minimize, 2)
+                .unwrap(),
+            1e-12
+        );
+
+        // Stalling while the step size decays gives an infinite ratio, crossing zero a ratio of 0
+        history.update(Mode::Minimize, &generation(2.0));
+        history.update_sigma(0.03125);
+        assert_eq!(
+            Some(f64::INFINITY),
+            history.sigma_decay_ratio(Mode::Minimize, 1)
+        );
+        history.update(Mode::Minimize, &generation(-1.0));
+        history.update_sigma(0.015625);
+        assert_eq!(Some(0.0), history.sigma_decay_ratio(Mode::Minimize, 1));
+
+        // A growing step size gives a ratio of 0
+        history.update(Mode::Minimize, &generation(-1.0));
+        history.update_sigma(1.0);
+        assert!(history.sigma_decay_rate(1).unwrap() < 0.0);
+        assert_eq!(Some(0.0), history.sigma_decay_ratio(Mode::Minimize, 1));
+    }
+
+    #[test]
+    fn test_improvement_probability() {
+        let mut history = History::new(1.0);
+        let generation = |values: [f64; 4]| {
+            values.map(|value| {
+                EvaluatedPoint::new(
+                    DVector::zeros(2),
+                    &DVector::zeros(2),
+                    1.0,
+                    PointMapping::default(),
+                    |_: &DVector<f64>| value,
+                )
+                .unwrap()
+            })
+        };
+
+        // The first generation has nothing to improve on
+        history.update(Mode::Minimize, &generation([1.0, 2.0, 3.0, 4.0]));
+        history.update(Mode::Minimize, &generation([0.0, 0.5, 3.0, 4.0]));
+        assert!(history.improvement_probability().is_none());
+
+        // Only the individual better than the new best value of 0 counts
+        history.update(Mode::Minimize, &generation([-1.0, 0.0, 3.0, 4.0]));
+        assert_eq!(Some(0.375), history.improvement_probability());
+
+        let mut history = History::new(1.0);
+        history.update(Mode::Maximize, &generation([4.0, 3.0, 2.0, 1.0]));
+        for _ in 0..100 {
+            history.update(Mode::Maximize, &generation([4.0, 3.0, 2.0, 1.0]));
+        }
+        assert_eq!(Some(0.0), history.improvement_probability());
+    }
+
+    #[test]
+    fn test_peak_improvement() {
+        let mut history = History::new(2.0);
+        let mut record = |best_value: f64| {
+            let entry = TrajectoryEntry {
+                generation: 1,
+                function_evals: 0,
+                best_value,
+                median_value: best_value,
+                sigma: 1.0,
+                axis_ratio: 1.0,
+                mean_norm: 1.0,
+            };
+            history.update_trajectory(entry, None);
+            history.update_peak_improvement(2);
+            history.peak_improvement()
+        };
+
+        // The values are divided by the value scale like the other histories, and fewer
+        // generations than the window are compared with the first one
+        assert_eq!(0.0, record(20.0));
+        assert_eq!(2.0, record(16.0));
+        assert_eq!(5.0, record(10.0));
+        // The peak is kept once progress slows down
+        assert_eq!(5.0, record(8.0));
+        assert_eq!(5.0, record(7.9));
+        assert_eq!(5.0, record(7.9));
+    }
 
     #[test]
     fn test_get_median_value() {
         let get_point = |value| {
-            EvaluatedPoint::new(DVector::zeros(2), &DVector::zeros(2), 1.0, &mut |_: &_| {
-                value
-            })
+            EvaluatedPoint::new(
+                DVector::zeros(2),
+                &DVector::zeros(2),
+                1.0,
+                PointMapping::default(),
+                &mut |_: &_| value,
+            )
             .unwrap()
         };
         let get_generation =
@@ -174,7 +668,7 @@ mod tests {
     }
 
     fn update_shared(mode: Mode, expected_best: f64, expected_median: f64) {
-        let mut history = History::new();
+        let mut history = History::new(1.0);
 
         assert!(history.best_function_values().is_empty());
         assert!(history.median_function_values().is_empty());
@@ -190,10 +684,22 @@ mod tests {
 
         let mut update = |h: &mut History| {
             let mut generation = [
-                EvaluatedPoint::new(DVector::zeros(4), &DVector::zeros(4), 0.0, &mut function)
-                    .unwrap(),
-                EvaluatedPoint::new(DVector::zeros(4), &DVector::zeros(4), 0.0, &mut function)
-                    .unwrap(),
+                EvaluatedPoint::new(
+                    DVector::zeros(4),
+                    &DVector::zeros(4),
+                    0.0,
+                    PointMapping::default(),
+                    &mut function,
+                )
+                .unwrap(),
+                EvaluatedPoint::new(
+                    DVector::zeros(4),
+                    &DVector::zeros(4),
+                    0.0,
+                    PointMapping::default(),
+                    &mut function,
+                )
+                .unwrap(),
             ];
 
             generation.sort_by(|a, b| mode.sort_cmp(a.value(), b.value()));
@@ -242,9 +748,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_flat_generations() {
+        let mut history = History::new(1.0);
+        let generation = |values: [f64; 3]| {
+            values.map(|value| {
+                EvaluatedPoint::new(
+                    DVector::zeros(2),
+                    &DVector::zeros(2),
+                    1.0,
+                    PointMapping::default(),
+                    |_: &DVector<f64>| value,
+                )
+                .unwrap()
+            })
+        };
+
+        history.update(Mode::Minimize, &generation([1.0; 3]));
+        history.update(Mode::Minimize, &generation([2.0; 3]));
+        assert_eq!(2, history.flat_generations());
+
+        history.update(Mode::Minimize, &generation([1.0, 1.0, 1.0 + 1e-15]));
+        assert_eq!(0, history.flat_generations());
+
+        history.update(Mode::Minimize, &generation([0.0; 3]));
+        assert_eq!(1, history.flat_generations());
+    }
+
+    #[test]
+    fn test_update_value_scale() {
+        let mut history = History::new(10.0);
+        let mut function = |x: &DVector<f64>| 100.0 + x.sum();
+
+        let generation = [0.0, 1.0, 2.0].map(|x| {
+            EvaluatedPoint::new(
+                DVector::from(vec![x]),
+                &DVector::zeros(1),
+                1.0,
+                PointMapping::default(),
+                &mut function,
+            )
+            .unwrap()
+        });
+        history.update(Mode::Minimize, &generation);
+
+        // The histories are scaled, but the best individuals are not
+        assert_eq!(Some(&10.0), history.best_function_values().front());
+        assert_eq!(Some(&10.1), history.median_function_values().front());
+        assert_eq!(Some(10.1), history.first_median_function_value());
+        assert_eq!(Some(10.1), history.best_median_function_value());
+        assert_eq!(100.0, history.current_best_individual().unwrap().value);
+        assert_eq!(100.0, history.overall_best_individual().unwrap().value);
+    }
+
     #[test]
     fn test_update_best_individuals_minimize() {
-        let mut history = History::new();
+        let mut history = History::new(1.0);
         let mode = Mode::Minimize;
 
         assert!(history.current_best_individual().is_none());
@@ -258,7 +817,7 @@ mod tests {
 
     #[test]
     fn test_update_best_individuals_maximize() {
-        let mut history = History::new();
+        let mut history = History::new(1.0);
         let mode = Mode::Maximize;
 
         assert!(history.current_best_individual().is_none());
@@ -280,17 +839,21 @@ mod tests {
             }
             counter
         };
-        let mut history = History::new();
+        let mut history = History::new(1.0);
 
         assert!(history.first_median_function_value().is_none());
         assert!(history.best_median_function_value().is_none());
 
         history.update(
             mode,
-            &[
-                EvaluatedPoint::new(DVector::zeros(4), &DVector::zeros(4), 0.0, &mut function)
-                    .unwrap(),
-            ],
+            &[EvaluatedPoint::new(
+                DVector::zeros(4),
+                &DVector::zeros(4),
+                0.0,
+                PointMapping::default(),
+                &mut function,
+            )
+            .unwrap()],
         );
 
         // For the first generation both values are equal
@@ -301,10 +864,14 @@ mod tests {
 
         history.update(
             mode,
-            &[
-                EvaluatedPoint::new(DVector::zeros(4), &DVector::zeros(4), 0.0, &mut function)
-                    .unwrap(),
-            ],
+            &[EvaluatedPoint::new(
+                DVector::zeros(4),
+                &DVector::zeros(4),
+                0.0,
+                PointMapping::default(),
+                &mut function,
+            )
+            .unwrap()],
         );
 
         // For subsequent generations only the best value improves
@@ -332,14 +899,18 @@ mod tests {
             }
             counter
         };
-        let mut history = History::new();
+        let mut history = History::new(1.0);
 
         history.update(
             mode,
-            &[
-                EvaluatedPoint::new(DVector::zeros(4), &DVector::zeros(4), 0.0, &mut function)
-                    .unwrap(),
-            ],
+            &[EvaluatedPoint::new(
+                DVector::zeros(4),
+                &DVector::zeros(4),
+                0.0,
+                PointMapping::default(),
+                &mut function,
+            )
+            .unwrap()],
         );
 
         // For the first generation both values are equal
@@ -348,10 +919,14 @@ mod tests {
 
         history.update(
             mode,
-            &[
-                EvaluatedPoint::new(DVector::zeros(4), &DVector::zeros(4), 0.0, &mut function)
-                    .unwrap(),
-            ],
+            &[EvaluatedPoint::new(
+                DVector::zeros(4),
+                &DVector::zeros(4),
+                0.0,
+                PointMapping::default(),
+                &mut function,
+            )
+            .unwrap()],
         );
 
         // For subsequent generations neither value changes because the median is increasing
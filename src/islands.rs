@@ -0,0 +1,381 @@
+//! Types for running multiple CMA-ES instances that periodically exchange their best individuals
+//! (an island model). Useful for highly multimodal problems, where independent populations explore
+//! different regions of the search space while still sharing progress. See [`Islands`] for full
+//! documentation.
+
+use rayon::prelude::*;
+
+use std::fmt::{self, Debug};
+
+use crate::{Individual, ObjectiveFunction, TerminationData, CMAES};
+
+/// The topology that determines which islands receive the best individual of each island during
+/// migration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// Each island sends its best individual to the next island, with the last island sending to
+    /// the first.
+    #[default]
+    Ring,
+    /// Each island sends its best individual to every other island.
+    FullyConnected,
+}
+
+impl Topology {
+    /// Returns the indices of the islands that island `index` sends its best individual to
+    fn targets(&self, index: usize, num_islands: usize) -> Vec<usize> {
+        match self {
+            Topology::Ring => vec![(index + 1) % num_islands],
+            Topology::FullyConnected => (0..num_islands).collect(),
+        }
+        .into_iter()
+        .filter(|&target| target != index)
+        .collect()
+    }
+}
+
+/// An error produced when creating an [`Islands`] with invalid arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidIslandsError {
+    /// No islands were provided.
+    NoIslands,
+    /// The migration interval is zero.
+    MigrationInterval,
+    /// The islands have different numbers of dimensions or optimization modes.
+    Mismatch,
+}
+
+/// Several independent [`CMAES`] instances (islands) that evolve side by side and periodically
+/// exchange their best individuals (migration).
+///
+/// Every `migration_interval` generations, the overall best individual of each island is sent to
+/// the islands given by the [`Topology`] and is injected into their next generation (see
+/// [`CMAES::inject`]). Islands that have already terminated still send migrants but no longer
+/// receive them. The islands may be created with different options (e.g. different initial means
+/// or population sizes), but they must have the same number of dimensions and the same
+/// optimization mode.
+///
+/// Migration lets a promising region found by one island attract the others, so the number of
+/// islands, the migration interval, and the topology control the balance between exploring
+/// separate regions and exploiting the best one found so far. A sparse topology (`Ring`) and a long
+/// interval preserve diversity for longer.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::islands::{Islands, Topology};
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let function = |x: &DVector<f64>| x.magnitude();
+/// let islands = [-5.0, 0.0, 5.0]
+///     .iter()
+///     .map(|&start| {
+///         CMAESOptions::new(vec![start; 4], 1.0)
+///             .build(function)
+///             .unwrap()
+///     })
+///     .collect();
+/// let mut islands = Islands::new(islands, 10, Topology::Ring).unwrap();
+///
+/// let results = islands.run_parallel();
+///
+/// assert_eq!(3, results.len());
+/// assert!(islands.global_best().unwrap().value < 1e-6);
+/// ```
+pub struct Islands<F> {
+    /// The CMA-ES instances
+    islands: Vec<CMAES<F>>,
+    /// The termination data of each island, if it has terminated
+    results: Vec<Option<TerminationData>>,
+    /// The number of generations between migrations
+    migration_interval: usize,
+    /// Which islands receive the migrants of each island
+    topology: Topology,
+    /// The number of generations that have been run
+    generation: usize,
+    /// The generation after which the next migration is performed
+    next_migration: usize,
+}
+
+impl<F> Islands<F> {
+    /// Creates a new `Islands` from the islands to run, the number of generations between
+    /// migrations, and the migration topology.
+    ///
+    /// Returns `Err` if no islands are provided, if `migration_interval` is zero, or if the islands
+    /// have different numbers of dimensions or optimization modes.
+    pub fn new(
+        islands: Vec<CMAES<F>>,
+        migration_interval: usize,
+        topology: Topology,
+    ) -> Result<Self, InvalidIslandsError> {
+        let first = match islands.first() {
+            Some(first) => first.parameters(),
+            None => return Err(InvalidIslandsError::NoIslands),
+        };
+
+        if migration_interval == 0 {
+            return Err(InvalidIslandsError::MigrationInterval);
+        }
+
+        if islands.iter().any(|island| {
+            island.parameters().dim() != first.dim() || island.parameters().mode() != first.mode()
+        }) {
+            return Err(InvalidIslandsError::Mismatch);
+        }
+
+        Ok(Self {
+            results: vec![None; islands.len()],
+            islands,
+            migration_interval,
+            topology,
+            generation: 0,
+            next_migration: migration_interval,
+        })
+    }
+
+    /// Returns the islands.
+    pub fn islands(&self) -> &[CMAES<F>] {
+        &self.islands
+    }
+
+    /// Returns the number of generations that have been run (counting a generation in which only
+    /// some of the islands were still running).
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Returns whether every island has terminated.
+    pub fn is_finished(&self) -> bool {
+        self.results.iter().all(Option::is_some)
+    }
+
+    /// Returns the overall best individual of each island, in the order the islands were provided.
+    pub fn island_bests(&self) -> Vec<Option<&Individual>> {
+        self.islands
+            .iter()
+            .map(|island| island.overall_best_individual())
+            .collect()
+    }
+
+    /// Returns the best individual found by any island.
+    pub fn global_best(&self) -> Option<&Individual> {
+        let mode = self.islands[0].parameters().mode();
+
+        self.island_bests()
+            .into_iter()
+            .flatten()
+            .fold(None, |best, individual| match best {
+                Some(best) if !mode.is_better(individual.value, best.value) => Some(best),
+                _ => Some(individual),
+            })
+    }
+
+    /// Consumes `self` and returns the islands.
+    pub fn into_islands(self) -> Vec<CMAES<F>> {
+        self.islands
+    }
+
+    /// Sends the best individual of each island to its targets if a migration is due
+    fn migrate(&mut self) {
+        if self.generation < self.next_migration {
+            return;
+        }
+        self.next_migration += self.migration_interval;
+
+        let num_islands = self.islands.len();
+        let migrants = self
+            .island_bests()
+            .into_iter()
+            .map(|best| best.map(|individual| individual.point.clone()))
+            .collect::<Vec<_>>();
+
+        for (index, migrant) in migrants.into_iter().enumerate() {
+            if let Some(migrant) = migrant {
+                for target in self.topology.targets(index, num_islands) {
+                    if self.results[target].is_none() {
+                        self.islands[target].inject([migrant.clone()]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Shared logic between `next` and `next_parallel`
+    fn next_internal(&mut self) -> Option<Vec<TerminationData>> {
+        self.generation += 1;
+
+        if self.is_finished() {
+            return Some(self.results.iter().flatten().cloned().collect());
+        }
+
+        self.migrate();
+        None
+    }
+}
+
+impl<F: ObjectiveFunction> Islands<F> {
+    /// Runs all islands until every island has terminated, migrating individuals between them
+    /// every `migration_interval` generations. Returns the termination data of each island, in the
+    /// order the islands were provided.
+    pub fn run(&mut self) -> Vec<TerminationData> {
+        loop {
+            if let Some(results) = self.next() {
+                break results;
+            }
+        }
+    }
+
+    /// Advances each running island by one generation and performs migration if it is due.
+    /// Returns the termination data of each island once every island has terminated.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<Vec<TerminationData>> {
+        for (island, result) in self.islands.iter_mut().zip(self.results.iter_mut()) {
+            next_island(island, result);
+        }
+
+        self.next_internal()
+    }
+}
+
+impl<F: ObjectiveFunction + Send> Islands<F> {
+    /// Like [`run`][Self::run], but runs the islands in parallel using multiple threads (each
+    /// island evaluates its own points on a single thread).
+    ///
+    /// Uses [rayon][rayon] internally.
+    pub fn run_parallel(&mut self) -> Vec<TerminationData> {
+        loop {
+            if let Some(results) = self.next_parallel() {
+                break results;
+            }
+        }
+    }
+
+    /// Like [`next`][Self::next], but advances the islands in parallel using multiple threads.
+    pub fn next_parallel(&mut self) -> Option<Vec<TerminationData>> {
+        self.islands
+            .par_iter_mut()
+            .zip(self.results.par_iter_mut())
+            .for_each(|(island, result)| next_island(island, result));
+
+        self.next_internal()
+    }
+}
+
+impl<F> Debug for Islands<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Islands")
+            .field("num_islands", &self.islands.len())
+            .field("migration_interval", &self.migration_interval)
+            .field("topology", &self.topology)
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Advances `island` by one generation if it has not terminated, storing its termination data in
+/// `result` once it does
+fn next_island<F: ObjectiveFunction>(island: &mut CMAES<F>, result: &mut Option<TerminationData>) {
+    if result.is_none() {
+        if let Some(data) = island.next() {
+            island.run_internal(&data);
+            *result = Some(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DVector;
+
+    use super::*;
+    use crate::{CMAESOptions, Mode};
+
+    fn sphere(x: &DVector<f64>) -> f64 {
+        x.magnitude_squared()
+    }
+
+    #[test]
+    fn test_topology_targets() {
+        assert_eq!(vec![1], Topology::Ring.targets(0, 3));
+        assert_eq!(vec![0], Topology::Ring.targets(2, 3));
+        assert!(Topology::Ring.targets(0, 1).is_empty());
+        assert_eq!(vec![0, 2], Topology::FullyConnected.targets(1, 3));
+        assert!(Topology::FullyConnected.targets(0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_islands() {
+        let island = |dim, mode| {
+            CMAESOptions::new(vec![1.0; dim], 1.0)
+                .mode(mode)
+                .build(sphere as fn(&DVector<f64>) -> f64)
+                .unwrap()
+        };
+
+        assert_eq!(
+            InvalidIslandsError::NoIslands,
+            Islands::<fn(&DVector<f64>) -> f64>::new(Vec::new(), 1, Topology::Ring).unwrap_err(),
+        );
+        assert_eq!(
+            InvalidIslandsError::MigrationInterval,
+            Islands::new(vec![island(2, Mode::Minimize)], 0, Topology::Ring).unwrap_err(),
+        );
+        assert_eq!(
+            InvalidIslandsError::Mismatch,
+            Islands::new(
+                vec![island(2, Mode::Minimize), island(3, Mode::Minimize)],
+                1,
+                Topology::Ring,
+            )
+            .unwrap_err(),
+        );
+        assert_eq!(
+            InvalidIslandsError::Mismatch,
+            Islands::new(
+                vec![island(2, Mode::Minimize), island(2, Mode::Maximize)],
+                1,
+                Topology::Ring,
+            )
+            .unwrap_err(),
+        );
+    }
+
+    #[test]
+    fn test_migration() {
+        // One island starts at the optimum, so after the first migration, every island has
+        // evaluated a point at least as good as the best individual of that island
+        let islands = [0.0, 50.0, 100.0]
+            .iter()
+            .map(|&start| {
+                CMAESOptions::new(vec![start; 3], 1e-3)
+                    .max_generations(5)
+                    .seed(1)
+                    .build(sphere as fn(&DVector<f64>) -> f64)
+                    .unwrap()
+            })
+            .collect();
+        let mut islands = Islands::new(islands, 1, Topology::FullyConnected).unwrap();
+
+        assert!(islands.next().is_none());
+        assert_eq!(1, islands.generation());
+        assert!(islands
+            .islands()
+            .iter()
+            .all(|island| island.num_injected() == 2));
+
+        let migrant = islands.island_bests()[0].unwrap().value;
+        assert!(migrant < 1e-4);
+        assert!(islands.island_bests()[2].unwrap().value > 1e4);
+
+        assert!(islands.next().is_none());
+        assert!(islands
+            .island_bests()
+            .iter()
+            .all(|island_best| island_best.unwrap().value < migrant + 1e-12));
+        assert!(islands.global_best().unwrap().value < migrant + 1e-12);
+
+        let results = islands.run();
+        assert_eq!(3, results.len());
+        assert!(islands.is_finished());
+    }
+}
@@ -36,6 +36,9 @@
 //!     .unwrap();
 //!
 //! let results = cmaes_state.run();
+//!
+//! // The best point found and its function value
+//! let (solution, value) = cmaes_state.best().unwrap();
 //! ```
 //!
 //! For many problems, it is useful to perform multiple independent restarts of the algorithm with
@@ -75,6 +78,28 @@
 //!
 //! The [`CMAES::next`] method provides finer control over iteration if needed.
 //!
+//! # Features
+//!
+//! The following Cargo features are enabled by default:
+//!
+//! - `plotters`: data plots ([`Plot`], [`PlotOptions`], and [`CMAES::get_plot`]), which require
+//!   the [plotters](https://docs.rs/plotters) crate and FreeType.
+//! - `statrs`: samples the normal distribution using the [statrs](https://docs.rs/statrs) crate.
+//!   Without it, a small built-in sampler is used instead. Both sample the same distribution, but
+//!   they produce different samples (and therefore different runs) for the same seed.
+//!
+//! The core optimizer is always available, so `default-features = false` provides a minimal
+//! dependency footprint (e.g. for WebAssembly). Parallel evaluation still uses [rayon][rayon],
+//! which falls back to sequential execution when threads are unavailable.
+//!
+//! The following features are optional:
+//!
+//! - `serde`: (de)serialization of configs (`CMAESConfig`) and recorded samples, and JSON
+//!   snapshots of the parameters and state (`CMAES::to_json`).
+//! - `unstable`: constructing a [`CMAES`] from a hand-crafted state (`CMAES::from_parts`). Exempt
+//!   from semantic versioning.
+//! - `openblas`, `netlib`, `accelerate`, `intel-mkl`: use a LAPACK implementation for nalgebra.
+//!
 //! # Citations
 //!
 //! The following contain more detailed information on the algorithms implemented by this library
@@ -103,44 +128,82 @@
 //
 // Automatic restart algorithms are contained in the `restart` module.
 
+mod auto_population;
+pub mod bounds;
+mod cache;
+#[cfg(feature = "serde")]
+pub mod config;
 pub mod functions;
+pub mod grad;
 mod history;
+pub mod islands;
 mod matrix;
 mod mode;
 pub mod objective_function;
 pub mod options;
+pub mod parallel;
 pub mod parameters;
 #[cfg(feature = "plotters")]
 pub mod plotting;
+pub mod recording;
 pub mod restart;
 mod sampling;
+pub mod selftest;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "unstable")]
+pub mod state;
+#[cfg(not(feature = "unstable"))]
 mod state;
+mod status;
+pub mod sweep;
 pub mod termination;
+pub mod transform;
 mod utils;
 
 pub use nalgebra::DVector;
 
+pub use crate::bounds::{BoundaryHandling, Bounds};
+pub use crate::cache::CacheStats;
+#[cfg(feature = "serde")]
+pub use crate::config::CMAESConfig;
 pub use crate::functions::*;
 pub use crate::history::MAX_HISTORY_LENGTH;
 pub use crate::mode::Mode;
-pub use crate::objective_function::{ObjectiveFunction, ParallelObjectiveFunction};
+pub use crate::objective_function::{
+    BatchObjectiveFunction, ObjectiveFunction, ParallelObjectiveFunction, PartialObjectiveFunction,
+};
 pub use crate::options::CMAESOptions;
-pub use crate::parameters::Weights;
+pub use crate::parallel::parallel_restarts;
+pub use crate::parameters::{AdaptivePopulation, AutoKick, RecombinationWeighting, Weights};
 #[cfg(feature = "plotters")]
 pub use crate::plotting::PlotOptions;
-pub use crate::termination::TerminationReason;
+pub use crate::recording::GenerationSamples;
+pub use crate::sampling::{Aggregation, EvaluatedPoint};
+pub use crate::sweep::sweep;
+pub use crate::termination::{
+    InvalidPolicy, StateComponent, TargetMode, TerminationDetail, TerminationPreset,
+    TerminationReason,
+};
+pub use crate::transform::{CoordinateTransform, FitnessTransform};
 
+use std::collections::HashMap;
 use std::f64;
+use std::io::{self, Write};
+use std::sync::mpsc::Sender;
 use std::time::{Duration, Instant};
 
-use crate::history::History;
+use crate::auto_population::PopulationController;
+use crate::history::{History, TrajectoryEntry};
 use crate::matrix::SquareMatrix;
-use crate::options::InvalidOptionsError;
-use crate::parameters::Parameters;
+use crate::options::{CmSchedule, ImprovementCallback, InvalidOptionsError};
+use crate::parameters::{Parameters, TerminationParameters};
 #[cfg(feature = "plotters")]
 use crate::plotting::Plot;
-use crate::sampling::{EvaluatedPoint, InvalidFunctionValueError, Sampler};
-use crate::state::State;
+use crate::recording::CovarianceRecorder;
+use crate::sampling::{InvalidFunctionValueError, Sampler};
+use crate::state::{State, UpdateError};
+use crate::status::LiveStatus;
 use crate::termination::TerminationCheck;
 
 /// An individual point with its corresponding objective function value.
@@ -156,6 +219,71 @@ impl Individual {
     }
 }
 
+/// Summary statistics of the eigenvalues of the covariance matrix. Obtained by calling
+/// [`CMAES::eigenvalue_stats`].
+///
+/// Comparing these over time shows whether the distribution is shrinking uniformly or whether
+/// individual axes are collapsing (the full spectrum is also recorded by [`Plot`] if enabled).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EigenStats {
+    /// The smallest eigenvalue.
+    pub min: f64,
+    /// The largest eigenvalue.
+    pub max: f64,
+    /// The median eigenvalue.
+    pub median: f64,
+}
+
+/// The quartiles of the objective function values of a generation. Obtained by calling
+/// [`CMAES::function_value_quartiles`].
+///
+/// The quartiles are computed by linear interpolation between the closest values, so `median` is
+/// the same as the median recorded in the function value history.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quartiles {
+    /// The first quartile (25th percentile).
+    pub lower: f64,
+    /// The median (50th percentile).
+    pub median: f64,
+    /// The third quartile (75th percentile).
+    pub upper: f64,
+}
+
+impl Quartiles {
+    /// Computes the quartiles of `values`, which must be non-empty
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_unstable_by(f64::total_cmp);
+        Self {
+            lower: utils::quantile(&values, 0.25),
+            median: utils::quantile(&values, 0.5),
+            upper: utils::quantile(&values, 0.75),
+        }
+    }
+
+    /// Returns the interquartile range (`upper - lower`), which is a cheap, outlier-resistant
+    /// estimate of the spread of the function values.
+    pub fn interquartile_range(&self) -> f64 {
+        self.upper - self.lower
+    }
+}
+
+/// A lightweight summary of the state after a generation, sent by [`CMAES::run_streaming`].
+///
+/// Unlike [`CMAES`] itself, it does not borrow anything and is cheap to copy, so it can be sent
+/// to other threads (e.g. to the event loop of a UI).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GenerationSnapshot {
+    /// The number of the generation (see [`CMAES::generation`]).
+    pub generation: usize,
+    /// The number of function evaluations used so far (see [`CMAES::function_evals`]).
+    pub function_evals: usize,
+    /// The best function value found so far, or `None` if no valid function value has been found
+    /// yet (see [`CMAES::overall_best_individual`]).
+    pub best_value: Option<f64>,
+    /// The step size of the distribution (see [`CMAES::sigma`]).
+    pub sigma: f64,
+}
+
 /// Data returned when the algorithm terminates.
 ///
 /// Contains the:
@@ -165,7 +293,14 @@ impl Individual {
 /// - Final mean, which may be better than either individual
 /// - Reasons for termination, which can be used to decide how to interpret the result and
 /// whether and how to restart the algorithm
+/// - Details of each reason (the measured quantity and threshold), which are useful for logging
+/// - Number of function evaluations used and the optimization mode, which are used by
+///   [`compare`][Self::compare]
+/// - Metadata of the run, which can be used to group results
+///
+/// Fields may be added in future versions, so this struct can only be constructed by this crate.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct TerminationData {
     /// Always `Some` unless the algorithm terminated in the first generation with
     /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
@@ -175,6 +310,151 @@ pub struct TerminationData {
     pub overall_best: Option<Individual>,
     pub final_mean: DVector<f64>,
     pub reasons: Vec<TerminationReason>,
+    /// The same reasons as `reasons` (in the same order), each along with the measured quantity
+    /// and threshold of its termination criterion (if any). Useful for logging.
+    pub details: Vec<TerminationDetail>,
+    /// The total number of function evaluations used by the run.
+    pub function_evals: usize,
+    /// The optimization mode of the run.
+    pub mode: Mode,
+    /// The metadata attached to the run with [`CMAESOptions::metadata`].
+    pub metadata: HashMap<String, String>,
+}
+
+impl TerminationData {
+    /// Returns a summary comparison between `self` and `other`, for example to analyze the results
+    /// of runs with different options on the same problem. See [`Comparison`].
+    ///
+    /// The results are assumed to come from the same objective function, and the mode of `self`
+    /// is used to decide which value is better.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, Preferred};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let short = CMAESOptions::new(vec![1.0; 2], 1.0)
+    ///     .max_generations(10)
+    ///     .build(function)
+    ///     .unwrap()
+    ///     .run();
+    /// let long = CMAESOptions::new(vec![1.0; 2], 1.0)
+    ///     .build(function)
+    ///     .unwrap()
+    ///     .run();
+    ///
+    /// let comparison = long.compare(&short);
+    ///
+    /// assert_eq!(Preferred::This, comparison.preferred);
+    /// assert!(comparison.evals_ratio.unwrap() > 1.0);
+    ///
+    /// // A run with a larger population size converges to the same point
+    /// let large = CMAESOptions::new(vec![1.0; 2], 1.0)
+    ///     .population_size(20)
+    ///     .build(function)
+    ///     .unwrap()
+    ///     .run();
+    ///
+    /// assert!(long.compare(&large).converged_nearby(1e-6));
+    /// ```
+    pub fn compare(&self, other: &Self) -> Comparison {
+        let (preferred, value_difference) = match (&self.overall_best, &other.overall_best) {
+            (Some(this), Some(other)) => {
+                let preferred = if self.mode.is_better(this.value, other.value) {
+                    Preferred::This
+                } else if self.mode.is_better(other.value, this.value) {
+                    Preferred::Other
+                } else {
+                    Preferred::Neither
+                };
+                (preferred, Some((this.value - other.value).abs()))
+            }
+            (Some(_), None) => (Preferred::This, None),
+            (None, Some(_)) => (Preferred::Other, None),
+            (None, None) => (Preferred::Neither, None),
+        };
+
+        Comparison {
+            preferred,
+            value_difference,
+            evals_ratio: match other.function_evals {
+                0 => None,
+                other_function_evals => {
+                    Some(self.function_evals as f64 / other_function_evals as f64)
+                }
+            },
+            mean_distance: (&self.final_mean - &other.final_mean).magnitude(),
+        }
+    }
+}
+
+/// The outcome of [`CMAES::run_for_evals`].
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum RunStatus {
+    /// The evaluation budget was used up (as far as possible in whole generations) before the
+    /// algorithm terminated. The run can be resumed by calling
+    /// [`run_for_evals`][CMAES::run_for_evals] or [`run`][CMAES::run] again.
+    Paused,
+    /// The algorithm terminated within the evaluation budget.
+    Terminated(TerminationData),
+}
+
+/// Which of two compared results found the better value. Used by [`Comparison`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preferred {
+    /// The result that [`TerminationData::compare`] was called on.
+    This,
+    /// The result passed to [`TerminationData::compare`].
+    Other,
+    /// Both results found the same value (or neither found a value).
+    Neither,
+}
+
+/// A summary comparison between two results. Obtained by calling [`TerminationData::compare`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    /// Which result found the better overall best value. A result with no best individual is
+    /// always worse than a result with one.
+    pub preferred: Preferred,
+    /// The absolute difference between the overall best values, or `None` if either result has no
+    /// best individual.
+    pub value_difference: Option<f64>,
+    /// The number of function evaluations used by `self` divided by the number used by `other`, or
+    /// `None` if `other` used no evaluations. Values below `1.0` mean that `self` used fewer
+    /// evaluations.
+    pub evals_ratio: Option<f64>,
+    /// The Euclidean distance between the final means of the results.
+    pub mean_distance: f64,
+}
+
+impl Comparison {
+    /// Returns whether the final means of the results lie within a distance of `tol` from each
+    /// other, indicating that both runs converged to the same region.
+    pub fn converged_nearby(&self, tol: f64) -> bool {
+        self.mean_distance <= tol
+    }
+}
+
+/// Represents an invalid covariance matrix passed to [`CMAES::set_covariance`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidCovarianceError {
+    /// The matrix is not square or its size does not match the number of dimensions.
+    Dimensions,
+    /// The matrix is not symmetric.
+    NotSymmetric,
+    /// The matrix is not positive-definite or contains non-finite entries.
+    NotPositiveDefinite,
+}
+
+/// Represents invalid evolution paths passed to [`CMAES::set_evolution_paths`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidEvolutionPathsError {
+    /// The length of a path does not match the number of dimensions.
+    Dimensions,
+    /// A path contains non-finite entries.
+    NonFinite,
 }
 
 /// A type that handles algorithm iteration and printing/plotting of results. Use [`CMAESOptions`]
@@ -196,7 +476,7 @@ pub struct TerminationData {
 ///     x
 /// };
 /// let cmaes_state = CMAESOptions::new(vec![0.0; 2], 1.0)
-///     .build(Box::new(function) as _)
+///     .build_boxed(Box::new(function))
 ///     .unwrap();
 /// let container = Container(cmaes_state);
 /// ```
@@ -209,6 +489,33 @@ pub struct CMAES<F> {
     state: State,
     /// Objective function value history
     history: History,
+    /// The mean of the distribution mapped into the feasible space if bounds are enabled
+    feasible_mean: Option<DVector<f64>>,
+    /// The individuals of the latest generation
+    current_population: Vec<EvaluatedPoint>,
+    /// The best individual of the generation before the latest
+    previous_best: Option<EvaluatedPoint>,
+    /// The generation in which the population size was last grown (`0` if never)
+    last_population_growth: usize,
+    /// Changes the population size according to the success rate, if enabled
+    population_controller: Option<PopulationController>,
+    /// The population size at the start of the run and after each change, paired with the number
+    /// of the generation after which it was set
+    population_size_trajectory: Vec<(usize, usize)>,
+    /// The generation of the latest automatic kick (`0` if never)
+    last_kick: usize,
+    /// The number of automatic kicks performed
+    kick_count: usize,
+    /// The generation in which `fun_target` was first reached
+    fun_target_generation: Option<usize>,
+    /// The target values and the number of the function evaluation that first reached each
+    target_hits: Vec<(f64, Option<usize>)>,
+    /// The best evaluated mean of the distribution, if `evaluate_mean` is enabled
+    best_mean_point: Option<Individual>,
+    /// The function values at the means of the latest and the previous generation, if
+    /// `evaluate_mean` is enabled (`None` if a value was invalid)
+    mean_value: Option<f64>,
+    previous_mean_value: Option<f64>,
     /// Data plot if enabled
     #[cfg(feature = "plotters")]
     plot: Option<Plot>,
@@ -217,72 +524,168 @@ pub struct CMAES<F> {
     print_gap_evals: Option<usize>,
     /// The last time [`CMAES::print_info`] was called, in function evaluations
     last_print_evals: usize,
+    /// The live status line, if enabled
+    live_status: Option<LiveStatus>,
+    /// Metadata attached to the run, which is passed through to the results
+    metadata: HashMap<String, String>,
+    /// Invoked whenever the overall best individual improves
+    on_improvement: Option<ImprovementCallback>,
+    /// Produces the learning rate for the mean of each generation
+    cm_schedule: Option<CmSchedule>,
     /// The time at which the `CMAES` was created
     time_created: Instant,
+    /// The time at which the overall best individual last improved (or the creation time if no
+    /// generation has been evaluated yet)
+    time_last_improvement: Instant,
+    /// Whether the initial mean was clamped into the bounds
+    initial_mean_clamped: bool,
+    /// Writes the covariance matrix to files periodically, if enabled
+    covariance_recorder: Option<CovarianceRecorder>,
 }
 
 impl<F> CMAES<F> {
-    /// Initializes a `CMAES` from a set of [`CMAESOptions`]. [`CMAESOptions::build`] should
-    /// generally be used instead.
-    pub fn new(objective_function: F, options: CMAESOptions) -> Result<Self, InvalidOptionsError> {
-        let dimensions = options.initial_mean.len();
-        // Check for invalid options
-        if dimensions == 0 {
+    /// Like [`new`][Self::new], but starts from a hand-crafted [`State`][crate::state::State]
+    /// instead of the initial mean and step size of `options` (which are ignored apart from the
+    /// number of dimensions). Requires the `unstable` feature.
+    ///
+    /// This is an escape hatch for research and for reproducing specific states in tests (e.g.
+    /// pathological covariance matrices) and comes with no stability guarantees: `State` and
+    /// this method may change or be removed in any release. The function value history starts
+    /// empty, so termination criteria that depend on it behave as in a fresh run.
+    ///
+    /// Returns `Err` if the options are invalid or the state has a different number of dimensions
+    /// than `options.initial_mean` (in which case [`InvalidOptionsError::Dimensions`] is returned).
+    #[cfg(feature = "unstable")]
+    pub fn from_parts(
+        objective_function: F,
+        options: CMAESOptions,
+        state: State,
+    ) -> Result<Self, InvalidOptionsError> {
+        if state.mean().len() != options.initial_mean.len() {
             return Err(InvalidOptionsError::Dimensions);
         }
 
-        if options.population_size < 2 {
-            return Err(InvalidOptionsError::PopulationSize);
-        }
+        let mut cmaes = Self::new(objective_function, options)?;
+        cmaes.state = state;
+        cmaes.update_feasible_mean();
+        Ok(cmaes)
+    }
 
-        if !options::is_initial_step_size_valid(options.initial_step_size) {
-            return Err(InvalidOptionsError::InitialStepSize);
-        }
+    /// Returns the variable state of the algorithm. Requires the `unstable` feature (see
+    /// [`from_parts`][Self::from_parts]).
+    #[cfg(feature = "unstable")]
+    pub fn state(&self) -> &State {
+        &self.state
+    }
 
-        if !options.cm.is_normal() || options.cm <= 0.0 || options.cm > 1.0 {
-            return Err(InvalidOptionsError::Cm);
-        }
+    /// Initializes a `CMAES` from a set of [`CMAESOptions`]. [`CMAESOptions::build`] should
+    /// generally be used instead.
+    pub fn new(objective_function: F, options: CMAESOptions) -> Result<Self, InvalidOptionsError> {
+        options.validate()?;
+        let dimensions = options.initial_mean.len();
 
         // Initialize point sampler
         let seed = options.seed.unwrap_or_else(rand::random);
-        let sampler = Sampler::new(
+        let mut sampler = Sampler::new(
             dimensions,
             options.population_size,
             objective_function,
             seed,
         );
+        if options.cache_evaluations {
+            sampler.enable_cache();
+        }
+        if options.record_samples {
+            sampler.enable_recording();
+        }
+        sampler.replay(options.replay_samples.clone());
+        sampler.set_invalid_policy(options.invalid_value_policy);
+        sampler.set_repeated_evaluations(
+            options.evaluations_per_candidate,
+            options.evaluation_aggregation,
+        );
+        sampler.set_penalty_weights(options.penalty_weights.clone());
 
         // Initialize constant parameters according to the options
         let parameters = Parameters::from_options(&options, seed);
 
-        // Initialize variable parameters
-        let state = State::new(options.initial_mean, options.initial_step_size);
+        // Initialize variable parameters (the initial mean is given in the feasible space and in
+        // natural coordinates, so an infeasible initial mean is clamped into the bounds first)
+        let initial_mean = options.draw_initial_mean(seed);
+        let mapping = parameters.point_mapping();
+        let clamped_mean = mapping.clamp_to_bounds(&initial_mean);
+        let initial_mean = mapping.to_internal(clamped_mean.as_ref().unwrap_or(&initial_mean));
+        let mut state = State::new(initial_mean, options.resolved_initial_step_size());
+        if options.initial_covariance_scale != 1.0 {
+            let scale = options.initial_covariance_scale;
+            // The scaled identity is always positive-definite because the scale is validated
+            state
+                .set_cov(
+                    SquareMatrix::from_diagonal_element(dimensions, dimensions, scale),
+                    None,
+                )
+                .unwrap();
+        }
 
         // Initialize function value history
-        let history = History::new();
+        let history = History::new(options.value_scale);
 
         // Initialize plot if enabled
         #[cfg(feature = "plotters")]
-        let plot = options
-            .plot_options
-            .map(|o| Plot::new(dimensions, o, options.mode));
+        let plot = options.plot_options.map(|o| {
+            Plot::new(
+                dimensions,
+                o,
+                options.mode,
+                options.max_recorded_generations,
+            )
+        });
 
-        let cmaes = Self {
+        let time_created = Instant::now();
+        let mut cmaes = Self {
             sampler,
             parameters,
             state,
             history,
+            feasible_mean: None,
+            current_population: Vec::new(),
+            previous_best: None,
+            last_population_growth: 0,
+            population_controller: options
+                .auto_population
+                .then(|| PopulationController::new(options.population_size)),
+            population_size_trajectory: vec![(0, options.population_size)],
+            last_kick: 0,
+            kick_count: 0,
+            fun_target_generation: None,
+            target_hits: options
+                .target_values
+                .iter()
+                .map(|&target| (target, None))
+                .collect(),
+            best_mean_point: None,
+            mean_value: None,
+            previous_mean_value: None,
             #[cfg(feature = "plotters")]
             plot,
             print_gap_evals: options.print_gap_evals,
             last_print_evals: 0,
-            time_created: Instant::now(),
+            live_status: options.live_status.then(LiveStatus::new),
+            metadata: options.metadata,
+            on_improvement: options.on_improvement,
+            cm_schedule: options.cm_schedule,
+            time_created,
+            time_last_improvement: time_created,
+            initial_mean_clamped: clamped_mean.is_some(),
+            covariance_recorder: options
+                .record_covariance
+                .map(|(every, path_template)| CovarianceRecorder::new(every, path_template)),
         };
 
+        cmaes.update_feasible_mean();
+
         // Plot initial state
         #[cfg(feature = "plotters")]
-        let mut cmaes = cmaes;
-        #[cfg(feature = "plotters")]
         cmaes.add_plot_point();
 
         // Print initial info
@@ -293,8 +696,175 @@ impl<F> CMAES<F> {
         Ok(cmaes)
     }
 
+    /// Returns a copy of the algorithm state that optimizes `objective_function` instead, for
+    /// example to fork the optimization and compare different interventions in each branch. The
+    /// objective function can be a clone of the original one or a shared reference to it.
+    ///
+    /// Everything except the objective function is copied, including the distribution,
+    /// histories, function evaluation count, plot, and creation time (so
+    /// [`MaxTime`][TerminationReason::MaxTime] is still measured from when `self` was created).
+    ///
+    /// The copy does not share the RNG of `self`. Instead, its RNG is seeded with numbers drawn
+    /// from a separate stream of a copy of the RNG of `self`, so:
+    ///
+    /// - The RNG of `self` is not advanced, and the two states sample different points from then
+    ///   on.
+    /// - The copy is reproducible: cloning states with the same seed at the same point in the
+    ///   optimization always produces the same RNG. In particular, cloning the same state twice
+    ///   without advancing it produces two copies that sample the same points. To get several
+    ///   diverging branches, clone the copies instead (or advance `self` in between).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .seed(1)
+    ///     .build(function)
+    ///     .unwrap();
+    /// let _ = cmaes_state.next();
+    ///
+    /// let mut branch = cmaes_state.clone_with(function);
+    ///
+    /// assert_eq!(cmaes_state.mean(), branch.mean());
+    /// let _ = cmaes_state.next();
+    /// let _ = branch.next();
+    /// assert_ne!(cmaes_state.mean(), branch.mean());
+    /// ```
+    pub fn clone_with<G>(&self, objective_function: G) -> CMAES<G> {
+        CMAES {
+            sampler: self.sampler.clone_with(objective_function),
+            parameters: self.parameters.clone(),
+            state: self.state.clone(),
+            history: self.history.clone(),
+            feasible_mean: self.feasible_mean.clone(),
+            current_population: self.current_population.clone(),
+            previous_best: self.previous_best.clone(),
+            last_population_growth: self.last_population_growth,
+            population_controller: self.population_controller.clone(),
+            population_size_trajectory: self.population_size_trajectory.clone(),
+            last_kick: self.last_kick,
+            kick_count: self.kick_count,
+            fun_target_generation: self.fun_target_generation,
+            target_hits: self.target_hits.clone(),
+            best_mean_point: self.best_mean_point.clone(),
+            mean_value: self.mean_value,
+            previous_mean_value: self.previous_mean_value,
+            #[cfg(feature = "plotters")]
+            plot: self.plot.clone(),
+            print_gap_evals: self.print_gap_evals,
+            last_print_evals: self.last_print_evals,
+            live_status: self.live_status.clone(),
+            metadata: self.metadata.clone(),
+            on_improvement: self.on_improvement.clone(),
+            cm_schedule: self.cm_schedule.clone(),
+            time_created: self.time_created,
+            time_last_improvement: self.time_last_improvement,
+            initial_mean_clamped: self.initial_mean_clamped,
+            covariance_recorder: self.covariance_recorder.clone(),
+        }
+    }
+
+    /// Replaces the termination and output options of the run with those of `options`, keeping
+    /// everything that has been learned so far (the distribution, evolution paths, histories, and
+    /// function evaluation count). This is useful for resuming a run that has terminated with
+    /// different limits, e.g. a tighter `fun_target` or a larger `max_function_evals`.
+    ///
+    /// The following options are taken from `options`:
+    ///
+    /// - All termination options (`max_function_evals`, `max_generations`, `max_time`,
+    ///   `improvement_timeout`, `fun_target`, `fun_target_mode`, `tol_*`, `relative_tol_x`,
+    ///   `target_sigma`, `flat_function_generations`, `validate_objective`, `mean_region`, and
+    ///   `min_generations`). Time limits are still measured from the creation of the run, and
+    ///   the default values of `tol_x` and `tol_stagnation` are derived from
+    ///   `options.initial_step_size` and `options.population_size` like in a new run.
+    /// - `print_gap_evals`, `live_status`, and `metadata`.
+    /// - `plot_options`, if set. An existing plot keeps its recorded data points and is not
+    ///   removed if `plot_options` is `None`.
+    ///
+    /// All other options are ignored. The optimization mode, population size (unless
+    /// [`adaptive_population`][CMAESOptions::adaptive_population] or
+    /// [`auto_population`][CMAESOptions::auto_population] is enabled), weights, and
+    /// recombination weighting determine the learning rates that the state was adapted with, so
+    /// `Err(InvalidOptionsError::IncompatibleOverride)` is returned if `options` changes any of
+    /// them. `Err(InvalidOptionsError::Dimensions)` is returned if `options` has a different
+    /// number of dimensions. The run is left unchanged if an error is returned, including if
+    /// `options` is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, TerminationReason};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let options = CMAESOptions::new(vec![1.0; 4], 1.0);
+    /// let mut cmaes_state = options
+    ///     .clone()
+    ///     .max_generations(10)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let result = cmaes_state.run();
+    /// assert_eq!(vec![TerminationReason::MaxGenerations], result.reasons);
+    ///
+    /// // Continue the run for another 20 generations
+    /// cmaes_state
+    ///     .override_options(options.max_generations(30))
+    ///     .unwrap();
+    /// let result = cmaes_state.run();
+    /// assert_eq!(vec![TerminationReason::MaxGenerations], result.reasons);
+    /// assert_eq!(30, cmaes_state.generation());
+    /// ```
+    pub fn override_options(&mut self, options: CMAESOptions) -> Result<(), InvalidOptionsError> {
+        options.validate()?;
+
+        let parameters = &self.parameters;
+        if options.initial_mean.len() != parameters.dim() {
+            return Err(InvalidOptionsError::Dimensions);
+        }
+        let population_size_changed = parameters.adaptive_population().is_none()
+            && self.population_controller.is_none()
+            && options.population_size != parameters.lambda();
+        if options.mode != parameters.mode()
+            || population_size_changed
+            || options.weights != parameters.weights_setting()
+            || options.recombination_weighting != parameters.recombination_weighting()
+        {
+            return Err(InvalidOptionsError::IncompatibleOverride);
+        }
+
+        self.parameters
+            .set_termination(TerminationParameters::from_options(&options));
+
+        #[cfg(feature = "plotters")]
+        if let Some(plot_options) = options.plot_options {
+            match self.plot {
+                Some(ref mut plot) => plot.set_options(plot_options),
+                None => {
+                    self.plot = Some(Plot::new(
+                        self.parameters.dim(),
+                        plot_options,
+                        self.parameters.mode(),
+                        self.parameters.max_recorded_generations(),
+                    ))
+                }
+            }
+        }
+
+        self.print_gap_evals = options.print_gap_evals;
+        self.live_status = match self.live_status.take() {
+            Some(live_status) if options.live_status => Some(live_status),
+            _ => options.live_status.then(LiveStatus::new),
+        };
+        self.metadata = options.metadata;
+
+        Ok(())
+    }
+
     /// Shared logic between `run` and `run_parallel`
-    fn run_internal(&mut self, result: &TerminationData) {
+    pub(crate) fn run_internal(&mut self, result: &TerminationData) {
         // Plot/print the final state
         #[cfg(feature = "plotters")]
         self.add_plot_point();
@@ -302,23 +872,155 @@ impl<F> CMAES<F> {
         if self.print_gap_evals.is_some() {
             self.print_final_info(&result.reasons);
         }
+
+        if let Some(mut live_status) = self.live_status.take() {
+            live_status.finish(&self.live_status_line());
+            self.live_status = Some(live_status);
+        }
+    }
+
+    /// Records the evaluation numbers of the target values that are reached for the first time by
+    /// the given pairs of function values and evaluation numbers
+    fn record_target_hits<I: Iterator<Item = (f64, usize)> + Clone>(&mut self, evaluations: I) {
+        let mode = self.parameters.mode();
+
+        for (target, hit) in self.target_hits.iter_mut().filter(|(_, hit)| hit.is_none()) {
+            *hit = evaluations
+                .clone()
+                .filter(|&(value, _)| !mode.is_better(*target, value))
+                .map(|(_, evaluation)| evaluation)
+                .min();
+        }
+    }
+
+    /// Records the progress of the latest generation, whose rankable individuals are `rankable`, in
+    /// the trajectory. Should be called after the histories have been updated and before the
+    /// distribution is updated
+    fn update_trajectory(&mut self, rankable: &[EvaluatedPoint]) {
+        let best = match self.history.overall_best_individual() {
+            Some(best) => best,
+            None => return,
+        };
+        // The quartiles are left over from an earlier generation if none of this generation's
+        // individuals could be ranked
+        let median_value = match (rankable.is_empty(), self.history.current_quartiles()) {
+            (false, Some(quartiles)) => quartiles.median,
+            _ => f64::NAN,
+        };
+
+        let entry = TrajectoryEntry {
+            generation: self.state.generation() + 1,
+            function_evals: self.sampler.function_evals(),
+            best_value: best.value,
+            median_value,
+            sigma: self.state.sigma(),
+            axis_ratio: self.state.axis_ratio(),
+            mean_norm: self.mean().magnitude(),
+        };
+        self.history
+            .update_trajectory(entry, self.parameters.max_recorded_generations());
     }
 
     /// Shared logic between `sample` and `sample_parallel`
     fn sample_internal(&mut self, individuals: &[EvaluatedPoint]) {
-        // Update histories
-        self.history.update(self.parameters.mode(), individuals);
+        // Unrankable individuals have no meaningful function value, so they are left out of the
+        // histories
+        let rankable = sampling::rankable_individuals(individuals);
+
+        // Track strictly better overall best individuals (the first one always counts)
+        let is_improvement = match (rankable.first(), self.history.overall_best_individual()) {
+            (Some(best), Some(overall_best)) => self
+                .parameters
+                .mode()
+                .is_better(best.value(), overall_best.value),
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        self.record_target_hits(individuals.iter().map(|p| (p.value(), p.evaluation())));
+
+        if is_improvement {
+            self.time_last_improvement = Instant::now();
+
+            if let Some(ref callback) = self.on_improvement {
+                callback.call(&individuals[0], self.sampler.function_evals());
+            }
+        }
+
+        // Update histories (skipped if no individual is rankable)
+        if !rankable.is_empty() {
+            self.history.update(self.parameters.mode(), rankable);
+            self.history.update_sigma(self.state.sigma());
+        }
+        self.update_trajectory(rankable);
+        let window = termination::tol_fun_window(self.parameters.dim(), self.parameters.lambda());
+        self.history.update_peak_improvement(window);
+
+        // Only compute the diversity if needed because it is expensive
+        if let Some(tol_diversity) = self.parameters.tol_diversity() {
+            self.history
+                .update_diversity(sampling::mean_pairwise_distance(individuals) < tol_diversity);
+        }
     }
 
     /// Shared logic between `next` and `next_parallel`
-    fn next_internal(&mut self, individuals: &[EvaluatedPoint]) -> Option<TerminationData> {
+    ///
+    /// `evaluate_mean` is used to evaluate the mean if enabled
+    fn next_internal<E: FnOnce(&mut F, &DVector<f64>) -> Option<f64>>(
+        &mut self,
+        individuals: Vec<EvaluatedPoint>,
+        evaluate_mean: E,
+    ) -> Option<TerminationData> {
+        let result = self.update_generation(&individuals, evaluate_mean);
+        let previous_population = std::mem::replace(&mut self.current_population, individuals);
+        self.previous_best = previous_population.into_iter().next();
+        result
+    }
+
+    /// Updates the state with a newly evaluated generation and checks for termination
+    fn update_generation<E: FnOnce(&mut F, &DVector<f64>) -> Option<f64>>(
+        &mut self,
+        individuals: &[EvaluatedPoint],
+        evaluate_mean: E,
+    ) -> Option<TerminationData> {
+        // Use the scheduled learning rate for the mean of this generation
+        if let Some(ref schedule) = self.cm_schedule {
+            if let Some(cm) = schedule.cm(self.state.generation()) {
+                self.parameters.set_cm(cm);
+            }
+        }
+
         // Update state
-        if self
-            .state
-            .update(self.sampler.function_evals(), &self.parameters, individuals)
-            .is_err()
+        if let Err(error) =
+            self.state
+                .update(self.sampler.function_evals(), &self.parameters, individuals)
         {
-            return Some(self.get_termination_data(vec![TerminationReason::PosDefCov]));
+            let detail = match error {
+                UpdateError::PosDefCov => TerminationReason::PosDefCov.into(),
+                UpdateError::NonFinite(component) => TerminationDetail::non_finite_state(component),
+            };
+            return Some(self.get_termination_data(vec![detail]));
+        }
+
+        self.update_feasible_mean();
+
+        if self.parameters.evaluate_mean() {
+            self.evaluate_mean(evaluate_mean);
+        }
+
+        // Record when the target value is first reached
+        if let Some(fun_target) = self.parameters.fun_target() {
+            if self.fun_target_generation.is_none()
+                && self
+                    .parameters
+                    .mode()
+                    .is_better(individuals[0].value(), fun_target)
+            {
+                self.fun_target_generation = Some(self.state.generation());
+            }
+        }
+
+        if let Some(ref mut recorder) = self.covariance_recorder {
+            recorder.record(&self.state);
         }
 
         // Plot latest state
@@ -345,24 +1047,128 @@ impl<F> CMAES<F> {
             }
         }
 
+        if let Some(mut live_status) = self.live_status.take() {
+            live_status.update(|| self.live_status_line());
+            self.live_status = Some(live_status);
+        }
+
         // Terminate with the current best individual if any termination criteria are met
-        let termination_reasons = TerminationCheck {
+        let termination_details = TerminationCheck {
             current_function_evals: self.sampler.function_evals(),
             time_created: self.time_created,
+            time_last_improvement: self.time_last_improvement,
             parameters: &self.parameters,
             state: &self.state,
             history: &self.history,
             individuals,
+            // The latest generation only replaces the current population afterwards
+            first_generation: self.current_population.is_empty(),
         }
-        .check_termination_criteria();
+        .check_termination_details();
 
-        if !termination_reasons.is_empty() {
-            Some(self.get_termination_data(termination_reasons))
+        if !termination_details.is_empty() {
+            Some(self.get_termination_data(termination_details))
         } else {
+            self.adapt_population_size();
+            self.auto_population_size();
+            self.auto_kick();
             None
         }
     }
 
+    /// Evaluates the objective function at the current mean and updates the best mean point
+    fn evaluate_mean<E: FnOnce(&mut F, &DVector<f64>) -> Option<f64>>(&mut self, evaluate: E) {
+        let point = self.parameters.point_mapping().map(self.state.mean()).0;
+
+        let value = self.sampler.evaluate_point(&point, evaluate);
+        self.previous_mean_value = std::mem::replace(&mut self.mean_value, value);
+
+        if let Some(value) = value {
+            self.record_target_hits(std::iter::once((value, self.sampler.function_evals())));
+            self.history.update_mean_value(value);
+
+            let is_better = match &self.best_mean_point {
+                Some(best) => self.parameters.mode().is_better(value, best.value),
+                None => true,
+            };
+            if is_better {
+                self.best_mean_point = Some(Individual::new(point, value));
+            }
+        }
+    }
+
+    /// Grows the population size if adaptive population sizing is enabled and the overall best
+    /// function value has not improved recently
+    fn adapt_population_size(&mut self) {
+        let adaptive_population = match self.parameters.adaptive_population() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let dim = self.parameters.dim() as f64;
+        let lambda = self.parameters.lambda();
+        let window = 10 + (30.0 * dim / lambda as f64).ceil() as usize;
+
+        if lambda < adaptive_population.max
+            && self.history.generations_without_improvement() >= window
+            && self.state.generation() >= self.last_population_growth + window
+        {
+            self.set_population_size(adaptive_population.grow(lambda));
+            self.last_population_growth = self.state.generation();
+        }
+    }
+
+    /// Grows or shrinks the population size according to the success rate of the mean update if
+    /// automatic population sizing is enabled. The mean update of a generation counts as
+    /// successful if the median function value improved over that of the previous generation
+    fn auto_population_size(&mut self) {
+        let controller = match self.population_controller {
+            Some(ref mut x) => x,
+            None => return,
+        };
+
+        let medians = self.history.median_function_values();
+        if medians.len() < 2 {
+            return;
+        }
+        let success = self.parameters.mode().is_better(medians[0], medians[1]);
+
+        if let Some(new_lambda) =
+            controller.update(success, self.state.generation(), self.parameters.lambda())
+        {
+            self.set_population_size(new_lambda);
+        }
+    }
+
+    /// Changes the population size, recalculating the weights and learning rates, and records the
+    /// change in the population size trajectory
+    fn set_population_size(&mut self, lambda: usize) {
+        self.parameters.set_lambda(lambda);
+        self.sampler.set_population_size(lambda);
+        self.population_size_trajectory
+            .push((self.state.generation(), lambda));
+    }
+
+    /// Displaces the mean and increases the step size if automatic kicks are enabled and the
+    /// overall best function value has not improved recently
+    fn auto_kick(&mut self) {
+        let auto_kick = match self.parameters.auto_kick() {
+            Some(x) => x,
+            None => return,
+        };
+
+        if self.history.generations_without_improvement() >= auto_kick.threshold
+            && self.state.generation() >= self.last_kick + auto_kick.threshold
+        {
+            let step =
+                auto_kick.strength * self.state.sigma() * self.sampler.sample_step(&self.state);
+            self.state.kick(&step, 1.0 + auto_kick.strength);
+            self.update_feasible_mean();
+            self.last_kick = self.state.generation();
+            self.kick_count += 1;
+        }
+    }
+
     /// Consumes `self` and returns the objective function. Useful for retrieving state stored in
     /// custom objective function types.
     pub fn into_objective_function(self) -> F {
@@ -374,6 +1180,143 @@ impl<F> CMAES<F> {
         &self.parameters
     }
 
+    /// Returns every termination criterion that can currently stop the run, in the order in which
+    /// they are declared in [`TerminationReason`], along with its configured threshold. Useful as
+    /// a pre-flight check of what will end a run.
+    ///
+    /// The threshold is the value of the corresponding option in its own units (e.g. seconds for
+    /// `MaxTime` and `ImprovementTimeout` or a number of generations for `FlatFunction` and
+    /// `TolStagnation`). For `TolFun`, it is the threshold currently in effect, which may be
+    /// larger than `tol_fun` if [`CMAESOptions::adaptive_tol_fun`] is enabled. It is `None` for
+    /// criteria that are not based on a threshold (`ConstantFunction`, `MeanOutOfRegion`,
+    /// `NoEffectAxis`, `NoEffectCoord`, `InvalidFunctionValue`, `NonFiniteState`, and
+    /// `PosDefCov`). Criteria that are disabled are omitted, including `FunTarget` if
+    /// [`TargetMode::RecordOnly`] is used and `TolStagnation` if `tol_stagnation` exceeds
+    /// [`MAX_HISTORY_LENGTH`]. [`Predicate`][TerminationReason::Predicate] is never included
+    /// because it only applies to [`run_until`][Self::run_until].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, TerminationReason};
+    ///
+    /// let cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .max_generations(100)
+    ///     .build(|x: &DVector<f64>| x.magnitude())
+    ///     .unwrap();
+    ///
+    /// let criteria = cmaes_state.active_criteria();
+    /// assert_eq!((TerminationReason::MaxGenerations, Some(100.0)), criteria[0]);
+    /// assert!(!criteria.iter().any(|(r, _)| *r == TerminationReason::MaxTime));
+    /// ```
+    pub fn active_criteria(&self) -> Vec<(TerminationReason, Option<f64>)> {
+        termination::active_criteria(&self.parameters, &self.history)
+    }
+
+    /// Returns a JSON snapshot of the resolved parameters and the current state of the algorithm,
+    /// intended for inspection by other tools (e.g. analysis scripts in other languages). Requires
+    /// the `serde` feature.
+    ///
+    /// Unlike a full serialization, the snapshot cannot be used to resume the run: it does not
+    /// include the RNG state, the function value histories, or the population. The schema is as
+    /// follows (vectors are arrays of numbers, and the covariance matrix is an array of rows):
+    ///
+    /// - `parameters`: the values returned by [`Parameters`]
+    ///   - `mode` (`"Minimize"` or `"Maximize"`), `dimensions`, `lambda`, `mu`, `mu_eff`
+    ///   - `weights_setting` (a [`Weights`] variant name), `weights`, `recombination_weighting` (a
+    ///     [`RecombinationWeighting`] variant name)
+    ///   - `initial_sigma`, `cc`, `c1`, `cs`, `cmu`, `cm`, `damp_s`, `seed`
+    ///   - `termination`: `max_function_evals`, `max_generations`, `max_time` (in seconds),
+    ///     `fun_target` (each `null` if disabled), `tol_fun`, `tol_fun_rel`, `tol_fun_hist`,
+    ///     `tol_x`, `tol_x_up`, `tol_stagnation`, `tol_condition_cov`
+    /// - `state`: the values returned by the corresponding methods of `CMAES`
+    ///   - `generation`, `function_evals`, `sigma`, `mean`, `axis_ratio`, `eigenvalues`,
+    ///     `covariance`
+    ///   - `path_c`, `path_sigma`: the evolution paths for the covariance matrix and the step size
+    ///   - `current_best`, `overall_best`: objects with a `point` and a `value` (`null` before
+    ///     [`next`][Self::next] has been called)
+    ///
+    /// Non-finite numbers are written as `null`, since JSON cannot represent them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 2], 1.0)
+    ///     .build(|x: &DVector<f64>| x.magnitude())
+    ///     .unwrap();
+    /// let _ = cmaes_state.next();
+    ///
+    /// let json = cmaes_state.to_json();
+    /// assert!(json.contains("\"lambda\": 6"));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&snapshot::Snapshot::new(self)).unwrap()
+    }
+
+    /// Returns the normalized recombination weights computed for the current configuration, in
+    /// order from the best to the worst ranked individual. The same values are returned by
+    /// [`Parameters::weights`].
+    ///
+    /// The first `mu` weights are positive and sum to `1`. With [`Weights::Negative`] (active
+    /// CMA-ES), the remaining weights are negative and are only used in the covariance matrix
+    /// update, with their sum scaled so that the covariance matrix remains positive-definite. With
+    /// the other settings, only the `mu` positive weights are stored. If
+    /// [`RecombinationWeighting::Value`] is used, the weights applied each generation are instead
+    /// computed from the function values.
+    pub fn weights_vector(&self) -> &[f64] {
+        self.parameters.weights().as_slice()
+    }
+
+    /// Returns the seed used for the RNG. This is the value of [`CMAESOptions::seed`] if it was
+    /// set, or otherwise the randomly generated seed, so any run can be reproduced after the fact
+    /// by passing the returned value to [`CMAESOptions::seed`].
+    pub fn seed(&self) -> u64 {
+        self.parameters.seed()
+    }
+
+    /// Returns the metadata attached to the run with [`CMAESOptions::metadata`].
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.metadata
+    }
+
+    /// Returns the current population size `lambda`. This is constant unless
+    /// [`AdaptivePopulation`] or [`CMAESOptions::auto_population`] is enabled.
+    pub fn population_size(&self) -> usize {
+        self.parameters.lambda()
+    }
+
+    /// Returns the population size at the start of the run and after each change as `(generation,
+    /// population_size)` pairs, where `generation` is the number of the generation after which the
+    /// population size was set (`0` for the initial population size). Only has more than one
+    /// entry if [`AdaptivePopulation`] or [`CMAESOptions::auto_population`] is enabled.
+    pub fn population_size_trajectory(&self) -> &[(usize, usize)] {
+        &self.population_size_trajectory
+    }
+
+    /// Returns the number of times the mean has been displaced because progress stalled. Always
+    /// `0` unless [`AutoKick`] is enabled.
+    pub fn kick_count(&self) -> usize {
+        self.kick_count
+    }
+
+    /// Returns the error of the latest failed attempt to write the covariance matrix to a file, or
+    /// `None` if [`CMAESOptions::record_covariance`] is disabled or no write has failed yet.
+    pub fn covariance_recording_error(&self) -> Option<&io::Error> {
+        self.covariance_recorder
+            .as_ref()
+            .and_then(CovarianceRecorder::last_error)
+    }
+
+    /// Returns whether [`CMAESOptions::initial_mean`] lay outside of the
+    /// [`bounds`][CMAESOptions::bounds] and was clamped into them. The search then started from the
+    /// clamped mean.
+    pub fn initial_mean_clamped(&self) -> bool {
+        self.initial_mean_clamped
+    }
+
     /// Returns the number of generations that have been completed.
     pub fn generation(&self) -> usize {
         self.state.generation()
@@ -384,61 +1327,826 @@ impl<F> CMAES<F> {
         self.sampler.function_evals()
     }
 
-    /// Returns the current mean of the distribution.
-    pub fn mean(&self) -> &DVector<f64> {
-        self.state.mean()
+    /// Returns the number of sampled points for which the objective function returned an invalid
+    /// (`NAN`) value. These points are skipped if allowed by
+    /// [`CMAESOptions::invalid_value_policy`] and are counted in
+    /// [`function_evals`][Self::function_evals] as well. Always at most `1` with the default
+    /// [`InvalidPolicy::Terminate`], because the run terminates at the first invalid value.
+    pub fn invalid_evals(&self) -> usize {
+        self.sampler.invalid_evals()
     }
 
-    /// Returns the current covariance matrix of the distribution.
-    pub fn covariance_matrix(&self) -> &SquareMatrix<f64> {
-        self.state.cov()
+    /// Returns the hit statistics of the evaluation cache, or `None` if
+    /// [`CMAESOptions::cache_evaluations`] is disabled. `misses` is the number of times the
+    /// objective function was actually called.
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.sampler.cache_stats()
     }
 
-    /// Returns the current eigenvalues of the distribution.
-    pub fn eigenvalues(&self) -> DVector<f64> {
-        self.state
-            .cov_sqrt_eigenvalues()
-            .diagonal()
-            .map(|x| x.powi(2))
+    /// Returns the samples recorded for each generation so far, or `None` if
+    /// [`CMAESOptions::record_samples`] is disabled.
+    pub fn sample_recording(&self) -> Option<&[GenerationSamples]> {
+        self.sampler.recording()
     }
 
-    /// Returns the current step size of the distribution.
-    pub fn sigma(&self) -> f64 {
-        self.state.sigma()
+    /// Returns the number of generations of [`CMAESOptions::replay_samples`] that were discarded
+    /// without being used because an entry did not match the population size or number of
+    /// dimensions. A nonzero value means that the run diverged from the recording.
+    pub fn discarded_replay_samples(&self) -> usize {
+        self.sampler.discarded_replay_samples()
     }
 
-    /// Returns the current axis ratio of the distribution.
-    pub fn axis_ratio(&self) -> f64 {
-        self.state.axis_ratio()
+    /// Returns the sizes of the chunks passed to the objective function in the most recent
+    /// generation when using [`run_batch`][Self::run_batch] or [`next_batch`][Self::next_batch].
+    /// Empty if batch evaluation has not been used.
+    pub fn chunk_sizes(&self) -> &[usize] {
+        self.sampler.chunk_sizes()
     }
 
-    /// Returns the best individual of the latest generation and its function value. Will always
-    /// return `Some` as long as [`next`][Self::next] has been called at least once and the
-    /// algorithm did not terminate in the first generation with
-    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
-    pub fn current_best_individual(&self) -> Option<&Individual> {
-        self.history.current_best_individual()
+    /// Returns the current mean of the distribution. If bounds are enabled, the mean is mapped into
+    /// the feasible space, and if a transform is enabled, it is decoded into natural coordinates.
+    pub fn mean(&self) -> &DVector<f64> {
+        self.feasible_mean
+            .as_ref()
+            .unwrap_or_else(|| self.state.mean())
     }
 
-    /// Returns the best individual of any generation and its function value. Will always
-    /// return `Some` as long as [`next`][Self::next] has been called at least once and the
-    /// algorithm did not terminate in the first generation with
-    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
-    pub fn overall_best_individual(&self) -> Option<&Individual> {
-        self.history.overall_best_individual()
+    /// Returns the mean of the distribution with the best function value of all generations so
+    /// far. Always returns `None` unless [`CMAESOptions::evaluate_mean`] is enabled (and also
+    /// before [`next`][Self::next] has been called or if every evaluated mean was invalid).
+    pub fn best_mean_point(&self) -> Option<&Individual> {
+        self.best_mean_point.as_ref()
     }
 
-    /// Returns the time at which the `CMAES` was created.
-    pub fn time_created(&self) -> Instant {
-        self.time_created
+    /// Returns the function value at the current [`mean`][Self::mean], as evaluated at the end of
+    /// the latest generation. Always returns `None` unless [`CMAESOptions::evaluate_mean`] is
+    /// enabled (and also before [`next`][Self::next] has been called or if the value was
+    /// invalid).
+    pub fn mean_value(&self) -> Option<f64> {
+        self.mean_value
     }
 
-    /// Returns the time elapsed since the `CMAES` was created.
-    pub fn elapsed(&self) -> Duration {
-        self.time_created.elapsed()
+    /// Returns the function value at the mean before the latest update, i.e. the value that
+    /// [`mean_value`][Self::mean_value] returned one generation earlier. Always returns `None`
+    /// unless [`CMAESOptions::evaluate_mean`] is enabled (and also before the second generation or
+    /// if the value was invalid).
+    ///
+    /// Together with [`mean_value`][Self::mean_value], this gives the actual change of the
+    /// function value caused by the latest mean update, e.g. for comparing it against the change
+    /// predicted by a model of the function in a hybrid trust-region method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .evaluate_mean(true)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let _ = cmaes_state.next();
+    /// assert!(cmaes_state.previous_mean_value().is_none());
+    ///
+    /// let _ = cmaes_state.next();
+    /// let previous = cmaes_state.previous_mean_value().unwrap();
+    /// let current = cmaes_state.mean_value().unwrap();
+    /// println!("actual reduction: {}", previous - current);
+    /// ```
+    pub fn previous_mean_value(&self) -> Option<f64> {
+        self.previous_mean_value
     }
 
-    /// Returns a reference to the data plot if enabled.
+    /// Returns an estimate of the variance of the noise of the objective function, obtained for
+    /// free from the function values at the mean. Always returns `None` unless
+    /// [`CMAESOptions::evaluate_mean`] is enabled, and until the mean has been evaluated in at
+    /// least three generations (means with invalid values are ignored).
+    ///
+    /// The estimator compares the function values at the means of consecutive generations. If the
+    /// mean barely moves between them, their difference is caused by the noise alone and has twice
+    /// the variance of the noise, so half of the variance of the differences is returned. The
+    /// average difference is subtracted first so that a steady improvement does not count as
+    /// noise. The differences are weighted exponentially, covering roughly the last 10
+    /// generations, so the estimate follows the run as it converges.
+    ///
+    /// The estimate assumes that the mean moves slowly, which is the case near convergence.
+    /// Earlier in the run, the actual changes of the function value between the means inflate it,
+    /// so it is best read as an upper bound. It is a cheap heuristic for whether the noise is
+    /// large compared to the differences between the function values of a generation (see
+    /// [`function_value_quartiles`][Self::function_value_quartiles]), in which case a full
+    /// uncertainty handling scheme may be needed.
+    pub fn estimated_objective_noise(&self) -> Option<f64> {
+        self.history.objective_noise()
+    }
+
+    /// Returns an estimate of the probability that a newly sampled individual improves on the
+    /// overall best individual, or `None` until the individuals of at least two generations have
+    /// been compared against a previous overall best individual (i.e. before the third
+    /// generation has been evaluated).
+    ///
+    /// The estimate is the fraction of individuals in each generation whose function value was
+    /// strictly better than the overall best value before that generation, averaged over the
+    /// generations with exponentially decaying weights that cover roughly the last 10 generations.
+    /// Individuals with invalid values never count as improvements.
+    ///
+    /// This is a heuristic that does not model the distribution itself: it assumes that the
+    /// success rate changes slowly between generations. A value that stays near zero indicates
+    /// that the run has stopped making progress, which can be used as a stopping criterion that
+    /// is independent of the scale of the objective function. Because of the smoothing, the
+    /// estimate lags behind sudden changes (e.g. when the run escapes from a plateau).
+    pub fn estimated_improvement_probability(&self) -> Option<f64> {
+        self.history.improvement_probability()
+    }
+
+    /// Returns the average rate at which the step size decreased per generation over the same
+    /// window of recent generations used by
+    /// [`TerminationReason::TolFun`][crate::TerminationReason::TolFun] (`10 + 30 * N / lambda`
+    /// generations), measured as `ln(old_sigma / sigma) / window`. The rate is negative if the
+    /// step size increased. Returns `None` until more generations than the window have been
+    /// completed.
+    pub fn sigma_decay_rate(&self) -> Option<f64> {
+        self.history.sigma_decay_rate(self.history_window())
+    }
+
+    /// Returns the ratio between the decay rate of the step size
+    /// ([`sigma_decay_rate`][Self::sigma_decay_rate]) and the improvement rate of the best
+    /// function value found so far over the same window, or `None` until more generations than
+    /// the window have been completed. This is the quantity checked by
+    /// [`TerminationReason::PrematureConvergence`][crate::TerminationReason::PrematureConvergence],
+    /// and can also be polled to warn about runs that are collapsing.
+    ///
+    /// The improvement rate is measured relative to the magnitude of the best value on the same
+    /// logarithmic scale as the step size, `ln(old_best / best) / window` (for positive values
+    /// when minimizing). While the algorithm converges to an optimum with a value of zero, the
+    /// function value of a smooth function shrinks with the square of the step size, so the
+    /// ratio stays around `0.5` (or `1.0` if the function grows linearly away from the optimum),
+    /// with temporary peaks of up to about `10.0` while the covariance matrix adapts. If the
+    /// distribution instead collapses without finding better solutions (e.g. onto a saddle point
+    /// or into a local optimum), the step size keeps decaying while the best value stalls, and
+    /// the ratio grows without bound. It is `0.0` if the step size did not decrease and infinite
+    /// if it did but the best value did not improve at all.
+    ///
+    /// Because the improvement is relative to the magnitude of the best value, the ratio also
+    /// grows when converging to an optimum with a value far from zero, as the relative
+    /// improvement becomes tiny. If the optimal value is known approximately, the objective
+    /// function should be shifted so that it is near zero (e.g. with
+    /// [`Offset`][crate::objective_function::Offset]).
+    pub fn sigma_decay_ratio(&self) -> Option<f64> {
+        self.history
+            .sigma_decay_ratio(self.parameters.mode(), self.history_window())
+    }
+
+    /// Returns the number of recent generations used by `TolFun` and the related criteria
+    fn history_window(&self) -> usize {
+        let dim = self.parameters.dim() as f64;
+        10 + (30.0 * dim / self.parameters.lambda() as f64).ceil() as usize
+    }
+
+    /// Returns the direction followed by the latest update of the mean, scaled by the inverse
+    /// covariance matrix: the weighted recombination of the steps from the old mean to the
+    /// selected individuals, `sum(w_i * (x_i - m))`, multiplied by `(sigma^2 * C)^-1`, where
+    /// `sigma^2 * C` is the covariance matrix of the distribution that the generation was sampled
+    /// from. All zeros until [`next`][Self::next] has been called.
+    ///
+    /// The weighted recombination is the change of the mean before the learning rate
+    /// [`cm`][crate::CMAESOptions::cm] is applied. It estimates the natural gradient of the
+    /// expected objective function value with respect to the mean, which is preconditioned by
+    /// `sigma^2 * C`; scaling it by the inverse removes the preconditioning, so the result
+    /// estimates the direction of the ordinary (Euclidean) gradient up to a positive factor and
+    /// can be blended with gradient information directly. The mean moved by
+    /// `cm * sigma^2 * C * search_direction()`, using the step size and covariance matrix that the
+    /// generation was sampled from.
+    ///
+    /// It points towards better function values in both [`Mode`]s. Unlike [`mean`][Self::mean],
+    /// it is given in the internal search space, which differs from the space of the objective
+    /// function if bounds or a transform are enabled.
+    pub fn search_direction(&self) -> DVector<f64> {
+        self.state.search_direction().clone()
+    }
+
+    /// Returns the vector the mean moved by in the latest generation (`mean_t - mean_{t-1}`), or
+    /// `None` before [`next`][Self::next] has been called. This is the step actually taken by the
+    /// algorithm, equal to `cm * sigma^2 * C * search_direction()` (see
+    /// [`search_direction`][Self::search_direction]). Like
+    /// [`search_direction`][Self::search_direction], it is given in the internal search space and
+    /// does not include displacements caused by [`auto_kick`][crate::CMAESOptions::auto_kick].
+    pub fn last_mean_shift(&self) -> Option<DVector<f64>> {
+        self.state.last_mean_shift().cloned()
+    }
+
+    /// Recomputes the mean mapped into the feasible space if bounds are enabled and decoded if a
+    /// transform is enabled
+    fn update_feasible_mean(&mut self) {
+        let mapping = self.parameters.point_mapping();
+        self.feasible_mean = if mapping.bounds.is_some() || mapping.transform.is_some() {
+            Some(mapping.map(self.state.mean()).0)
+        } else {
+            None
+        };
+    }
+
+    /// Returns the current covariance matrix of the distribution.
+    pub fn covariance_matrix(&self) -> &SquareMatrix<f64> {
+        self.state.cov()
+    }
+
+    /// Replaces the covariance matrix of the distribution mid-run (e.g. with a better estimate
+    /// after the problem has changed), recomputing its eigendecomposition. The run continues from
+    /// the next generation with the new matrix, while the mean, step size, and histories are
+    /// unchanged. The matrix is given in the same space as
+    /// [`covariance_matrix`][Self::covariance_matrix] and describes the shape of the distribution
+    /// (it is scaled by `sigma^2`). Covariance matrices can be created with nalgebra's `DMatrix`.
+    ///
+    /// The evolution paths are not reset. They accumulate recent steps of the mean measured
+    /// relative to the old matrix, so they keep steering the rank-one update and the step size
+    /// adaptation as before, and fade out over the next roughly `1 / cc` and `1 / cs` generations.
+    /// This is harmless when the new matrix refines the old one, but after a substantial change
+    /// of shape they can briefly push the distribution back towards the old shape or change the
+    /// step size unexpectedly. In that case, creating a new `CMAES` (e.g. with `CMAES::from_parts`
+    /// and zero paths when the `unstable` feature is enabled) gives a cleaner start.
+    ///
+    /// If [`CMAESOptions::min_eigenvalue_ratio`] is set, the eigenvalues of the new matrix are
+    /// floored accordingly.
+    ///
+    /// Returns `Err` and leaves the state unchanged if the matrix does not have the correct size,
+    /// is not symmetric (up to a relative tolerance of `1e-10`), or is not positive-definite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    /// use nalgebra::DMatrix;
+    ///
+    /// let function = |x: &DVector<f64>| x[0].powi(2) + 1e4 * x[1].powi(2);
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 2], 1.0)
+    ///     .build(function)
+    ///     .unwrap();
+    /// let _ = cmaes_state.next();
+    ///
+    /// let cov = DMatrix::from_diagonal(&DVector::from(vec![1.0, 1e-4]));
+    /// cmaes_state.set_covariance(cov.clone()).unwrap();
+    /// assert_eq!(&cov, cmaes_state.covariance_matrix());
+    ///
+    /// let result = cmaes_state.run();
+    /// assert!(result.overall_best.unwrap().value < 1e-10);
+    /// ```
+    pub fn set_covariance(&mut self, cov: SquareMatrix<f64>) -> Result<(), InvalidCovarianceError> {
+        let dim = self.parameters.dim();
+        if cov.nrows() != dim || cov.ncols() != dim {
+            return Err(InvalidCovarianceError::Dimensions);
+        }
+        if cov.iter().any(|x| !x.is_finite()) {
+            return Err(InvalidCovarianceError::NotPositiveDefinite);
+        }
+        let tol = 1e-10 * cov.amax();
+        if (&cov - cov.transpose()).amax() > tol {
+            return Err(InvalidCovarianceError::NotSymmetric);
+        }
+
+        self.state
+            .set_cov(cov, self.parameters.min_eigenvalue_ratio())
+            .map_err(|_| InvalidCovarianceError::NotPositiveDefinite)
+    }
+
+    /// Returns the current evolution paths `(path_c, path_sigma)` of the covariance matrix and
+    /// the step size. See [`set_evolution_paths`][Self::set_evolution_paths] for their
+    /// normalization.
+    pub fn evolution_paths(&self) -> (&DVector<f64>, &DVector<f64>) {
+        (self.state.path_c(), self.state.path_sigma())
+    }
+
+    /// Replaces the evolution paths of the covariance matrix (`path_c`) and the step size
+    /// (`path_sigma`), starting with the next generation. Together with
+    /// [`set_covariance`][Self::set_covariance], this allows reproducing a mid-run state from
+    /// summary statistics (e.g. a published intermediate state) or transferring the momentum of
+    /// one run to another (e.g. when migrating between islands).
+    ///
+    /// Both paths are given in the internal search space (like
+    /// [`search_direction`][Self::search_direction]) and are normalized by the step size:
+    ///
+    /// - `path_c` accumulates the normalized mean shifts `(m_new - m_old) / (cm * sigma)`, scaled
+    ///   by `sqrt(cc * (2 - cc) * mu_eff)`. Under random selection it is distributed like
+    ///   `N(0, C)`, where `C` is the covariance matrix (without `sigma^2`).
+    /// - `path_sigma` accumulates the same steps, additionally multiplied by `C^(-1/2)`, and scaled
+    ///   by `sqrt(cs * (2 - cs) * mu_eff)`. Under random selection it is distributed like
+    ///   `N(0, I)`, and the step size grows or shrinks depending on whether its length is larger
+    ///   or smaller than the expected length of such a vector (about `sqrt(dimensions)`).
+    ///
+    /// Both paths are zero vectors at the start of a run. Paths taken from a run with a different
+    /// covariance matrix should be used together with that matrix, since `path_sigma` depends on
+    /// it.
+    ///
+    /// Returns `Err` and leaves the state unchanged if the length of either path does not match
+    /// the number of dimensions or if either path contains non-finite entries.
+    pub fn set_evolution_paths(
+        &mut self,
+        path_c: DVector<f64>,
+        path_sigma: DVector<f64>,
+    ) -> Result<(), InvalidEvolutionPathsError> {
+        let dim = self.parameters.dim();
+        if path_c.len() != dim || path_sigma.len() != dim {
+            return Err(InvalidEvolutionPathsError::Dimensions);
+        }
+        if path_c
+            .iter()
+            .chain(path_sigma.iter())
+            .any(|x| !x.is_finite())
+        {
+            return Err(InvalidEvolutionPathsError::NonFinite);
+        }
+
+        self.state.set_paths(path_c, path_sigma);
+        Ok(())
+    }
+
+    /// Returns the current eigenvalues of the distribution.
+    pub fn eigenvalues(&self) -> DVector<f64> {
+        self.state
+            .cov_sqrt_eigenvalues()
+            .diagonal()
+            .map(|x| x.powi(2))
+    }
+
+    /// Returns the natural logarithm of the probability density of `point` under the current search
+    /// distribution, the multivariate normal distribution `N(mean, sigma^2 * C)`. Useful for
+    /// importance sampling and other analyses that need the likelihood of a point.
+    ///
+    /// The determinant and inverse of the covariance matrix are taken from its cached
+    /// eigendecomposition, which is the one used to sample new points (it may lag behind
+    /// [`covariance_matrix`][Self::covariance_matrix] by a few generations in high dimensions), so
+    /// this is the density of the distribution that the next generation is sampled from.
+    ///
+    /// The point is given in the space of the objective function and is mapped into the internal
+    /// search space first if bounds or a transform are enabled. The density is that of the
+    /// internal distribution, without correcting for the change of variables.
+    pub fn log_density(&self, point: &DVector<f64>) -> f64 {
+        let point = self.parameters.point_mapping().to_internal(point);
+        self.state.log_density(&point)
+    }
+
+    /// Returns the quartiles of the objective function values of the latest generation, or `None`
+    /// if [`next`][Self::next] has not been called yet.
+    ///
+    /// For noisy functions, the [interquartile range][Quartiles::interquartile_range] serves as a
+    /// cheap noise indicator: if it stops shrinking while the step size keeps decreasing, the
+    /// differences between individuals are likely dominated by noise. The interquartile range is
+    /// also recorded by [`Plot`] if enabled.
+    pub fn function_value_quartiles(&self) -> Option<Quartiles> {
+        self.history.current_quartiles()
+    }
+
+    /// Returns the minimum, maximum, and median of the current eigenvalues of the distribution.
+    pub fn eigenvalue_stats(&self) -> EigenStats {
+        let mut eigenvalues = self.eigenvalues();
+        let sorted = eigenvalues.as_mut_slice();
+        sorted.sort_by(|a, b| utils::partial_cmp(*a, *b));
+
+        let n = sorted.len();
+        let median = if n % 2 == 1 {
+            sorted[n / 2]
+        } else {
+            (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+        };
+
+        EigenStats {
+            min: sorted[0],
+            max: sorted[n - 1],
+            median,
+        }
+    }
+
+    /// Returns the effective standard deviation of the distribution along each of its principal
+    /// axes, `sigma * sqrt(eigenvalue)`, paired with the (unit length) eigenvector of the axis.
+    /// The axes are sorted from the longest to the shortest, so the first entries are the
+    /// directions in which the search is still exploring and the last entries are the directions
+    /// in which it has converged the most.
+    ///
+    /// The values are taken from the cached eigendecomposition of the covariance matrix (see
+    /// [`log_density`][Self::log_density]) and are given in the internal search space, which
+    /// differs from the space of the objective function if bounds or a transform are enabled.
+    /// They are the semi-axes of the one standard deviation ellipsoid of the distribution around
+    /// the mean, which is useful for visualizing confidence ellipses.
+    pub fn principal_step_lengths(&self) -> Vec<(f64, DVector<f64>)> {
+        let sigma = self.state.sigma();
+        let eigenvectors = self.state.cov_eigenvectors();
+        let mut axes = self
+            .state
+            .cov_sqrt_eigenvalues()
+            .diagonal()
+            .iter()
+            .zip(eigenvectors.column_iter())
+            .map(|(sqrt_eigenvalue, eigenvector)| (sigma * sqrt_eigenvalue, eigenvector.into()))
+            .collect::<Vec<_>>();
+        axes.sort_by(|(a, _), (b, _)| utils::partial_cmp(*b, *a));
+        axes
+    }
+
+    /// Returns the current step size of the distribution.
+    pub fn sigma(&self) -> f64 {
+        self.state.sigma()
+    }
+
+    /// Resets the step size to `sigma` while keeping the mean, covariance matrix, and evolution
+    /// paths. Used by restart strategies to continue a run that terminated due to numerical
+    /// precision.
+    pub(crate) fn reinflate_sigma(&mut self, sigma: f64) {
+        self.state.set_sigma(sigma);
+    }
+
+    /// Returns the current multiplier of the covariance matrix learning rates. See
+    /// [`set_covariance_learning_scale`][Self::set_covariance_learning_scale].
+    pub fn covariance_learning_scale(&self) -> f64 {
+        self.state.covariance_learning_scale()
+    }
+
+    /// Sets a multiplier applied to the learning rates of the covariance matrix update (`c1` for
+    /// the rank-one update and `cmu` for the rank-mu update), starting with the next generation.
+    /// The default value is `1.0`, which leaves the update unchanged. The scale stays in effect
+    /// until it is changed again, so an external schedule can call this before each generation to
+    /// downweight the covariance contribution of, for example, early noisy generations.
+    ///
+    /// The learning rates themselves are fixed by the problem dimension and population size, so
+    /// this only scales them; the mean and step size updates are unaffected. A value of `0.0`
+    /// freezes the covariance matrix. Values greater than `1.0` speed up adaptation, but are capped
+    /// so that `scale * (c1 + cmu)` does not exceed `1.0` (at which point the old covariance
+    /// matrix is discarded entirely). Negative and NaN values are treated as `0.0`.
+    ///
+    /// Extreme values can stall adaptation: very small values prevent the distribution from
+    /// learning the shape of the function, while very large values make the covariance matrix
+    /// follow the noise of single generations and can cause early termination. Large values also
+    /// make the negative weights outweigh the remaining weight of the old covariance matrix, in
+    /// which case they are left out of the update (see
+    /// [`active_update_applied`][Self::active_update_applied]).
+    pub fn set_covariance_learning_scale(&mut self, scale: f64) {
+        self.state.set_covariance_learning_scale(scale.max(0.0));
+    }
+
+    /// Sets whether the covariance matrix is frozen, starting with the next generation. While it is
+    /// frozen, the rank-one and rank-mu updates of the covariance matrix and its
+    /// eigendecomposition are skipped, but the mean and the step size are still adapted. This
+    /// allows a two-phase strategy: learn the shape of the function first, then freeze it and let
+    /// only the step size adapt to refine the solution along the learned principal axes. The
+    /// frozen generations are also cheaper, because the updates are the most expensive part of a
+    /// generation in high dimensions. The covariance matrix is not frozen by default.
+    ///
+    /// Both evolution paths are still updated while the covariance matrix is frozen. The path of
+    /// the step size is normalized by the frozen covariance matrix, so cumulative step-size
+    /// adaptation works as usual. The path of the covariance matrix keeps accumulating the recent
+    /// steps without being used, so the rank-one update resumes with an up-to-date path when the
+    /// covariance matrix is unfrozen.
+    ///
+    /// Unlike a [`covariance_learning_scale`][Self::set_covariance_learning_scale] of `0.0`, which
+    /// computes the updates and discards them, this avoids the computation entirely. If the
+    /// eigendecomposition was out of date when the covariance matrix was frozen, it is updated
+    /// once in the first frozen generation so that points are sampled from the frozen matrix.
+    pub fn freeze_covariance(&mut self, frozen: bool) {
+        self.state.freeze_covariance(frozen);
+    }
+
+    /// Returns whether the covariance matrix is frozen. See
+    /// [`freeze_covariance`][Self::freeze_covariance].
+    pub fn covariance_frozen(&self) -> bool {
+        self.state.covariance_frozen()
+    }
+
+    /// Returns whether the latest covariance matrix update included the contribution of the
+    /// negative weights (the active update of active CMA-ES). Always `false` before
+    /// [`next`][Self::next] has been called, while the covariance matrix is
+    /// [frozen][Self::freeze_covariance], and if [`Weights::Positive`] is used.
+    ///
+    /// The negative contribution is left out for a generation if it could make the covariance
+    /// matrix lose positive-definiteness, which is the case when it outweighs the remaining weight
+    /// of the old covariance matrix. This never happens with the default weights and learning
+    /// rates, but can be caused by a large
+    /// [`covariance_learning_scale`][Self::set_covariance_learning_scale]. It is also `false` if
+    /// the covariance matrix learning rates are scaled to zero or if every individual with a
+    /// negative weight was unrankable.
+    pub fn active_update_applied(&self) -> bool {
+        self.state.active_update_applied()
+    }
+
+    /// Returns the current axis ratio of the distribution.
+    pub fn axis_ratio(&self) -> f64 {
+        self.state.axis_ratio()
+    }
+
+    /// Returns the generalized volume of the distribution, `sigma^N * sqrt(det(C))`. This is
+    /// proportional to the volume of the ellipsoid of points within one standard deviation of the
+    /// mean, which is the volume of the unit ball times this value. It is computed from the cached
+    /// eigendecomposition of the covariance matrix, so it is cheap to call.
+    ///
+    /// Unlike [`sigma`][Self::sigma] or [`axis_ratio`][Self::axis_ratio], it summarizes the size
+    /// of the distribution in all directions at once, which makes it a convenient single-number
+    /// convergence monitor: it shrinks geometrically while the algorithm converges. It may
+    /// underflow to `0.0` or overflow to infinity in high dimensions, so its logarithm is usually
+    /// the more useful quantity to track.
+    ///
+    /// It is measured in the internal search space, which differs from the space of the objective
+    /// function if bounds or a transform are enabled.
+    pub fn distribution_volume(&self) -> f64 {
+        self.state.distribution_volume()
+    }
+
+    /// Returns the best individual of the latest generation and its function value. Will always
+    /// return `Some` as long as [`next`][Self::next] has been called at least once and the
+    /// algorithm did not terminate in the first generation with
+    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
+    pub fn current_best_individual(&self) -> Option<&Individual> {
+        self.history.current_best_individual()
+    }
+
+    /// Returns the evaluated individuals of the latest generation, sorted from best to worst.
+    /// Empty until [`next`][Self::next] has been called at least once, and not updated for a
+    /// generation that terminated with
+    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
+    pub fn current_population(&self) -> &[EvaluatedPoint] {
+        &self.current_population
+    }
+
+    /// Returns the permutation that sorted the latest generation by fitness: for each rank
+    /// position (best first), the index of the point in the order in which the points were
+    /// sampled and passed to the objective function. `current_population()[r]` is therefore the
+    /// point sampled at index `last_ranking()[r]`, which allows correlating data recorded per
+    /// sampled point (e.g. by the objective function) with the ranks. Any
+    /// [injected][Self::inject] points are sampled first. Empty and not updated under the same
+    /// conditions as [`current_population`][Self::current_population].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// // Record some metadata for each sampled point, in sampling order
+    /// let metadata = Arc::new(Mutex::new(Vec::new()));
+    /// let recorded = metadata.clone();
+    /// let function = move |x: &DVector<f64>| {
+    ///     recorded.lock().unwrap().push(x.clone());
+    ///     x.magnitude()
+    /// };
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let _ = cmaes_state.next();
+    ///
+    /// let metadata = metadata.lock().unwrap();
+    /// for (individual, &index) in cmaes_state
+    ///     .current_population()
+    ///     .iter()
+    ///     .zip(cmaes_state.last_ranking())
+    /// {
+    ///     assert_eq!(individual.point(), &metadata[index]);
+    /// }
+    /// ```
+    pub fn last_ranking(&self) -> &[usize] {
+        self.sampler.ranking()
+    }
+
+    /// Returns the best individual of the generation before the latest one, which can be compared
+    /// with the first individual of [`current_population`][Self::current_population] to compute
+    /// the change in the best function value between consecutive generations. Returns `None`
+    /// until [`next`][Self::next] has been called at least twice.
+    pub fn previous_best(&self) -> Option<&EvaluatedPoint> {
+        self.previous_best.as_ref()
+    }
+
+    /// Returns the number of individuals in the latest generation that could not be evaluated
+    /// (for which a [`PartialObjectiveFunction`] returned `None` or whose invalid value was skipped
+    /// according to [`CMAESOptions::invalid_value_policy`]). Always `0` unless
+    /// [`run_partial`][Self::run_partial] or [`next_partial`][Self::next_partial] is used or
+    /// invalid values are skipped.
+    pub fn unrankable_count(&self) -> usize {
+        self.current_population
+            .iter()
+            .filter(|individual| !individual.rankable())
+            .count()
+    }
+
+    /// Draws `n` points from the current search distribution (using the current mean, step size,
+    /// and covariance matrix), for example to analyze the sensitivity of the objective function
+    /// around the optimum after termination. The points are not evaluated, so this does not count
+    /// as function evaluations and does not advance the algorithm.
+    ///
+    /// The points are mapped into the feasible space if bounds are enabled and decoded if a
+    /// transform is enabled.
+    ///
+    /// The points are drawn from the same RNG used by the algorithm, so the result is
+    /// reproducible for a given seed, but calling this method before continuing optimization will
+    /// change the points sampled in later generations.
+    pub fn sample_final(&mut self, n: usize) -> Vec<DVector<f64>> {
+        self.sampler.sample_unevaluated(
+            &self.state,
+            n,
+            self.parameters.parallel_update(),
+            self.parameters.point_mapping(),
+        )
+    }
+
+    /// Injects points to be evaluated in place of sampled points in the following generations, for
+    /// example to seed the search with known good solutions. The points are given in the same
+    /// space as the input to the objective function (i.e., they are encoded and mapped into the
+    /// internal search space if a transform or bounds are enabled). At most
+    /// [`population_size`][Self::population_size] points are evaluated per generation, and any
+    /// remaining points are carried over to later generations.
+    ///
+    /// Injected points take part in the update of the distribution like sampled points, so good
+    /// points pull the mean towards them. Each point is evaluated as given, but if it lies much
+    /// farther from the mean than a typical sampled point, the step towards it is shortened when
+    /// updating the distribution so that the step size and covariance matrix adaptation are not
+    /// disrupted.
+    pub fn inject<I: IntoIterator<Item = DVector<f64>>>(&mut self, points: I) {
+        let mapping = self.parameters.point_mapping();
+        self.sampler
+            .inject(points.into_iter().map(|point| mapping.to_internal(&point)));
+    }
+
+    /// Returns the number of injected points (see [`inject`][Self::inject]) that have not been
+    /// evaluated yet.
+    pub fn num_injected(&self) -> usize {
+        self.sampler.num_injected()
+    }
+
+    /// Returns the diversity of the latest generation, defined as the mean Euclidean distance
+    /// between all pairs of individuals in [`current_population`][Self::current_population] (in
+    /// the feasible space if bounds are enabled). Returns `0.0` until [`next`][Self::next] has
+    /// been called at least once.
+    ///
+    /// Unlike the step size, this measures the actual spread of the evaluated points, so it can be
+    /// used to detect a population that has collapsed onto a small region. Computing it takes
+    /// O(lambda^2 * N) time, so it is only computed automatically if the opt-in
+    /// [`TerminationReason::TolDiversity`] criterion is enabled.
+    pub fn population_diversity(&self) -> f64 {
+        sampling::mean_pairwise_distance(&self.current_population)
+    }
+
+    /// Returns the generation in which an individual first reached the
+    /// [`fun_target`][CMAESOptions::fun_target] (in either [`TargetMode`]), or `None` if it has
+    /// not been reached (or is not set). Generations are counted from `1`, so the corresponding
+    /// number of function evaluations can be looked up in
+    /// [`convergence_curve`][Self::convergence_curve] at index `generation - 1` (unless entries
+    /// have been discarded due to [`CMAESOptions::max_recorded_generations`]).
+    pub fn fun_target_generation(&self) -> Option<usize> {
+        self.fun_target_generation
+    }
+
+    /// Returns each of the [`target_values`][CMAESOptions::target_values] along with the number of
+    /// the function evaluation that first reached it, or `None` if it has not been reached. The
+    /// targets are in the order in which they were set.
+    ///
+    /// These are the data points for computing the expected running time (ERT) of benchmarking
+    /// methodologies such as COCO/BBOB: the ERT of a target is the total number of evaluations of
+    /// all runs divided by the number of runs that reached it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude_squared();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .target_values(vec![1e-2, 1e-4, 1e-8, -1.0])
+    ///     .max_generations(500)
+    ///     .build(function)
+    ///     .unwrap();
+    /// let _ = cmaes_state.run();
+    ///
+    /// let hits = cmaes_state.target_hits();
+    /// assert!(hits[0].1.unwrap() <= hits[1].1.unwrap());
+    /// assert!(hits[1].1.unwrap() <= hits[2].1.unwrap());
+    /// assert_eq!((-1.0, None), hits[3]);
+    /// ```
+    pub fn target_hits(&self) -> Vec<(f64, Option<usize>)> {
+        self.target_hits.clone()
+    }
+
+    /// Returns the convergence curve of the run: for each generation, the number of function
+    /// evaluations performed by the end of that generation paired with the best function value
+    /// found so far (the overall best). This is the standard way to plot and compare convergence
+    /// speed across algorithms and population sizes.
+    ///
+    /// One entry is recorded per generation (except for a generation that terminated with
+    /// [`TerminationReason::InvalidFunctionValue`] and for generations before the first individual
+    /// that could be ranked). Entries are never discarded unless
+    /// [`CMAESOptions::max_recorded_generations`] is set, in which case only the entries of the
+    /// latest generations are kept.
+    pub fn convergence_curve(&self) -> Vec<(usize, f64)> {
+        self.history
+            .trajectory()
+            .iter()
+            .map(|entry| (entry.function_evals, entry.best_value))
+            .collect()
+    }
+
+    /// Writes the trajectory of the run to `writer` in CSV format, for quick analysis in a
+    /// spreadsheet or with a data frame library (without enabling the `plotters` feature). The
+    /// first line is the header, followed by one row per generation:
+    ///
+    /// ```text
+    /// generation,function_evals,best_value,median_value,sigma,axis_ratio,mean_norm
+    /// ```
+    ///
+    /// - `generation`: the number of the generation (starting at `1`)
+    /// - `function_evals`: the number of function evaluations performed by the end of the
+    ///   generation
+    /// - `best_value`: the best function value found so far (the overall best, as in
+    ///   [`convergence_curve`][Self::convergence_curve])
+    /// - `median_value`: the median function value of the rankable individuals of the generation
+    ///   (`NaN` if none of them could be ranked, see [`next_partial`][Self::next_partial])
+    /// - `sigma`, `axis_ratio`, `mean_norm`: the step size, the axis ratio (see
+    ///   [`axis_ratio`][Self::axis_ratio]), and the Euclidean norm of the mean (see
+    ///   [`mean`][Self::mean]) of the distribution that the generation was sampled from
+    ///
+    /// The integers are written in decimal and the function values and other quantities in
+    /// scientific notation (e.g. `1.5e-3`). Rows are recorded like the entries of the convergence
+    /// curve, so only the latest generations are written if
+    /// [`CMAESOptions::max_recorded_generations`] is set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    ///
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let _ = cmaes_state.run();
+    /// cmaes_state
+    ///     .write_trajectory_csv(File::create("trajectory.csv").unwrap())
+    ///     .unwrap();
+    /// ```
+    pub fn write_trajectory_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        recording::write_trajectory_csv(writer, self.history.trajectory())
+    }
+
+    /// Returns the best point found so far and its function value, which is the answer to the
+    /// optimization problem and usually what should be used after a run. Returns `None` in the same
+    /// cases as [`overall_best_individual`][Self::overall_best_individual], which this is a
+    /// shorthand for.
+    ///
+    /// The other estimates of the optimum are less suited for this: the
+    /// [`current_best_individual`][Self::current_best_individual] is only the best point of the
+    /// latest generation, and the [`mean`][Self::mean] of the distribution has not been evaluated
+    /// (see [`best_mean_point`][Self::best_mean_point] for noisy functions, where the best
+    /// evaluated mean may be more reliable than the best sampled point).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .build(function)
+    ///     .unwrap();
+    /// assert!(cmaes_state.best().is_none());
+    ///
+    /// cmaes_state.run();
+    ///
+    /// let (point, value) = cmaes_state.best().unwrap();
+    /// assert_eq!(function(&point), value);
+    /// assert!(value < 1e-10);
+    /// ```
+    pub fn best(&self) -> Option<(DVector<f64>, f64)> {
+        self.overall_best_individual()
+            .map(|individual| (individual.point.clone(), individual.value))
+    }
+
+    /// Returns the best individual of any generation and its function value. Will always
+    /// return `Some` as long as [`next`][Self::next] has been called at least once and the
+    /// algorithm did not terminate in the first generation with
+    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
+    pub fn overall_best_individual(&self) -> Option<&Individual> {
+        self.history.overall_best_individual()
+    }
+
+    /// Returns a [`GenerationSnapshot`] of the current state
+    fn snapshot(&self) -> GenerationSnapshot {
+        GenerationSnapshot {
+            generation: self.generation(),
+            function_evals: self.function_evals(),
+            best_value: self.overall_best_individual().map(|best| best.value),
+            sigma: self.sigma(),
+        }
+    }
+
+    /// Returns the time at which the `CMAES` was created.
+    pub fn time_created(&self) -> Instant {
+        self.time_created
+    }
+
+    /// Returns the time elapsed since the `CMAES` was created.
+    pub fn elapsed(&self) -> Duration {
+        self.time_created.elapsed()
+    }
+
+    /// Returns a reference to the data plot if enabled.
     #[cfg(feature = "plotters")]
     pub fn get_plot(&self) -> Option<&Plot> {
         self.plot.as_ref()
@@ -464,12 +2172,18 @@ impl<F> CMAES<F> {
     }
 
     /// Returns a `TerminationData` with the current best individual/value and the given reasons.
-    fn get_termination_data(&self, reasons: Vec<TerminationReason>) -> TerminationData {
+    fn get_termination_data<D: Into<TerminationDetail>>(&self, details: Vec<D>) -> TerminationData {
+        let details = details.into_iter().map(Into::into).collect::<Vec<_>>();
+
         return TerminationData {
             current_best: self.current_best_individual().cloned(),
             overall_best: self.overall_best_individual().cloned(),
-            final_mean: self.state.mean().clone(),
-            reasons,
+            final_mean: self.mean().clone(),
+            reasons: details.iter().map(|d| d.reason).collect(),
+            details,
+            function_evals: self.function_evals(),
+            mode: self.parameters.mode(),
+            metadata: self.metadata.clone(),
         };
     }
 
@@ -545,6 +2259,21 @@ impl<F> CMAES<F> {
         );
     }
 
+    /// Returns the status line shown if [`CMAESOptions::live_status`] is enabled
+    fn live_status_line(&self) -> String {
+        let best = self
+            .overall_best_individual()
+            .map(|x| format!("{:.6e}", x.value))
+            .unwrap_or_else(|| "-".to_string());
+        format!(
+            "gen {} | evals {} | best {} | sigma {:.3e}",
+            self.state.generation(),
+            self.sampler.function_evals(),
+            best,
+            self.state.sigma(),
+        )
+    }
+
     /// Calls [`print_info`][Self::print_info] if not already called automatically this generation
     /// and prints the results. The values that are printed are the:
     ///
@@ -576,7 +2305,7 @@ impl<F> CMAES<F> {
             println!("Overall best function value: {:e}", overall.value);
         }
 
-        println!("Final mean: {}", self.state.mean());
+        println!("Final mean: {}", self.mean());
     }
 }
 
@@ -596,50 +2325,194 @@ impl<F: ObjectiveFunction> CMAES<F> {
         result
     }
 
-    /// Samples `lambda` points from the distribution and returns the points sorted by their
-    /// objective function values. Also updates the histories of the best and median function
-    /// values.
+    /// Like [`run`][Self::run], but also terminates with [`TerminationReason::Predicate`] as soon
+    /// as `predicate` returns `true`. This is equivalent to calling [`next`][Self::next] in a loop
+    /// and checking the predicate after each generation, except that the final state is
+    /// plotted/printed like in [`run`][Self::run].
     ///
-    /// Returns `Err` if an invalid function value was encountered.
-    fn sample(&mut self) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
-        // Sample points
-        let individuals = self.sampler.sample(
-            &self.state,
-            self.parameters.mode(),
-            self.parameters.parallel_update(),
-        )?;
-
-        self.sample_internal(&individuals);
-
-        Ok(individuals)
-    }
+    /// The predicate is called with the updated state after each generation in which no built-in
+    /// termination criterion was met (so it is never called before the first generation).
+    /// Built-in termination criteria take precedence: if any are met, the run terminates with
+    /// only those reasons and the predicate is not called for that generation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, TerminationReason};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let result = cmaes_state.run_until(|state| state.generation() >= 10);
+    ///
+    /// assert_eq!(result.reasons, vec![TerminationReason::Predicate]);
+    /// assert_eq!(cmaes_state.generation(), 10);
+    /// ```
+    pub fn run_until<P: FnMut(&Self) -> bool>(&mut self, mut predicate: P) -> TerminationData {
+        let result = loop {
+            if let Some(data) = self.next() {
+                break data;
+            }
 
-    /// Advances to the next generation. Returns `Some` if a termination condition has been reached
-    /// and the algorithm should be stopped. [`run`][Self::run] is generally easier to use, but
-    /// iteration can be performed manually if finer control is needed (plotting/printing the final
-    /// state must be done manually as well in this case).
-    #[allow(clippy::should_implement_trait)]
-    #[must_use]
-    pub fn next(&mut self) -> Option<TerminationData> {
-        // Sample individuals
-        let individuals = match self.sample() {
-            Ok(x) => x,
-            Err(_) => {
-                return Some(
-                    self.get_termination_data(vec![TerminationReason::InvalidFunctionValue]),
-                );
+            if predicate(self) {
+                break self.get_termination_data(vec![TerminationReason::Predicate]);
             }
         };
 
-        self.next_internal(&individuals)
-    }
-}
+        self.run_internal(&result);
+
+        result
+    }
+
+    /// Like [`run`][Self::run], but also sends a [`GenerationSnapshot`] to `sender` after each
+    /// generation (including the final one), for consuming the progress of a run on another
+    /// thread. This allows running the algorithm on a blocking thread while an asynchronous
+    /// application (e.g. its UI) receives the progress.
+    ///
+    /// If the receiver has been dropped, the snapshots are discarded and the run continues
+    /// normally; use [`run_until`][Self::run_until] to stop a run early instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    ///
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .max_generations(100)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let handle = thread::spawn(move || cmaes_state.run_streaming(sender));
+    ///
+    /// for snapshot in receiver {
+    ///     println!("{}: {:?}", snapshot.generation, snapshot.best_value);
+    /// }
+    /// let result = handle.join().unwrap();
+    /// ```
+    pub fn run_streaming(&mut self, sender: Sender<GenerationSnapshot>) -> TerminationData {
+        let result = loop {
+            let data = self.next();
+            let _ = sender.send(self.snapshot());
+
+            if let Some(data) = data {
+                break data;
+            }
+        };
+
+        self.run_internal(&result);
+
+        result
+    }
+
+    /// Like [`run`][Self::run], but pauses before using more than `n` further function
+    /// evaluations, for example to give several algorithms the same budget in interleaved
+    /// benchmarks. Returns [`RunStatus::Paused`] if the budget was used up and
+    /// [`RunStatus::Terminated`] if the algorithm terminated first, in which case the final state
+    /// is plotted/printed like in [`run`][Self::run].
+    ///
+    /// Generations are never split, so the budget is respected as an upper bound: a generation is
+    /// only started if all of its evaluations (`lambda` times
+    /// [`CMAESOptions::evaluations_per_candidate`], plus one if
+    /// [`CMAESOptions::evaluate_mean`] is enabled) fit within the remaining budget. The number of
+    /// evaluations used is therefore exactly `n` only if it is a multiple of the evaluations per
+    /// generation, and up to one generation's worth of the budget may be left unused otherwise
+    /// (including all of it if `n` is smaller than a generation). The unused remainder can be
+    /// added to the budget of the next call. Splitting generations instead would change the
+    /// course of the run compared to an uninterrupted one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, RunStatus};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .population_size(8)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// // 100 evaluations fit 12 generations of 8 points
+    /// assert!(matches!(cmaes_state.run_for_evals(100), RunStatus::Paused));
+    /// assert_eq!(96, cmaes_state.function_evals());
+    ///
+    /// // Resume the run
+    /// assert!(matches!(cmaes_state.run_for_evals(104), RunStatus::Paused));
+    /// assert_eq!(200, cmaes_state.function_evals());
+    /// ```
+    pub fn run_for_evals(&mut self, n: usize) -> RunStatus {
+        let budget_end = self.sampler.function_evals().saturating_add(n);
+
+        loop {
+            let evals_per_generation =
+                self.sampler.generation_evals() + usize::from(self.parameters.evaluate_mean());
+            if self.sampler.function_evals() + evals_per_generation > budget_end {
+                return RunStatus::Paused;
+            }
+
+            if let Some(data) = self.next() {
+                self.run_internal(&data);
+                return RunStatus::Terminated(data);
+            }
+        }
+    }
+
+    /// Samples `lambda` points from the distribution and returns the points sorted by their
+    /// objective function values. Also updates the histories of the best and median function
+    /// values.
+    ///
+    /// Returns `Err` if an invalid function value was encountered.
+    fn sample(&mut self) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
+        // Sample points
+        let individuals = self.sampler.sample(
+            &self.state,
+            self.parameters.mode(),
+            self.parameters.parallel_update(),
+            self.parameters.point_mapping(),
+        )?;
+
+        self.sample_internal(&individuals);
+
+        Ok(individuals)
+    }
+
+    /// Advances to the next generation. Returns `Some` if a termination condition has been reached
+    /// and the algorithm should be stopped. [`run`][Self::run] is generally easier to use, but
+    /// iteration can be performed manually if finer control is needed (plotting/printing the final
+    /// state must be done manually as well in this case).
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn next(&mut self) -> Option<TerminationData> {
+        // Sample individuals
+        let individuals = match self.sample() {
+            Ok(x) => x,
+            Err(_) => {
+                return Some(
+                    self.get_termination_data(vec![TerminationReason::InvalidFunctionValue]),
+                );
+            }
+        };
+
+        self.next_internal(individuals, |f, x| Some(f.evaluate(x)))
+    }
+}
 
 impl<F: ParallelObjectiveFunction> CMAES<F> {
     /// Like [`run`][Self::run], but executes the objective function in parallel using multiple
     /// threads. Requires that `F` implements
     /// [`ParallelObjectiveFunction`][crate::objective_function::ParallelObjectiveFunction].
     ///
+    /// Only the evaluations of the objective function are performed in parallel, and the function
+    /// values are collected in the order in which the points were sampled, so the results are
+    /// bit-identical to those of [`run`][Self::run] with the same seed (as long as the
+    /// objective function itself is deterministic).
+    ///
     /// Uses [rayon][rayon] internally.
     pub fn run_parallel(&mut self) -> TerminationData {
         let result = loop {
@@ -653,12 +2526,34 @@ impl<F: ParallelObjectiveFunction> CMAES<F> {
         result
     }
 
+    /// Like [`run_until`][Self::run_until], but executes the objective function in parallel like
+    /// [`run_parallel`][Self::run_parallel].
+    pub fn run_parallel_until<P: FnMut(&Self) -> bool>(
+        &mut self,
+        mut predicate: P,
+    ) -> TerminationData {
+        let result = loop {
+            if let Some(data) = self.next_parallel() {
+                break data;
+            }
+
+            if predicate(self) {
+                break self.get_termination_data(vec![TerminationReason::Predicate]);
+            }
+        };
+
+        self.run_internal(&result);
+
+        result
+    }
+
     /// Like `sample`, but evaluates the sampled points using multiple threads
     fn sample_parallel(&mut self) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
         let individuals = self.sampler.sample_parallel(
             &self.state,
             self.parameters.mode(),
             self.parameters.parallel_update(),
+            self.parameters.point_mapping(),
         )?;
 
         self.sample_internal(&individuals);
@@ -670,6 +2565,11 @@ impl<F: ParallelObjectiveFunction> CMAES<F> {
     /// threads. Requires that `F` implements
     /// [`ParallelObjectiveFunction`][crate::objective_function::ParallelObjectiveFunction].
     ///
+    /// Only the evaluations of the objective function are performed in parallel, and the function
+    /// values are collected in the order in which the points were sampled, so the results are
+    /// bit-identical to those of [`next`][Self::next] with the same seed (as long as the
+    /// objective function itself is deterministic).
+    ///
     /// Uses [rayon][rayon] internally.
     pub fn next_parallel(&mut self) -> Option<TerminationData> {
         // Sample individuals
@@ -682,12 +2582,113 @@ impl<F: ParallelObjectiveFunction> CMAES<F> {
             }
         };
 
-        self.next_internal(&individuals)
+        self.next_internal(individuals, |f, x| Some(f.evaluate_parallel(x)))
+    }
+}
+
+impl<F: BatchObjectiveFunction> CMAES<F> {
+    /// Like [`run`][Self::run], but evaluates each generation in chunks using a single objective
+    /// function call per chunk. Requires that `F` implements
+    /// [`BatchObjectiveFunction`][crate::objective_function::BatchObjectiveFunction].
+    pub fn run_batch(&mut self) -> TerminationData {
+        let result = loop {
+            if let Some(data) = self.next_batch() {
+                break data;
+            }
+        };
+
+        self.run_internal(&result);
+
+        result
+    }
+
+    /// Like `sample`, but evaluates the sampled points in chunks
+    fn sample_batch(&mut self) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
+        let individuals = self.sampler.sample_batch(
+            &self.state,
+            self.parameters.mode(),
+            self.parameters.parallel_update(),
+            self.parameters.point_mapping(),
+        )?;
+
+        self.sample_internal(&individuals);
+
+        Ok(individuals)
+    }
+
+    /// Like [`next`][Self::next], but evaluates each generation in chunks using a single objective
+    /// function call per chunk. Requires that `F` implements
+    /// [`BatchObjectiveFunction`][crate::objective_function::BatchObjectiveFunction].
+    pub fn next_batch(&mut self) -> Option<TerminationData> {
+        // Sample individuals
+        let individuals = match self.sample_batch() {
+            Ok(x) => x,
+            Err(_) => {
+                return Some(
+                    self.get_termination_data(vec![TerminationReason::InvalidFunctionValue]),
+                );
+            }
+        };
+
+        self.next_internal(individuals, |f, x| {
+            f.evaluate_batch(std::slice::from_ref(x)).first().copied()
+        })
+    }
+}
+
+impl<F: PartialObjectiveFunction> CMAES<F> {
+    /// Like [`run`][Self::run], but the objective function may return `None` for points that it
+    /// cannot evaluate. These points are ranked last and do not contribute to the update of the
+    /// distribution. Requires that `F` implements
+    /// [`PartialObjectiveFunction`][crate::objective_function::PartialObjectiveFunction].
+    pub fn run_partial(&mut self) -> TerminationData {
+        let result = loop {
+            if let Some(data) = self.next_partial() {
+                break data;
+            }
+        };
+
+        self.run_internal(&result);
+
+        result
+    }
+
+    /// Like `sample`, but points that cannot be evaluated are marked as unrankable
+    fn sample_partial(&mut self) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
+        let individuals = self.sampler.sample_partial(
+            &self.state,
+            self.parameters.mode(),
+            self.parameters.parallel_update(),
+            self.parameters.point_mapping(),
+        )?;
+
+        self.sample_internal(&individuals);
+
+        Ok(individuals)
+    }
+
+    /// Like [`next`][Self::next], but the objective function may return `None` for points that it
+    /// cannot evaluate. Requires that `F` implements
+    /// [`PartialObjectiveFunction`][crate::objective_function::PartialObjectiveFunction].
+    pub fn next_partial(&mut self) -> Option<TerminationData> {
+        // Sample individuals
+        let individuals = match self.sample_partial() {
+            Ok(x) => x,
+            Err(_) => {
+                return Some(
+                    self.get_termination_data(vec![TerminationReason::InvalidFunctionValue]),
+                );
+            }
+        };
+
+        self.next_internal(individuals, |f, x| f.evaluate_partial(x))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
     use super::*;
 
     fn dummy_function(_: &DVector<f64>) -> f64 {
@@ -709,6 +2710,20 @@ mod tests {
         assert!(cmaes.overall_best_individual().is_some());
     }
 
+    #[test]
+    fn test_best() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .max_generations(30)
+            .build(function)
+            .unwrap();
+        assert!(cmaes.best().is_none());
+
+        let result = cmaes.run();
+        let overall_best = result.overall_best.unwrap();
+        assert_eq!(Some((overall_best.point, overall_best.value)), cmaes.best());
+    }
+
     #[test]
     fn test_immediate_termination() {
         let function = |_: &DVector<f64>| f64::NAN;
@@ -724,6 +2739,2086 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_value_policy() {
+        // Every fourth evaluation is invalid
+        let function = |evals: &mut usize, x: &DVector<f64>| {
+            *evals = (*evals + 1) % 4;
+            if *evals == 0 {
+                f64::NAN
+            } else {
+                x.magnitude()
+            }
+        };
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .population_size(8)
+            .max_generations(20);
+
+        let mut evals = 0;
+        let mut cmaes = options
+            .clone()
+            .build(|x: &DVector<f64>| function(&mut evals, x))
+            .unwrap();
+        let result = cmaes.run();
+        assert_eq!(
+            vec![TerminationReason::InvalidFunctionValue],
+            result.reasons
+        );
+        assert_eq!(1, cmaes.invalid_evals());
+        assert_eq!(0, cmaes.function_evals());
+
+        // Skipped individuals are ranked last and the run continues
+        let mut evals = 0;
+        let mut cmaes = options
+            .clone()
+            .invalid_value_policy(InvalidPolicy::SkipIndividual)
+            .build(|x: &DVector<f64>| function(&mut evals, x))
+            .unwrap();
+        let result = cmaes.run();
+        assert_eq!(vec![TerminationReason::MaxGenerations], result.reasons);
+        assert_eq!(40, cmaes.invalid_evals());
+        assert_eq!(2, cmaes.unrankable_count());
+        assert!(cmaes.current_population()[..6]
+            .iter()
+            .all(|individual| individual.rankable() && individual.value().is_finite()));
+
+        // Invalid values occur in every generation
+        let mut evals = 0;
+        let mut cmaes = options
+            .clone()
+            .invalid_value_policy(InvalidPolicy::TerminateAfter(3))
+            .build(|x: &DVector<f64>| function(&mut evals, x))
+            .unwrap();
+        let result = cmaes.run();
+        assert_eq!(
+            vec![TerminationReason::InvalidFunctionValue],
+            result.reasons
+        );
+        assert_eq!(3, cmaes.generation() + 1);
+        assert_eq!(6, cmaes.invalid_evals());
+
+        // A generation without any valid values always terminates the run
+        let mut cmaes = options
+            .invalid_value_policy(InvalidPolicy::SkipIndividual)
+            .build(|_: &DVector<f64>| f64::NAN)
+            .unwrap();
+        let result = cmaes.run();
+        assert_eq!(
+            vec![TerminationReason::InvalidFunctionValue],
+            result.reasons
+        );
+        assert_eq!(8, cmaes.invalid_evals());
+        assert_eq!(8, cmaes.function_evals());
+    }
+
+    #[test]
+    fn test_run_for_evals() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .population_size(10)
+            .seed(1);
+
+        let mut cmaes = options.clone().build(function).unwrap();
+        assert!(matches!(cmaes.run_for_evals(9), RunStatus::Paused));
+        assert_eq!(0, cmaes.function_evals());
+        assert!(matches!(cmaes.run_for_evals(55), RunStatus::Paused));
+        assert_eq!(50, cmaes.function_evals());
+        assert!(matches!(cmaes.run_for_evals(50), RunStatus::Paused));
+        assert_eq!(100, cmaes.function_evals());
+
+        // The evaluation of the mean counts towards the budget
+        let mut with_mean = options.clone().evaluate_mean(true).build(function).unwrap();
+        assert!(matches!(with_mean.run_for_evals(50), RunStatus::Paused));
+        assert_eq!(44, with_mean.function_evals());
+
+        // Each repeated evaluation counts towards the budget
+        let mut repeated = options
+            .clone()
+            .evaluations_per_candidate(3)
+            .build(function)
+            .unwrap();
+        assert!(matches!(repeated.run_for_evals(90), RunStatus::Paused));
+        assert_eq!(90, repeated.function_evals());
+        assert!(matches!(repeated.run_for_evals(50), RunStatus::Paused));
+        assert_eq!(120, repeated.function_evals());
+
+        // Pausing does not change the course of the run
+        let mut paused = options.clone().build(function).unwrap();
+        let status = loop {
+            match paused.run_for_evals(30) {
+                RunStatus::Paused => {}
+                status => break status,
+            }
+        };
+        let expected = options.build(function).unwrap().run();
+        match status {
+            RunStatus::Terminated(result) => {
+                assert_eq!(expected.reasons, result.reasons);
+                assert_eq!(expected.function_evals, result.function_evals);
+                assert_eq!(expected.final_mean, result.final_mean);
+            }
+            RunStatus::Paused => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_function_value_quartiles() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .population_size(9)
+            .build(function)
+            .unwrap();
+        assert!(cmaes.function_value_quartiles().is_none());
+
+        let _ = cmaes.next();
+        let values = cmaes
+            .current_population()
+            .iter()
+            .map(|p| p.value())
+            .collect::<Vec<_>>();
+        let quartiles = cmaes.function_value_quartiles().unwrap();
+        // The population is sorted, so the quartiles of 9 values fall on the 3rd, 5th, and 7th
+        assert_eq!(
+            Quartiles {
+                lower: values[2],
+                median: values[4],
+                upper: values[6],
+            },
+            quartiles
+        );
+        assert_eq!(values[6] - values[2], quartiles.interquartile_range());
+        assert_eq!(2.5, Quartiles::new(vec![4.0, 1.0, 3.0, 2.0]).median);
+    }
+
+    #[test]
+    fn test_eigenvalue_stats() {
+        let function = |x: &DVector<f64>| {
+            x.iter()
+                .enumerate()
+                .map(|(i, xi)| 10f64.powi(i as i32) * xi.powi(2))
+                .sum()
+        };
+        let mut cmaes = CMAESOptions::new(vec![1.0; 5], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        let initial = cmaes.eigenvalue_stats();
+        assert_eq!(
+            EigenStats {
+                min: 1.0,
+                max: 1.0,
+                median: 1.0
+            },
+            initial
+        );
+
+        for _ in 0..100 {
+            let _ = cmaes.next();
+        }
+
+        let stats = cmaes.eigenvalue_stats();
+        assert!(stats.min <= stats.median && stats.median <= stats.max);
+        assert_eq!(cmaes.eigenvalues().min(), stats.min);
+        assert_eq!(cmaes.eigenvalues().max(), stats.max);
+    }
+
+    #[test]
+    fn test_principal_step_lengths() {
+        let function = |x: &DVector<f64>| {
+            x.iter()
+                .enumerate()
+                .map(|(i, xi)| 10f64.powi(2 * i as i32) * xi.powi(2))
+                .sum()
+        };
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 0.5)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        let axes = cmaes.principal_step_lengths();
+        assert_eq!(3, axes.len());
+        assert!(axes.iter().all(|(length, _)| *length == 0.5));
+
+        for _ in 0..150 {
+            let _ = cmaes.next();
+        }
+
+        let axes = cmaes.principal_step_lengths();
+        assert!(axes.windows(2).all(|pair| pair[0].0 >= pair[1].0));
+        for (length, eigenvector) in &axes {
+            assert_approx_eq!(1.0, eigenvector.magnitude(), 1e-12);
+            // Each axis is an eigenvector of the covariance matrix
+            let scaled = cmaes.sigma().powi(2) * (cmaes.covariance_matrix() * eigenvector);
+            assert_approx_eq!(
+                length.powi(2),
+                scaled.dot(eigenvector),
+                1e-6 * length.powi(2)
+            );
+        }
+        // The longest axis follows the least sensitive coordinate
+        assert!(axes[0].1[0].abs() > 0.9);
+        assert!(axes[2].1[2].abs() > 0.9);
+    }
+
+    #[test]
+    fn test_last_ranking() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        assert!(cmaes.last_ranking().is_empty());
+
+        // The injected point is sampled first and is the best of the generation
+        cmaes.inject([DVector::zeros(4)]);
+        let _ = cmaes.next();
+        let ranking = cmaes.last_ranking().to_vec();
+        assert_eq!(0, ranking[0]);
+        let mut sorted = ranking.clone();
+        sorted.sort_unstable();
+        assert_eq!((0..cmaes.population_size()).collect::<Vec<_>>(), sorted);
+
+        // The ranking matches the evaluation numbers of the sorted population
+        let _ = cmaes.next();
+        let first_evaluation = cmaes.function_evals() - cmaes.population_size() + 1;
+        for (individual, &index) in cmaes.current_population().iter().zip(cmaes.last_ranking()) {
+            assert_eq!(first_evaluation + index, individual.evaluation());
+        }
+    }
+
+    #[test]
+    fn test_distribution_volume() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 0.5)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        assert_approx_eq!(0.5f64.powi(4), cmaes.distribution_volume(), 1e-15);
+
+        let mut previous = cmaes.distribution_volume();
+        for _ in 0..20 {
+            for _ in 0..10 {
+                let _ = cmaes.next();
+            }
+            let volume = cmaes.distribution_volume();
+            let expected = cmaes.sigma().powi(4) * cmaes.covariance_matrix().determinant().sqrt();
+            assert_approx_eq!(expected, volume, 1e-8 * expected);
+            // The volume shrinks as the algorithm converges
+            assert!(volume < previous);
+            previous = volume;
+        }
+    }
+
+    #[test]
+    fn test_bounds_transform() {
+        // The unconstrained optimum lies outside of the bounds, so the constrained optimum is on
+        // the upper bound
+        let function = |x: &DVector<f64>| (x - DVector::from(vec![2.0; 3])).magnitude();
+        let mut cmaes = CMAESOptions::new(vec![0.5; 3], 1.0)
+            .bounds(vec![-1.0; 3], vec![1.0; 3], BoundaryHandling::Transform)
+            .max_generations(300)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        assert_eq!(&DVector::from(vec![0.5; 3]), cmaes.mean());
+
+        let result = cmaes.run();
+        let best = result.overall_best.unwrap();
+
+        assert!(best.point.iter().all(|x| (-1.0..=1.0).contains(x)));
+        assert!(best.point.iter().all(|x| *x > 0.99));
+        assert!(result.final_mean.iter().all(|x| (-1.0..=1.0).contains(x)));
+
+        // The transform never produces infeasible points
+        let population = cmaes.current_population();
+        assert_eq!(cmaes.parameters().lambda(), population.len());
+        assert!(population
+            .iter()
+            .all(|p| p.feasible() && p.violation() == 0.0));
+    }
+
+    #[test]
+    fn test_covariance_recording_error() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .build(function)
+            .unwrap();
+        let _ = cmaes.next();
+        assert!(cmaes.covariance_recording_error().is_none());
+
+        // The directory does not exist, so writing fails without stopping the run
+        let path = std::env::temp_dir().join("cmaes_missing_dir/cov_{generation}.csv");
+        let mut cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .record_covariance(1, path.to_str().unwrap())
+            .build(function)
+            .unwrap();
+        assert!(cmaes.next().is_none());
+        assert!(cmaes.covariance_recording_error().is_some());
+    }
+
+    #[test]
+    fn test_initial_mean_clamped() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![2.0, 0.5, -3.0], 0.5).bounds(
+            vec![-1.0; 3],
+            vec![1.0; 3],
+            BoundaryHandling::Penalty,
+        );
+
+        let cmaes = options.clone().build(function).unwrap();
+        assert!(cmaes.initial_mean_clamped());
+        assert_eq!(&DVector::from(vec![1.0, 0.5, -1.0]), cmaes.mean());
+
+        let cmaes = options
+            .clone()
+            .bounds(vec![-1.0; 3], vec![1.0; 3], BoundaryHandling::Transform)
+            .build(function)
+            .unwrap();
+        assert!(cmaes.initial_mean_clamped());
+        assert_approx_eq!(1.0, cmaes.mean()[0], 1e-9);
+        assert_approx_eq!(0.5, cmaes.mean()[1], 1e-9);
+        assert_approx_eq!(-1.0, cmaes.mean()[2], 1e-9);
+
+        // Feasible initial means are left unchanged
+        let cmaes = options
+            .initial_mean(vec![1.0, 0.5, -1.0])
+            .build(function)
+            .unwrap();
+        assert!(!cmaes.initial_mean_clamped());
+        assert_eq!(&DVector::from(vec![1.0, 0.5, -1.0]), cmaes.mean());
+
+        let cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .build(function)
+            .unwrap();
+        assert!(!cmaes.initial_mean_clamped());
+    }
+
+    #[test]
+    fn test_override_options() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).seed(1);
+        let mut cmaes = options.clone().fun_target(0.5).build(function).unwrap();
+        let result = cmaes.run();
+        assert_eq!(vec![TerminationReason::FunTarget], result.reasons);
+
+        let mean = cmaes.mean().clone();
+        let sigma = cmaes.sigma();
+        let function_evals = cmaes.function_evals();
+
+        // Options that affect the learned state cannot be changed
+        for (invalid, error) in [
+            (
+                CMAESOptions::new(vec![1.0; 4], 1.0),
+                InvalidOptionsError::Dimensions,
+            ),
+            (
+                options.clone().mode(Mode::Maximize),
+                InvalidOptionsError::IncompatibleOverride,
+            ),
+            (
+                options.clone().population_size(20),
+                InvalidOptionsError::IncompatibleOverride,
+            ),
+            (
+                options.clone().weights(Weights::Uniform),
+                InvalidOptionsError::IncompatibleOverride,
+            ),
+            (options.clone().cm(2.0), InvalidOptionsError::Cm),
+        ] {
+            assert_eq!(Err(error), cmaes.override_options(invalid));
+            assert_eq!(Some(0.5), cmaes.parameters().fun_target());
+        }
+
+        let metadata = HashMap::from([("stage".to_string(), "2".to_string())]);
+        cmaes
+            .override_options(options.fun_target(1e-6).metadata(metadata.clone()))
+            .unwrap();
+        assert_eq!(Some(1e-6), cmaes.parameters().fun_target());
+        assert_eq!(&metadata, cmaes.metadata());
+        // The learned state is kept
+        assert_eq!(&mean, cmaes.mean());
+        assert_eq!(sigma, cmaes.sigma());
+        assert_eq!(function_evals, cmaes.function_evals());
+
+        let result = cmaes.run();
+        assert_eq!(vec![TerminationReason::FunTarget], result.reasons);
+        assert!(result.overall_best.unwrap().value < 1e-6);
+        assert_eq!(metadata, result.metadata);
+    }
+
+    #[test]
+    fn test_non_finite_state() {
+        // The function value does not depend on the point so that non-finite points are still
+        // rankable
+        let function = |_: &DVector<f64>| 1.0;
+        let new_cmaes = || {
+            CMAESOptions::new(vec![1.0; 3], 1.0)
+                .build(function)
+                .unwrap()
+        };
+        let check = |cmaes: &mut CMAES<_>, component| {
+            let result = cmaes.next().unwrap();
+            assert_eq!(vec![TerminationReason::NonFiniteState], result.reasons);
+            assert_eq!(Some(component), result.details[0].component);
+            assert_eq!(
+                format!("NonFiniteState (component: {})", component),
+                result.details[0].to_string(),
+            );
+        };
+
+        let mut cmaes = new_cmaes();
+        cmaes.state.mut_mean()[1] = f64::NAN;
+        check(&mut cmaes, StateComponent::Mean);
+
+        let mut cmaes = new_cmaes();
+        *cmaes.state.mut_sigma() = f64::INFINITY;
+        check(&mut cmaes, StateComponent::Sigma);
+
+        // Without an eigendecomposition update, the samples are still drawn from the old matrix
+        let mut cmaes = new_cmaes();
+        let mut cov = SquareMatrix::identity(3, 3);
+        cov[(0, 0)] = f64::NAN;
+        cmaes.state.mut_cov().set_cov(cov, false).unwrap();
+        check(&mut cmaes, StateComponent::Covariance);
+
+        // Finite states are unaffected
+        let mut cmaes = new_cmaes();
+        assert!(cmaes.next().is_none());
+    }
+
+    #[test]
+    fn test_bounds_penalty() {
+        // The constrained optimum is on the upper bound, which the penalty locates precisely
+        let function = |x: &DVector<f64>| (x - DVector::from(vec![2.0; 3])).magnitude();
+        let options = CMAESOptions::new(vec![0.5; 3], 0.5)
+            .bounds(vec![-1.0; 3], vec![1.0; 3], BoundaryHandling::Penalty)
+            .max_generations(300)
+            .seed(1);
+
+        let mut cmaes = options.clone().build(function).unwrap();
+        assert_eq!(&DVector::from(vec![0.5; 3]), cmaes.mean());
+        let result = cmaes.run();
+        let best = result.overall_best.unwrap();
+        assert!(best.point.iter().all(|x| (-1.0..=1.0).contains(x)));
+        assert!(best.point.iter().all(|x| *x > 1.0 - 1e-6));
+        assert!(result.final_mean.iter().all(|x| (-1.0..=1.0).contains(x)));
+
+        // Infeasible points are repaired and penalized
+        let population = cmaes.current_population();
+        assert!(population.iter().any(|p| !p.feasible()));
+        for individual in population {
+            assert!(individual.point().iter().all(|x| (-1.0..=1.0).contains(x)));
+            if individual.feasible() {
+                assert_eq!(function(individual.point()), individual.value());
+                assert!(!individual.repaired());
+                assert_eq!(individual.point(), individual.raw_point());
+            } else {
+                assert!(individual.value() > function(individual.point()));
+                // The raw point is the sampled point, which the repair clamped into the bounds
+                assert!(individual.repaired());
+                let raw_point = individual.raw_point();
+                assert!(raw_point.iter().any(|x| !(-1.0..=1.0).contains(x)));
+                assert_eq!(
+                    individual.repaired_point(),
+                    &raw_point.map(|x| x.clamp(-1.0, 1.0))
+                );
+                assert_approx_eq!(
+                    individual.violation(),
+                    (raw_point - individual.point()).abs().sum(),
+                    1e-12
+                );
+            }
+        }
+
+        // Fixed weights
+        let result = options
+            .penalty_weights(vec![1.0, 10.0, 100.0])
+            .build(function)
+            .unwrap()
+            .run();
+        assert!(result
+            .overall_best
+            .unwrap()
+            .point
+            .iter()
+            .all(|x| *x > 1.0 - 1e-6));
+    }
+
+    #[test]
+    fn test_coordinate_transform() {
+        // The function is only defined for positive values, which the transform guarantees
+        let function = |x: &DVector<f64>| {
+            assert!(x.iter().all(|xi| *xi > 0.0));
+            (x[0] / 1e-3).ln().powi(2) + (x[1] / 1e4).ln().powi(2)
+        };
+        let mut cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .transform(|x| x.map(f64::ln), |x| x.map(f64::exp))
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        // The mean is reported in natural coordinates
+        assert!((cmaes.mean() - DVector::from(vec![1.0; 2])).magnitude() < 1e-12);
+
+        let result = cmaes.run();
+        let best = result.overall_best.unwrap();
+
+        assert!((best.point[0] / 1e-3 - 1.0).abs() < 1e-3);
+        assert!((best.point[1] / 1e4 - 1.0).abs() < 1e-3);
+        assert!((result.final_mean[1] / 1e4 - 1.0).abs() < 1e-3);
+        assert!(cmaes
+            .sample_final(10)
+            .iter()
+            .all(|x| x.iter().all(|xi| *xi > 0.0)));
+    }
+
+    #[test]
+    fn test_compare() {
+        let data = |best: Option<f64>, mean: f64, function_evals, mode| TerminationData {
+            current_best: None,
+            overall_best: best.map(|value| Individual::new(DVector::zeros(2), value)),
+            final_mean: DVector::from(vec![mean; 2]),
+            reasons: Vec::new(),
+            details: Vec::new(),
+            function_evals,
+            mode,
+            metadata: HashMap::new(),
+        };
+
+        let a = data(Some(1.0), 0.0, 100, Mode::Minimize);
+        let b = data(Some(3.0), 0.5, 400, Mode::Minimize);
+        let comparison = a.compare(&b);
+
+        assert_eq!(Preferred::This, comparison.preferred);
+        assert_eq!(Some(2.0), comparison.value_difference);
+        assert_eq!(Some(0.25), comparison.evals_ratio);
+        assert_eq!(0.5f64.hypot(0.5), comparison.mean_distance);
+        assert!(comparison.converged_nearby(1.0));
+        assert!(!comparison.converged_nearby(0.5));
+        assert_eq!(Preferred::Other, b.compare(&a).preferred);
+        assert_eq!(Some(4.0), b.compare(&a).evals_ratio);
+
+        // The mode of `self` decides which value is better
+        let a = data(Some(1.0), 0.0, 100, Mode::Maximize);
+        assert_eq!(Preferred::Other, a.compare(&b).preferred);
+        assert_eq!(Preferred::Neither, a.compare(&a).preferred);
+
+        // A missing best individual is always worse
+        let c = data(None, 0.0, 100, Mode::Minimize);
+        assert_eq!(Preferred::This, a.compare(&c).preferred);
+        assert_eq!(Preferred::Other, c.compare(&a).preferred);
+        assert_eq!(Preferred::Neither, c.compare(&c).preferred);
+        assert_eq!(None, c.compare(&a).value_difference);
+
+        // A result without function evals has no evals ratio
+        let d = data(Some(1.0), 0.0, 0, Mode::Minimize);
+        assert_eq!(None, a.compare(&d).evals_ratio);
+        assert_eq!(Some(0.0), d.compare(&a).evals_ratio);
+    }
+
+    #[test]
+    fn test_inject() {
+        let optimum = DVector::from(vec![3.0; 4]);
+        let function = |x: &DVector<f64>| (x - DVector::from(vec![3.0; 4])).magnitude();
+        let mut cmaes = CMAESOptions::new(vec![0.0; 4], 0.1)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        let lambda = cmaes.population_size();
+
+        // More points than fit in one generation are carried over
+        cmaes.inject(vec![optimum.clone(); lambda + 2]);
+        assert_eq!(lambda + 2, cmaes.num_injected());
+
+        let mean = cmaes.mean().clone();
+        let _ = cmaes.next();
+        assert_eq!(2, cmaes.num_injected());
+
+        // The injected points are evaluated as given
+        let best = cmaes.overall_best_individual().unwrap();
+        assert!((&best.point - &optimum).magnitude() < 1e-12);
+        assert!(best.value < 1e-12);
+
+        // But the distant points only move the mean by a limited amount
+        assert!((cmaes.mean() - &mean).magnitude() < (&optimum - &mean).magnitude());
+
+        let _ = cmaes.next();
+        assert_eq!(0, cmaes.num_injected());
+    }
+
+    #[test]
+    fn test_previous_best() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .build(function)
+            .unwrap();
+
+        let _ = cmaes.next();
+        assert!(cmaes.previous_best().is_none());
+
+        for _ in 0..5 {
+            let best = cmaes.current_population()[0].clone();
+            let _ = cmaes.next();
+            let previous_best = cmaes.previous_best().unwrap();
+
+            assert_eq!(best.value(), previous_best.value());
+            assert_eq!(best.point(), previous_best.point());
+        }
+    }
+
+    #[test]
+    fn test_partial() {
+        // Points with a negative first coordinate cannot be evaluated; the optimum lies inside the
+        // evaluable region
+        let function = |x: &DVector<f64>| {
+            if x[0] < 0.0 {
+                None
+            } else {
+                Some(x.map(|xi| (xi - 0.5).powi(2)).sum())
+            }
+        };
+        let mut cmaes = CMAESOptions::new(vec![0.5; 4], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        let _ = cmaes.next_partial();
+        let unrankable_count = cmaes.unrankable_count();
+        assert!(unrankable_count > 0);
+        let population = cmaes.current_population();
+        assert!(population
+            .iter()
+            .rev()
+            .take(unrankable_count)
+            .all(|individual| !individual.rankable() && individual.point()[0] < 0.0));
+        assert!(population
+            .iter()
+            .take(population.len() - unrankable_count)
+            .all(|individual| individual.rankable() && individual.value().is_finite()));
+
+        let result = cmaes.run_partial();
+        assert!(result.overall_best.unwrap().value < 1e-10);
+    }
+
+    #[test]
+    fn test_partial_tol_fun() {
+        // The optimum lies on the border of the evaluable region, so about half of each generation
+        // remains unrankable until the end of the run
+        let function = |x: &DVector<f64>| {
+            if x[0] < 0.0 {
+                None
+            } else {
+                Some(x.magnitude_squared())
+            }
+        };
+        let mut cmaes = CMAESOptions::new(vec![0.5; 4], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        let result = cmaes.run_partial();
+        assert!(cmaes.unrankable_count() > 0);
+        assert!(result.reasons.contains(&TerminationReason::TolFun));
+        assert!(cmaes
+            .history
+            .best_function_values()
+            .iter()
+            .chain(cmaes.history.median_function_values())
+            .all(|value| value.is_finite()));
+    }
+
+    #[test]
+    fn test_cache_evaluations() {
+        let mut evals = 0;
+        let function = |x: &DVector<f64>| {
+            evals += 1;
+            x.magnitude()
+        };
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .population_size(6)
+            .cache_evaluations(true)
+            .build(function)
+            .unwrap();
+
+        let _ = cmaes.next();
+        assert_eq!(
+            Some(CacheStats {
+                hits: 0,
+                misses: 6,
+                entries: 6,
+            }),
+            cmaes.cache_stats(),
+        );
+
+        // Re-evaluating points of the previous generation hits the cache
+        let previous = cmaes
+            .current_population()
+            .iter()
+            .take(2)
+            .map(|individual| individual.point().clone())
+            .collect::<Vec<_>>();
+        cmaes.inject(previous);
+        let _ = cmaes.next();
+
+        let stats = cmaes.cache_stats().unwrap();
+        assert_eq!(2, stats.hits);
+        assert_eq!(10, stats.misses);
+        assert_eq!(12, cmaes.function_evals());
+        drop(cmaes);
+        assert_eq!(10, evals);
+
+        let cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .build(dummy_function)
+            .unwrap();
+        assert!(cmaes.cache_stats().is_none());
+    }
+
+    #[test]
+    fn test_search_direction() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 4], 1.0)
+            .cm(0.5)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        assert_eq!(DVector::zeros(4), cmaes.search_direction());
+
+        // The mean moves towards the optimum at the origin
+        let _ = cmaes.next();
+        assert!(cmaes.search_direction().sum() < 0.0);
+
+        for _ in 0..10 {
+            let old_mean = cmaes.mean().clone();
+            // The covariance matrix that the next generation is sampled from
+            let transform = cmaes.state.cov_transform();
+            let old_cov = cmaes.sigma().powi(2) * transform * transform.transpose();
+            let _ = cmaes.next();
+            let shift = old_cov * cmaes.search_direction();
+
+            for i in 0..4 {
+                assert_approx_eq!(cmaes.mean()[i] - old_mean[i], 0.5 * shift[i], 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cm_schedule() {
+        use std::sync::{Arc, Mutex};
+
+        let generations = Arc::new(Mutex::new(Vec::new()));
+        let generations_clone = generations.clone();
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 4], 1.0)
+            .cm_schedule(move |generation| {
+                generations_clone.lock().unwrap().push(generation);
+                match generation {
+                    0 => 2.0,
+                    1 => f64::NAN,
+                    _ => 0.25,
+                }
+            })
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        // Values above 1 are clamped
+        let _ = cmaes.next();
+        assert_eq!(1.0, cmaes.parameters().cm());
+
+        // NAN keeps the previous value
+        let _ = cmaes.next();
+        assert_eq!(1.0, cmaes.parameters().cm());
+
+        let transform = cmaes.state.cov_transform();
+        let old_cov = cmaes.sigma().powi(2) * transform * transform.transpose();
+        let _ = cmaes.next();
+        assert_eq!(0.25, cmaes.parameters().cm());
+        let shift = cmaes.last_mean_shift().unwrap();
+        let direction = old_cov * cmaes.search_direction();
+        for i in 0..4 {
+            assert_approx_eq!(shift[i], 0.25 * direction[i], 1e-9);
+        }
+
+        assert_eq!(vec![0, 1, 2], *generations.lock().unwrap());
+    }
+
+    #[test]
+    fn test_last_mean_shift() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 4], 1.0)
+            .cm(0.5)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        assert!(cmaes.last_mean_shift().is_none());
+
+        for _ in 0..10 {
+            let old_mean = cmaes.mean().clone();
+            let transform = cmaes.state.cov_transform();
+            let old_cov = cmaes.sigma().powi(2) * transform * transform.transpose();
+            let _ = cmaes.next();
+            let shift = cmaes.last_mean_shift().unwrap();
+
+            assert_approx_eq!(
+                shift.magnitude(),
+                0.5 * (old_cov * cmaes.search_direction()).magnitude(),
+                1e-9
+            );
+            for i in 0..4 {
+                assert_approx_eq!(cmaes.mean()[i] - old_mean[i], shift[i], 1e-12);
+            }
+        }
+    }
+
+    #[cfg(feature = "unstable")]
+    #[test]
+    fn test_from_parts() {
+        use crate::state::InvalidStateError;
+
+        let cov = SquareMatrix::from_diagonal(&DVector::from(vec![1.0, 1e-8]));
+        let state = State::from_parts(
+            DVector::from(vec![1.0, 2.0]),
+            0.5,
+            cov.clone(),
+            DVector::zeros(2),
+            DVector::zeros(2),
+            10,
+        )
+        .unwrap();
+
+        let options = CMAESOptions::new(vec![0.0; 2], 1.0);
+        let cmaes = CMAES::from_parts(dummy_function, options.clone(), state.clone()).unwrap();
+        assert_eq!(&DVector::from(vec![1.0, 2.0]), cmaes.mean());
+        assert_eq!(0.5, cmaes.sigma());
+        assert_eq!(10, cmaes.generation());
+        assert_eq!(&cov, cmaes.covariance_matrix());
+        assert_approx_eq!(1e4, cmaes.axis_ratio(), 1e-6);
+
+        assert!(matches!(
+            CMAES::from_parts(
+                dummy_function,
+                CMAESOptions::new(vec![0.0; 3], 1.0),
+                state.clone(),
+            ),
+            Err(InvalidOptionsError::Dimensions),
+        ));
+        let from_parts = |sigma, cov, path_c| {
+            State::from_parts(DVector::zeros(2), sigma, cov, path_c, DVector::zeros(2), 0)
+        };
+        let not_pos_def = SquareMatrix::from_diagonal(&DVector::from(vec![1.0, -1.0]));
+        assert!(matches!(
+            from_parts(1.0, not_pos_def, DVector::zeros(2)),
+            Err(InvalidStateError::PosDefCov),
+        ));
+        assert!(matches!(
+            from_parts(1.0, cov.clone(), DVector::zeros(3)),
+            Err(InvalidStateError::Dimensions),
+        ));
+        assert!(matches!(
+            from_parts(1.0, SquareMatrix::identity(3, 3), DVector::zeros(2)),
+            Err(InvalidStateError::Dimensions),
+        ));
+        for sigma in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                from_parts(sigma, cov.clone(), DVector::zeros(2)),
+                Err(InvalidStateError::Sigma),
+            ));
+        }
+
+        // The objective function is validated on the first generation evaluated from the state
+        let mut cmaes = CMAES::from_parts(
+            |_: &DVector<f64>| 1.0,
+            options.validate_objective(true),
+            state,
+        )
+        .unwrap();
+        let result = cmaes.next().unwrap();
+        assert_eq!(vec![TerminationReason::ConstantFunction], result.reasons);
+        assert_eq!(11, cmaes.generation());
+    }
+
+    #[test]
+    fn test_auto_kick() {
+        // The function never improves, so a kick happens every `threshold` generations
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .auto_kick(AutoKick::new(10, 0.5))
+            .max_generations(35)
+            .tol_fun_hist(0.0)
+            .tol_fun(0.0)
+            .build(dummy_function)
+            .unwrap();
+
+        let mut kicks = Vec::new();
+        while cmaes.next().is_none() {
+            if cmaes.kick_count() > kicks.len() {
+                kicks.push(cmaes.generation());
+            }
+        }
+        assert_eq!(vec![11, 21, 31], kicks);
+
+        // A kick moves the mean and increases the step size but keeps the covariance matrix
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).seed(1);
+        let mut kicked = options
+            .clone()
+            .auto_kick(AutoKick::new(1, 0.5))
+            .build(dummy_function)
+            .unwrap();
+        let mut unkicked = options.build(dummy_function).unwrap();
+        for _ in 0..2 {
+            let _ = kicked.next();
+            let _ = unkicked.next();
+        }
+        assert_eq!(1, kicked.kick_count());
+        assert_eq!(0, unkicked.kick_count());
+        assert_approx_eq!(1.5 * unkicked.sigma(), kicked.sigma(), 1e-12);
+        assert_eq!(unkicked.covariance_matrix(), kicked.covariance_matrix());
+        assert_ne!(unkicked.mean(), kicked.mean());
+    }
+
+    #[test]
+    fn test_evaluate_mean() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0).max_generations(20);
+
+        let mut cmaes = options.clone().build(function).unwrap();
+        let lambda = cmaes.parameters().lambda();
+        let result = cmaes.run();
+        assert_eq!(20 * lambda, result.function_evals);
+        assert!(cmaes.best_mean_point().is_none());
+        assert!(cmaes.mean_value().is_none());
+        assert!(cmaes.previous_mean_value().is_none());
+
+        let mut cmaes = options.clone().evaluate_mean(true).build(function).unwrap();
+        assert!(cmaes.mean_value().is_none());
+        let _ = cmaes.next();
+        let first_value = cmaes.mean_value().unwrap();
+        assert_eq!(function(cmaes.mean()), first_value);
+        assert!(cmaes.previous_mean_value().is_none());
+        let _ = cmaes.next();
+        assert_eq!(Some(first_value), cmaes.previous_mean_value());
+        assert_eq!(function(cmaes.mean()), cmaes.mean_value().unwrap());
+
+        let mut cmaes = options.clone().evaluate_mean(true).build(function).unwrap();
+        let result = cmaes.run();
+        assert_eq!(20 * (lambda + 1), result.function_evals);
+        let best_mean = cmaes.best_mean_point().unwrap();
+        assert_eq!(function(&best_mean.point), best_mean.value);
+        assert!(best_mean.value <= function(cmaes.mean()));
+
+        // The mean is mapped into the space of the objective function
+        let mut cmaes = options
+            .evaluate_mean(true)
+            .bounds(vec![2.0; 4], vec![3.0; 4], BoundaryHandling::Transform)
+            .build(function)
+            .unwrap();
+        let _ = cmaes.next();
+        let best_mean = cmaes.best_mean_point().unwrap();
+        assert_eq!(cmaes.mean(), &best_mean.point);
+        assert!(best_mean.point.iter().all(|&x| (2.0..=3.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_estimated_objective_noise() {
+        use rand::{Rng, SeedableRng};
+        use rand_chacha::ChaCha12Rng;
+
+        // The function is nearly flat, so the mean value only changes due to the noise, which is
+        // uniform with a variance of 1 / 12
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        let function = move |x: &DVector<f64>| 1e-9 * x.magnitude() + rng.gen::<f64>();
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0).max_generations(200);
+
+        let mut cmaes = options.clone().build(function.clone()).unwrap();
+        let _ = cmaes.run();
+        assert!(cmaes.estimated_objective_noise().is_none());
+
+        let mut cmaes = options.evaluate_mean(true).build(function).unwrap();
+        for _ in 0..2 {
+            let _ = cmaes.next();
+            assert!(cmaes.estimated_objective_noise().is_none());
+        }
+        let _ = cmaes.run();
+        let noise = cmaes.estimated_objective_noise().unwrap();
+        assert!(noise > 0.5 / 12.0 && noise < 2.0 / 12.0, "{}", noise);
+    }
+
+    #[test]
+    fn test_on_improvement() {
+        use std::sync::{Arc, Mutex};
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let calls_clone = Arc::clone(&calls);
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .max_generations(50)
+            .on_improvement(move |individual, evals| {
+                calls_clone
+                    .lock()
+                    .unwrap()
+                    .push((individual.value(), evals));
+            })
+            .build(|x: &DVector<f64>| x.magnitude())
+            .unwrap();
+        let lambda = cmaes.parameters().lambda();
+        let result = cmaes.run();
+
+        let calls = calls.lock().unwrap();
+        assert!(calls.len() > 1);
+        assert_eq!(lambda, calls[0].1);
+        assert!(calls
+            .windows(2)
+            .all(|w| w[1].0 < w[0].0 && w[1].1 > w[0].1 && w[1].1 % lambda == 0));
+        assert_eq!(result.overall_best.unwrap().value, calls.last().unwrap().0);
+
+        // Ties are not improvements
+        let count = Arc::new(Mutex::new(0));
+        let count_clone = Arc::clone(&count);
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .max_generations(5)
+            .on_improvement(move |_, _| *count_clone.lock().unwrap() += 1)
+            .build(dummy_function)
+            .unwrap();
+        let _ = cmaes.run();
+        assert_eq!(1, *count.lock().unwrap());
+    }
+
+    #[test]
+    fn test_fitness_transform() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .seed(1)
+            .max_generations(10);
+
+        let mut rank = options.clone().build(function).unwrap();
+        let rank_result = rank.run();
+
+        // Constant transformed values fall back to rank-based weights, while the raw values are
+        // still reported
+        let mut value = options
+            .recombination_weighting(RecombinationWeighting::Value)
+            .fitness_transform(FitnessTransform::new(|_| 0.0))
+            .build(function)
+            .unwrap();
+        let value_result = value.run();
+
+        assert_eq!(rank.mean(), value.mean());
+        let best = value_result.overall_best.unwrap();
+        assert_eq!(rank_result.overall_best.unwrap().value, best.value);
+        assert_eq!(function(&best.point), best.value);
+    }
+
+    #[test]
+    fn test_covariance_learning_scale() {
+        let function = |x: &DVector<f64>| {
+            x.iter()
+                .enumerate()
+                .map(|(i, xi)| (i + 1) as f64 * xi * xi)
+                .sum()
+        };
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).seed(1);
+
+        let mut cmaes = options.clone().build(function).unwrap();
+        assert_eq!(1.0, cmaes.covariance_learning_scale());
+
+        // A scale of zero freezes the covariance matrix
+        cmaes.set_covariance_learning_scale(0.0);
+        let _ = cmaes.next();
+        let cov = cmaes.covariance_matrix().clone();
+        let _ = cmaes.next();
+        assert_eq!(cov, *cmaes.covariance_matrix());
+        assert_eq!(SquareMatrix::identity(3, 3), cov);
+
+        cmaes.set_covariance_learning_scale(f64::NAN);
+        assert_eq!(0.0, cmaes.covariance_learning_scale());
+
+        // A scale of one matches the default update
+        let mut scaled = options.clone().build(function).unwrap();
+        let mut default = options.clone().build(function).unwrap();
+        scaled.set_covariance_learning_scale(1.0);
+        for _ in 0..5 {
+            let _ = scaled.next();
+            let _ = default.next();
+        }
+        assert_eq!(default.covariance_matrix(), scaled.covariance_matrix());
+
+        // The change of the covariance matrix is proportional to the scale
+        let mut scaled = options.clone().build(function).unwrap();
+        let mut default = options.build(function).unwrap();
+        scaled.set_covariance_learning_scale(2.0);
+        assert!(scaled.next().is_none());
+        assert!(default.next().is_none());
+        let identity = SquareMatrix::identity(3, 3);
+        let expected = 2.0 * (default.covariance_matrix() - &identity);
+        let actual = scaled.covariance_matrix() - identity;
+        for (a, b) in expected.iter().zip(actual.iter()) {
+            assert_approx_eq!(a, b, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_freeze_covariance() {
+        let function = |x: &DVector<f64>| {
+            x.iter()
+                .enumerate()
+                .map(|(i, xi)| 10f64.powi(i as i32) * xi * xi)
+                .sum()
+        };
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).seed(1);
+
+        let mut frozen = options.clone().build(function).unwrap();
+        let mut scaled = options.build(function).unwrap();
+        assert!(!frozen.covariance_frozen());
+        for _ in 0..10 {
+            assert!(frozen.next().is_none());
+            assert!(scaled.next().is_none());
+        }
+
+        // Freezing skips the update with the same result as a learning rate scale of zero, but
+        // the step size and the evolution paths are still adapted
+        frozen.freeze_covariance(true);
+        scaled.set_covariance_learning_scale(0.0);
+        assert!(frozen.covariance_frozen());
+        let cov = frozen.covariance_matrix().clone();
+        let (path_c, path_sigma) = frozen.evolution_paths();
+        let (path_c, path_sigma) = (path_c.clone(), path_sigma.clone());
+        let sigma = frozen.sigma();
+        for _ in 0..10 {
+            assert!(frozen.next().is_none());
+            assert!(scaled.next().is_none());
+            assert!(!frozen.active_update_applied());
+        }
+        assert_eq!(&cov, frozen.covariance_matrix());
+        assert_eq!(scaled.covariance_matrix(), frozen.covariance_matrix());
+        assert_eq!(scaled.sigma(), frozen.sigma());
+        assert_eq!(scaled.mean(), frozen.mean());
+        assert_ne!(sigma, frozen.sigma());
+        assert_ne!((&path_c, &path_sigma), frozen.evolution_paths());
+
+        frozen.freeze_covariance(false);
+        assert!(frozen.next().is_none());
+        assert_ne!(&cov, frozen.covariance_matrix());
+    }
+
+    #[test]
+    fn test_active_update_applied() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0).seed(1);
+
+        let mut cmaes = options.clone().build(function).unwrap();
+        assert!(!cmaes.active_update_applied());
+        for _ in 0..10 {
+            assert!(cmaes.next().is_none());
+            assert!(cmaes.active_update_applied());
+        }
+
+        let mut cmaes = options
+            .clone()
+            .weights(Weights::Positive)
+            .build(function)
+            .unwrap();
+        let _ = cmaes.next();
+        assert!(!cmaes.active_update_applied());
+
+        // The maximum scale leaves no weight for the negative contribution, so it is left out and
+        // the covariance matrix stays positive-definite
+        let mut cmaes = options.build(function).unwrap();
+        cmaes.set_covariance_learning_scale(f64::INFINITY);
+        for _ in 0..10 {
+            assert!(cmaes.next().is_none());
+            assert!(!cmaes.active_update_applied());
+        }
+        cmaes.set_covariance_learning_scale(1.0);
+        let _ = cmaes.next();
+        assert!(cmaes.active_update_applied());
+    }
+
+    #[test]
+    fn test_record_samples() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).max_generations(10);
+
+        let mut recorded = options
+            .clone()
+            .record_samples(true)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        let recorded_result = recorded.run();
+        let recording = recorded.sample_recording().unwrap().to_vec();
+        assert_eq!(10, recording.len());
+        assert!(recording.iter().all(|generation| {
+            generation.standard_normal.len() == 7 && generation.points.len() == 7
+        }));
+
+        // Replaying with a different seed reproduces the run exactly
+        let mut replayed = options
+            .clone()
+            .record_samples(true)
+            .replay_samples(recording.clone())
+            .seed(2)
+            .build(function)
+            .unwrap();
+        let replayed_result = replayed.run();
+        for (a, b) in recording.iter().zip(replayed.sample_recording().unwrap()) {
+            assert_eq!(a, b);
+            assert_eq!(None, a.first_divergence(b, 0.0));
+        }
+        assert_eq!(
+            recorded_result.overall_best.unwrap().value,
+            replayed_result.overall_best.unwrap().value
+        );
+        assert_eq!(0, replayed.discarded_replay_samples());
+
+        // Samples that do not match the population size are discarded along with the rest
+        let mut mismatched = options
+            .clone()
+            .population_size(8)
+            .replay_samples(recording.clone())
+            .build(function)
+            .unwrap();
+        let _ = mismatched.next();
+        assert_eq!(10, mismatched.discarded_replay_samples());
+
+        // The RNG is used once the replayed samples run out
+        let mut partially_replayed = options
+            .max_generations(12)
+            .replay_samples(recording[..5].to_vec())
+            .seed(2)
+            .build(function)
+            .unwrap();
+        assert!(partially_replayed.sample_recording().is_none());
+        assert_eq!(12, partially_replayed.run().function_evals / 7);
+    }
+
+    #[test]
+    fn test_max_sigma_change() {
+        // The initial step size is far too small, so the step size grows as quickly as allowed
+        let function = |x: &DVector<f64>| x.magnitude();
+        let max_sigma_change = 1.1;
+        let mut cmaes = CMAESOptions::new(vec![1e3; 4], 1e-6)
+            .max_sigma_change(max_sigma_change)
+            .build(function)
+            .unwrap();
+
+        let mut sigmas = vec![cmaes.sigma()];
+        for _ in 0..50 {
+            let _ = cmaes.next();
+            sigmas.push(cmaes.sigma());
+        }
+
+        assert!(sigmas.windows(2).all(|s| {
+            let ratio = s[1] / s[0];
+            ratio <= max_sigma_change * (1.0 + 1e-12) && ratio >= (1.0 - 1e-12) / max_sigma_change
+        }));
+        assert!(sigmas
+            .windows(2)
+            .any(|s| s[1] / s[0] > max_sigma_change - 1e-12));
+    }
+
+    #[test]
+    fn test_active_criteria() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let reasons = |cmaes: &CMAES<_>| {
+            cmaes
+                .active_criteria()
+                .into_iter()
+                .map(|(reason, _)| reason)
+                .collect::<Vec<_>>()
+        };
+
+        let cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .tol_stagnation(MAX_HISTORY_LENGTH + 1)
+            .build(function)
+            .unwrap();
+        assert_eq!(
+            vec![
+                TerminationReason::TolFun,
+                TerminationReason::TolFunRel,
+                TerminationReason::TolFunHist,
+                TerminationReason::TolX,
+                TerminationReason::TolXUp,
+                TerminationReason::NoEffectAxis,
+                TerminationReason::NoEffectCoord,
+                TerminationReason::TolConditionCov,
+                TerminationReason::InvalidFunctionValue,
+                TerminationReason::NonFiniteState,
+                TerminationReason::PosDefCov,
+            ],
+            reasons(&cmaes),
+        );
+
+        let cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .max_time(Duration::from_millis(1500))
+            .fun_target(1e-8)
+            .mean_region(vec![-5.0; 2], vec![5.0; 2])
+            .tol_x(1e-9)
+            .build(function)
+            .unwrap();
+        let criteria = cmaes.active_criteria();
+        assert!(criteria.contains(&(TerminationReason::MaxTime, Some(1.5))));
+        assert!(criteria.contains(&(TerminationReason::FunTarget, Some(1e-8))));
+        assert!(criteria.contains(&(TerminationReason::MeanOutOfRegion, None)));
+        assert!(criteria.contains(&(TerminationReason::TolX, Some(1e-9))));
+        assert!(criteria
+            .iter()
+            .any(|(r, _)| *r == TerminationReason::TolStagnation));
+
+        // FunTarget never terminates the run in RecordOnly mode
+        let cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .fun_target(1e-8)
+            .fun_target_mode(TargetMode::RecordOnly)
+            .build(function)
+            .unwrap();
+        assert!(!reasons(&cmaes).contains(&TerminationReason::FunTarget));
+
+        // TolConditionCov is still reported if the eigenvalue floor caps the condition number
+        // below its threshold because a degenerate condition number can still end the run
+        let cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .min_eigenvalue_ratio(1e-10)
+            .build(function)
+            .unwrap();
+        assert!(reasons(&cmaes).contains(&TerminationReason::TolConditionCov));
+
+        // The adaptive TolFun threshold grows with the improvements made so far
+        let tol_fun = |cmaes: &CMAES<_>| {
+            cmaes
+                .active_criteria()
+                .into_iter()
+                .find(|(reason, _)| *reason == TerminationReason::TolFun)
+                .unwrap()
+                .1
+                .unwrap()
+        };
+        let mut cmaes = CMAESOptions::new(vec![1e10; 2], 1e9)
+            .tol_fun(1e-12)
+            .adaptive_tol_fun(true)
+            .build(function)
+            .unwrap();
+        assert_eq!(1e-12, tol_fun(&cmaes));
+        for _ in 0..20 {
+            let _ = cmaes.next();
+        }
+        assert!(tol_fun(&cmaes) > 1e-12);
+    }
+
+    #[test]
+    fn test_initial_covariance_scale() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 3], 0.5).seed(3);
+
+        let mut cmaes = options
+            .clone()
+            .initial_covariance_scale(4.0)
+            .build(function)
+            .unwrap();
+        assert_eq!(4.0, cmaes.parameters().initial_covariance_scale());
+        assert_eq!(
+            &(SquareMatrix::identity(3, 3) * 4.0),
+            cmaes.covariance_matrix()
+        );
+        assert_eq!(0.5, cmaes.sigma());
+        assert_eq!(1.0, cmaes.axis_ratio());
+
+        // The first generation matches sampling with the equivalent step size
+        let mut equivalent = options.initial_step_size(1.0).build(function).unwrap();
+        let _ = cmaes.next();
+        let _ = equivalent.next();
+        for (a, b) in cmaes
+            .current_population()
+            .iter()
+            .zip(equivalent.current_population())
+        {
+            assert_approx_eq!(a.value(), b.value(), 1e-12);
+        }
+
+        assert!(cmaes.run().overall_best.unwrap().value < 1e-8);
+    }
+
+    #[test]
+    fn test_set_covariance() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .build(function)
+            .unwrap();
+        let _ = cmaes.next();
+        let original = cmaes.covariance_matrix().clone();
+
+        let invalid = [
+            (
+                SquareMatrix::identity(3, 3),
+                InvalidCovarianceError::Dimensions,
+            ),
+            (
+                SquareMatrix::identity(2, 3),
+                InvalidCovarianceError::Dimensions,
+            ),
+            (
+                SquareMatrix::from_row_slice(2, 2, &[1.0, 0.5, 0.0, 1.0]),
+                InvalidCovarianceError::NotSymmetric,
+            ),
+            (
+                SquareMatrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 1.0]),
+                InvalidCovarianceError::NotPositiveDefinite,
+            ),
+            (
+                SquareMatrix::from_diagonal_element(2, 2, f64::NAN),
+                InvalidCovarianceError::NotPositiveDefinite,
+            ),
+        ];
+        for (cov, error) in invalid {
+            assert_eq!(Err(error), cmaes.set_covariance(cov));
+            assert_eq!(&original, cmaes.covariance_matrix());
+        }
+
+        let cov = SquareMatrix::from_row_slice(2, 2, &[4.0, 1.0, 1.0, 2.0]);
+        cmaes.set_covariance(cov.clone()).unwrap();
+        assert_eq!(&cov, cmaes.covariance_matrix());
+        let eigen = cov.symmetric_eigen();
+        assert_approx_eq!(
+            eigen.eigenvalues.max().sqrt(),
+            cmaes.axis_ratio() * eigen.eigenvalues.min().sqrt(),
+            1e-12
+        );
+        assert!(cmaes.next().is_none());
+    }
+
+    #[test]
+    fn test_log_density() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0, -1.0], 0.5)
+            .build(function)
+            .unwrap();
+        let cov = SquareMatrix::from_row_slice(2, 2, &[4.0, 1.0, 1.0, 2.0]);
+        cmaes.set_covariance(cov.clone()).unwrap();
+
+        // Compare against the density computed directly from sigma^2 * C
+        let full_cov = 0.25 * &cov;
+        let inverse = full_cov.clone().try_inverse().unwrap();
+        let mean = cmaes.mean().clone();
+        for point in [
+            mean.clone(),
+            DVector::from(vec![2.0, 0.5]),
+            DVector::from(vec![-3.0, 4.0]),
+        ] {
+            let diff = &point - &mean;
+            let expected = -0.5
+                * (2.0 * (2.0 * std::f64::consts::PI).ln()
+                    + full_cov.determinant().ln()
+                    + (diff.transpose() * &inverse * &diff)[0]);
+            assert_approx_eq!(expected, cmaes.log_density(&point), 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_set_evolution_paths() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        let mut other = cmaes.clone_with(function);
+
+        let zeros = DVector::zeros(2);
+        let invalid = [
+            (
+                DVector::zeros(3),
+                zeros.clone(),
+                InvalidEvolutionPathsError::Dimensions,
+            ),
+            (
+                zeros.clone(),
+                DVector::zeros(1),
+                InvalidEvolutionPathsError::Dimensions,
+            ),
+            (
+                DVector::from(vec![f64::NAN, 0.0]),
+                zeros.clone(),
+                InvalidEvolutionPathsError::NonFinite,
+            ),
+            (
+                zeros.clone(),
+                DVector::from(vec![0.0, f64::INFINITY]),
+                InvalidEvolutionPathsError::NonFinite,
+            ),
+        ];
+        for (path_c, path_sigma, error) in invalid {
+            assert_eq!(Err(error), cmaes.set_evolution_paths(path_c, path_sigma));
+            assert_eq!((&zeros, &zeros), cmaes.evolution_paths());
+        }
+
+        let path_c = DVector::from(vec![1.0, -1.0]);
+        let path_sigma = DVector::from(vec![10.0, 10.0]);
+        cmaes
+            .set_evolution_paths(path_c.clone(), path_sigma.clone())
+            .unwrap();
+        assert_eq!((&path_c, &path_sigma), cmaes.evolution_paths());
+
+        // A long step size path increases the step size compared to an identical run without it
+        let _ = cmaes.next();
+        let _ = other.next();
+        assert!(cmaes.sigma() > other.sigma());
+    }
+
+    #[test]
+    fn test_min_eigenvalue_ratio() {
+        let function = |x: &DVector<f64>| {
+            x.iter()
+                .enumerate()
+                .map(|(i, xi)| 1e6f64.powi(i as i32) * xi * xi)
+                .sum()
+        };
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).max_generations(300);
+
+        let mut cmaes = options.clone().build(function).unwrap();
+        let _ = cmaes.run();
+        assert!(cmaes.axis_ratio() > 100.0);
+
+        let mut cmaes = options
+            .clone()
+            .min_eigenvalue_ratio(1e-4)
+            .build(function)
+            .unwrap();
+        let _ = cmaes.run();
+        assert!(cmaes.axis_ratio() <= 100.0 * (1.0 + 1e-9));
+
+        // Capping the condition number keeps TolConditionCov from being reached
+        let tol_condition_cov = |options: CMAESOptions| {
+            let mut cmaes = options.tol_condition_cov(1e8).build(function).unwrap();
+            cmaes
+                .run()
+                .reasons
+                .contains(&TerminationReason::TolConditionCov)
+        };
+        assert!(tol_condition_cov(options.clone()));
+        assert!(!tol_condition_cov(
+            options.clone().cap_condition_number(Some(1e6))
+        ));
+        assert!(tol_condition_cov(
+            options
+                .cap_condition_number(Some(1e6))
+                .cap_condition_number(None)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json() {
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .seed(5)
+            .max_generations(10)
+            .build(|x: &DVector<f64>| x.magnitude())
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&cmaes.to_json()).unwrap();
+        assert_eq!(0, json["state"]["generation"]);
+        assert!(json["state"]["overall_best"].is_null());
+
+        let _ = cmaes.run();
+        let json: serde_json::Value = serde_json::from_str(&cmaes.to_json()).unwrap();
+
+        let parameters = &json["parameters"];
+        assert_eq!("Minimize", parameters["mode"]);
+        assert_eq!(3, parameters["dimensions"]);
+        assert_eq!(cmaes.parameters().lambda(), parameters["lambda"]);
+        assert_eq!(5, parameters["seed"]);
+        assert_eq!(10, parameters["termination"]["max_generations"]);
+        assert!(parameters["termination"]["max_function_evals"].is_null());
+
+        let state = &json["state"];
+        assert_eq!(10, state["generation"]);
+        // Parsing may lose the last bit of precision
+        let number = |value: &serde_json::Value| value.as_f64().unwrap();
+        assert_approx_eq!(cmaes.sigma(), number(&state["sigma"]), 1e-12);
+        assert_approx_eq!(cmaes.mean()[1], number(&state["mean"][1]), 1e-12);
+        assert_approx_eq!(
+            cmaes.covariance_matrix()[(0, 2)],
+            number(&state["covariance"][0][2]),
+            1e-12
+        );
+        assert_eq!(3, state["path_sigma"].as_array().unwrap().len());
+        assert_approx_eq!(
+            cmaes.overall_best_individual().unwrap().value,
+            number(&state["overall_best"]["value"]),
+            1e-12
+        );
+    }
+
+    #[test]
+    fn test_sample_final() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+        let _ = cmaes.run();
+
+        let evals = cmaes.function_evals();
+        let generation = cmaes.generation();
+        let points = cmaes.sample_final(50);
+
+        assert_eq!(50, points.len());
+        assert_eq!(evals, cmaes.function_evals());
+        assert_eq!(generation, cmaes.generation());
+
+        // The distribution has converged, so all points are close to the optimum
+        assert!(points.iter().all(|p| p.magnitude() < 1e-6));
+    }
+
+    #[test]
+    fn test_weights_vector() {
+        let cmaes = CMAESOptions::new(vec![0.0; 4], 1.0)
+            .population_size(10)
+            .build(dummy_function)
+            .unwrap();
+        let weights = cmaes.weights_vector();
+        let mu = cmaes.parameters().mu();
+
+        assert_eq!(10, weights.len());
+        assert_approx_eq!(1.0, weights[..mu].iter().sum::<f64>(), 1e-12);
+        assert!(weights[..mu].iter().all(|w| *w > 0.0));
+        assert!(weights[mu..].iter().all(|w| *w < 0.0));
+        assert!(weights.windows(2).all(|w| w[0] >= w[1]));
+
+        let cmaes = CMAESOptions::new(vec![0.0; 4], 1.0)
+            .population_size(10)
+            .weights(Weights::Positive)
+            .build(dummy_function)
+            .unwrap();
+
+        assert_eq!(mu, cmaes.weights_vector().len());
+    }
+
+    #[test]
+    fn test_seed() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let options = CMAESOptions::new(vec![1.0; 4], 1.0).max_generations(50);
+
+        assert_eq!(5, options.clone().seed(5).build(function).unwrap().seed());
+
+        // A randomly generated seed reproduces the run
+        let mut cmaes = options.clone().build(function).unwrap();
+        let result = cmaes.run();
+        let mut cmaes_repeat = options.seed(cmaes.seed()).build(function).unwrap();
+        let result_repeat = cmaes_repeat.run();
+
+        assert_eq!(
+            result.overall_best.unwrap().point,
+            result_repeat.overall_best.unwrap().point,
+        );
+        assert_eq!(result.final_mean, result_repeat.final_mean);
+    }
+
+    #[test]
+    fn test_clone_with() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        for _ in 0..5 {
+            let _ = cmaes.next();
+        }
+
+        let mut a = cmaes.clone_with(function);
+        let mut b = cmaes.clone_with(function);
+
+        assert_eq!(cmaes.mean(), a.mean());
+        assert_eq!(cmaes.sigma(), a.sigma());
+        assert_eq!(cmaes.function_evals(), a.function_evals());
+        assert_eq!(cmaes.generation(), a.generation());
+
+        let _ = cmaes.next();
+        let _ = a.next();
+        let _ = b.next();
+
+        // The clone diverges from the original, but clones are reproducible
+        assert_ne!(cmaes.mean(), a.mean());
+        assert_eq!(a.mean(), b.mean());
+
+        // Clones of clones diverge as well
+        let mut c = a.clone_with(function);
+        let _ = a.next();
+        let _ = c.next();
+
+        assert_ne!(a.mean(), c.mean());
+    }
+
+    #[test]
+    fn test_termination_details() {
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .max_generations(5)
+            .build(dummy_function)
+            .unwrap();
+        let result = cmaes.run();
+
+        assert_eq!(result.reasons.len(), result.details.len());
+        let detail = result
+            .details
+            .iter()
+            .find(|d| d.reason == TerminationReason::MaxGenerations)
+            .unwrap();
+        assert_eq!(Some(5.0), detail.measured);
+        assert_eq!(Some(5.0), detail.threshold);
+
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .build(|_: &DVector<f64>| f64::NAN)
+            .unwrap();
+        let result = cmaes.run();
+
+        assert_eq!(
+            vec![TerminationDetail::from(
+                TerminationReason::InvalidFunctionValue
+            )],
+            result.details
+        );
+    }
+
+    #[test]
+    fn test_adaptive_population() {
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 3], 1.0)
+                .adaptive_population(AdaptivePopulation::new(6, 4, 2.0))
+                .build(dummy_function),
+            Err(InvalidOptionsError::AdaptivePopulation),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 3], 1.0)
+                .adaptive_population(AdaptivePopulation::new(6, 20, 1.0))
+                .build(dummy_function),
+            Err(InvalidOptionsError::AdaptivePopulation),
+        ));
+
+        // The function never improves, so the population grows until it reaches the maximum
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .adaptive_population(AdaptivePopulation::new(6, 20, 2.0))
+            .max_generations(200)
+            .tol_fun_hist(0.0)
+            .tol_fun(0.0)
+            .build(dummy_function)
+            .unwrap();
+
+        assert_eq!(6, cmaes.population_size());
+
+        let mut sizes = vec![cmaes.population_size()];
+        while cmaes.next().is_none() {
+            if *sizes.last().unwrap() != cmaes.population_size() {
+                sizes.push(cmaes.population_size());
+            }
+        }
+
+        assert_eq!(vec![6, 12, 20], sizes);
+        assert_eq!(20, cmaes.current_population().len());
+        assert_eq!(20, cmaes.parameters().lambda());
+        assert_eq!(10, cmaes.parameters().mu());
+        assert_eq!(20, cmaes.parameters().weights().len());
+        assert_eq!(
+            cmaes.population_size(),
+            cmaes.population_size_trajectory().last().unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_auto_population() {
+        // The function never improves, so the population grows until it reaches the maximum
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .population_size(6)
+            .auto_population(true)
+            .max_generations(100)
+            .tol_fun_hist(0.0)
+            .tol_fun(0.0)
+            .build(dummy_function)
+            .unwrap();
+
+        assert_eq!(&[(0, 6)], cmaes.population_size_trajectory());
+
+        let _ = cmaes.run();
+        let trajectory = cmaes.population_size_trajectory();
+        let sizes = trajectory.iter().map(|(_, size)| *size).collect::<Vec<_>>();
+
+        assert_eq!(vec![6, 12, 24, 48, 96], sizes);
+        assert!(trajectory.windows(2).all(|w| w[1].0 >= w[0].0 + 10));
+        assert_eq!(96, cmaes.population_size());
+        assert_eq!(96, cmaes.current_population().len());
+        assert_eq!(96, cmaes.parameters().weights().len());
+    }
+
+    #[test]
+    fn test_convergence_curve() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .max_generations(50)
+            .build(function)
+            .unwrap();
+
+        assert!(cmaes.convergence_curve().is_empty());
+
+        let result = cmaes.run();
+        let curve = cmaes.convergence_curve();
+        let lambda = cmaes.parameters().lambda();
+
+        assert_eq!(50, curve.len());
+        for (i, (evals, _)) in curve.iter().enumerate() {
+            assert_eq!((i + 1) * lambda, *evals);
+        }
+        assert!(curve.windows(2).all(|w| w[1].1 <= w[0].1));
+        assert_eq!(result.overall_best.unwrap().value, curve.last().unwrap().1);
+    }
+
+    #[test]
+    fn test_convergence_curve_unrankable_generation() {
+        // No individual of the second generation can be evaluated
+        let mut evals = 0;
+        let function = |x: &DVector<f64>| {
+            evals += 1;
+            if (8..=14).contains(&evals) {
+                None
+            } else {
+                Some(x.magnitude())
+            }
+        };
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .population_size(7)
+            .build(function)
+            .unwrap();
+
+        for _ in 0..3 {
+            assert!(cmaes.next_partial().is_none());
+        }
+
+        // The generation is still recorded, but without a median
+        let trajectory = cmaes.history.trajectory();
+        assert_eq!(3, trajectory.len());
+        assert_eq!(
+            vec![1, 2, 3],
+            trajectory.iter().map(|e| e.generation).collect::<Vec<_>>()
+        );
+        assert_eq!(trajectory[0].best_value, trajectory[1].best_value);
+        assert!(trajectory[1].median_value.is_nan());
+        assert!(!trajectory[0].median_value.is_nan() && !trajectory[2].median_value.is_nan());
+        assert_eq!(3, cmaes.convergence_curve().len());
+    }
+
+    #[test]
+    fn test_write_trajectory_csv() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![3.0, 4.0], 2.0)
+            .max_generations(20)
+            .build(function)
+            .unwrap();
+
+        let mut csv = Vec::new();
+        cmaes.write_trajectory_csv(&mut csv).unwrap();
+        assert_eq!(
+            "generation,function_evals,best_value,median_value,sigma,axis_ratio,mean_norm\n",
+            String::from_utf8(csv).unwrap()
+        );
+
+        let _ = cmaes.run();
+        let mut csv = Vec::new();
+        cmaes.write_trajectory_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let rows = csv
+            .lines()
+            .skip(1)
+            .map(|line| {
+                line.split(',')
+                    .map(|value| value.parse::<f64>().unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(20, rows.len());
+        for (i, (row, (evals, best))) in rows.iter().zip(cmaes.convergence_curve()).enumerate() {
+            assert_eq!(7, row.len());
+            assert_eq!((i + 1) as f64, row[0]);
+            assert_eq!(evals as f64, row[1]);
+            assert_eq!(best, row[2]);
+            assert!(row[3] >= row[2]);
+        }
+
+        // The first generation is sampled from the initial distribution
+        assert_eq!(vec![2.0, 1.0, 5.0], rows[0][4..].to_vec());
+    }
+
+    #[cfg(feature = "plotters")]
+    #[test]
+    fn test_max_recorded_generations() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .max_recorded_generations(10)
+            .enable_plot(PlotOptions::new(0, false))
+            .max_generations(50)
+            .build(function)
+            .unwrap();
+
+        let result = cmaes.run();
+        let curve = cmaes.convergence_curve();
+        let lambda = cmaes.parameters().lambda();
+
+        // Only the latest generations are kept
+        assert_eq!(10, curve.len());
+        for (i, (evals, _)) in curve.iter().enumerate() {
+            assert_eq!((i + 41) * lambda, *evals);
+        }
+        assert_eq!(result.overall_best.unwrap().value, curve.last().unwrap().1);
+        assert_eq!(10, cmaes.get_plot().unwrap().len());
+    }
+
+    #[test]
+    fn test_fun_target_record_only() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![5.0; 3], 1.0)
+            .fun_target(1e-3)
+            .fun_target_mode(TargetMode::RecordOnly)
+            .max_generations(500)
+            .build(function)
+            .unwrap();
+
+        assert_eq!(None, cmaes.fun_target_generation());
+
+        let result = cmaes.run();
+        let generation = cmaes.fun_target_generation().unwrap();
+        let curve = cmaes.convergence_curve();
+
+        // The run continued past the target
+        assert!(!result.reasons.contains(&TerminationReason::FunTarget));
+        assert!(generation < cmaes.generation());
+        assert!(curve[generation - 1].1 < 1e-3);
+        assert!(curve[generation - 2].1 >= 1e-3);
+        assert!(result.overall_best.unwrap().value < 1e-6);
+    }
+
+    #[test]
+    fn test_target_hits() {
+        use std::sync::{Arc, Mutex};
+
+        let values = Arc::new(Mutex::new(Vec::new()));
+        let values_clone = values.clone();
+        let function = move |x: &DVector<f64>| {
+            let value = x.magnitude();
+            values_clone.lock().unwrap().push(value);
+            value
+        };
+        let targets = vec![f64::INFINITY, 4.0, 1.0, 1e-2, -1.0];
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .target_values(targets.clone())
+            .max_generations(50)
+            .seed(1)
+            .build(function)
+            .unwrap();
+
+        assert!(cmaes.target_hits().iter().all(|(_, hit)| hit.is_none()));
+        let _ = cmaes.run();
+
+        // Each target is reached by the first evaluation with a value at least as good
+        let values = values.lock().unwrap();
+        let hits = cmaes.target_hits();
+        assert_eq!(targets.len(), hits.len());
+        for (target, (hit_target, hit)) in targets.into_iter().zip(hits) {
+            assert_eq!(target, hit_target);
+            assert_eq!(values.iter().position(|v| *v <= target).map(|i| i + 1), hit);
+        }
+        assert_eq!(Some(1), cmaes.target_hits()[0].1);
+        assert_eq!(None, cmaes.target_hits()[4].1);
+    }
+
+    #[test]
+    fn test_run_until() {
+        // Built-in termination criteria take precedence over the predicate
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .max_generations(5)
+            .build(dummy_function)
+            .unwrap();
+        let mut calls = 0;
+        let result = cmaes.run_until(|_| {
+            calls += 1;
+            false
+        });
+
+        assert_eq!(4, calls);
+        assert!(result.reasons.contains(&TerminationReason::MaxGenerations));
+        assert!(!result.reasons.contains(&TerminationReason::Predicate));
+
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .build(function)
+            .unwrap();
+        let result = cmaes.run_until(|state| state.overall_best_individual().unwrap().value < 1e-3);
+
+        assert_eq!(vec![TerminationReason::Predicate], result.reasons);
+        assert!(result.overall_best.unwrap().value < 1e-3);
+    }
+
+    #[test]
+    fn test_sigma_decay_ratio() {
+        let function = |x: &DVector<f64>| x.magnitude_squared();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .max_sigma_decay_ratio(100.0)
+            .build(function)
+            .unwrap();
+
+        // The window is 10 + 30 * 4 / 8 = 25 generations
+        for _ in 0..25 {
+            let _ = cmaes.next();
+            assert!(cmaes.sigma_decay_rate().is_none());
+            assert!(cmaes.sigma_decay_ratio().is_none());
+        }
+
+        // On a converging run, the function value shrinks with the square of the step size
+        let result = cmaes.run();
+        assert!(!result
+            .reasons
+            .contains(&TerminationReason::PrematureConvergence));
+        assert!(cmaes.sigma_decay_rate().unwrap() > 0.0);
+        let ratio = cmaes.sigma_decay_ratio().unwrap();
+        assert!(ratio > 0.1 && ratio < 2.0, "{}", ratio);
+    }
+
+    #[test]
+    fn test_run_streaming() {
+        let function = |x: &DVector<f64>| x.magnitude();
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .max_generations(20)
+            .build(function)
+            .unwrap();
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let result = std::thread::spawn(move || {
+            let result = cmaes.run_streaming(sender);
+            (result, cmaes.sigma())
+        });
+        let snapshots = receiver.iter().collect::<Vec<_>>();
+        let (result, sigma) = result.join().unwrap();
+
+        assert_eq!(20, snapshots.len());
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            assert_eq!(i + 1, snapshot.generation);
+        }
+        assert!(snapshots
+            .windows(2)
+            .all(|w| w[1].best_value.unwrap() <= w[0].best_value.unwrap()));
+
+        let last = snapshots.last().unwrap();
+        assert_eq!(result.function_evals, last.function_evals);
+        assert_eq!(result.overall_best.unwrap().value, last.best_value.unwrap());
+        assert_eq!(sigma, last.sigma);
+
+        // The run is unaffected by a dropped receiver
+        let mut cmaes = CMAESOptions::new(vec![1.0; 3], 1.0)
+            .max_generations(20)
+            .build(function)
+            .unwrap();
+        let (sender, _) = std::sync::mpsc::channel();
+        assert_eq!(
+            20,
+            cmaes.run_streaming(sender).function_evals / cmaes.population_size()
+        );
+    }
+
+    #[cfg(feature = "plotters")]
     #[test]
     fn test_run_final_plot() {
         let evals_per_plot_point = 100;
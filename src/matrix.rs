@@ -1,12 +1,13 @@
 //! Types related to matrix math.
 
 use nalgebra::base::VecStorage;
-use nalgebra::Dyn;
+use nalgebra::{DVector, Dyn};
 
 pub type SquareMatrix<T> = nalgebra::SquareMatrix<T, Dyn, VecStorage<T, Dyn, Dyn>>;
 
 /// A symmetric square matrix that stores and updates its eigendecomposition and inverse square root
 /// (`C^(-1/2)`)
+#[derive(Clone)]
 pub struct CovarianceMatrix {
     /// Covariance matrix
     cov: SquareMatrix<f64>,
@@ -88,15 +89,44 @@ impl CovarianceMatrix {
         }
 
         self.eigenvectors = eigen.eigenvectors;
-        self.sqrt_eigenvalues = SquareMatrix::from_diagonal(&eigen.eigenvalues.map(|x| x.sqrt()));
+        self.set_eigenvalues(&eigen.eigenvalues);
+
+        Ok(())
+    }
+
+    /// Raises all eigenvalues below `ratio` times the largest eigenvalue to that value and
+    /// rebuilds the matrix from the floored eigenvalues. Uses the stored eigendecomposition, so it
+    /// should only be called right after it is updated.
+    ///
+    /// Returns whether any eigenvalues were raised
+    pub fn floor_eigenvalues(&mut self, ratio: f64) -> bool {
+        let eigenvalues = self.sqrt_eigenvalues.diagonal().map(|d| d.powi(2));
+        let floor = ratio * eigenvalues.max();
+        if eigenvalues.min() >= floor {
+            return false;
+        }
+
+        let eigenvalues = eigenvalues.map(|x| x.max(floor));
+        self.cov = &self.eigenvectors
+            * SquareMatrix::from_diagonal(&eigenvalues)
+            * self.eigenvectors.transpose();
+        // Ensure symmetry
+        self.cov.fill_lower_triangle_with_upper_triangle();
+        self.set_eigenvalues(&eigenvalues);
+
+        true
+    }
+
+    /// Updates the matrices derived from the eigendecomposition given the eigenvalues
+    /// corresponding to the stored eigenvectors
+    fn set_eigenvalues(&mut self, eigenvalues: &DVector<f64>) {
+        self.sqrt_eigenvalues = SquareMatrix::from_diagonal(&eigenvalues.map(|x| x.sqrt()));
         self.sqrt_inv = &self.eigenvectors
             * self
                 .sqrt_eigenvalues
                 .map(|d| if d > 0.0 { 1.0 / d } else { d })
             * self.eigenvectors.transpose();
         self.transform = &self.eigenvectors * &self.sqrt_eigenvalues;
-
-        Ok(())
     }
 
     pub fn eigenvectors(&self) -> &SquareMatrix<f64> {
@@ -157,4 +187,32 @@ mod tests {
         .unwrap();
         assert!(cov.update_eigendecomposition().is_err());
     }
+
+    #[test]
+    fn test_floor_eigenvalues() {
+        let mut cov = CovarianceMatrix::new(2);
+        cov.set_cov(
+            SquareMatrix::from_iterator(2, 2, [4.0, 0.0, 0.0, 1e-6]),
+            true,
+        )
+        .unwrap();
+
+        // Already above the floor
+        assert!(!cov.floor_eigenvalues(1e-8));
+        assert_approx_eq!(1e-6, cov.cov[(1, 1)], 1e-18);
+
+        assert!(cov.floor_eigenvalues(1e-2));
+        assert_approx_eq!(4.0, cov.cov[(0, 0)], 1e-12);
+        assert_approx_eq!(4e-2, cov.cov[(1, 1)], 1e-12);
+        assert_approx_eq!(0.2, cov.sqrt_eigenvalues.diagonal().min(), 1e-12);
+        assert_approx_eq!(5.0, cov.sqrt_inv[(1, 1)], 1e-9);
+
+        // The derived matrices match a fresh eigendecomposition
+        let transform = cov.transform.clone();
+        cov.update_eigendecomposition().unwrap();
+        let reconstructed = &transform * transform.transpose();
+        for x in (reconstructed - &cov.cov).iter() {
+            assert_approx_eq!(x, 0.0);
+        }
+    }
 }
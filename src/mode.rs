@@ -8,6 +8,7 @@ use crate::utils;
 ///
 /// The default value is `Minimize`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     /// Minimize the value of the function.
     Minimize,
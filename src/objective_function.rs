@@ -86,7 +86,7 @@ impl<F: FnMut(&DVector<f64>) -> f64> ObjectiveFunction for F {
     }
 }
 
-impl ObjectiveFunction for Box<dyn ObjectiveFunction> {
+impl<'a> ObjectiveFunction for Box<dyn ObjectiveFunction + 'a> {
     fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
         self.as_mut().evaluate(x)
     }
@@ -171,6 +171,145 @@ impl ParallelObjectiveFunction for Box<dyn ParallelObjectiveFunction> {
     }
 }
 
+/// Like [`ObjectiveFunction`], but for objective functions that evaluate many points at once, such
+/// as GPU kernels or external simulators with a high per-call overhead. Used with
+/// [`CMAES::run_batch`][crate::CMAES::run_batch].
+///
+/// Each generation is split into consecutive chunks of [`chunk_size`][Self::chunk_size] points
+/// (the last chunk may be smaller), and `evaluate_batch` is called once per chunk. The returned
+/// values must be in the same order as the points and have the same length. If the length
+/// differs, the values cannot be matched to the points, so the run terminates with
+/// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue]
+/// (regardless of the [`InvalidPolicy`][crate::InvalidPolicy]).
+///
+/// The trait is implemented for functions and closures with the correct signature, which evaluate
+/// the whole generation in a single call. [`Chunked`] can be used to set a chunk size for them:
+///
+/// ```
+/// use cmaes::objective_function::Chunked;
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let function = |xs: &[DVector<f64>]| xs.iter().map(|x| x.magnitude()).collect::<Vec<_>>();
+///
+/// let mut cmaes_state = CMAESOptions::new(vec![1.0; 10], 1.0)
+///     .population_size(20)
+///     .max_generations(10)
+///     .build(Chunked::new(function, 8))
+///     .unwrap();
+/// let solution = cmaes_state.run_batch();
+///
+/// // Each generation was evaluated in chunks of 8, 8, and 4 points
+/// assert_eq!(&[8, 8, 4], cmaes_state.chunk_sizes());
+/// ```
+pub trait BatchObjectiveFunction {
+    /// Evaluates each of `xs` and returns their values in the same order.
+    fn evaluate_batch(&mut self, xs: &[DVector<f64>]) -> Vec<f64>;
+
+    /// Returns the preferred number of points to pass to each
+    /// [`evaluate_batch`][Self::evaluate_batch] call, or `None` to evaluate each generation in a
+    /// single call. The default implementation returns `None`.
+    fn chunk_size(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<F: FnMut(&[DVector<f64>]) -> Vec<f64>> BatchObjectiveFunction for F {
+    fn evaluate_batch(&mut self, xs: &[DVector<f64>]) -> Vec<f64> {
+        (self)(xs)
+    }
+}
+
+impl BatchObjectiveFunction for Box<dyn BatchObjectiveFunction> {
+    fn evaluate_batch(&mut self, xs: &[DVector<f64>]) -> Vec<f64> {
+        self.as_mut().evaluate_batch(xs)
+    }
+
+    fn chunk_size(&self) -> Option<usize> {
+        self.as_ref().chunk_size()
+    }
+}
+
+/// A trait for objective functions that may be unable to evaluate some points, for example
+/// because they lie outside of a hidden feasible region. Used with
+/// [`CMAES::run_partial`][crate::CMAES::run_partial].
+///
+/// Returning `None` marks a point as unrankable: it is ranked after every evaluated point and does
+/// not contribute to the update of the distribution (its weight is set to zero and the remaining
+/// positive weights are renormalized), so unrankable points do not corrupt the adaptation. The
+/// number of unrankable points in the latest generation can be obtained from
+/// [`CMAES::unrankable_count`][crate::CMAES::unrankable_count]. Returning `Some(NAN)` is still
+/// treated as an invalid function value.
+///
+/// The trait is implemented for functions and closures with the correct signature:
+///
+/// ```
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// // The function cannot be evaluated for negative coordinates
+/// let function = |x: &DVector<f64>| {
+///     if x.iter().all(|xi| *xi >= 0.0) {
+///         Some(x.map(|xi| (xi - 1.0).powi(2)).sum())
+///     } else {
+///         None
+///     }
+/// };
+///
+/// let mut cmaes_state = CMAESOptions::new(vec![2.0; 4], 1.0).build(function).unwrap();
+/// let result = cmaes_state.run_partial();
+///
+/// assert!(result.overall_best.unwrap().value < 1e-8);
+/// ```
+pub trait PartialObjectiveFunction {
+    /// Evaluates `x`, returning `None` if it cannot be evaluated.
+    fn evaluate_partial(&mut self, x: &DVector<f64>) -> Option<f64>;
+}
+
+impl<F: FnMut(&DVector<f64>) -> Option<f64>> PartialObjectiveFunction for F {
+    fn evaluate_partial(&mut self, x: &DVector<f64>) -> Option<f64> {
+        (self)(x)
+    }
+}
+
+impl PartialObjectiveFunction for Box<dyn PartialObjectiveFunction> {
+    fn evaluate_partial(&mut self, x: &DVector<f64>) -> Option<f64> {
+        self.as_mut().evaluate_partial(x)
+    }
+}
+
+/// A type that wraps any [`BatchObjectiveFunction`] and sets the number of points passed to each
+/// of its calls. See [`BatchObjectiveFunction`] for an example.
+#[derive(Clone)]
+pub struct Chunked<F> {
+    function: F,
+    chunk_size: usize,
+}
+
+impl<F> Chunked<F> {
+    /// Returns a new `Chunked`, wrapping `function` and evaluating at most `chunk_size` points in
+    /// each call. A `chunk_size` of zero is treated as one.
+    pub fn new(function: F, chunk_size: usize) -> Self {
+        Self {
+            function,
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    /// Consumes `self` and returns the wrapped function.
+    pub fn into_wrapped_function(self) -> F {
+        self.function
+    }
+}
+
+impl<F: BatchObjectiveFunction> BatchObjectiveFunction for Chunked<F> {
+    fn evaluate_batch(&mut self, xs: &[DVector<f64>]) -> Vec<f64> {
+        self.function.evaluate_batch(xs)
+    }
+
+    fn chunk_size(&self) -> Option<usize> {
+        Some(self.chunk_size)
+    }
+}
+
 /// A type that wraps any [`ObjectiveFunction`] and scales the input vectors before passing them to
 /// the wrapped function.
 ///
@@ -247,6 +386,228 @@ impl<'a, F: ParallelObjectiveFunction> ParallelObjectiveFunction for &'a Scale<F
     }
 }
 
+/// A type that wraps any [`ObjectiveFunction`] and multiplies its values by a constant factor.
+/// Unlike [`Scale`], which scales the inputs of the function, this scales its outputs.
+///
+/// Together with [`Offset`], [`Sum`], and [`Negated`], it allows objective functions to be
+/// composed without writing closures by hand.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::objective_function::{Offset, Scaled, Sum};
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let sphere = |x: &DVector<f64>| x.magnitude_squared();
+/// let penalty = |x: &DVector<f64>| x.iter().map(|xi| xi.abs()).sum::<f64>();
+///
+/// // sphere(x) + 0.1 * penalty(x) + 1.0
+/// let function = Offset::new(
+///     Sum::new(vec![
+///         Box::new(sphere) as Box<dyn cmaes::ObjectiveFunction>,
+///         Box::new(Scaled::new(penalty, 0.1)),
+///     ]),
+///     1.0,
+/// );
+///
+/// let mut cmaes_state = CMAESOptions::new(vec![1.0; 2], 1.0).build(function).unwrap();
+/// let result = cmaes_state.run();
+///
+/// assert!((result.overall_best.unwrap().value - 1.0).abs() < 1e-6);
+/// ```
+#[derive(Clone)]
+pub struct Scaled<F> {
+    function: F,
+    factor: f64,
+}
+
+impl<F> Scaled<F> {
+    /// Returns a new `Scaled`, wrapping `function` and multiplying its values by `factor`.
+    pub fn new(function: F, factor: f64) -> Self {
+        Self { function, factor }
+    }
+
+    /// Returns the factor.
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    /// Consumes `self` and returns the wrapped function.
+    pub fn into_wrapped_function(self) -> F {
+        self.function
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for Scaled<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        self.factor * self.function.evaluate(x)
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for &mut Scaled<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        ObjectiveFunction::evaluate(*self, x)
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for Scaled<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        self.factor * self.function.evaluate_parallel(x)
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for &Scaled<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        ParallelObjectiveFunction::evaluate_parallel(*self, x)
+    }
+}
+
+/// A type that wraps any [`ObjectiveFunction`] and adds a constant to its values. See [`Scaled`]
+/// for an example.
+#[derive(Clone)]
+pub struct Offset<F> {
+    function: F,
+    offset: f64,
+}
+
+impl<F> Offset<F> {
+    /// Returns a new `Offset`, wrapping `function` and adding `offset` to its values.
+    pub fn new(function: F, offset: f64) -> Self {
+        Self { function, offset }
+    }
+
+    /// Returns the offset.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Consumes `self` and returns the wrapped function.
+    pub fn into_wrapped_function(self) -> F {
+        self.function
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for Offset<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        self.function.evaluate(x) + self.offset
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for &mut Offset<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        ObjectiveFunction::evaluate(*self, x)
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for Offset<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        self.function.evaluate_parallel(x) + self.offset
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for &Offset<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        ParallelObjectiveFunction::evaluate_parallel(*self, x)
+    }
+}
+
+/// A type that wraps several [`ObjectiveFunction`]s of the same type and sums their values (`0.0`
+/// if there are none). Functions of different types can be combined by boxing them as
+/// `Box<dyn ObjectiveFunction>`. See [`Scaled`] for an example.
+#[derive(Clone)]
+pub struct Sum<F> {
+    functions: Vec<F>,
+}
+
+impl<F> Sum<F> {
+    /// Returns a new `Sum`, wrapping `functions` and summing their values.
+    pub fn new<V: Into<Vec<F>>>(functions: V) -> Self {
+        Self {
+            functions: functions.into(),
+        }
+    }
+
+    /// Consumes `self` and returns the wrapped functions.
+    pub fn into_wrapped_functions(self) -> Vec<F> {
+        self.functions
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for Sum<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        self.functions
+            .iter_mut()
+            .map(|function| function.evaluate(x))
+            .sum()
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for &mut Sum<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        ObjectiveFunction::evaluate(*self, x)
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for Sum<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        self.functions
+            .iter()
+            .map(|function| function.evaluate_parallel(x))
+            .sum()
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for &Sum<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        ParallelObjectiveFunction::evaluate_parallel(*self, x)
+    }
+}
+
+/// A type that wraps any [`ObjectiveFunction`] and negates its values, for example to maximize a
+/// function while minimizing (or vice versa). Setting
+/// [`CMAESOptions::mode`][crate::CMAESOptions::mode] is usually simpler, but negating the function
+/// is useful when it is combined with other functions (see [`Scaled`]).
+#[derive(Clone)]
+pub struct Negated<F> {
+    function: F,
+}
+
+impl<F> Negated<F> {
+    /// Returns a new `Negated`, wrapping `function` and negating its values.
+    pub fn new(function: F) -> Self {
+        Self { function }
+    }
+
+    /// Consumes `self` and returns the wrapped function.
+    pub fn into_wrapped_function(self) -> F {
+        self.function
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for Negated<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        -self.function.evaluate(x)
+    }
+}
+
+impl<F: ObjectiveFunction> ObjectiveFunction for &mut Negated<F> {
+    fn evaluate(&mut self, x: &DVector<f64>) -> f64 {
+        ObjectiveFunction::evaluate(*self, x)
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for Negated<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        -self.function.evaluate_parallel(x)
+    }
+}
+
+impl<F: ParallelObjectiveFunction> ParallelObjectiveFunction for &Negated<F> {
+    fn evaluate_parallel(&self, x: &DVector<f64>) -> f64 {
+        ParallelObjectiveFunction::evaluate_parallel(*self, x)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,4 +624,36 @@ mod tests {
         );
         assert_eq!(14.0, scale.evaluate(&vec![1.0; 3].into()));
     }
+
+    #[test]
+    fn test_combinators() {
+        let x = DVector::from(vec![1.0, 2.0, 3.0]);
+        let sum = |x: &DVector<f64>| x.iter().sum::<f64>();
+        let product = |x: &DVector<f64>| x.iter().product::<f64>();
+
+        assert_eq!(12.0, Scaled::new(sum, 2.0).evaluate(&x));
+        assert_eq!(5.0, Offset::new(sum, -1.0).evaluate(&x));
+        assert_eq!(-6.0, Negated::new(sum).evaluate(&x));
+        assert_eq!(18.0, Sum::new(vec![sum, sum, sum]).evaluate(&x));
+        assert_eq!(
+            0.0,
+            Sum::<fn(&DVector<f64>) -> f64>::new(vec![]).evaluate(&x)
+        );
+
+        // Functions of different types can be combined when boxed
+        let mut combined = Offset::new(
+            Sum::new(vec![
+                Box::new(sum) as Box<dyn ObjectiveFunction>,
+                Box::new(Negated::new(Scaled::new(product, 0.5))),
+            ]),
+            10.0,
+        );
+        assert_eq!(13.0, combined.evaluate(&x));
+
+        // The parallel implementations give the same values
+        assert_eq!(12.0, Scaled::new(sum, 2.0).evaluate_parallel(&x));
+        assert_eq!(5.0, Offset::new(sum, -1.0).evaluate_parallel(&x));
+        assert_eq!(-6.0, Negated::new(sum).evaluate_parallel(&x));
+        assert_eq!(12.0, Sum::new(vec![sum, sum]).evaluate_parallel(&x));
+    }
 }
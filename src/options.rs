@@ -1,32 +1,48 @@
 //! Types related to initializing a [`CMAES`]. See [`CMAESOptions`] for full documentation.
 
 use nalgebra::DVector;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
+use crate::bounds::{BoundaryHandling, Bounds};
 use crate::mode::Mode;
-use crate::parameters::Weights;
+use crate::objective_function::ObjectiveFunction;
+use crate::parameters::{AdaptivePopulation, AutoKick, RecombinationWeighting, Weights};
+use crate::recording::{GenerationSamples, GENERATION_PLACEHOLDER};
+use crate::sampling::{Aggregation, EvaluatedPoint};
+use crate::termination::{InvalidPolicy, TargetMode, TerminationPreset};
+use crate::transform::{CoordinateTransform, FitnessTransform};
 #[cfg(feature = "plotters")]
 use crate::PlotOptions;
 use crate::CMAES;
 
+/// The stream of the RNG used to draw a random initial mean, which is distinct from the stream
+/// used by the sampler so that the initial mean is not correlated with the first generation
+const INITIAL_MEAN_RNG_STREAM: u64 = 1 << 33;
+
 /// A builder for [`CMAES`]. Used to adjust parameters of the algorithm to each particular
 /// problem and to change other options. See the fields and methods for a full list of options.
 ///
 /// # Examples
 ///
 /// ```
-/// use cmaes::{CMAESOptions, DVector, PlotOptions, Weights};
+/// use cmaes::{CMAESOptions, DVector, Weights};
 ///
 /// let function = |x: &DVector<f64>| x.magnitude();
 /// let dim = 3;
-/// let mut cmaes_state = CMAESOptions::new(vec![2.0; dim], 5.0)
+/// let options = CMAESOptions::new(vec![2.0; dim], 5.0)
 ///     .weights(Weights::Positive)
 ///     .population_size(100)
-///     .enable_plot(PlotOptions::new(0, false))
-///     .enable_printing(200)
-///     .build(function)
-///     .unwrap();
+///     .enable_printing(200);
+/// // Plots require the `plotters` feature
+/// #[cfg(feature = "plotters")]
+/// let options = options.enable_plot(cmaes::PlotOptions::new(0, false));
+/// let mut cmaes_state = options.build(function).unwrap();
 /// ```
 #[derive(Clone, Debug)]
 pub struct CMAESOptions {
@@ -40,6 +56,41 @@ pub struct CMAESOptions {
     /// separately in each dimension, the appropriate transformation should be made to the objective
     /// function itself using [`Scale`][crate::objective_function::Scale]
     pub initial_step_size: f64,
+    /// Whether `initial_step_size` is replaced by the value suggested by the bounds when the
+    /// [`CMAES`][crate::CMAES] is built. Default value is `false`. See
+    /// [`initial_step_size_from_bounds`][Self::initial_step_size_from_bounds].
+    pub initial_step_size_from_bounds: bool,
+    /// Scale of the initial covariance matrix, which is set to `initial_covariance_scale * I`
+    /// (the initial standard deviation in each coordinate is then
+    /// `initial_step_size * sqrt(initial_covariance_scale)`). Must be positive. Default value is
+    /// `1.0`.
+    ///
+    /// Mathematically, this overlaps with `initial_step_size`: both scale the initial
+    /// distribution isotropically. They behave differently under adaptation, however. The step
+    /// size is adapted as a single global factor by cumulative step size adaptation, whose
+    /// evolution path is normalized by the covariance matrix and is therefore unaffected by this
+    /// scale. The covariance matrix only changes through its per-axis updates, which pull it
+    /// towards the shape of the selected steps at a rate of `c1 + cmu` per generation, so a
+    /// scale far from `1.0` is shrunk or expanded gradually by the covariance update rather than
+    /// quickly by the step size adaptation. This is mainly useful for studying the redundancy
+    /// between the two scalings; otherwise, adjusting `initial_step_size` is recommended.
+    ///
+    /// Criteria that are relative to the initial step size (the default `tol_x` and `TolXUp`)
+    /// do not account for this scale.
+    pub initial_covariance_scale: f64,
+    /// The lower and upper corners of a box from which the initial mean is drawn uniformly at
+    /// random when the [`CMAES`] is built. Default value is `None` (the initial mean is fixed).
+    /// Both must have the same length as `initial_mean` and satisfy `lower[i] <= upper[i]`, and
+    /// all bounds must be finite.
+    ///
+    /// If set, `initial_mean` is only used to determine the problem dimension and its values are
+    /// ignored. The mean is drawn using an RNG derived from the [`seed`][Self::seed], so runs
+    /// with the same seed start from the same mean, while runs with distinct seeds (e.g. the
+    /// runs of a [`sweep`][crate::sweep()] or of [`parallel_restarts`][crate::parallel_restarts])
+    /// start from independently drawn means. The drawn mean can be read from
+    /// [`CMAES::mean`] before the first generation. Like a fixed initial mean, it is clamped into
+    /// the [`bounds`][Self::bounds] if it lies outside of them.
+    pub random_initial_mean: Option<(DVector<f64>, DVector<f64>)>,
     /// Number of points to generate each generation (`lambda`). Default value is
     /// `4 + floor(3 * ln(dimensions))`.
     ///
@@ -53,20 +104,227 @@ pub struct CMAESOptions {
     /// module).
     pub population_size: usize,
     /// The distribution to use when assigning weights to individuals. Default value is
-    /// [`Weights::Negative`].
+    /// [`Weights::recommended`] for the default population size (usually [`Weights::Negative`]).
+    /// If the population size is changed, [`Weights::recommended`] can be used again to pick a
+    /// setting suited to the new size.
     pub weights: Weights,
+    /// The smallest variance-effective selection mass `mu_eff` (see [`Weights::mu_eff`]) that the
+    /// weights may have for the population size. Default value is `None` (only a degenerate
+    /// `mu_eff` is rejected). If set, building fails with [`InvalidOptionsError::EffectiveMass`]
+    /// if `mu_eff` is smaller. Must be at least `1.0`.
+    ///
+    /// The learning rates are derived from `mu_eff`, and the formulas assume that it lies
+    /// between `1` and `mu`. With a very small `mu_eff`, most of the weight rests on the best
+    /// individual: the rank-mu update of the covariance matrix vanishes (its learning rate is `0`
+    /// at `mu_eff = 1`), the step size adapts from a single sample, and the search becomes
+    /// sensitive to noise. The default weights give `mu_eff` close to `population_size / 4`
+    /// (e.g. `2.6` for a population size of `8`), and values of at least about `2` are
+    /// recommended. This is useful as a guard when the population size or weights are chosen
+    /// programmatically (e.g. in a [`sweep`][crate::sweep()]), so that a configuration that would
+    /// silently produce a poorly adapting optimizer is rejected instead.
+    ///
+    /// A `mu_eff` that is not finite or is less than `1` is always rejected, regardless of this
+    /// option.
+    pub min_mu_eff: Option<f64>,
+    /// Settings for growing the population size within the run when progress stalls. Default
+    /// value is `None` (the population size is fixed). See [`AdaptivePopulation`].
+    pub adaptive_population: Option<AdaptivePopulation>,
+    /// Whether to grow and shrink the population size within the run according to the success
+    /// rate of the mean update. Default value is `false`. Experimental; see
+    /// [`auto_population`][Self::auto_population].
+    pub auto_population: bool,
+    /// Settings for displacing the mean and increasing the step size when progress stalls.
+    /// Default value is `None` (disabled). See [`AutoKick`].
+    pub auto_kick: Option<AutoKick>,
+    /// How the weights of the selected individuals are determined each generation. Default value
+    /// is [`RecombinationWeighting::Rank`], which is the most robust choice.
+    pub recombination_weighting: RecombinationWeighting,
+    /// A function applied to the raw objective function values before they are used for the
+    /// value-based recombination weights. Default value is `None` (raw values are used). See
+    /// [`FitnessTransform`] for which computations see transformed values.
+    pub fitness_transform: Option<FitnessTransform>,
     /// Whether to perform the state update in parallel using multiple threads. Default value is
     /// `false`.
     ///
     /// This will likely degrade performance for smaller population sizes but can be a major
     /// performance boost for very large population sizes (e.g., `512 * default`) as might be used
-    /// with [`IPOP`][crate::restart::IPOP] or [`BIPOP`][crate::restart::BIPOP]). Due to floating
-    /// point errors this will reduce the determinism guarantee of the [`seed`][Self::seed] option,
-    /// but the degree to which determinism is affected can vary significantly.
+    /// with [`IPOP`][crate::restart::IPOP] or [`BIPOP`][crate::restart::BIPOP]). Only independent
+    /// computations (the sampled steps and the terms of the covariance matrix update) are
+    /// performed in parallel, while all sums are performed sequentially in a fixed order, so the
+    /// results are bit-identical to those of a sequential update regardless of how the work is
+    /// scheduled across threads.
     pub parallel_update: bool,
     /// The learning rate for adapting the mean. Can be reduced for noisy functions. Default value
     /// is `1.0`.
     pub cm: f64,
+    /// A schedule producing the learning rate for the mean in each generation, overriding
+    /// [`cm`][Self::cm]. Default value is `None`. See [`cm_schedule`][Self::cm_schedule].
+    pub cm_schedule: Option<CmSchedule>,
+    /// The maximum factor by which the step size may change in a single generation (e.g. `2.0`
+    /// allows the step size to at most double or halve each generation). Default value is `None`
+    /// (unlimited). Must be at least `1.0`.
+    ///
+    /// This smooths the step size adaptation on noisy functions, where the step size may otherwise
+    /// oscillate strongly between generations, at the cost of responding more slowly when the
+    /// step size really needs to change quickly (e.g. after a poor choice of the initial step
+    /// size). In particular, it limits how quickly the step size can grow, so
+    /// [`TerminationReason::TolXUp`][crate::TerminationReason::TolXUp] takes at least
+    /// `log(tol_x_up) / log(max_sigma_change)` generations to be reached.
+    pub max_sigma_change: Option<f64>,
+    /// The smallest allowed ratio between the smallest and the largest eigenvalue of the
+    /// covariance matrix. Default value is `None` (unlimited). Must be in `(0, 1]`.
+    ///
+    /// Whenever the eigendecomposition is updated, eigenvalues below `min_eigenvalue_ratio` times
+    /// the largest eigenvalue are raised to that floor and the covariance matrix is rebuilt from
+    /// the floored eigenvalues. This bounds the condition number of the covariance matrix by
+    /// `1 / min_eigenvalue_ratio` (and the axis ratio by its square root), which prevents a
+    /// collapsing direction from triggering
+    /// [`TerminationReason::NoEffectAxis`][crate::TerminationReason::NoEffectAxis] or
+    /// [`TerminationReason::TolConditionCov`][crate::TerminationReason::TolConditionCov]
+    /// prematurely.
+    ///
+    /// This biases the distribution away from what the algorithm has learned: the floored
+    /// directions are sampled with a larger variance than the function warrants, so on problems
+    /// that really are more ill-conditioned than the floor allows, convergence slows down to that
+    /// of a partially adapted distribution. It should be used sparingly, with a small value (e.g.
+    /// `1e-12`), as a safeguard rather than a tuning parameter.
+    pub min_eigenvalue_ratio: Option<f64>,
+    /// Box bounds on the search space and the method used to handle them. Default value is `None`
+    /// (unbounded). See [`Bounds`].
+    ///
+    /// If the initial mean lies outside of the bounds, it is clamped into them when the
+    /// [`CMAES`][crate::CMAES] is built, which can be checked with
+    /// [`CMAES::initial_mean_clamped`][crate::CMAES::initial_mean_clamped].
+    pub bounds: Option<Bounds>,
+    /// Fixed weights of the boundary penalty for each coordinate, used with
+    /// [`BoundaryHandling::Penalty`]. Default value is `None` (the weights are adapted). See
+    /// [`penalty_weights`][Self::penalty_weights].
+    pub penalty_weights: Option<DVector<f64>>,
+    /// A transform between the natural coordinates of the objective function and the encoded
+    /// coordinates that the algorithm operates in. Default value is `None` (no transform). The
+    /// covariance matrix adapts in the encoded space. See [`CoordinateTransform`].
+    pub transform: Option<CoordinateTransform>,
+    /// The factor that objective function values are divided by before being recorded in the
+    /// function value histories and used in the tolerance-based termination criteria. Default
+    /// value is `1.0`. Must be positive.
+    ///
+    /// This is useful for objective functions with very large or very small values, for which the
+    /// absolute tolerances (`tol_fun`, `tol_fun_hist`) would otherwise need to be adjusted by
+    /// hand. Scaled values are used by the [`TolFun`][crate::TerminationReason::TolFun],
+    /// [`TolFunRel`][crate::TerminationReason::TolFunRel],
+    /// [`TolFunHist`][crate::TerminationReason::TolFunHist], and
+    /// [`TolStagnation`][crate::TerminationReason::TolStagnation] termination criteria. Original
+    /// (unscaled) values are used for ranking individuals, for the
+    /// [`FunTarget`][crate::TerminationReason::FunTarget] termination criterion, and everywhere
+    /// values are reported to the user (best individuals, printing, and plots).
+    pub value_scale: f64,
+    /// The maximum number of generations kept in each per-generation recording. Default value is
+    /// `None` (unbounded). If set, the oldest entries are discarded once the limit is reached
+    /// (ring-buffer policy), so memory usage stays constant regardless of the length of the run.
+    /// Must be at least `1`.
+    ///
+    /// The following recordings are bounded by this limit:
+    ///
+    /// - The convergence curve ([`CMAES::convergence_curve`]) and the rows written by
+    ///   [`CMAES::write_trajectory_csv`], which are unbounded by default
+    /// - The data points of the plot (see [`CMAES::get_plot`], requires the `plotters` feature),
+    ///   which are unbounded by default
+    ///
+    /// The objective function value histories used by the termination criteria are always bounded
+    /// by [`MAX_HISTORY_LENGTH`][crate::MAX_HISTORY_LENGTH] (`20_000`) and are not affected by this
+    /// option. The following state is not affected by this option either and keeps growing for
+    /// the rest of the run:
+    ///
+    /// - The recorded samples (see [`record_samples`][Self::record_samples]), which are disabled
+    ///   by default
+    /// - The evaluation cache (see [`cache_evaluations`][Self::cache_evaluations]), which is
+    ///   disabled by default
+    /// - The population size trajectory ([`CMAES::population_size_trajectory`]), which only grows
+    ///   when the population size changes
+    ///
+    /// All other state (e.g. the distribution and the current population) has a constant size for
+    /// a given dimension and population size.
+    pub max_recorded_generations: Option<usize>,
+    /// Whether to cache the objective function values of evaluated points so that evaluating the
+    /// same point again (e.g. a point injected with [`CMAES::inject`] or migrated between
+    /// [`Islands`][crate::islands::Islands]) does not call the objective function. Default value is
+    /// `false`. Hit statistics can be obtained from [`CMAES::cache_stats`].
+    ///
+    /// Points are keyed by their coordinates (in the space of the objective function) with the
+    /// lowest 12 of the 52 mantissa bits rounded away, so points whose coordinates differ by a
+    /// relative amount of less than about `1e-12` share a cached value. Points that lie close to
+    /// a rounding boundary may still receive different keys. `-0.0` and `0.0` are treated as
+    /// equal, and `NAN` values are never cached.
+    ///
+    /// Every distinct point is stored for the rest of the run, using roughly
+    /// `8 * (dimensions + 4)` bytes per point, so memory usage grows linearly with the number of
+    /// function evaluations. Only [`run`][CMAES::run], [`next`][CMAES::next],
+    /// [`run_parallel`][CMAES::run_parallel], and [`next_parallel`][CMAES::next_parallel] use the
+    /// cache; cached evaluations still count towards [`CMAES::function_evals`] and the
+    /// `max_function_evals` limit. In parallel runs, a point evaluated more than once within the
+    /// same generation may miss the cache.
+    pub cache_evaluations: bool,
+    /// The number of times the objective function is evaluated at each sampled point, with the
+    /// values combined according to [`evaluation_aggregation`][Self::evaluation_aggregation]
+    /// before ranking. Default value is `1`. Must be at least `1`. See
+    /// [`evaluations_per_candidate`][Self::evaluations_per_candidate].
+    pub evaluations_per_candidate: usize,
+    /// How the values of the repeated evaluations of each sampled point are combined. Default
+    /// value is [`Aggregation::Mean`]. Has no effect unless
+    /// [`evaluations_per_candidate`][Self::evaluations_per_candidate] is greater than `1`.
+    pub evaluation_aggregation: Aggregation,
+    /// Whether to evaluate the objective function at the mean of the distribution after each
+    /// generation and keep track of the best mean found (see [`CMAES::best_mean_point`]). Default
+    /// value is `false`.
+    ///
+    /// Near convergence, the mean is usually a better estimate of the optimum than the best
+    /// sampled point (especially on noisy functions), so evaluating it is commonly recommended when
+    /// reporting a final answer. Each evaluation counts towards [`CMAES::function_evals`] and the
+    /// `max_function_evals` limit, so this adds one evaluation per generation (about `1 /
+    /// population_size` more evaluations in total). The mean value does not affect the adaptation
+    /// of the distribution, the overall best individual, or termination, but is used to estimate
+    /// the noise of the objective function (see [`CMAES::estimated_objective_noise`]). Means for
+    /// which the objective function returns `NAN` (or `None` with
+    /// [`run_partial`][CMAES::run_partial]) are ignored.
+    pub evaluate_mean: bool,
+    /// Whether to record the samples of each generation. Default value is `false`. The recording
+    /// can be obtained from [`CMAES::sample_recording`] and replayed in another run using
+    /// [`replay_samples`][Self::replay_samples].
+    ///
+    /// For each generation, the recording contains a [`GenerationSamples`] with the samples drawn
+    /// from the standard normal distribution (the only values that depend on the RNG) and the
+    /// points that were evaluated, both as one `Vec<f64>` of length `dimensions` per individual in
+    /// sampling order. With the `serde` feature, the recording can be serialized (e.g. to JSON) to
+    /// move it between machines.
+    ///
+    /// This is intended for debugging: replaying the recording of a run on another machine
+    /// removes any differences caused by the RNG, so if the evaluated points of the two runs
+    /// still diverge (see [`GenerationSamples::first_divergence`]), the divergence is caused by
+    /// floating point arithmetic, and the first generation in which it occurs shows where.
+    ///
+    /// The recording grows by `16 * population_size * dimensions` bytes per generation (plus a
+    /// small constant overhead per individual) for the rest of the run, so it should only be
+    /// enabled for runs of limited length. It is not bounded by
+    /// [`max_recorded_generations`][Self::max_recorded_generations], because replaying a
+    /// recording requires the samples of every generation from the start of the run.
+    pub record_samples: bool,
+    /// Recorded samples (see [`record_samples`][Self::record_samples]) to use in place of the RNG,
+    /// one entry per generation starting from the first. Default value is empty (the RNG is used).
+    ///
+    /// Only the standard normal samples are used; the evaluated points are recomputed from them.
+    /// Once all entries are used, or as soon as an entry does not match the current population
+    /// size and number of dimensions (in which case the remaining entries are discarded and counted
+    /// by [`CMAES::discarded_replay_samples`]), samples are drawn from the RNG as usual. Injected
+    /// points (see [`CMAES::inject`]) still replace sampled points and must be injected again to
+    /// be reproduced.
+    ///
+    /// The displacements of the mean by [`auto_kick`][Self::auto_kick] are drawn from the RNG and
+    /// are not recorded, so a replayed run diverges from the recording at the first kick.
+    pub replay_samples: Vec<GenerationSamples>,
+    /// How often (every how many generations) and where to write the covariance matrix of the
+    /// distribution to a file, as `(every, path_template)`. Default value is `None` (disabled).
+    /// See [`record_covariance`][Self::record_covariance].
+    pub record_covariance: Option<(usize, String)>,
     /// The value to use for the
     /// [`TerminationReason::MaxFunctionEvals`][crate::TerminationReason::MaxFunctionEvals]
     /// termination criterion. Default value is `None`.
@@ -79,12 +337,42 @@ pub struct CMAESOptions {
     /// termination criterion. Default value is `None`.
     pub max_time: Option<Duration>,
     /// The value to use for the
+    /// [`TerminationReason::ImprovementTimeout`][crate::TerminationReason::ImprovementTimeout]
+    /// termination criterion. Default value is `None`.
+    pub improvement_timeout: Option<Duration>,
+    /// The value to use for the
     /// [`TerminationReason::FunTarget`][crate::TerminationReason::FunTarget] termination criterion.
     /// Default value is `None`.
     pub fun_target: Option<f64>,
+    /// How the [`TerminationReason::FunTarget`][crate::TerminationReason::FunTarget] termination
+    /// criterion is handled. Default value is [`TargetMode::Terminate`].
+    pub fun_target_mode: TargetMode,
+    /// The target values for which to record the number of function evaluations needed to reach
+    /// them (see [`target_values`][Self::target_values]). Default value is empty.
+    pub target_values: Vec<f64>,
     /// The value to use for the [`TerminationReason::TolFun`][crate::TerminationReason::TolFun]
     /// termination criterion. Default value is `1e-12`.
     pub tol_fun: f64,
+    /// Whether to raise the threshold of the
+    /// [`TerminationReason::TolFun`][crate::TerminationReason::TolFun] termination criterion
+    /// according to the improvements achieved earlier in the run. Default value is `false`.
+    ///
+    /// After each generation, the improvement of the overall best function value over the latest
+    /// `10 + 30 * N / lambda` generations (the window used by `TolFun`) is measured, and the
+    /// largest such improvement of the whole run is taken as the peak improvement. The effective
+    /// threshold is then `max(tol_fun, 1e-10 * peak_improvement)`, so the run terminates once the
+    /// improvement over the latest window has become negligible compared to the fastest
+    /// improvement achieved before, without needing to know the scale of the objective function
+    /// in advance. Because the overall best value only ever improves, generation-to-generation
+    /// scatter of a noisy objective function does not count as improvement. The measured peak is
+    /// reported as part of the threshold in
+    /// [`TerminationData::details`][crate::TerminationData::details].
+    ///
+    /// Unlike [`tol_fun_rel`][Self::tol_fun_rel], which compares against the total improvement of
+    /// the median function value since the first generation (and is therefore dominated by how bad
+    /// the initial mean was), this compares against the largest improvement of the overall best
+    /// function value over a single window.
+    pub adaptive_tol_fun: bool,
     /// The value to use for the
     /// [`TerminationReason::TolFunRel`][crate::TerminationReason::TolFunRel] termination criterion.
     /// Default value is `0` (disabled).
@@ -94,9 +382,49 @@ pub struct CMAESOptions {
     /// criterion. Default value is `1e-12`.
     pub tol_fun_hist: f64,
     /// The value to use for the [`TerminationReason::TolX`][crate::TerminationReason::TolX]
-    /// termination criterion. Default value is `1e-12 * initial_step_size`, used if this field is
-    /// `None`.
+    /// termination criterion. Default value is `relative_tol_x * initial_step_size`, used if this
+    /// field is `None`.
     pub tol_x: Option<f64>,
+    /// The default value of `tol_x` relative to the initial step size, used if `tol_x` is `None`.
+    /// It is multiplied by the initial step size when the [`CMAES`][crate::CMAES] is built, so it
+    /// also applies to a step size taken from the bounds. Default value is `1e-12`.
+    pub relative_tol_x: f64,
+    /// The value to use for the
+    /// [`TerminationReason::TargetSigma`][crate::TerminationReason::TargetSigma] termination
+    /// criterion. Default value is `None`.
+    pub target_sigma: Option<f64>,
+    /// The value to use for the
+    /// [`TerminationReason::TolDiversity`][crate::TerminationReason::TolDiversity] termination
+    /// criterion. Default value is `None` (disabled).
+    pub tol_diversity: Option<f64>,
+    /// The number of consecutive generations used by the
+    /// [`TerminationReason::TolDiversity`][crate::TerminationReason::TolDiversity] termination
+    /// criterion. Default value is `10`.
+    pub tol_diversity_generations: usize,
+    /// The number of consecutive generations used by the
+    /// [`TerminationReason::FlatFunction`][crate::TerminationReason::FlatFunction] termination
+    /// criterion. Default value is `None` (disabled). Must be at least `1`.
+    pub flat_function_generations: Option<usize>,
+    /// Whether to check that the objective function does not ignore its input (see
+    /// [`validate_objective`][Self::validate_objective]). Default value is `false`.
+    pub validate_objective: bool,
+    /// How invalid objective function values are handled (see
+    /// [`invalid_value_policy`][Self::invalid_value_policy]). Default value is
+    /// [`InvalidPolicy::Terminate`].
+    pub invalid_value_policy: InvalidPolicy,
+    /// The lower and upper corners of the region used by the
+    /// [`TerminationReason::MeanOutOfRegion`][crate::TerminationReason::MeanOutOfRegion]
+    /// termination criterion. Default value is `None` (disabled). Both must have the same length
+    /// as the initial mean and satisfy `lower[i] <= upper[i]`.
+    pub mean_region: Option<(DVector<f64>, DVector<f64>)>,
+    /// The value to use for the
+    /// [`TerminationReason::TolImprovementRate`][crate::TerminationReason::TolImprovementRate]
+    /// termination criterion. Default value is `None` (disabled).
+    pub tol_improvement_rate: Option<f64>,
+    /// The value to use for the
+    /// [`TerminationReason::PrematureConvergence`][crate::TerminationReason::PrematureConvergence]
+    /// termination criterion. Default value is `None` (disabled).
+    pub max_sigma_decay_ratio: Option<f64>,
     /// The minimum number of generations over which to measure the
     /// [`TerminationReason::TolStagnation`][crate::TerminationReason::TolStagnation] termination
     /// criterion. Default value is `100 + 100 * dimensions^1.5 / lambda`, used if this field is
@@ -109,11 +437,17 @@ pub struct CMAESOptions {
     /// [`TerminationReason::TolConditionCov`][crate::TerminationReason::TolConditionCov]
     /// termination criterion. Default value is `1e+14`.
     pub tol_condition_cov: f64,
+    /// The number of generations that must be completed before any of the convergence-based
+    /// termination criteria can terminate the algorithm (see
+    /// [`min_generations`][Self::min_generations]). Default value is `0` (no minimum).
+    pub min_generations: usize,
     /// The seed for the RNG used in the algorithm. Can be set manually for deterministic runs. By
-    /// default a random seed is used if this field is `None`.
+    /// default a random seed is used if this field is `None`. Either way, the seed that was used
+    /// can be read back with [`CMAES::seed`] to reproduce the run.
     ///
-    /// If used in conjunction with the [`parallel_update`][Self::parallel_update] option, the
-    /// determinism guarantee is reduced due to floating point errors.
+    /// Runs are also reproducible with the [`parallel_update`][Self::parallel_update] option and
+    /// with parallel evaluation (e.g. [`CMAES::run_parallel`]), which produce bit-identical
+    /// results to the sequential path.
     pub seed: Option<u64>,
     /// Options for the data plot. Default value is `None`, meaning no plot will be generated. See
     /// [`Plot`][crate::plotting::Plot].
@@ -123,6 +457,19 @@ pub struct CMAESOptions {
     /// [`CMAES::print_info`] call. Default value is `None`, meaning no info will be
     /// automatically printed.
     pub print_gap_evals: Option<usize>,
+    /// Whether to show a single status line with the generation, the number of function
+    /// evaluations, the overall best function value, and the step size, which is updated in place
+    /// as the run progresses. Default value is `false`. See
+    /// [`live_status`][Self::live_status].
+    pub live_status: bool,
+    /// Arbitrary key-value metadata attached to the run, such as an experiment name or the
+    /// problem instance. It does not affect the algorithm and is only passed through to
+    /// [`CMAES::metadata`] and [`TerminationData::metadata`][crate::TerminationData::metadata].
+    /// Default value is empty. See [`metadata`][Self::metadata].
+    pub metadata: HashMap<String, String>,
+    /// A callback invoked whenever the overall best individual improves. Default value is `None`.
+    /// See [`on_improvement`][Self::on_improvement].
+    pub on_improvement: Option<ImprovementCallback>,
 }
 
 impl CMAESOptions {
@@ -137,29 +484,72 @@ impl CMAESOptions {
     pub fn new<V: Into<DVector<f64>>>(initial_mean: V, initial_step_size: f64) -> Self {
         let initial_mean = initial_mean.into();
         let dimensions = initial_mean.len();
+        let population_size = 4 + (3.0 * (dimensions as f64).ln()).floor() as usize;
         Self {
             mode: Mode::Minimize,
             initial_mean,
             initial_step_size,
-            population_size: 4 + (3.0 * (dimensions as f64).ln()).floor() as usize,
-            weights: Weights::Negative,
+            initial_step_size_from_bounds: false,
+            initial_covariance_scale: 1.0,
+            random_initial_mean: None,
+            population_size,
+            weights: Weights::recommended(population_size),
+            recombination_weighting: RecombinationWeighting::default(),
+            fitness_transform: None,
+            adaptive_population: None,
+            auto_population: false,
+            auto_kick: None,
             parallel_update: false,
             cm: 1.0,
+            cm_schedule: None,
+            max_sigma_change: None,
+            min_mu_eff: None,
+            min_eigenvalue_ratio: None,
+            bounds: None,
+            penalty_weights: None,
+            transform: None,
+            value_scale: 1.0,
+            max_recorded_generations: None,
+            cache_evaluations: false,
+            evaluations_per_candidate: 1,
+            evaluation_aggregation: Aggregation::default(),
+            evaluate_mean: false,
+            record_samples: false,
+            replay_samples: Vec::new(),
+            record_covariance: None,
             max_function_evals: None,
             max_generations: None,
             max_time: None,
+            improvement_timeout: None,
             fun_target: None,
+            fun_target_mode: TargetMode::default(),
+            target_values: Vec::new(),
             tol_fun: 1e-12,
+            adaptive_tol_fun: false,
             tol_fun_rel: 0.0,
             tol_fun_hist: 1e-12,
             tol_x: None,
+            relative_tol_x: 1e-12,
+            target_sigma: None,
+            tol_diversity: None,
+            tol_diversity_generations: 10,
+            flat_function_generations: None,
+            validate_objective: false,
+            invalid_value_policy: InvalidPolicy::default(),
+            mean_region: None,
+            tol_improvement_rate: None,
+            max_sigma_decay_ratio: None,
             tol_stagnation: None,
             tol_x_up: 1e8,
             tol_condition_cov: 1e14,
+            min_generations: 0,
             seed: None,
             #[cfg(feature = "plotters")]
             plot_options: None,
             print_gap_evals: None,
+            live_status: false,
+            metadata: HashMap::new(),
+            on_improvement: None,
         }
     }
 
@@ -175,9 +565,59 @@ impl CMAESOptions {
         self
     }
 
-    /// Changes the initial step size. Must be positive.
+    /// Draws the initial mean uniformly at random from the box between `lower` and `upper` when
+    /// the [`CMAES`] is built, in place of the fixed `initial_mean` (see
+    /// [`random_initial_mean`][Self::random_initial_mean]). `lower` and `upper` must have the same
+    /// length as the initial mean, be finite, and satisfy `lower[i] <= upper[i]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// // The values of the initial mean are ignored, but it still sets the dimension
+    /// let options =
+    ///     CMAESOptions::new(vec![0.0; 3], 1.0).random_initial_mean(vec![-5.0; 3], vec![5.0; 3]);
+    ///
+    /// let first = options.clone().seed(1).build(function).unwrap();
+    /// let second = options.clone().seed(2).build(function).unwrap();
+    ///
+    /// assert!(first.mean().iter().all(|x| (-5.0..=5.0).contains(x)));
+    /// assert_ne!(first.mean(), second.mean());
+    /// // The same seed gives the same initial mean
+    /// assert_eq!(first.mean(), options.seed(1).build(function).unwrap().mean());
+    /// ```
+    pub fn random_initial_mean<V: Into<DVector<f64>>>(mut self, lower: V, upper: V) -> Self {
+        self.random_initial_mean = Some((lower.into(), upper.into()));
+        self
+    }
+
+    /// Changes the initial step size. Must be positive. Overrides an earlier call to
+    /// [`initial_step_size_from_bounds`][Self::initial_step_size_from_bounds].
     pub fn initial_step_size(mut self, initial_step_size: f64) -> Self {
         self.initial_step_size = initial_step_size;
+        self.initial_step_size_from_bounds = false;
+        self
+    }
+
+    /// Changes the scale of the initial covariance matrix from the default value (see
+    /// [`initial_covariance_scale`][Self::initial_covariance_scale]). Must be positive.
+    pub fn initial_covariance_scale(mut self, initial_covariance_scale: f64) -> Self {
+        self.initial_covariance_scale = initial_covariance_scale;
+        self
+    }
+
+    /// Changes the initial step size to the value suggested by
+    /// [`Bounds::suggested_step_size`] (`0.5` with [`BoundaryHandling::Transform`], or a quarter of
+    /// the smallest box width with [`BoundaryHandling::Penalty`]), for when there is no better
+    /// first guess. The step size is resolved when the [`CMAES`][crate::CMAES] is built, so this
+    /// method may be called before or after [`bounds`][Self::bounds], but building fails with
+    /// [`InvalidOptionsError::InitialStepSize`] if no bounds are set or no coordinate has both a
+    /// finite lower and upper bound. The chosen value can be read back from
+    /// [`Parameters::initial_sigma`][crate::parameters::Parameters::initial_sigma].
+    pub fn initial_step_size_from_bounds(mut self) -> Self {
+        self.initial_step_size_from_bounds = true;
         self
     }
 
@@ -194,6 +634,93 @@ impl CMAESOptions {
         self
     }
 
+    /// Sets the smallest allowed variance-effective selection mass of the weights (see
+    /// [`min_mu_eff`][Self::min_mu_eff]). Must be at least `1.0`.
+    pub fn min_mu_eff(mut self, min_mu_eff: f64) -> Self {
+        self.min_mu_eff = Some(min_mu_eff);
+        self
+    }
+
+    /// Enables growing the population size within the run when progress stalls (see
+    /// [`AdaptivePopulation`]). Also sets the population size to `adaptive_population.min`. A
+    /// population size that is changed afterwards must lie within `[min, max]`.
+    pub fn adaptive_population(mut self, adaptive_population: AdaptivePopulation) -> Self {
+        self.population_size = adaptive_population.min;
+        self.adaptive_population = Some(adaptive_population);
+        self
+    }
+
+    /// Enables or disables growing and shrinking the population size within the run according
+    /// to the success rate of the mean update (see [`auto_population`][Self::auto_population]).
+    /// This is an alternative to restart strategies with increasing population sizes (such as
+    /// [`IPOP`][crate::restart::IPOP]) that keeps everything learned about the function. Cannot be
+    /// combined with [`adaptive_population`][Self::adaptive_population].
+    ///
+    /// **Experimental:** the controller and its constants may change in any release.
+    ///
+    /// The mean update of a generation counts as successful if the median function value of the
+    /// generation is better than that of the previous generation. The success rate is the
+    /// exponentially weighted average of the successes, covering roughly the last 5 generations.
+    /// While progress is steady, the median improves in most generations, while on noisy or
+    /// multimodal functions, a population that is too small makes it stall.
+    ///
+    /// - If the success rate drops below `0.2`, the population size is doubled (up to 16 times the
+    ///   initial population size), which smooths out noise and local structure.
+    /// - If the success rate rises above `0.6`, the population size is halved (down to the initial
+    ///   population size), which makes each generation cheaper while progress is fast.
+    ///
+    /// After each change, the population size is kept for at least 10 generations and the
+    /// success rate starts over from `0.4`. Like with [`AdaptivePopulation`], the weights, `mu`,
+    /// `mu_eff`, and all learning rates are recalculated for the new population size, while the
+    /// mean, step size, covariance matrix, and evolution paths are kept. The population size
+    /// after each change can be obtained from [`CMAES::population_size_trajectory`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .auto_population(true)
+    ///     .max_generations(200)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let _ = cmaes_state.run();
+    /// for (generation, population_size) in cmaes_state.population_size_trajectory() {
+    ///     println!("generation {}: population size {}", generation, population_size);
+    /// }
+    /// ```
+    pub fn auto_population(mut self, auto_population: bool) -> Self {
+        self.auto_population = auto_population;
+        self
+    }
+
+    /// Enables displacing the mean and increasing the step size when progress stalls (see
+    /// [`AutoKick`]).
+    pub fn auto_kick(mut self, auto_kick: AutoKick) -> Self {
+        self.auto_kick = Some(auto_kick);
+        self
+    }
+
+    /// Changes how the weights of the selected individuals are determined from the default value.
+    /// See [`RecombinationWeighting`] for possible settings.
+    pub fn recombination_weighting(
+        mut self,
+        recombination_weighting: RecombinationWeighting,
+    ) -> Self {
+        self.recombination_weighting = recombination_weighting;
+        self
+    }
+
+    /// Sets a function to apply to the raw objective function values before they are used for the
+    /// value-based recombination weights (see [`FitnessTransform`]).
+    pub fn fitness_transform(mut self, fitness_transform: FitnessTransform) -> Self {
+        self.fitness_transform = Some(fitness_transform);
+        self
+    }
+
     /// Sets whether to perform the state update in parallel.
     pub fn parallel_update(mut self, parallel_update: bool) -> Self {
         self.parallel_update = parallel_update;
@@ -207,6 +734,230 @@ impl CMAESOptions {
         self
     }
 
+    /// Sets a schedule for the learning rate for the mean, overriding [`cm`][Self::cm]. The
+    /// function receives the index of the generation being updated (starting from `0`) and returns
+    /// its learning rate. Annealing the learning rate downwards can stabilize the final
+    /// convergence on noisy functions.
+    ///
+    /// The returned values are clamped to `(0, 1]` (with non-positive values becoming the smallest
+    /// positive `f64`), and `NAN` leaves the learning rate of the previous generation unchanged.
+    /// The current value is available from [`Parameters::cm`][crate::parameters::Parameters::cm].
+    ///
+    /// The default learning rates and damping of the covariance matrix and step size adaptation
+    /// are derived assuming a fixed `cm = 1`. The evolution paths are computed from the search
+    /// direction before `cm` is applied, so with a small `cm` the mean moves less than the paths
+    /// suggest and consecutive steps stay correlated, which tends to keep the step size larger
+    /// than it would be otherwise. Decreasing `cm` gradually over many generations works best.
+    ///
+    /// The schedule is shared between clones of the options and of the [`CMAES`] (see
+    /// [`CMAES::clone_with`]).
+    pub fn cm_schedule<C>(mut self, schedule: C) -> Self
+    where
+        C: FnMut(usize) -> f64 + Send + 'static,
+    {
+        self.cm_schedule = Some(CmSchedule::new(schedule));
+        self
+    }
+
+    /// Limits the factor by which the step size may change in a single generation (see
+    /// [`max_sigma_change`][Self::max_sigma_change]). Must be at least `1.0`.
+    pub fn max_sigma_change(mut self, max_sigma_change: f64) -> Self {
+        self.max_sigma_change = Some(max_sigma_change);
+        self
+    }
+
+    /// Floors the eigenvalues of the covariance matrix at a fraction of the largest eigenvalue
+    /// (see [`min_eigenvalue_ratio`][Self::min_eigenvalue_ratio]). Must be in `(0, 1]`.
+    pub fn min_eigenvalue_ratio(mut self, min_eigenvalue_ratio: f64) -> Self {
+        self.min_eigenvalue_ratio = Some(min_eigenvalue_ratio);
+        self
+    }
+
+    /// Caps the condition number of the covariance matrix by flooring its eigenvalues instead of
+    /// terminating when it gets too large, or removes the cap if `None` is passed. Must be at
+    /// least `1.0` and finite.
+    ///
+    /// This is the same as setting [`min_eigenvalue_ratio`][Self::min_eigenvalue_ratio] to
+    /// `1 / cap_condition_number`, expressed in terms of the condition number, and has the same
+    /// bias: the floored directions are sampled with a larger variance than the algorithm has
+    /// learned, so convergence slows down on problems that are more ill-conditioned than the cap.
+    ///
+    /// As the condition number can no longer exceed the cap,
+    /// [`TerminationReason::TolConditionCov`][crate::TerminationReason::TolConditionCov] is
+    /// effectively disabled while the cap is below
+    /// [`tol_condition_cov`][Self::tol_condition_cov] (`1e14` by default). A cap above it has no
+    /// effect, as the run terminates before the cap is reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::CMAESOptions;
+    ///
+    /// let options = CMAESOptions::new(vec![1.0; 4], 1.0).cap_condition_number(Some(1e10));
+    /// assert_eq!(Some(1e-10), options.min_eigenvalue_ratio);
+    /// ```
+    pub fn cap_condition_number(mut self, cap_condition_number: Option<f64>) -> Self {
+        self.min_eigenvalue_ratio = cap_condition_number.map(|cap| 1.0 / cap);
+        self
+    }
+
+    /// Sets box bounds on the search space, handled using the given method (see [`Bounds`] and
+    /// [`BoundaryHandling`]). `lower` and `upper` must have the same length as the initial mean
+    /// and satisfy `lower[i] < upper[i]`.
+    pub fn bounds<V: Into<DVector<f64>>>(
+        mut self,
+        lower: V,
+        upper: V,
+        handling: BoundaryHandling,
+    ) -> Self {
+        self.bounds = Some(Bounds::new(lower, upper, handling));
+        self
+    }
+
+    /// Sets fixed weights of the boundary penalty for each coordinate in place of the default
+    /// adaptive weights (see [`BoundaryHandling::Penalty`]). The penalty of a point sampled outside
+    /// of the bounds is `sum(weights[i] * d[i]^2)` for the distance `d[i]` outside of the bounds
+    /// in coordinate `i`, so the weights are measured in units of the function value per squared
+    /// unit of each coordinate. Useful if the sensitivity of the function differs between
+    /// coordinates in a known way. Only used with [`BoundaryHandling::Penalty`].
+    ///
+    /// Must have the same length as the initial mean, and each weight must be non-negative and
+    /// finite.
+    pub fn penalty_weights<V: Into<DVector<f64>>>(mut self, weights: V) -> Self {
+        self.penalty_weights = Some(weights.into());
+        self
+    }
+
+    /// Sets a transform between the natural coordinates of the objective function and the encoded
+    /// coordinates that the algorithm operates in (see [`CoordinateTransform`]). Points are
+    /// decoded before being evaluated, and the covariance matrix adapts in the encoded space.
+    pub fn transform<E, D>(mut self, encode: E, decode: D) -> Self
+    where
+        E: Fn(&DVector<f64>) -> DVector<f64> + Send + Sync + 'static,
+        D: Fn(&DVector<f64>) -> DVector<f64> + Send + Sync + 'static,
+    {
+        self.transform = Some(CoordinateTransform::new(encode, decode));
+        self
+    }
+
+    /// Like [`transform`][Self::transform], but takes an existing [`CoordinateTransform`] (e.g. one
+    /// created with [`CoordinateTransform::affine`]).
+    pub fn coordinate_transform(mut self, transform: CoordinateTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
+    /// Changes the factor that objective function values are divided by in the histories and
+    /// tolerance checks from the default value (see [`value_scale`][Self::value_scale]). Must be
+    /// positive.
+    pub fn value_scale(mut self, value_scale: f64) -> Self {
+        self.value_scale = value_scale;
+        self
+    }
+
+    /// Limits the number of generations kept in each per-generation recording (see
+    /// [`max_recorded_generations`][Self::max_recorded_generations]). Must be at least `1`.
+    pub fn max_recorded_generations(mut self, max_recorded_generations: usize) -> Self {
+        self.max_recorded_generations = Some(max_recorded_generations);
+        self
+    }
+
+    /// Sets whether to cache the objective function values of evaluated points (see
+    /// [`cache_evaluations`][Self::cache_evaluations]).
+    pub fn cache_evaluations(mut self, cache_evaluations: bool) -> Self {
+        self.cache_evaluations = cache_evaluations;
+        self
+    }
+
+    /// Evaluates the objective function `evaluations_per_candidate` times at each sampled point
+    /// and ranks the points by the combined values (see
+    /// [`evaluation_aggregation`][Self::evaluation_aggregation]), a simple way of reducing the
+    /// noise of noisy objective functions. Must be at least `1`.
+    ///
+    /// Every evaluation counts towards [`CMAES::function_evals`] and the `max_function_evals`
+    /// limit, so each generation uses `evaluations_per_candidate * population_size` evaluations.
+    /// Averaging `k` values reduces the standard deviation of the noise by a factor of `sqrt(k)`,
+    /// so this is most useful when the noise is large compared to the differences between the
+    /// function values of a generation. Only sampled points are evaluated repeatedly (not the
+    /// mean of [`evaluate_mean`][Self::evaluate_mean]). If
+    /// [`cache_evaluations`][Self::cache_evaluations] is enabled, the combined value is cached,
+    /// so a cached point is not evaluated again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{Aggregation, CMAESOptions, DVector};
+    ///
+    /// let function = |x: &DVector<f64>| x.magnitude() + 0.01 * rand::random::<f64>();
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .evaluations_per_candidate(5)
+    ///     .evaluation_aggregation(Aggregation::Median)
+    ///     .max_generations(10)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let _ = cmaes_state.run();
+    /// assert_eq!(10 * 5 * cmaes_state.population_size(), cmaes_state.function_evals());
+    /// ```
+    pub fn evaluations_per_candidate(mut self, evaluations_per_candidate: usize) -> Self {
+        self.evaluations_per_candidate = evaluations_per_candidate;
+        self
+    }
+
+    /// Changes how the values of the repeated evaluations of each sampled point are combined
+    /// from the default value (see [`Aggregation`] and
+    /// [`evaluations_per_candidate`][Self::evaluations_per_candidate]).
+    pub fn evaluation_aggregation(mut self, evaluation_aggregation: Aggregation) -> Self {
+        self.evaluation_aggregation = evaluation_aggregation;
+        self
+    }
+
+    /// Sets whether to evaluate the mean of the distribution after each generation (see
+    /// [`evaluate_mean`][Self::evaluate_mean]).
+    pub fn evaluate_mean(mut self, evaluate_mean: bool) -> Self {
+        self.evaluate_mean = evaluate_mean;
+        self
+    }
+
+    /// Sets whether to record the samples of each generation (see
+    /// [`record_samples`][Self::record_samples]).
+    pub fn record_samples(mut self, record_samples: bool) -> Self {
+        self.record_samples = record_samples;
+        self
+    }
+
+    /// Sets recorded samples to use in place of the RNG (see
+    /// [`replay_samples`][Self::replay_samples]).
+    pub fn replay_samples(mut self, replay_samples: Vec<GenerationSamples>) -> Self {
+        self.replay_samples = replay_samples;
+        self
+    }
+
+    /// Writes the covariance matrix of the distribution to a file after every `every`
+    /// generations (i.e. after generations `every`, `2 * every`, ...), for analyzing how the shape
+    /// of the distribution evolves after the run. `every` must be at least `1`.
+    ///
+    /// The file of each generation is named by replacing `{generation}` in `path_template` with
+    /// the number of the generation (e.g. `"cov/gen_{generation}.csv"` produces
+    /// `cov/gen_10.csv`), so the template must contain `{generation}`. The directory must already
+    /// exist, and existing files are overwritten.
+    ///
+    /// Each file contains the full covariance matrix of the sampling distribution, `sigma^2 * C`
+    /// (where `C` is returned by [`CMAES::covariance_matrix`] and `sigma` by
+    /// [`CMAES::sigma`]), in the internal search space (see
+    /// [`CMAESOptions::transform`][Self::transform]). It is written as plain text with one line
+    /// per row and the values of each row separated by commas, formatted in scientific notation
+    /// with enough digits to be read back exactly. This can be loaded with
+    /// `numpy.loadtxt(path, delimiter=",")` or any CSV reader.
+    ///
+    /// Writing a matrix takes `O(dimensions^2)` time and space, so for large problems `every`
+    /// should be chosen accordingly. Errors while writing a file do not stop the run; the latest
+    /// one can be obtained from [`CMAES::covariance_recording_error`].
+    pub fn record_covariance<S: Into<String>>(mut self, every: usize, path_template: S) -> Self {
+        self.record_covariance = Some((every, path_template.into()));
+        self
+    }
+
     /// Changes the value for the `MaxFunctionEvals` termination criterion from the default value
     /// (see [`TerminationReason::MaxFunctionEvals`][crate::TerminationReason::MaxFunctionEvals]).
     pub fn max_function_evals(mut self, max_function_evals: usize) -> Self {
@@ -228,6 +979,19 @@ impl CMAESOptions {
         self
     }
 
+    /// Enables the `ImprovementTimeout` termination criterion, which terminates the algorithm if
+    /// the overall best function value has not improved within the given wall-clock time (see
+    /// [`TerminationReason::ImprovementTimeout`][crate::TerminationReason::ImprovementTimeout]).
+    ///
+    /// Both this and `max_time` are checked once per generation and are independent of each
+    /// other: `max_time` bounds the total running time, while this bounds the time spent without
+    /// progress. An improvement timeout that is at least `max_time` never has an effect, because
+    /// `MaxTime` is always met first (if both are met in the same generation, both are reported).
+    pub fn improvement_timeout(mut self, improvement_timeout: Duration) -> Self {
+        self.improvement_timeout = Some(improvement_timeout);
+        self
+    }
+
     /// Changes the value for the `FunTarget` termination criterion from the default value (see
     /// [`TerminationReason::FunTarget`][crate::TerminationReason::FunTarget]).
     pub fn fun_target(mut self, fun_target: f64) -> Self {
@@ -235,6 +999,67 @@ impl CMAESOptions {
         self
     }
 
+    /// Changes how the `FunTarget` termination criterion is handled from the default value (see
+    /// [`TargetMode`]).
+    pub fn fun_target_mode(mut self, fun_target_mode: TargetMode) -> Self {
+        self.fun_target_mode = fun_target_mode;
+        self
+    }
+
+    /// Sets target values for which the number of function evaluations needed to reach each of
+    /// them is recorded, as used in benchmarking methodologies such as COCO/BBOB (where the
+    /// targets are the optimal value plus a set of target precisions). The results are available
+    /// from [`CMAES::target_hits`]. The targets do not affect the run in any way.
+    ///
+    /// A target is reached by the first evaluated point whose value is at least as good as the
+    /// target, and the recorded count is the number of that evaluation over the whole run
+    /// (counted from `1`, including evaluations of the mean if
+    /// [`evaluate_mean`][Self::evaluate_mean] is enabled), not only the number at the end of its
+    /// generation. Targets must not be `NAN`.
+    pub fn target_values<V: Into<Vec<f64>>>(mut self, target_values: V) -> Self {
+        self.target_values = target_values.into();
+        self
+    }
+
+    /// Sets all termination tolerances and the generation limit at once from a
+    /// [`TerminationPreset`] (see its documentation for the values used). `fun_target`,
+    /// `max_function_evals`, `max_time`, and the criteria that are disabled by default are not
+    /// changed.
+    ///
+    /// The generation limits depend on the problem dimension and the population size, so this
+    /// method should be called after [`population_size`][Self::population_size] and
+    /// [`adaptive_population`][Self::adaptive_population]. `tol_x` is set relative to the initial
+    /// step size (see [`relative_tol_x`][Self::relative_tol_x]), which is only applied when the
+    /// [`CMAES`][crate::CMAES] is built. Options set by individual methods after this one override
+    /// the values of the preset, while those set before it are overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, TerminationPreset};
+    ///
+    /// let options = CMAESOptions::new(vec![1.0; 10], 1.0)
+    ///     .termination_preset(TerminationPreset::Fast)
+    ///     .max_generations(50);
+    ///
+    /// assert_eq!(Some(50), options.max_generations);
+    /// assert_eq!(1e-8, options.tol_fun);
+    /// ```
+    pub fn termination_preset(mut self, preset: TerminationPreset) -> Self {
+        let dim = self.initial_mean.len();
+        let (tol_fun, tol_fun_hist, tol_x, tol_x_up, tol_condition_cov) = preset.tolerances();
+
+        self.tol_fun = tol_fun;
+        self.tol_fun_hist = tol_fun_hist;
+        self.tol_x = None;
+        self.relative_tol_x = tol_x;
+        self.tol_x_up = tol_x_up;
+        self.tol_condition_cov = tol_condition_cov;
+        self.tol_stagnation = Some(preset.tol_stagnation(dim, self.population_size));
+        self.max_generations = Some(preset.max_generations(dim, self.population_size).max(1));
+        self
+    }
+
     /// Changes the value for the `TolFun` termination criterion from the default value (see
     /// [`TerminationReason::TolFun`][crate::TerminationReason::TolFun]).
     pub fn tol_fun(mut self, tol_fun: f64) -> Self {
@@ -242,6 +1067,13 @@ impl CMAESOptions {
         self
     }
 
+    /// Sets whether to raise the threshold of the `TolFun` termination criterion according to
+    /// earlier improvements (see [`adaptive_tol_fun`][Self::adaptive_tol_fun]).
+    pub fn adaptive_tol_fun(mut self, adaptive_tol_fun: bool) -> Self {
+        self.adaptive_tol_fun = adaptive_tol_fun;
+        self
+    }
+
     /// Changes the value for the `TolFunRel` termination criterion from the default value (see
     /// [`TerminationReason::TolFunRel`][crate::TerminationReason::TolFunRel]).
     pub fn tol_fun_rel(mut self, tol_fun_rel: f64) -> Self {
@@ -263,6 +1095,112 @@ impl CMAESOptions {
         self
     }
 
+    /// Changes the value for the `TargetSigma` termination criterion from the default value (see
+    /// [`TerminationReason::TargetSigma`][crate::TerminationReason::TargetSigma]).
+    pub fn target_sigma(mut self, target_sigma: f64) -> Self {
+        self.target_sigma = Some(target_sigma);
+        self
+    }
+
+    /// Enables the `TolDiversity` termination criterion with the given value (see
+    /// [`TerminationReason::TolDiversity`][crate::TerminationReason::TolDiversity]).
+    pub fn tol_diversity(mut self, tol_diversity: f64) -> Self {
+        self.tol_diversity = Some(tol_diversity);
+        self
+    }
+
+    /// Changes the number of consecutive generations used by the `TolDiversity` termination
+    /// criterion from the default value (see
+    /// [`TerminationReason::TolDiversity`][crate::TerminationReason::TolDiversity]). Must be at
+    /// least `1`.
+    pub fn tol_diversity_generations(mut self, tol_diversity_generations: usize) -> Self {
+        self.tol_diversity_generations = tol_diversity_generations;
+        self
+    }
+
+    /// Enables the `FlatFunction` termination criterion with the given number of consecutive
+    /// generations (see
+    /// [`TerminationReason::FlatFunction`][crate::TerminationReason::FlatFunction]). Must be at
+    /// least `1`.
+    pub fn flat_function_generations(mut self, flat_function_generations: usize) -> Self {
+        self.flat_function_generations = Some(flat_function_generations);
+        self
+    }
+
+    /// Sets whether to check that the objective function does not ignore its input, which is a
+    /// common bug. If enabled and every individual of the first generation evaluated by the
+    /// [`CMAES`] (the `lambda` points sampled around the initial mean, or around the mean of the
+    /// state it was created from) has the same function value, the algorithm terminates
+    /// immediately with
+    /// [`TerminationReason::ConstantFunction`][crate::TerminationReason::ConstantFunction].
+    ///
+    /// The check is performed on the first generation rather than in [`build`][Self::build]
+    /// because the way the function is evaluated (e.g. [`CMAES::next`] or
+    /// [`CMAES::next_parallel`]) is only known once the run starts. It therefore costs no
+    /// evaluations beyond the first generation, but wastes that generation if the run would
+    /// otherwise have been fine. Disabled by default, because functions with plateaus or a very
+    /// small initial step size can legitimately produce a flat first generation.
+    pub fn validate_objective(mut self, validate_objective: bool) -> Self {
+        self.validate_objective = validate_objective;
+        self
+    }
+
+    /// Changes how invalid (`NAN`) objective function values are handled from the default value
+    /// (see [`InvalidPolicy`]). By default, the algorithm terminates with
+    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue]
+    /// as soon as an invalid value is returned, which may be undesirable for functions that
+    /// occasionally fail to evaluate a point. The number of skipped evaluations can be obtained
+    /// from [`CMAES::invalid_evals`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, InvalidPolicy, TerminationReason};
+    ///
+    /// // The function fails to evaluate some points
+    /// let function = |x: &DVector<f64>| if x[0] > 1.0 { f64::NAN } else { x.magnitude() };
+    /// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .invalid_value_policy(InvalidPolicy::SkipIndividual)
+    ///     .max_generations(100)
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let result = cmaes_state.run();
+    ///
+    /// assert!(!result.reasons.contains(&TerminationReason::InvalidFunctionValue));
+    /// assert!(cmaes_state.invalid_evals() > 0);
+    /// ```
+    pub fn invalid_value_policy(mut self, invalid_value_policy: InvalidPolicy) -> Self {
+        self.invalid_value_policy = invalid_value_policy;
+        self
+    }
+
+    /// Enables the `MeanOutOfRegion` termination criterion with the given region (see
+    /// [`TerminationReason::MeanOutOfRegion`][crate::TerminationReason::MeanOutOfRegion]).
+    /// `lower` and `upper` must have the same length as the initial mean and satisfy
+    /// `lower[i] <= upper[i]`. Bounds may be infinite.
+    pub fn mean_region<V: Into<DVector<f64>>>(mut self, lower: V, upper: V) -> Self {
+        self.mean_region = Some((lower.into(), upper.into()));
+        self
+    }
+
+    /// Enables the `TolImprovementRate` termination criterion with the given value (see
+    /// [`TerminationReason::TolImprovementRate`][crate::TerminationReason::TolImprovementRate]).
+    pub fn tol_improvement_rate(mut self, tol_improvement_rate: f64) -> Self {
+        self.tol_improvement_rate = Some(tol_improvement_rate);
+        self
+    }
+
+    /// Enables the `PrematureConvergence` termination criterion with the given value (see
+    /// [`TerminationReason::PrematureConvergence`][crate::TerminationReason::PrematureConvergence]
+    /// and [`CMAES::sigma_decay_ratio`]). The ratio typically stays below `10.0` on runs that
+    /// converge to an optimum with a value of zero, so a value of around `100.0` is a reasonable
+    /// starting point.
+    pub fn max_sigma_decay_ratio(mut self, max_sigma_decay_ratio: f64) -> Self {
+        self.max_sigma_decay_ratio = Some(max_sigma_decay_ratio);
+        self
+    }
+
     /// Changes the minimum value for the `TolStagnation` termination criterion from the default
     /// value (see [`TerminationReason::TolStagnation`][crate::TerminationReason::TolStagnation]).
     pub fn tol_stagnation(mut self, tol_stagnation: usize) -> Self {
@@ -284,6 +1222,22 @@ impl CMAESOptions {
         self
     }
 
+    /// Suppresses the convergence-based termination criteria until at least `min_generations`
+    /// generations have been completed. This is a safeguard against spurious termination on
+    /// problems where the algorithm briefly looks converged in the first few generations.
+    ///
+    /// The criteria that are suppressed are `TolFun`, `TolFunRel`, `TolFunHist`, `TolX`,
+    /// `TolDiversity`, `TolImprovementRate`, `PrematureConvergence`, `TolStagnation`, `TolXUp`,
+    /// `TolConditionCov`, `NoEffectAxis`, and `NoEffectCoord`. All other criteria still apply, in
+    /// particular the `Max*` limits, `FunTarget`, and `InvalidFunctionValue`.
+    ///
+    /// Note that `TolConditionCov`, `NoEffectAxis`, and `NoEffectCoord` also guard against
+    /// numerical problems, so a large value may lead to `PosDefCov` instead.
+    pub fn min_generations(mut self, min_generations: usize) -> Self {
+        self.min_generations = min_generations;
+        self
+    }
+
     /// Sets the seed for the RNG.
     pub fn seed(mut self, seed: u64) -> Self {
         self.seed = Some(seed);
@@ -306,10 +1260,342 @@ impl CMAESOptions {
         self
     }
 
+    /// Enables or disables the live status line (see
+    /// [`live_status`][Self::live_status]), a compact alternative to
+    /// [`enable_printing`][Self::enable_printing] for interactive runs.
+    ///
+    /// If stdout is a terminal, the line is redrawn in place using a carriage return at most four
+    /// times per second. Otherwise (e.g. if stdout is redirected to a file or piped into another
+    /// program), carriage returns would clutter the output, so the status is instead printed as a
+    /// separate line at most once every 10 seconds. Whether stdout is a terminal is detected once
+    /// when the [`CMAES`] is built. The final status is printed when the run terminates through
+    /// [`CMAES::run`] or one of its variants, which also ends the line; if the run is driven
+    /// using [`CMAES::next`], the line is left open.
+    ///
+    /// Should not be combined with [`enable_printing`][Self::enable_printing], whose lines would
+    /// interleave with the status line.
+    pub fn live_status(mut self, live_status: bool) -> Self {
+        self.live_status = live_status;
+        self
+    }
+
+    /// Attaches key-value metadata to the run (see [`metadata`][Self::metadata]), replacing any
+    /// that was set before. Useful for grouping the results of many runs when aggregating them,
+    /// e.g. in a [`sweep`][crate::sweep()] or a benchmark.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use cmaes::{CMAESOptions, DVector};
+    ///
+    /// let metadata = HashMap::from([("problem".to_string(), "sphere".to_string())]);
+    /// let mut cmaes = CMAESOptions::new(vec![1.0; 4], 1.0)
+    ///     .metadata(metadata)
+    ///     .build(|x: &DVector<f64>| x.magnitude())
+    ///     .unwrap();
+    ///
+    /// let result = cmaes.run();
+    /// assert_eq!("sphere", result.metadata["problem"]);
+    /// ```
+    pub fn metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets a callback to invoke whenever a new overall best individual is found. The callback
+    /// receives the new best individual and the number of function evaluations performed so far
+    /// (including those of the generation that found it).
+    ///
+    /// The best individual of each generation is an improvement if it is strictly better than the
+    /// previous overall best according to the [`Mode`] (`<` when minimizing, `>` when maximizing),
+    /// comparing the original (unscaled) objective function values. Ties are not improvements. The
+    /// best individual of the first generation always counts as an improvement, since there is no
+    /// previous overall best.
+    ///
+    /// The callback is shared between clones of the options and of the [`CMAES`] (see
+    /// [`CMAES::clone_with`]), so it is invoked by every run of
+    /// [`parallel_restarts`][crate::parallel_restarts], each with its own overall best.
+    pub fn on_improvement<C>(mut self, callback: C) -> Self
+    where
+        C: FnMut(&EvaluatedPoint, usize) + Send + 'static,
+    {
+        self.on_improvement = Some(ImprovementCallback::new(callback));
+        self
+    }
+
+    /// Returns the initial step size in effect, which is the value suggested by the bounds if
+    /// `initial_step_size_from_bounds` is enabled (`NAN` if there is no suggestion)
+    pub(crate) fn resolved_initial_step_size(&self) -> f64 {
+        if self.initial_step_size_from_bounds {
+            self.bounds
+                .as_ref()
+                .and_then(Bounds::suggested_step_size)
+                .unwrap_or(f64::NAN)
+        } else {
+            self.initial_step_size
+        }
+    }
+
+    /// Checks the options for invalid values, returning the first error found
+    pub(crate) fn validate(&self) -> Result<(), InvalidOptionsError> {
+        let dimensions = self.initial_mean.len();
+        if dimensions == 0 {
+            return Err(InvalidOptionsError::Dimensions);
+        }
+
+        if self.population_size < 2 {
+            return Err(InvalidOptionsError::PopulationSize);
+        }
+
+        let mu_eff = self.weights.mu_eff(self.population_size);
+        if !mu_eff.is_finite() || mu_eff < 1.0 {
+            return Err(InvalidOptionsError::EffectiveMass);
+        }
+        if let Some(min_mu_eff) = self.min_mu_eff {
+            if !min_mu_eff.is_finite() || min_mu_eff < 1.0 || mu_eff < min_mu_eff {
+                return Err(InvalidOptionsError::EffectiveMass);
+            }
+        }
+
+        if !is_initial_step_size_valid(self.resolved_initial_step_size()) {
+            return Err(InvalidOptionsError::InitialStepSize);
+        }
+
+        if !self.initial_covariance_scale.is_normal() || self.initial_covariance_scale <= 0.0 {
+            return Err(InvalidOptionsError::InitialCovarianceScale);
+        }
+
+        if !self.cm.is_normal() || self.cm <= 0.0 || self.cm > 1.0 {
+            return Err(InvalidOptionsError::Cm);
+        }
+
+        if !self.value_scale.is_normal() || self.value_scale <= 0.0 {
+            return Err(InvalidOptionsError::ValueScale);
+        }
+
+        if let Some(max_sigma_change) = self.max_sigma_change {
+            if max_sigma_change.is_nan() || max_sigma_change < 1.0 {
+                return Err(InvalidOptionsError::MaxSigmaChange);
+            }
+        }
+
+        if let Some(min_eigenvalue_ratio) = self.min_eigenvalue_ratio {
+            if !(min_eigenvalue_ratio > 0.0 && min_eigenvalue_ratio <= 1.0) {
+                return Err(InvalidOptionsError::MinEigenvalueRatio);
+            }
+        }
+
+        if self.target_values.iter().any(|target| target.is_nan()) {
+            return Err(InvalidOptionsError::TargetValues);
+        }
+
+        if self.max_recorded_generations == Some(0) {
+            return Err(InvalidOptionsError::MaxRecordedGenerations);
+        }
+
+        if let Some((every, ref path_template)) = self.record_covariance {
+            if every == 0 || !path_template.contains(GENERATION_PLACEHOLDER) {
+                return Err(InvalidOptionsError::RecordCovariance);
+            }
+        }
+
+        if self.evaluations_per_candidate == 0 {
+            return Err(InvalidOptionsError::EvaluationsPerCandidate);
+        }
+
+        if self.flat_function_generations == Some(0) {
+            return Err(InvalidOptionsError::FlatFunctionGenerations);
+        }
+
+        if self.tol_diversity_generations == 0 {
+            return Err(InvalidOptionsError::TolDiversityGenerations);
+        }
+
+        if let Some(target_sigma) = self.target_sigma {
+            if target_sigma.is_nan() || target_sigma <= 0.0 {
+                return Err(InvalidOptionsError::TargetSigma);
+            }
+        }
+
+        if self.invalid_value_policy == InvalidPolicy::TerminateAfter(0) {
+            return Err(InvalidOptionsError::InvalidValuePolicy);
+        }
+
+        if let Some((ref lower, ref upper)) = self.random_initial_mean {
+            if lower.len() != dimensions
+                || upper.len() != dimensions
+                || !lower
+                    .iter()
+                    .zip(upper.iter())
+                    .all(|(l, u)| l.is_finite() && u.is_finite() && l <= u)
+            {
+                return Err(InvalidOptionsError::RandomInitialMean);
+            }
+        }
+
+        if let Some((ref lower, ref upper)) = self.mean_region {
+            if lower.len() != dimensions
+                || upper.len() != dimensions
+                || !lower.iter().zip(upper.iter()).all(|(l, u)| l <= u)
+            {
+                return Err(InvalidOptionsError::MeanRegion);
+            }
+        }
+
+        if self.auto_population && self.adaptive_population.is_some() {
+            return Err(InvalidOptionsError::AutoPopulation);
+        }
+
+        if let Some(adaptive_population) = self.adaptive_population {
+            if !adaptive_population.is_valid()
+                || self.population_size < adaptive_population.min
+                || self.population_size > adaptive_population.max
+            {
+                return Err(InvalidOptionsError::AdaptivePopulation);
+            }
+        }
+
+        if let Some(auto_kick) = self.auto_kick {
+            if !auto_kick.is_valid() {
+                return Err(InvalidOptionsError::AutoKick);
+            }
+        }
+
+        if let Some(ref bounds) = self.bounds {
+            if !bounds.is_valid(dimensions) {
+                return Err(InvalidOptionsError::Bounds);
+            }
+        }
+
+        if let Some(ref penalty_weights) = self.penalty_weights {
+            if penalty_weights.len() != dimensions
+                || !penalty_weights.iter().all(|w| w.is_finite() && *w >= 0.0)
+            {
+                return Err(InvalidOptionsError::PenaltyWeights);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the initial mean, which is drawn from the box of
+    /// [`random_initial_mean`][Self::random_initial_mean] using an RNG derived from `seed` if set
+    pub(crate) fn draw_initial_mean(&self, seed: u64) -> DVector<f64> {
+        match self.random_initial_mean {
+            Some((ref lower, ref upper)) => {
+                let mut rng = ChaCha12Rng::seed_from_u64(seed);
+                rng.set_stream(INITIAL_MEAN_RNG_STREAM);
+                lower.zip_map(upper, |l, u| l + (u - l) * rng.gen::<f64>())
+            }
+            None => self.initial_mean.clone(),
+        }
+    }
+
     /// Attempts to build the [`CMAES`] using the chosen options.
     pub fn build<F>(self, objective_function: F) -> Result<CMAES<F>, InvalidOptionsError> {
         CMAES::new(objective_function, self)
     }
+
+    /// Like [`build`][Self::build], but accepts an objective function that is already a trait
+    /// object, for when its type is only known at runtime (e.g. when it is chosen from a registry
+    /// of functions). The function may borrow from its environment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, DVector, ObjectiveFunction};
+    ///
+    /// let offset = 1.0;
+    /// let registry: Vec<(&str, Box<dyn ObjectiveFunction>)> = vec![
+    ///     ("sphere", Box::new(|x: &DVector<f64>| x.magnitude_squared())),
+    ///     ("shifted", Box::new(move |x: &DVector<f64>| x.add_scalar(-offset).magnitude())),
+    /// ];
+    ///
+    /// for (name, function) in registry {
+    ///     let mut cmaes_state = CMAESOptions::new(vec![0.0; 2], 1.0)
+    ///         .build_boxed(function)
+    ///         .unwrap();
+    ///     println!("{}: {:?}", name, cmaes_state.run().overall_best);
+    /// }
+    /// ```
+    pub fn build_boxed<'a>(
+        self,
+        objective_function: Box<dyn ObjectiveFunction + 'a>,
+    ) -> Result<CMAES<Box<dyn ObjectiveFunction + 'a>>, InvalidOptionsError> {
+        CMAES::new(objective_function, self)
+    }
+}
+
+/// A callback invoked with a new overall best individual and the current number of function
+/// evaluations
+type ImprovementFn = dyn FnMut(&EvaluatedPoint, usize) + Send;
+
+/// A callback invoked whenever a new overall best individual is found. Set using
+/// [`CMAESOptions::on_improvement`].
+#[derive(Clone)]
+pub struct ImprovementCallback(Arc<Mutex<ImprovementFn>>);
+
+impl ImprovementCallback {
+    /// Creates a new `ImprovementCallback` from a function receiving the new best individual and
+    /// the current number of function evaluations.
+    pub fn new<C>(callback: C) -> Self
+    where
+        C: FnMut(&EvaluatedPoint, usize) + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(callback)))
+    }
+
+    /// Invokes the callback
+    pub(crate) fn call(&self, individual: &EvaluatedPoint, function_evals: usize) {
+        let mut callback = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (callback)(individual, function_evals);
+    }
+}
+
+impl Debug for ImprovementCallback {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("ImprovementCallback")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A function returning the learning rate for the mean of a generation
+type CmScheduleFn = dyn FnMut(usize) -> f64 + Send;
+
+/// A schedule of learning rates for the mean across generations. Set using
+/// [`CMAESOptions::cm_schedule`].
+#[derive(Clone)]
+pub struct CmSchedule(Arc<Mutex<CmScheduleFn>>);
+
+impl CmSchedule {
+    /// Creates a new `CmSchedule` from a function receiving the index of a generation (starting
+    /// from `0`) and returning its learning rate for the mean.
+    pub fn new<S>(schedule: S) -> Self
+    where
+        S: FnMut(usize) -> f64 + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(schedule)))
+    }
+
+    /// Returns the learning rate for the generation with index `generation` clamped to `(0, 1]`,
+    /// or `None` if the schedule returned `NAN`
+    pub(crate) fn cm(&self, generation: usize) -> Option<f64> {
+        let mut schedule = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let cm = (schedule)(generation);
+        if cm.is_nan() {
+            None
+        } else {
+            Some(cm.clamp(f64::MIN_POSITIVE, 1.0))
+        }
+    }
+}
+
+impl Debug for CmSchedule {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("CmSchedule").finish_non_exhaustive()
+    }
 }
 
 /// Represents invalid options for CMA-ES.
@@ -319,10 +1605,66 @@ pub enum InvalidOptionsError {
     Dimensions,
     /// The population size is too small (must be at least 2).
     PopulationSize,
-    /// The initial step size is negative or non-normal.
+    /// The variance-effective selection mass of the weights is degenerate (not finite or less than
+    /// `1.0`) or is less than [`CMAESOptions::min_mu_eff`], or `min_mu_eff` itself is less than
+    /// `1.0` or is not finite.
+    EffectiveMass,
+    /// The initial step size is negative or non-normal, or it is taken from the bounds but no
+    /// coordinate has both a finite lower and upper bound.
     InitialStepSize,
+    /// The initial covariance scale is not positive or is non-normal.
+    InitialCovarianceScale,
     /// The learning rate is outside the valid range (`0.0` to `1.0`).
     Cm,
+    /// The value scale is not positive or is non-normal.
+    ValueScale,
+    /// The bounds have a different number of dimensions than the initial mean or a lower bound is
+    /// not less than its upper bound.
+    Bounds,
+    /// The box of the random initial mean has a different number of dimensions than the initial
+    /// mean, a lower bound is greater than its upper bound, or a bound is not finite.
+    RandomInitialMean,
+    /// The penalty weights have a different number of dimensions than the initial mean or a weight
+    /// is negative or non-finite.
+    PenaltyWeights,
+    /// The adaptive population settings are invalid (see [`AdaptivePopulation`]) or the population
+    /// size lies outside of their `[min, max]` range.
+    AdaptivePopulation,
+    /// [`CMAESOptions::auto_population`] is enabled together with
+    /// [`CMAESOptions::adaptive_population`].
+    AutoPopulation,
+    /// The automatic kick settings are invalid (see [`AutoKick`]).
+    AutoKick,
+    /// The maximum number of recorded generations is zero.
+    MaxRecordedGenerations,
+    /// The number of evaluations per candidate is zero.
+    EvaluationsPerCandidate,
+    /// The covariance matrix is recorded every zero generations or the path template does not
+    /// contain `{generation}` (see [`CMAESOptions::record_covariance`]).
+    RecordCovariance,
+    /// The maximum step size change factor is less than `1.0` or is `NAN`.
+    MaxSigmaChange,
+    /// The minimum eigenvalue ratio is not in `(0, 1]` or is `NAN` (or the condition number cap
+    /// is less than `1.0`, is infinite, or is `NAN`).
+    MinEigenvalueRatio,
+    /// The number of generations for the `FlatFunction` termination criterion is zero.
+    FlatFunctionGenerations,
+    /// The number of generations for the `TolDiversity` termination criterion is zero.
+    TolDiversityGenerations,
+    /// The value for the `TargetSigma` termination criterion is not positive or is `NAN`.
+    TargetSigma,
+    /// The number of generations of [`InvalidPolicy::TerminateAfter`] is zero.
+    InvalidValuePolicy,
+    /// The region for the `MeanOutOfRegion` termination criterion has a different number of
+    /// dimensions than the initial mean or a lower bound is greater than its upper bound (or is
+    /// `NAN`).
+    MeanRegion,
+    /// A target value is `NAN`.
+    TargetValues,
+    /// The options passed to [`CMAES::override_options`] change an option that determines how
+    /// the state of the run was learned (the optimization mode, population size, weights, or
+    /// recombination weighting).
+    IncompatibleOverride,
 }
 
 /// Returns whether the initial step size is valid (greater than zero and normal)
@@ -334,6 +1676,149 @@ pub(crate) fn is_initial_step_size_valid(step_size: f64) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_default_weights() {
+        for dim in [1, 2, 10, 100] {
+            let options = CMAESOptions::new(vec![1.0; dim], 1.0);
+            assert_eq!(
+                Weights::recommended(options.population_size),
+                options.weights
+            );
+            assert_eq!(Weights::Negative, options.weights);
+        }
+    }
+
+    #[test]
+    fn test_termination_preset() {
+        let dim = 10;
+        let default = CMAESOptions::new(vec![1.0; dim], 2.0);
+        let fast = default.clone().termination_preset(TerminationPreset::Fast);
+        let balanced = default
+            .clone()
+            .termination_preset(TerminationPreset::Balanced);
+        let thorough = default
+            .clone()
+            .termination_preset(TerminationPreset::Thorough);
+
+        // Balanced uses the default tolerances
+        assert_eq!(default.tol_fun, balanced.tol_fun);
+        assert_eq!(default.tol_fun_hist, balanced.tol_fun_hist);
+        assert_eq!(None, balanced.tol_x);
+        assert_eq!(default.relative_tol_x, balanced.relative_tol_x);
+        assert_eq!(default.tol_x_up, balanced.tol_x_up);
+        assert_eq!(default.tol_condition_cov, balanced.tol_condition_cov);
+
+        // Presets are ordered from loosest to tightest
+        for (looser, tighter) in [(&fast, &balanced), (&balanced, &thorough)] {
+            assert!(looser.tol_fun > tighter.tol_fun);
+            assert!(looser.tol_fun_hist > tighter.tol_fun_hist);
+            assert!(looser.relative_tol_x > tighter.relative_tol_x);
+            assert!(looser.tol_x_up < tighter.tol_x_up);
+            assert!(looser.tol_condition_cov < tighter.tol_condition_cov);
+            assert!(looser.tol_stagnation < tighter.tol_stagnation);
+            assert!(looser.max_generations < tighter.max_generations);
+        }
+
+        // The generation limit accounts for the population size
+        let large_population = default
+            .clone()
+            .population_size(100)
+            .termination_preset(TerminationPreset::Balanced);
+        assert!(large_population.max_generations < balanced.max_generations);
+
+        // Options set afterward override the preset
+        let overridden = default
+            .clone()
+            .termination_preset(TerminationPreset::Fast)
+            .tol_fun(1e-3)
+            .max_generations(7);
+        assert_eq!(1e-3, overridden.tol_fun);
+        assert_eq!(Some(7), overridden.max_generations);
+        assert_eq!(fast.tol_fun_hist, overridden.tol_fun_hist);
+
+        let dummy_function = |_: &DVector<f64>| 0.0;
+        let cmaes = balanced.clone().build(dummy_function).unwrap();
+        assert_eq!(1e-12 * 2.0, cmaes.parameters().tol_x());
+        for options in [fast, balanced, thorough] {
+            assert!(options.build(dummy_function).is_ok());
+        }
+
+        // The preset's tol_x is relative to a step size taken from bounds set afterward
+        let cmaes = default
+            .initial_step_size_from_bounds()
+            .termination_preset(TerminationPreset::Fast)
+            .bounds(vec![0.0; dim], vec![10.0; dim], BoundaryHandling::Penalty)
+            .build(dummy_function)
+            .unwrap();
+        assert_eq!(2.5, cmaes.parameters().initial_sigma());
+        assert_eq!(1e-8 * 2.5, cmaes.parameters().tol_x());
+    }
+
+    #[test]
+    fn test_initial_step_size_from_bounds() {
+        let options = CMAESOptions::new(vec![1.0; 2], 3.0);
+        let function = |x: &DVector<f64>| x.magnitude();
+
+        // Building fails if there are no bounds to take the step size from
+        assert!(matches!(
+            options
+                .clone()
+                .initial_step_size_from_bounds()
+                .build(function),
+            Err(InvalidOptionsError::InitialStepSize),
+        ));
+
+        // The step size does not depend on the order in which the bounds are set
+        let bounds_first = options
+            .clone()
+            .bounds(vec![0.0; 2], vec![10.0; 2], BoundaryHandling::Transform)
+            .initial_step_size_from_bounds();
+        let bounds_last = options.clone().initial_step_size_from_bounds().bounds(
+            vec![0.0; 2],
+            vec![10.0; 2],
+            BoundaryHandling::Transform,
+        );
+        for options in [bounds_first, bounds_last] {
+            let cmaes = options.build(function).unwrap();
+            assert_eq!(0.5, cmaes.parameters().initial_sigma());
+            assert_eq!(0.5e-12, cmaes.parameters().tol_x());
+        }
+
+        // An explicit step size set afterwards takes precedence
+        let cmaes = options
+            .bounds(vec![0.0; 2], vec![10.0; 2], BoundaryHandling::Transform)
+            .initial_step_size_from_bounds()
+            .initial_step_size(2.0)
+            .build(function)
+            .unwrap();
+        assert_eq!(2.0, cmaes.parameters().initial_sigma());
+    }
+
+    #[test]
+    fn test_build_boxed() {
+        // The function borrows from its environment
+        let mut evals = 0;
+        {
+            let function: Box<dyn ObjectiveFunction> = Box::new(|x: &DVector<f64>| {
+                evals += 1;
+                x.magnitude()
+            });
+            let mut cmaes = CMAESOptions::new(vec![1.0; 2], 1.0)
+                .population_size(6)
+                .build_boxed(function)
+                .unwrap();
+            assert!(cmaes.next().is_none());
+        }
+        assert_eq!(6, evals);
+
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 2], 1.0)
+                .population_size(1)
+                .build_boxed(Box::new(|_: &DVector<f64>| 0.0)),
+            Err(InvalidOptionsError::PopulationSize),
+        ));
+    }
+
     #[test]
     fn test_build() {
         let dummy_function = |_: &DVector<f64>| 0.0;
@@ -390,5 +1875,222 @@ mod tests {
                 .build(dummy_function),
             Err(InvalidOptionsError::Cm),
         ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .initial_covariance_scale(0.0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::InitialCovarianceScale),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .initial_covariance_scale(-1.0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::InitialCovarianceScale),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .initial_covariance_scale(f64::NAN)
+                .build(dummy_function),
+            Err(InvalidOptionsError::InitialCovarianceScale),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .value_scale(0.0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::ValueScale),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .value_scale(-1.0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::ValueScale),
+        ));
+        assert!(CMAESOptions::new(vec![1.0; 5], 1.0)
+            .min_mu_eff(2.0)
+            .build(dummy_function)
+            .is_ok());
+        for (population_size, min_mu_eff) in [(4, 2.0), (8, 3.0), (20, 0.5), (20, f64::NAN)] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 5], 1.0)
+                    .population_size(population_size)
+                    .min_mu_eff(min_mu_eff)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::EffectiveMass),
+            ));
+        }
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .max_sigma_change(0.5)
+                .build(dummy_function),
+            Err(InvalidOptionsError::MaxSigmaChange),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .max_sigma_change(f64::NAN)
+                .build(dummy_function),
+            Err(InvalidOptionsError::MaxSigmaChange),
+        ));
+        for min_eigenvalue_ratio in [0.0, 1.5, f64::NAN] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 5], 1.0)
+                    .min_eigenvalue_ratio(min_eigenvalue_ratio)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::MinEigenvalueRatio),
+            ));
+        }
+        for cap_condition_number in [0.5, f64::INFINITY, f64::NAN] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 5], 1.0)
+                    .cap_condition_number(Some(cap_condition_number))
+                    .build(dummy_function),
+                Err(InvalidOptionsError::MinEigenvalueRatio),
+            ));
+        }
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .max_recorded_generations(0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::MaxRecordedGenerations),
+        ));
+        for (every, path_template) in [(0, "cov_{generation}.csv"), (5, "cov.csv")] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 5], 1.0)
+                    .record_covariance(every, path_template)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::RecordCovariance),
+            ));
+        }
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .adaptive_population(AdaptivePopulation::new(8, 32, 2.0))
+                .auto_population(true)
+                .build(dummy_function),
+            Err(InvalidOptionsError::AutoPopulation),
+        ));
+        for population_size in [4, 64] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 5], 1.0)
+                    .adaptive_population(AdaptivePopulation::new(8, 32, 2.0))
+                    .population_size(population_size)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::AdaptivePopulation),
+            ));
+        }
+        assert!(CMAESOptions::new(vec![1.0; 5], 1.0)
+            .adaptive_population(AdaptivePopulation::new(8, 32, 2.0))
+            .population_size(16)
+            .build(dummy_function)
+            .is_ok());
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .evaluations_per_candidate(0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::EvaluationsPerCandidate),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .flat_function_generations(0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::FlatFunctionGenerations),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .tol_diversity_generations(0)
+                .build(dummy_function),
+            Err(InvalidOptionsError::TolDiversityGenerations),
+        ));
+        for target_sigma in [0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 5], 1.0)
+                    .target_sigma(target_sigma)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::TargetSigma),
+            ));
+        }
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .invalid_value_policy(InvalidPolicy::TerminateAfter(0))
+                .build(dummy_function),
+            Err(InvalidOptionsError::InvalidValuePolicy),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .target_values(vec![1.0, f64::NAN])
+                .build(dummy_function),
+            Err(InvalidOptionsError::TargetValues),
+        ));
+        assert!(CMAESOptions::new(vec![1.0; 2], 1.0)
+            .bounds(vec![0.0; 2], vec![2.0; 2], BoundaryHandling::Transform)
+            .build(dummy_function)
+            .is_ok());
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 5], 1.0)
+                .bounds(vec![0.0; 2], vec![2.0; 2], BoundaryHandling::Transform)
+                .build(dummy_function),
+            Err(InvalidOptionsError::Bounds),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 2], 1.0)
+                .bounds(vec![2.0; 2], vec![0.0; 2], BoundaryHandling::Transform)
+                .build(dummy_function),
+            Err(InvalidOptionsError::Bounds),
+        ));
+        assert!(CMAESOptions::new(vec![1.0; 2], 1.0)
+            .random_initial_mean(vec![0.0, 1.0], vec![2.0, 1.0])
+            .build(dummy_function)
+            .is_ok());
+        for (lower, upper) in [
+            (vec![0.0; 3], vec![1.0; 3]),
+            (vec![0.0, 2.0], vec![1.0; 2]),
+            (vec![f64::NEG_INFINITY, 0.0], vec![1.0; 2]),
+            (vec![0.0, f64::NAN], vec![1.0; 2]),
+        ] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 2], 1.0)
+                    .random_initial_mean(lower, upper)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::RandomInitialMean),
+            ));
+        }
+        for penalty_weights in [vec![1.0; 3], vec![1.0, -1.0], vec![1.0, f64::NAN]] {
+            assert!(matches!(
+                CMAESOptions::new(vec![1.0; 2], 1.0)
+                    .penalty_weights(penalty_weights)
+                    .build(dummy_function),
+                Err(InvalidOptionsError::PenaltyWeights),
+            ));
+        }
+        assert!(CMAESOptions::new(vec![1.0; 2], 1.0)
+            .auto_kick(AutoKick::new(5, 0.5))
+            .build(dummy_function)
+            .is_ok());
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 2], 1.0)
+                .auto_kick(AutoKick::new(0, 0.5))
+                .build(dummy_function),
+            Err(InvalidOptionsError::AutoKick),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 2], 1.0)
+                .auto_kick(AutoKick::new(5, 0.0))
+                .build(dummy_function),
+            Err(InvalidOptionsError::AutoKick),
+        ));
+        assert!(CMAESOptions::new(vec![1.0; 2], 1.0)
+            .mean_region(vec![0.0, f64::NEG_INFINITY], vec![0.0, 1.0])
+            .build(dummy_function)
+            .is_ok());
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 2], 1.0)
+                .mean_region(vec![0.0; 3], vec![1.0; 3])
+                .build(dummy_function),
+            Err(InvalidOptionsError::MeanRegion),
+        ));
+        assert!(matches!(
+            CMAESOptions::new(vec![1.0; 2], 1.0)
+                .mean_region(vec![0.0, f64::NAN], vec![1.0; 2])
+                .build(dummy_function),
+            Err(InvalidOptionsError::MeanRegion),
+        ));
     }
 }
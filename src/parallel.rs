@@ -0,0 +1,160 @@
+//! Running several independent CMA-ES instances concurrently. See [`parallel_restarts`] for full
+//! documentation.
+
+use rayon::prelude::*;
+
+use crate::options::InvalidOptionsError;
+use crate::{CMAESOptions, Individual, ObjectiveFunction, TerminationData};
+
+/// The results of [`parallel_restarts`].
+#[derive(Clone, Debug)]
+pub struct ParallelResults {
+    /// The termination data of each instance, in the order of their seeds.
+    pub results: Vec<TerminationData>,
+    /// The best individual found by any instance. `None` if no instances were run or if every
+    /// instance terminated in its first generation with
+    /// [`TerminationReason::InvalidFunctionValue`][crate::TerminationReason::InvalidFunctionValue].
+    pub best: Option<Individual>,
+    /// The base seed from which the seed of each instance was derived. Setting
+    /// [`CMAESOptions::seed`] to this value reproduces all instances.
+    pub seed: u64,
+}
+
+/// Runs `k` independent CMA-ES instances with the same `options` concurrently and returns the
+/// results of all of them along with the best individual found.
+///
+/// Each instance evaluates points using its own clone of `objective_function` on a single thread,
+/// and the instances are distributed across the threads of the global [rayon][rayon] thread pool
+/// (so at most one instance per available core runs at a time). This is instance-level
+/// parallelism, unlike [`CMAES::run_parallel`][crate::CMAES::run_parallel], which parallelizes the
+/// evaluation of points within a single instance. Unlike the [`restart`][crate::restart]
+/// strategies, all instances use the same options and do not depend on each other.
+///
+/// The instances differ only in their seeds. The base seed is `options.seed` if set and random
+/// otherwise, and instance `i` (for `i` in `0..k`) is run with the seed `base_seed + i` (wrapping
+/// on overflow). A single instance can therefore be reproduced by building a
+/// [`CMAES`][crate::CMAES] from the same options with that seed, and all instances can be
+/// reproduced by passing the base seed returned in [`ParallelResults::seed`].
+///
+/// Returns `Err` if the options are invalid. Returns no results if `k` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let function = |x: &DVector<f64>| x.magnitude();
+/// let options = CMAESOptions::new(vec![1.0; 5], 1.0).seed(42);
+///
+/// let results = cmaes::parallel_restarts(options, 4, function).unwrap();
+///
+/// assert_eq!(4, results.results.len());
+/// assert_eq!(42, results.seed);
+/// assert!(results.best.unwrap().value < 1e-6);
+/// ```
+pub fn parallel_restarts<F: ObjectiveFunction + Clone + Send>(
+    options: CMAESOptions,
+    k: usize,
+    objective_function: F,
+) -> Result<ParallelResults, InvalidOptionsError> {
+    options.validate()?;
+
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let mode = options.mode;
+
+    // Clone everything up front so that `F` does not need to be `Sync`
+    let instances = (0..k)
+        .map(|i| {
+            (
+                options.clone().seed(seed.wrapping_add(i as u64)),
+                objective_function.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let results = instances
+        .into_par_iter()
+        .map(|(options, objective_function)| {
+            options
+                .build(objective_function)
+                .map(|mut cmaes| cmaes.run())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let best = results
+        .iter()
+        .filter_map(|result| result.overall_best.as_ref())
+        .fold(None, |best: Option<&Individual>, individual| match best {
+            Some(best) if !mode.is_better(individual.value, best.value) => Some(best),
+            _ => Some(individual),
+        })
+        .cloned();
+
+    Ok(ParallelResults {
+        results,
+        best,
+        seed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DVector;
+
+    use super::*;
+    use crate::Mode;
+
+    #[test]
+    fn test_parallel_restarts() {
+        let function = |x: &DVector<f64>| (x.magnitude() - 1.0).abs();
+        let options = CMAESOptions::new(vec![2.0; 3], 1.0)
+            .max_generations(20)
+            .seed(10);
+        let results = parallel_restarts(options.clone(), 3, function).unwrap();
+
+        assert_eq!(3, results.results.len());
+        assert_eq!(10, results.seed);
+
+        // Each instance is reproducible from its derived seed
+        for (i, result) in results.results.iter().enumerate() {
+            let expected = options
+                .clone()
+                .seed(10 + i as u64)
+                .build(function)
+                .unwrap()
+                .run();
+            assert_eq!(
+                expected.overall_best.unwrap().value,
+                result.overall_best.as_ref().unwrap().value
+            );
+        }
+
+        let best = results.best.unwrap().value;
+        assert!(results
+            .results
+            .iter()
+            .all(|result| best <= result.overall_best.as_ref().unwrap().value));
+
+        // Maximization picks the largest value
+        let results = parallel_restarts(
+            options.clone().mode(Mode::Maximize).max_generations(3),
+            3,
+            function,
+        )
+        .unwrap();
+        let best = results.best.unwrap().value;
+        assert!(results
+            .results
+            .iter()
+            .all(|result| best >= result.overall_best.as_ref().unwrap().value));
+
+        assert!(parallel_restarts(options.clone(), 0, function)
+            .unwrap()
+            .best
+            .is_none());
+        assert!(matches!(
+            parallel_restarts(options.population_size(1), 2, function),
+            Err(InvalidOptionsError::PopulationSize),
+        ));
+    }
+}
@@ -6,12 +6,112 @@ use nalgebra::DVector;
 
 use std::time::Duration;
 
-pub use weights::Weights;
+pub use weights::{RecombinationWeighting, Weights};
 
+use crate::bounds::Bounds;
 use crate::mode::Mode;
-use crate::{termination, CMAESOptions};
+use crate::sampling::PointMapping;
+use crate::termination::{self, TargetMode};
+use crate::transform::{CoordinateTransform, FitnessTransform};
+use crate::CMAESOptions;
+pub(crate) use weights::{discard_unrankable_weights, recombination_weights};
 use weights::{FinalWeights, InitialWeights};
 
+/// Settings for growing the population size `lambda` within a single run when progress stalls.
+/// Set using [`CMAESOptions::adaptive_population`][crate::CMAESOptions::adaptive_population].
+///
+/// The run starts with a population size of `min`. Whenever the overall best function value has
+/// not improved for `10 + 30 * N / lambda` consecutive generations (the same window used by
+/// [`TolFun`][crate::TerminationReason::TolFun]), the population size is multiplied by `growth`
+/// (rounded up), up to at most `max`. The weights, `mu`, `mu_eff`, and all learning rates are
+/// recalculated for the new population size, while the mean, step size, covariance matrix, and
+/// evolution paths are kept, so nothing learned about the function is discarded (unlike with a
+/// restart strategy such as [`IPOP`][crate::restart::IPOP]).
+///
+/// The evolution paths are carried over unchanged and are updated with the new learning rates
+/// from then on. Because each path update is normalized by `mu_eff`, the paths keep their
+/// expected length under random selection, so the step size adaptation is not disturbed by the
+/// change beyond the different learning rates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptivePopulation {
+    /// The initial (and smallest) population size. Must be at least `2`.
+    pub min: usize,
+    /// The largest population size. Must be at least `min`.
+    pub max: usize,
+    /// The factor that the population size is multiplied by each time it grows. Must be greater
+    /// than `1.0`.
+    pub growth: f64,
+}
+
+impl AdaptivePopulation {
+    /// Returns a new `AdaptivePopulation` with the provided settings.
+    pub fn new(min: usize, max: usize, growth: f64) -> Self {
+        Self { min, max, growth }
+    }
+
+    /// Returns whether the settings are valid
+    pub(crate) fn is_valid(&self) -> bool {
+        self.min >= 2 && self.max >= self.min && self.growth.is_finite() && self.growth > 1.0
+    }
+
+    /// Returns the population size to grow to from `lambda`
+    pub(crate) fn grow(&self, lambda: usize) -> usize {
+        ((lambda as f64 * self.growth).ceil() as usize).clamp(lambda, self.max)
+    }
+
+    /// Returns the settings for a run that starts with a population size of `lambda` (used by
+    /// restart strategies that change the population size between runs)
+    pub(crate) fn starting_at(&self, lambda: usize) -> Self {
+        Self {
+            min: lambda,
+            max: self.max.max(lambda),
+            growth: self.growth,
+        }
+    }
+}
+
+/// Settings for automatically displacing the mean of a stuck run (a "kick"). Set using
+/// [`CMAESOptions::auto_kick`][crate::CMAESOptions::auto_kick].
+///
+/// Whenever the overall best function value has not improved for `threshold` consecutive
+/// generations (and at least `threshold` generations have passed since the previous kick), the
+/// mean is moved by a random step drawn from the current distribution, scaled by `strength`, and
+/// the step size is multiplied by `1 + strength`. The covariance matrix and evolution paths are
+/// kept, so the learned shape of the distribution is preserved. The number of kicks can be
+/// obtained from [`CMAES::kick_count`][crate::CMAES::kick_count].
+///
+/// Unlike [`LocalReinflate`][crate::restart::LocalReinflate], which only reinflates the step size
+/// once a run has terminated and continues from the same mean, a kick also displaces the mean
+/// (so the search continues from a different point of the same basin or a neighboring one) and
+/// happens during the run, before any termination criterion is met. Larger values of `strength`
+/// move further away from the current mean and therefore lose more of the progress made so far.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutoKick {
+    /// The number of generations without improvement of the overall best function value after
+    /// which to kick. Must be at least `1`.
+    pub threshold: usize,
+    /// The length of the displacement of the mean relative to a typical sampled step, and the
+    /// relative increase of the step size. Must be positive.
+    pub strength: f64,
+}
+
+impl AutoKick {
+    /// Returns a new `AutoKick` with the provided settings.
+    pub fn new(threshold: usize, strength: f64) -> Self {
+        Self {
+            threshold,
+            strength,
+        }
+    }
+
+    /// Returns whether the settings are valid
+    pub(crate) fn is_valid(&self) -> bool {
+        self.threshold >= 1 && self.strength.is_finite() && self.strength > 0.0
+    }
+}
+
 /// Parameters of the termination criteria
 #[derive(Clone, Debug)]
 pub(crate) struct TerminationParameters {
@@ -21,29 +121,55 @@ pub(crate) struct TerminationParameters {
     pub max_generations: Option<usize>,
     /// Value for the MaxTime termination criterion (disabled if `None`)
     pub max_time: Option<Duration>,
+    /// Value for the ImprovementTimeout termination criterion (disabled if `None`)
+    pub improvement_timeout: Option<Duration>,
     /// Value for the FunTarget termination criterion (disabled if `None`)
     pub fun_target: Option<f64>,
+    /// How the FunTarget termination criterion is handled
+    pub fun_target_mode: TargetMode,
     /// Value for the TolFun termination criterion
     pub tol_fun: f64,
+    /// Whether the TolFun threshold is raised according to earlier improvements
+    pub adaptive_tol_fun: bool,
     /// Value for the TolFunRel termination criterion
     pub tol_fun_rel: f64,
     /// Value for the TolFunHist termination criterion
     pub tol_fun_hist: f64,
     /// Value for the TolX termination criterion
     pub tol_x: f64,
+    /// Value for the TargetSigma termination criterion (disabled if `None`)
+    pub target_sigma: Option<f64>,
+    /// Value for the TolDiversity termination criterion (disabled if `None`)
+    pub tol_diversity: Option<f64>,
+    /// Number of generations for the TolDiversity termination criterion
+    pub tol_diversity_generations: usize,
+    /// Number of generations for the FlatFunction termination criterion (disabled if `None`)
+    pub flat_function_generations: Option<usize>,
+    /// Whether to check for the ConstantFunction termination criterion
+    pub validate_objective: bool,
+    /// Region for the MeanOutOfRegion termination criterion (disabled if `None`)
+    pub mean_region: Option<(DVector<f64>, DVector<f64>)>,
+    /// Value for the TolImprovementRate termination criterion (disabled if `None`)
+    pub tol_improvement_rate: Option<f64>,
+    /// Value for the PrematureConvergence termination criterion (disabled if `None`)
+    pub max_sigma_decay_ratio: Option<f64>,
     /// Minimum value for the TolStagnation termination criterion
     pub tol_stagnation: usize,
     /// Value for the TolXUp termination criterion
     pub tol_x_up: f64,
     /// Value for the TolConditionCov termination criterion
     pub tol_condition_cov: f64,
+    /// Number of generations before the convergence-based termination criteria apply
+    pub min_generations: usize,
 }
 
 impl TerminationParameters {
     /// Initializes the `TerminationParameters` with the parameters set in `options`
     pub(crate) fn from_options(options: &CMAESOptions) -> Self {
         let dimensions = options.initial_mean.len();
-        let tol_x = options.tol_x.unwrap_or(1e-12 * options.initial_step_size);
+        let tol_x = options
+            .tol_x
+            .unwrap_or(options.relative_tol_x * options.resolved_initial_step_size());
         let default_tol_stagnation =
             termination::get_default_tol_stagnation_option(dimensions, options.population_size);
         let tol_stagnation = options.tol_stagnation.unwrap_or(default_tol_stagnation);
@@ -52,14 +178,74 @@ impl TerminationParameters {
             max_function_evals: options.max_function_evals,
             max_generations: options.max_generations,
             max_time: options.max_time,
+            improvement_timeout: options.improvement_timeout,
             fun_target: options.fun_target,
+            fun_target_mode: options.fun_target_mode,
             tol_fun: options.tol_fun,
+            adaptive_tol_fun: options.adaptive_tol_fun,
             tol_fun_rel: options.tol_fun_rel,
             tol_fun_hist: options.tol_fun_hist,
             tol_x,
+            target_sigma: options.target_sigma,
+            tol_diversity: options.tol_diversity,
+            tol_diversity_generations: options.tol_diversity_generations,
+            flat_function_generations: options.flat_function_generations,
+            validate_objective: options.validate_objective,
+            mean_region: options.mean_region.clone(),
+            tol_improvement_rate: options.tol_improvement_rate,
+            max_sigma_decay_ratio: options.max_sigma_decay_ratio,
             tol_stagnation,
             tol_x_up: options.tol_x_up,
             tol_condition_cov: options.tol_condition_cov,
+            min_generations: options.min_generations,
+        }
+    }
+}
+
+/// The parameters that depend on the population size
+struct PopulationParameters {
+    mu: usize,
+    mu_eff: f64,
+    weights: FinalWeights,
+    cc: f64,
+    c1: f64,
+    cs: f64,
+    cmu: f64,
+    damp_s: f64,
+}
+
+impl PopulationParameters {
+    /// Calculates the parameters for the given problem dimension, population size, and weights
+    /// setting
+    fn new(dim: usize, lambda: usize, weights: Weights) -> Self {
+        let initial_weights = InitialWeights::new(lambda, weights);
+        let mu = initial_weights.mu();
+        let mu_eff = initial_weights.mu_eff();
+
+        // Covariance matrix adaptation
+        let a_cov = 2.0;
+        let cc = (4.0 + mu_eff / dim as f64) / (dim as f64 + 4.0 + 2.0 * mu_eff / dim as f64);
+        let c1 = a_cov / ((dim as f64 + 1.3).powi(2) + mu_eff);
+        let cmu = (1.0 - c1).min(
+            a_cov * (mu_eff - 2.0 + 1.0 / mu_eff)
+                / ((dim as f64 + 2.0).powi(2) + a_cov * mu_eff / 2.0),
+        );
+
+        let weights = initial_weights.finalize(dim, c1, cmu);
+
+        // Step size adaptation
+        let cs = (mu_eff + 2.0) / (dim as f64 + mu_eff + 5.0);
+        let damp_s = 1.0 + cs + 2.0 * (((mu_eff - 1.0) / (dim as f64 + 1.0)).sqrt() - 1.0).max(0.0);
+
+        Self {
+            mu,
+            mu_eff,
+            weights,
+            cc,
+            c1,
+            cs,
+            cmu,
+            damp_s,
         }
     }
 }
@@ -78,10 +264,16 @@ pub struct Parameters {
     mu: usize,
     /// Initial value for sigma
     initial_sigma: f64,
+    /// Scale of the initial covariance matrix
+    initial_covariance_scale: f64,
     /// Variance-effective selection mass
     mu_eff: f64,
     /// Individual weights
     weights: FinalWeights,
+    /// How the weights of the selected individuals are determined each generation
+    recombination_weighting: RecombinationWeighting,
+    /// Function applied to the raw function values before computing the value-based weights
+    fitness_transform: Option<FitnessTransform>,
     /// Learning rate for rank-one update cumulation
     cc: f64,
     /// Learning rate for rank-one update
@@ -92,8 +284,28 @@ pub struct Parameters {
     cmu: f64,
     /// Learning rate for the mean
     cm: f64,
+    /// Maximum factor by which the step size may change in a single generation
+    max_sigma_change: Option<f64>,
+    /// Smallest allowed ratio between the smallest and largest eigenvalue of the covariance matrix
+    min_eigenvalue_ratio: Option<f64>,
     /// Damping parameter for step size update
     damp_s: f64,
+    /// Box bounds on the search space
+    bounds: Option<Bounds>,
+    /// Transform between the coordinates of the objective function and the search space
+    transform: Option<CoordinateTransform>,
+    /// Factor that function values are divided by in the histories and tolerance checks
+    value_scale: f64,
+    /// Maximum number of generations kept in each per-generation recording
+    max_recorded_generations: Option<usize>,
+    /// Whether to cache the objective function values of evaluated points
+    cache_evaluations: bool,
+    /// Whether to evaluate the mean of the distribution after each generation
+    evaluate_mean: bool,
+    /// Settings for growing the population size during the run
+    adaptive_population: Option<AdaptivePopulation>,
+    /// Settings for displacing the mean when progress stalls
+    auto_kick: Option<AutoKick>,
     /// Parameters of the termination criteria
     termination: TerminationParameters,
     /// Seed for the RNG
@@ -110,45 +322,57 @@ impl Parameters {
         termination: TerminationParameters,
     ) -> Self {
         let dim = options.initial_mean.len();
-        let initial_weights = InitialWeights::new(options.population_size, options.weights);
-        let mu = initial_weights.mu();
-        let mu_eff = initial_weights.mu_eff();
-
-        // Covariance matrix adaptation
-        let a_cov = 2.0;
-        let cc = (4.0 + mu_eff / dim as f64) / (dim as f64 + 4.0 + 2.0 * mu_eff / dim as f64);
-        let c1 = a_cov / ((dim as f64 + 1.3).powi(2) + mu_eff);
-        let cmu = (1.0 - c1).min(
-            a_cov * (mu_eff - 2.0 + 1.0 / mu_eff)
-                / ((dim as f64 + 2.0).powi(2) + a_cov * mu_eff / 2.0),
-        );
-
-        let final_weights = initial_weights.finalize(dim, c1, cmu);
-
-        // Step size adaptation
-        let cs = (mu_eff + 2.0) / (dim as f64 + mu_eff + 5.0);
-        let damp_s = 1.0 + cs + 2.0 * (((mu_eff - 1.0) / (dim as f64 + 1.0)).sqrt() - 1.0).max(0.0);
+        let population = PopulationParameters::new(dim, options.population_size, options.weights);
 
         Parameters {
             mode: options.mode,
             dim,
             lambda: options.population_size,
-            mu,
-            initial_sigma: options.initial_step_size,
-            mu_eff,
-            weights: final_weights,
-            cc,
-            c1,
-            cs,
-            cmu,
+            mu: population.mu,
+            initial_sigma: options.resolved_initial_step_size(),
+            initial_covariance_scale: options.initial_covariance_scale,
+            mu_eff: population.mu_eff,
+            weights: population.weights,
+            recombination_weighting: options.recombination_weighting,
+            fitness_transform: options.fitness_transform.clone(),
+            cc: population.cc,
+            c1: population.c1,
+            cs: population.cs,
+            cmu: population.cmu,
             cm: options.cm,
-            damp_s,
+            max_sigma_change: options.max_sigma_change,
+            min_eigenvalue_ratio: options.min_eigenvalue_ratio,
+            damp_s: population.damp_s,
+            bounds: options.bounds.clone(),
+            transform: options.transform.clone(),
+            value_scale: options.value_scale,
+            max_recorded_generations: options.max_recorded_generations,
+            cache_evaluations: options.cache_evaluations,
+            evaluate_mean: options.evaluate_mean,
+            adaptive_population: options.adaptive_population,
+            auto_kick: options.auto_kick,
             termination,
             seed,
             parallel_update: options.parallel_update,
         }
     }
 
+    /// Changes the population size, recalculating the weights and all parameters that depend on
+    /// it
+    pub(crate) fn set_lambda(&mut self, lambda: usize) {
+        let population = PopulationParameters::new(self.dim, lambda, self.weights.setting());
+
+        self.lambda = lambda;
+        self.mu = population.mu;
+        self.mu_eff = population.mu_eff;
+        self.weights = population.weights;
+        self.cc = population.cc;
+        self.c1 = population.c1;
+        self.cs = population.cs;
+        self.cmu = population.cmu;
+        self.damp_s = population.damp_s;
+    }
+
     /// Calculates and returns a new set of `Parameters` from the provided options
     pub(crate) fn from_options(options: &CMAESOptions, seed: u64) -> Self {
         Self::new(options, seed, TerminationParameters::from_options(options))
@@ -164,7 +388,8 @@ impl Parameters {
         self.dim
     }
 
-    /// Returns the population size `lambda`.
+    /// Returns the population size `lambda`. This may change during the run if
+    /// [`AdaptivePopulation`] is enabled.
     pub fn lambda(&self) -> usize {
         self.lambda
     }
@@ -179,6 +404,12 @@ impl Parameters {
         self.initial_sigma
     }
 
+    /// Returns the scale of the initial covariance matrix (see
+    /// [`CMAESOptions::initial_covariance_scale`][crate::CMAESOptions::initial_covariance_scale]).
+    pub fn initial_covariance_scale(&self) -> f64 {
+        self.initial_covariance_scale
+    }
+
     /// Returns the variance-effective selection mass `mu_eff`.
     pub fn mu_eff(&self) -> f64 {
         self.mu_eff
@@ -189,11 +420,46 @@ impl Parameters {
         &self.weights
     }
 
+    /// Returns the number of individuals with positive weights, which are recombined to form the
+    /// new mean. This is always equal to [`mu`][Self::mu].
+    pub fn num_positive_weights(&self) -> usize {
+        self.weights.num_positive()
+    }
+
+    /// Returns the number of individuals with negative weights, which are used for the active
+    /// downdate of the covariance matrix. This is `0` unless [`Weights::Negative`] is used, in
+    /// which case it is `lambda - mu` for even population sizes and `lambda - mu - 1` for odd
+    /// population sizes (the middle individual has a weight of zero).
+    pub fn num_negative_weights(&self) -> usize {
+        self.weights.num_negative()
+    }
+
+    /// Returns the settings for growing the population size during the run, if enabled.
+    pub fn adaptive_population(&self) -> Option<AdaptivePopulation> {
+        self.adaptive_population
+    }
+
+    /// Returns the settings for displacing the mean when progress stalls, if enabled.
+    pub fn auto_kick(&self) -> Option<AutoKick> {
+        self.auto_kick
+    }
+
     /// Returns the setting used for calculating the weights.
     pub fn weights_setting(&self) -> Weights {
         self.weights.setting()
     }
 
+    /// Returns how the weights of the selected individuals are determined each generation.
+    pub fn recombination_weighting(&self) -> RecombinationWeighting {
+        self.recombination_weighting
+    }
+
+    /// Returns the function applied to the raw function values before computing the value-based
+    /// weights, if set.
+    pub fn fitness_transform(&self) -> Option<&FitnessTransform> {
+        self.fitness_transform.as_ref()
+    }
+
     /// Returns the learning rate for rank-one update cumulation `cc`.
     pub fn cc(&self) -> f64 {
         self.cc
@@ -214,16 +480,81 @@ impl Parameters {
         self.cmu
     }
 
-    /// Returns the learning rate for the mean update `cm`.
+    /// Returns the learning rate for the mean update `cm`. If
+    /// [`CMAESOptions::cm_schedule`][crate::CMAESOptions::cm_schedule] is set, this is the value
+    /// used in the latest generation.
     pub fn cm(&self) -> f64 {
         self.cm
     }
 
+    /// Changes the learning rate for the mean update
+    pub(crate) fn set_cm(&mut self, cm: f64) {
+        self.cm = cm;
+    }
+
+    /// Replaces the parameters of the termination criteria
+    pub(crate) fn set_termination(&mut self, termination: TerminationParameters) {
+        self.termination = termination;
+    }
+
+    /// Returns the maximum factor by which the step size may change in a single generation, if
+    /// limited.
+    pub fn max_sigma_change(&self) -> Option<f64> {
+        self.max_sigma_change
+    }
+
+    /// Returns the smallest allowed ratio between the smallest and the largest eigenvalue of the
+    /// covariance matrix, if limited.
+    pub fn min_eigenvalue_ratio(&self) -> Option<f64> {
+        self.min_eigenvalue_ratio
+    }
+
     /// Returns the damping factor for the step size update `damp_s`.
     pub fn damp_s(&self) -> f64 {
         self.damp_s
     }
 
+    /// Returns the box bounds on the search space, if set.
+    pub fn bounds(&self) -> Option<&Bounds> {
+        self.bounds.as_ref()
+    }
+
+    /// Returns the transform between the coordinates of the objective function and the search
+    /// space, if set.
+    pub fn transform(&self) -> Option<&CoordinateTransform> {
+        self.transform.as_ref()
+    }
+
+    /// Returns the mapping from the internal search space to the space of the objective function
+    pub(crate) fn point_mapping(&self) -> PointMapping<'_> {
+        PointMapping {
+            bounds: self.bounds(),
+            transform: self.transform(),
+        }
+    }
+
+    /// Returns the factor that function values are divided by in the histories and tolerance
+    /// checks.
+    pub fn value_scale(&self) -> f64 {
+        self.value_scale
+    }
+
+    /// Returns the maximum number of generations kept in each per-generation recording, if
+    /// limited.
+    pub fn max_recorded_generations(&self) -> Option<usize> {
+        self.max_recorded_generations
+    }
+
+    /// Returns whether the objective function values of evaluated points are cached.
+    pub fn cache_evaluations(&self) -> bool {
+        self.cache_evaluations
+    }
+
+    /// Returns whether the mean of the distribution is evaluated after each generation.
+    pub fn evaluate_mean(&self) -> bool {
+        self.evaluate_mean
+    }
+
     /// Returns the value for the
     /// [`TerminationReason::MaxFunctionEvals`][crate::TerminationReason::MaxFunctionEvals]
     /// termination criterion.
@@ -244,18 +575,38 @@ impl Parameters {
         self.termination.max_time
     }
 
+    /// Returns the value for the
+    /// [`TerminationReason::ImprovementTimeout`][crate::TerminationReason::ImprovementTimeout]
+    /// termination criterion.
+    pub fn improvement_timeout(&self) -> Option<Duration> {
+        self.termination.improvement_timeout
+    }
+
     /// Returns the value for the
     /// [`TerminationReason::FunTarget`][crate::TerminationReason::FunTarget] termination criterion.
     pub fn fun_target(&self) -> Option<f64> {
         self.termination.fun_target
     }
 
+    /// Returns how the [`TerminationReason::FunTarget`][crate::TerminationReason::FunTarget]
+    /// termination criterion is handled.
+    pub fn fun_target_mode(&self) -> TargetMode {
+        self.termination.fun_target_mode
+    }
+
     /// Returns the value for the [`TerminationReason::TolFun`][crate::TerminationReason::TolFun]
     /// termination criterion.
     pub fn tol_fun(&self) -> f64 {
         self.termination.tol_fun
     }
 
+    /// Returns whether the threshold of the
+    /// [`TerminationReason::TolFun`][crate::TerminationReason::TolFun] termination criterion is
+    /// raised according to earlier improvements.
+    pub fn adaptive_tol_fun(&self) -> bool {
+        self.termination.adaptive_tol_fun
+    }
+
     /// Returns the value for the
     /// [`TerminationReason::TolFunRel`][crate::TerminationReason::TolFunRel] termination criterion.
     pub fn tol_fun_rel(&self) -> f64 {
@@ -275,6 +626,65 @@ impl Parameters {
         self.termination.tol_x
     }
 
+    /// Returns the value for the
+    /// [`TerminationReason::TargetSigma`][crate::TerminationReason::TargetSigma] termination
+    /// criterion.
+    pub fn target_sigma(&self) -> Option<f64> {
+        self.termination.target_sigma
+    }
+
+    /// Returns the value for the
+    /// [`TerminationReason::TolDiversity`][crate::TerminationReason::TolDiversity] termination
+    /// criterion.
+    pub fn tol_diversity(&self) -> Option<f64> {
+        self.termination.tol_diversity
+    }
+
+    /// Returns the number of consecutive generations used by the
+    /// [`TerminationReason::TolDiversity`][crate::TerminationReason::TolDiversity] termination
+    /// criterion.
+    pub fn tol_diversity_generations(&self) -> usize {
+        self.termination.tol_diversity_generations
+    }
+
+    /// Returns the number of consecutive generations used by the
+    /// [`TerminationReason::FlatFunction`][crate::TerminationReason::FlatFunction] termination
+    /// criterion, if enabled.
+    pub fn flat_function_generations(&self) -> Option<usize> {
+        self.termination.flat_function_generations
+    }
+
+    /// Returns whether the
+    /// [`TerminationReason::ConstantFunction`][crate::TerminationReason::ConstantFunction]
+    /// termination criterion is checked.
+    pub fn validate_objective(&self) -> bool {
+        self.termination.validate_objective
+    }
+
+    /// Returns the lower and upper corners of the region used by the
+    /// [`TerminationReason::MeanOutOfRegion`][crate::TerminationReason::MeanOutOfRegion]
+    /// termination criterion, if enabled.
+    pub fn mean_region(&self) -> Option<(&DVector<f64>, &DVector<f64>)> {
+        self.termination
+            .mean_region
+            .as_ref()
+            .map(|(lower, upper)| (lower, upper))
+    }
+
+    /// Returns the value for the
+    /// [`TerminationReason::TolImprovementRate`][crate::TerminationReason::TolImprovementRate]
+    /// termination criterion.
+    pub fn tol_improvement_rate(&self) -> Option<f64> {
+        self.termination.tol_improvement_rate
+    }
+
+    /// Returns the value for the
+    /// [`TerminationReason::PrematureConvergence`][crate::TerminationReason::PrematureConvergence]
+    /// termination criterion.
+    pub fn max_sigma_decay_ratio(&self) -> Option<f64> {
+        self.termination.max_sigma_decay_ratio
+    }
+
     /// Returns the minimum value for the
     /// [`TerminationReason::TolStagnation`][crate::TerminationReason::TolStagnation] termination
     /// criterion.
@@ -295,6 +705,13 @@ impl Parameters {
         self.termination.tol_condition_cov
     }
 
+    /// Returns the number of generations that must be completed before the convergence-based
+    /// termination criteria apply (see
+    /// [`CMAESOptions::min_generations`][crate::CMAESOptions::min_generations]).
+    pub fn min_generations(&self) -> usize {
+        self.termination.min_generations
+    }
+
     /// Returns the seed for the RNG.
     pub fn seed(&self) -> u64 {
         self.seed
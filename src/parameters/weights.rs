@@ -6,6 +6,7 @@ use std::ops::Deref;
 
 /// The distribution of weights for the population. The default value is `Negative`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Weights {
     /// Weights are higher for higher-ranked selected individuals and are zero for the rest of the
     /// population. Usually performs slightly worse than `Negative`.
@@ -18,12 +19,60 @@ pub enum Weights {
     Uniform,
 }
 
+impl Weights {
+    /// Returns the recommended weights setting for the given population size. This is the default
+    /// used by [`CMAESOptions::new`][crate::CMAESOptions::new].
+    ///
+    /// `Negative` is recommended in almost all cases. For population sizes below `4`, only a single
+    /// individual is selected each generation, so the rank-mu update (the only place where negative
+    /// weights have an effect) is effectively disabled and `Positive` is recommended instead to
+    /// avoid needlessly risking the positive-definiteness of the covariance matrix.
+    pub fn recommended(population_size: usize) -> Self {
+        if population_size < 4 {
+            Self::Positive
+        } else {
+            Self::Negative
+        }
+    }
+
+    /// Returns the variance-effective selection mass `mu_eff` of these weights for the given
+    /// population size, `(sum w_i)^2 / sum(w_i^2)` over the positive weights. It lies between `1`
+    /// (all weight on the best individual) and `mu = population_size / 2` (equal weights). See
+    /// [`CMAESOptions::min_mu_eff`][crate::CMAESOptions::min_mu_eff] for the recommended range.
+    pub fn mu_eff(self, population_size: usize) -> f64 {
+        InitialWeights::new(population_size, self).mu_eff()
+    }
+}
+
 impl Default for Weights {
     fn default() -> Self {
         Self::Negative
     }
 }
 
+/// How the weights of the selected individuals are determined each generation. The default value
+/// is `Rank`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecombinationWeighting {
+    /// The weights depend only on the rank of each individual and are given by [`Weights`]. This
+    /// makes the algorithm invariant to any order-preserving transformation of the objective
+    /// function and is the most robust choice.
+    #[default]
+    Rank,
+    /// The weights of the `mu` selected individuals are proportional to how much better their
+    /// objective function value is than that of the best non-selected individual (or of the worst
+    /// individual if none are non-selected), normalized by the range of these differences in the
+    /// generation. This may converge faster on some smooth problems but is only invariant to
+    /// affine (rather than all order-preserving) transformations of the objective function.
+    ///
+    /// Only the positive weights are replaced; negative weights from [`Weights::Negative`] remain
+    /// rank-based. The learning rates and `mu_eff` are still calculated from the rank-based
+    /// weights. If all selected individuals have the same value, the rank-based weights are used
+    /// for that generation.
+    Value,
+}
+
 /// Initial distribution of weights, before normalization
 #[derive(Clone, Debug)]
 pub(super) struct InitialWeights {
@@ -146,6 +195,70 @@ impl FinalWeights {
     pub fn setting(&self) -> Weights {
         self.setting
     }
+
+    /// Returns the number of positive weights
+    pub fn num_positive(&self) -> usize {
+        self.weights.iter().filter(|w| **w > 0.0).count()
+    }
+
+    /// Returns the number of negative weights
+    pub fn num_negative(&self) -> usize {
+        self.weights.iter().filter(|w| **w < 0.0).count()
+    }
+}
+
+/// Returns the weights to use for recombining `values` (the sorted objective function values of
+/// the generation, best first) given the rank-based final weights and the number of selected
+/// individuals
+pub(crate) fn recombination_weights(
+    weighting: RecombinationWeighting,
+    rank_weights: &DVector<f64>,
+    mu: usize,
+    values: &[f64],
+) -> DVector<f64> {
+    let mut weights = rank_weights.clone();
+
+    if let (RecombinationWeighting::Value, Some(best)) = (weighting, values.first()) {
+        // Value of the best non-selected individual
+        let reference = values[mu.min(values.len() - 1)];
+        let range = (best - reference).abs();
+
+        if range > 0.0 {
+            let differences = values
+                .iter()
+                .take(mu)
+                .map(|v| (v - reference).abs() / range)
+                .collect::<Vec<_>>();
+            let sum = differences.iter().sum::<f64>();
+
+            for (w, d) in weights.iter_mut().zip(differences) {
+                *w = d / sum;
+            }
+        }
+    }
+
+    weights
+}
+
+/// Sets the weights of the unrankable individuals (all individuals after the first
+/// `num_rankable`) to zero and renormalizes the remaining positive weights to sum to 1
+pub(crate) fn discard_unrankable_weights(weights: &mut DVector<f64>, num_rankable: usize) {
+    if num_rankable >= weights.len() {
+        return;
+    }
+
+    for w in weights.iter_mut().skip(num_rankable) {
+        *w = 0.0;
+    }
+
+    let sum_positive_weights = weights.iter().filter(|w| **w > 0.0).sum::<f64>();
+    if sum_positive_weights > 0.0 {
+        for w in weights.iter_mut() {
+            if *w > 0.0 {
+                *w /= sum_positive_weights;
+            }
+        }
+    }
 }
 
 impl Deref for FinalWeights {
@@ -199,4 +312,119 @@ mod tests {
             assert!(final_weights.iter().skip(mu).all(|w| *w <= 0.0));
         }
     }
+
+    #[test]
+    fn test_recombination_weights() {
+        let lambda = 6;
+        let rank_weights = InitialWeights::new(lambda, Weights::Negative).finalize(4, 0.2, 0.3);
+        let mu = 3;
+        let values = [1.0, 2.0, 4.0, 5.0, 6.0, 7.0];
+
+        let rank = recombination_weights(RecombinationWeighting::Rank, &rank_weights, mu, &values);
+        assert_eq!(*rank_weights, rank);
+
+        // Differences from the reference value of 5.0 are 4, 3, and 1
+        let value =
+            recombination_weights(RecombinationWeighting::Value, &rank_weights, mu, &values);
+        assert_approx_eq!(0.5, value[0], 1e-12);
+        assert_approx_eq!(0.375, value[1], 1e-12);
+        assert_approx_eq!(0.125, value[2], 1e-12);
+        assert_eq!(
+            rank_weights.rows(mu, lambda - mu),
+            value.rows(mu, lambda - mu)
+        );
+
+        // Invariant to affine transformations, including reversing the order for maximization
+        let transformed = values.map(|v| -3.0 * v + 100.0);
+        let value_transformed = recombination_weights(
+            RecombinationWeighting::Value,
+            &rank_weights,
+            mu,
+            &transformed,
+        );
+        for i in 0..lambda {
+            assert_approx_eq!(value[i], value_transformed[i], 1e-12);
+        }
+
+        // Equal values fall back to rank-based weights
+        let value_equal =
+            recombination_weights(RecombinationWeighting::Value, &rank_weights, mu, &[1.0; 6]);
+        assert_eq!(*rank_weights, value_equal);
+    }
+
+    #[test]
+    fn test_discard_unrankable_weights() {
+        let lambda = 8;
+        let rank_weights = InitialWeights::new(lambda, Weights::Negative).finalize(4, 0.2, 0.3);
+
+        // Only the last negative weights are discarded
+        let mut weights = (*rank_weights).clone();
+        discard_unrankable_weights(&mut weights, 6);
+        for i in 0..6 {
+            assert_approx_eq!(rank_weights[i], weights[i], 1e-12);
+        }
+        assert!(weights.iter().skip(6).all(|w| *w == 0.0));
+
+        // Some positive weights are discarded
+        let mut weights = (*rank_weights).clone();
+        discard_unrankable_weights(&mut weights, 2);
+        assert_approx_eq!(1.0, weights.iter().take(2).sum::<f64>(), 1e-12);
+        assert_approx_eq!(
+            rank_weights[0] / rank_weights[1],
+            weights[0] / weights[1],
+            1e-12
+        );
+        assert!(weights.iter().skip(2).all(|w| *w == 0.0));
+
+        // No individuals are rankable
+        let mut weights = (*rank_weights).clone();
+        discard_unrankable_weights(&mut weights, 0);
+        assert!(weights.iter().all(|w| *w == 0.0));
+
+        // All individuals are rankable
+        let mut weights = (*rank_weights).clone();
+        discard_unrankable_weights(&mut weights, lambda);
+        assert_eq!(*rank_weights, weights);
+    }
+
+    #[test]
+    fn test_num_weights() {
+        for lambda in 4..50 {
+            let mu = lambda / 2;
+            for setting in [Weights::Uniform, Weights::Positive] {
+                let weights = InitialWeights::new(lambda, setting).finalize(4, 0.2, 0.3);
+                assert_eq!(mu, weights.num_positive());
+                assert_eq!(0, weights.num_negative());
+            }
+
+            // The middle weight is zero for odd population sizes
+            let weights = InitialWeights::new(lambda, Weights::Negative).finalize(4, 0.2, 0.3);
+            assert_eq!(mu, weights.num_positive());
+            assert_eq!(lambda - mu - lambda % 2, weights.num_negative());
+        }
+    }
+
+    #[test]
+    fn test_weights_mu_eff() {
+        assert_eq!(1.0, Weights::Negative.mu_eff(2));
+        assert_eq!(1.0, Weights::Positive.mu_eff(3));
+        for lambda in [4, 8, 20, 100] {
+            let mu = (lambda / 2) as f64;
+            let mu_eff = Weights::Negative.mu_eff(lambda);
+            assert_eq!(mu_eff, Weights::Positive.mu_eff(lambda));
+            assert!(mu_eff > 1.0 && mu_eff < mu);
+            assert_approx_eq!(mu, Weights::Uniform.mu_eff(lambda), 1e-12);
+        }
+        assert_approx_eq!(2.6001788261131793, Weights::Negative.mu_eff(8), 1e-12);
+    }
+
+    #[test]
+    fn test_weights_recommended() {
+        for lambda in 2..4 {
+            assert_eq!(Weights::Positive, Weights::recommended(lambda));
+        }
+        for lambda in [4, 6, 10, 100, 10000] {
+            assert_eq!(Weights::Negative, Weights::recommended(lambda));
+        }
+    }
 }
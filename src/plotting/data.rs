@@ -1,5 +1,7 @@
 //! Handling of adding and storing data points for the plot
 
+use std::collections::VecDeque;
+
 use super::utils::apply_offset;
 use crate::history::History;
 use crate::state::State;
@@ -9,30 +11,36 @@ use crate::utils::partial_cmp;
 #[derive(Clone, Debug)]
 pub struct PlotData {
     /// Function evals at which other data points were recorded
-    function_evals: Vec<usize>,
-    best_function_value: Vec<f64>,
-    median_function_value: Vec<f64>,
-    sigma: Vec<f64>,
-    axis_ratio: Vec<f64>,
+    function_evals: VecDeque<usize>,
+    best_function_value: VecDeque<f64>,
+    median_function_value: VecDeque<f64>,
+    /// Interquartile range of the function values of each generation
+    interquartile_range: VecDeque<f64>,
+    sigma: VecDeque<f64>,
+    axis_ratio: VecDeque<f64>,
+    /// Generalized volume of the distribution (`NAN` if it is not finite)
+    distribution_volume: VecDeque<f64>,
     // Each element of the following contains the histories of an individual dimension
-    mean_dimensions: Vec<Vec<f64>>,
-    sqrt_eigenvalues: Vec<Vec<f64>>,
+    mean_dimensions: Vec<VecDeque<f64>>,
+    sqrt_eigenvalues: Vec<VecDeque<f64>>,
     // Standard deviation in each coordinate axis (without sigma)
-    coord_axis_scales: Vec<Vec<f64>>,
+    coord_axis_scales: Vec<VecDeque<f64>>,
 }
 
 impl PlotData {
     /// Creates an empty `PlotData`
     pub fn new(dimensions: usize) -> Self {
         Self {
-            function_evals: Vec::new(),
-            best_function_value: Vec::new(),
-            median_function_value: Vec::new(),
-            sigma: Vec::new(),
-            axis_ratio: Vec::new(),
-            mean_dimensions: (0..dimensions).map(|_| Vec::new()).collect(),
-            sqrt_eigenvalues: (0..dimensions).map(|_| Vec::new()).collect(),
-            coord_axis_scales: (0..dimensions).map(|_| Vec::new()).collect(),
+            function_evals: VecDeque::new(),
+            best_function_value: VecDeque::new(),
+            median_function_value: VecDeque::new(),
+            interquartile_range: VecDeque::new(),
+            sigma: VecDeque::new(),
+            axis_ratio: VecDeque::new(),
+            distribution_volume: VecDeque::new(),
+            mean_dimensions: (0..dimensions).map(|_| VecDeque::new()).collect(),
+            sqrt_eigenvalues: (0..dimensions).map(|_| VecDeque::new()).collect(),
+            coord_axis_scales: (0..dimensions).map(|_| VecDeque::new()).collect(),
         }
     }
 
@@ -51,44 +59,54 @@ impl PlotData {
         self.len() == 0
     }
 
-    pub fn function_evals(&self) -> &[usize] {
+    pub fn function_evals(&self) -> &VecDeque<usize> {
         &self.function_evals
     }
 
-    pub fn best_function_value(&self) -> &[f64] {
+    pub fn best_function_value(&self) -> &VecDeque<f64> {
         &self.best_function_value
     }
 
-    pub fn median_function_value(&self) -> &[f64] {
+    pub fn median_function_value(&self) -> &VecDeque<f64> {
         &self.median_function_value
     }
 
-    pub fn sigma(&self) -> &[f64] {
+    pub fn interquartile_range(&self) -> &VecDeque<f64> {
+        &self.interquartile_range
+    }
+
+    pub fn sigma(&self) -> &VecDeque<f64> {
         &self.sigma
     }
 
-    pub fn axis_ratio(&self) -> &[f64] {
+    pub fn axis_ratio(&self) -> &VecDeque<f64> {
         &self.axis_ratio
     }
 
-    pub fn mean_dimensions(&self) -> &[Vec<f64>] {
+    pub fn distribution_volume(&self) -> &VecDeque<f64> {
+        &self.distribution_volume
+    }
+
+    pub fn mean_dimensions(&self) -> &[VecDeque<f64>] {
         &self.mean_dimensions
     }
 
-    pub fn sqrt_eigenvalues(&self) -> &[Vec<f64>] {
+    pub fn sqrt_eigenvalues(&self) -> &[VecDeque<f64>] {
         &self.sqrt_eigenvalues
     }
 
-    pub fn coord_axis_scales(&self) -> &[Vec<f64>] {
+    pub fn coord_axis_scales(&self) -> &[VecDeque<f64>] {
         &self.coord_axis_scales
     }
 
-    /// Adds a data point to the plot from the current state
+    /// Adds a data point to the plot from the current state, discarding the oldest data point if
+    /// more than `max_len` are stored
     pub fn add_data_point(
         &mut self,
         current_function_evals: usize,
         state: &State,
         history: &History,
+        max_len: Option<usize>,
     ) {
         let best_function_value = history
             .current_best_individual()
@@ -103,38 +121,91 @@ impl PlotData {
             // later
             .unwrap_or(f64::NAN);
 
-        self.function_evals.push(current_function_evals);
+        let interquartile_range = history
+            .current_quartiles()
+            .map(|quartiles| quartiles.interquartile_range())
+            // Filtered later like the median function value
+            .unwrap_or(f64::NAN);
+
+        self.function_evals.push_back(current_function_evals);
         self.best_function_value
-            .push(apply_offset(best_function_value));
+            .push_back(apply_offset(best_function_value));
         self.median_function_value
-            .push(apply_offset(median_function_value));
-        self.sigma.push(apply_offset(state.sigma()));
-
-        self.axis_ratio.push(apply_offset(state.axis_ratio()));
+            .push_back(apply_offset(median_function_value));
+        self.interquartile_range
+            .push_back(apply_offset(interquartile_range));
+        self.sigma.push_back(apply_offset(state.sigma()));
+
+        self.axis_ratio.push_back(apply_offset(state.axis_ratio()));
+
+        // An overflowed volume would break the y-axis range, so filter it like a missing value
+        let distribution_volume = state.distribution_volume();
+        self.distribution_volume
+            .push_back(if distribution_volume.is_finite() {
+                apply_offset(distribution_volume)
+            } else {
+                f64::NAN
+            });
 
         let mean = state.mean();
         for (i, x) in mean.iter().enumerate() {
-            self.mean_dimensions[i].push(*x);
+            self.mean_dimensions[i].push_back(*x);
         }
 
         let mut sqrt_eigenvalues = state.cov_sqrt_eigenvalues().diagonal();
         let sorted_sqrt_eigenvalues = sqrt_eigenvalues.as_mut_slice();
         sorted_sqrt_eigenvalues.sort_by(|a, b| partial_cmp(*a, *b));
         for (i, x) in sorted_sqrt_eigenvalues.iter().enumerate() {
-            self.sqrt_eigenvalues[i].push(apply_offset(*x));
+            self.sqrt_eigenvalues[i].push_back(apply_offset(*x));
         }
 
         let cov_diagonal = state.cov().diagonal();
         let coord_axis_scales = cov_diagonal.iter().map(|x| x.sqrt());
         for (i, x) in coord_axis_scales.enumerate() {
-            self.coord_axis_scales[i].push(apply_offset(x));
+            self.coord_axis_scales[i].push_back(apply_offset(x));
+        }
+
+        if let Some(max_len) = max_len {
+            self.discard_oldest(self.len().saturating_sub(max_len));
+        }
+    }
+
+    /// Discards the `n` oldest data points in each history
+    fn discard_oldest(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+
+        let discard = |data: &mut VecDeque<f64>| {
+            data.drain(..n);
+        };
+
+        self.function_evals.drain(..n);
+
+        discard(&mut self.best_function_value);
+        discard(&mut self.median_function_value);
+        discard(&mut self.interquartile_range);
+        discard(&mut self.sigma);
+        discard(&mut self.axis_ratio);
+        discard(&mut self.distribution_volume);
+
+        for x in &mut self.mean_dimensions {
+            discard(x);
+        }
+
+        for x in &mut self.coord_axis_scales {
+            discard(x);
+        }
+
+        for x in &mut self.sqrt_eigenvalues {
+            discard(x);
         }
     }
 
     /// Clears the plot except for the most recent data point in each history (note that the
     /// memory is not actually freed; it is only cleared for reuse).
     pub fn clear(&mut self) {
-        let clear = |data: &mut Vec<_>| {
+        let clear = |data: &mut VecDeque<_>| {
             let len = data.len();
             data.swap(0, len - 1);
             data.truncate(1);
@@ -146,8 +217,10 @@ impl PlotData {
 
         clear(&mut self.best_function_value);
         clear(&mut self.median_function_value);
+        clear(&mut self.interquartile_range);
         clear(&mut self.sigma);
         clear(&mut self.axis_ratio);
+        clear(&mut self.distribution_volume);
 
         for x in &mut self.mean_dimensions {
             clear(x);
@@ -4,18 +4,20 @@ use plotters::chart::{ChartBuilder, ChartContext, SeriesAnno, SeriesLabelPositio
 use plotters::coord;
 use plotters::coord::cartesian::Cartesian2d;
 use plotters::coord::combinators::{IntoLogRange, LogCoord};
+use plotters::coord::ranged1d::Ranged;
 use plotters::coord::ranged1d::{AsRangedCoord, ValueFormatter};
 use plotters::coord::types::RangedCoordusize;
 use plotters::drawing::{DrawingArea, DrawingAreaErrorKind};
-use plotters::element::{Cross, PathElement};
+use plotters::element::{Cross, PathElement, Polygon};
 use plotters::prelude::DrawingBackend;
 use plotters::series::LineSeries;
-use plotters::style::{colors, Color, Palette, Palette99};
+use plotters::style::{colors, Color, Palette, Palette99, RGBColor};
 
 use std::ops::Range;
 
 use super::data::PlotData;
 use super::options::PlotOptions;
+use super::overlay::ConvergenceOverlay;
 use super::utils::apply_offset;
 use super::{Backend, DrawingError};
 use crate::utils::partial_cmp;
@@ -25,6 +27,8 @@ use crate::Mode;
 const FONT: &str = "sans-serif";
 /// The maximum number of elements to allow in a legend before removing it
 const MAX_LEGEND_VALUES: usize = 18;
+/// The color of the distribution volume, which is not among the predefined colors
+const ORANGE: RGBColor = RGBColor(255, 140, 0);
 
 /// Parameters for a y-axis
 struct YAxis<Y> {
@@ -58,7 +62,7 @@ enum YAxisKind {
 }
 
 /// Draws all single-dimensioned data to the drawing area (abs(f - best), abs(f), abs(median),
-/// sigma, axis ratio)
+/// interquartile range, sigma, axis ratio, distribution volume)
 pub fn draw_single_dimensioned<'a>(
     mode: Mode,
     data: &PlotData,
@@ -91,13 +95,17 @@ pub fn draw_single_dimensioned<'a>(
 
     let abs_best_value = data.best_function_value().iter().map(|y| y.abs());
     let abs_median_value = data.median_function_value().iter().map(|y| y.abs());
+    let interquartile_range = data.interquartile_range().iter().cloned();
 
     // Excludes a few values to not break the y-axis range
     let mut all_y_values = abs_best_value
         .clone()
         .chain(abs_median_value.clone())
+        // A zero range (e.g. on a plateau) would stretch the axis down to the offset
+        .chain(interquartile_range.clone().filter(|y| *y > 1e-20))
         .chain(data.sigma().iter().cloned())
         .chain(data.axis_ratio().iter().cloned())
+        .chain(data.distribution_volume().iter().cloned())
         .collect::<Vec<_>>();
 
     if let Some(dist) = dist_to_best.clone() {
@@ -172,6 +180,14 @@ pub fn draw_single_dimensioned<'a>(
             colors::MAGENTA,
         );
 
+        // Interquartile range of function values
+        let points_interquartile_range = get_points(function_evals.clone(), interquartile_range);
+        add_to_legend(
+            context.draw_series(LineSeries::new(points_interquartile_range, &colors::BLACK))?,
+            "IQR",
+            colors::BLACK,
+        );
+
         // Sigma
         let points_sigma = get_points(function_evals.clone(), data.sigma().iter().cloned());
         add_to_legend(
@@ -188,13 +204,24 @@ pub fn draw_single_dimensioned<'a>(
             "Axis Ratio",
             colors::RED,
         );
+
+        // Distribution volume
+        let points_distribution_volume = get_points(
+            function_evals.clone(),
+            data.distribution_volume().iter().cloned(),
+        );
+        add_to_legend(
+            context.draw_series(LineSeries::new(points_distribution_volume, &ORANGE))?,
+            "Volume",
+            ORANGE,
+        );
         Ok(())
     };
 
     DrawingAreaSetup {
         area,
-        function_evals_history: data.function_evals(),
-        caption: "abs(f - best), abs(f), abs(median) Sigma, Axis Ratio",
+        function_evals_bounds: get_function_evals_bounds(data.function_evals()),
+        caption: "abs(f - best), abs(f), abs(median), IQR, Sigma, Axis Ratio, Volume",
         legend_position: Some(SeriesLabelPosition::LowerLeft),
         y_axis,
         draw,
@@ -237,7 +264,7 @@ pub fn draw_mean<'a>(
 
     DrawingAreaSetup {
         area,
-        function_evals_history: data.function_evals(),
+        function_evals_bounds: get_function_evals_bounds(data.function_evals()),
         caption: "Mean",
         legend_position,
         y_axis,
@@ -268,7 +295,7 @@ pub fn draw_sqrt_eigenvalues<'a>(
 
     DrawingAreaSetup {
         area,
-        function_evals_history: data.function_evals(),
+        function_evals_bounds: get_function_evals_bounds(data.function_evals()),
         caption: "Distribution Axis Scales",
         legend_position: None,
         y_axis,
@@ -311,7 +338,7 @@ pub fn draw_coord_axis_scales<'a>(
 
     DrawingAreaSetup {
         area,
-        function_evals_history: data.function_evals(),
+        function_evals_bounds: get_function_evals_bounds(data.function_evals()),
         caption: "Coord. Axis Standard Deviations (without sigma)",
         legend_position,
         y_axis,
@@ -320,12 +347,88 @@ pub fn draw_coord_axis_scales<'a>(
     .configure_area()
 }
 
+/// Draws the median and interquartile range of the convergence curves of multiple runs to the
+/// drawing area
+pub fn draw_convergence_overlay<'a>(
+    overlay: &ConvergenceOverlay,
+    area: &DrawingArea<Backend, coord::Shift>,
+) -> Result<(), DrawingAreaErrorKind<<Backend<'a> as DrawingBackend>::ErrorType>> {
+    area.fill(&colors::WHITE)?;
+
+    let all_y_values = overlay
+        .lower_quartile()
+        .iter()
+        .chain(overlay.upper_quartile())
+        .cloned();
+    let caption = "Convergence (Median and Interquartile Range)";
+
+    // Function values can only be drawn on a log scale if they are all positive
+    if all_y_values.clone().all(|y| y > 0.0) {
+        DrawingAreaSetup {
+            area,
+            function_evals_bounds: get_function_evals_bounds(overlay.function_evals()),
+            caption,
+            legend_position: Some(SeriesLabelPosition::UpperRight),
+            y_axis: get_log_y_axis(all_y_values),
+            draw: |context: &mut ChartContext<_, _>| draw_quartiles(overlay, context),
+        }
+        .configure_area()
+    } else {
+        DrawingAreaSetup {
+            area,
+            function_evals_bounds: get_function_evals_bounds(overlay.function_evals()),
+            caption,
+            legend_position: Some(SeriesLabelPosition::UpperRight),
+            y_axis: get_linear_y_axis(all_y_values, true),
+            draw: |context: &mut ChartContext<_, _>| draw_quartiles(overlay, context),
+        }
+        .configure_area()
+    }
+}
+
+/// Draws the median of the convergence curves and a shaded band between their quartiles
+fn draw_quartiles<'a, 'b, Y: Ranged<ValueType = f64>>(
+    overlay: &ConvergenceOverlay,
+    context: &mut ChartContext<'a, Backend<'b>, Cartesian2d<RangedCoordusize, Y>>,
+) -> Result<(), DrawingError<'a>> {
+    let function_evals = overlay.function_evals().iter().cloned();
+    let band = get_points(
+        function_evals.clone(),
+        overlay.lower_quartile().iter().cloned(),
+    )
+    .chain(
+        get_points(
+            function_evals.clone(),
+            overlay.upper_quartile().iter().cloned(),
+        )
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev(),
+    )
+    .collect::<Vec<_>>();
+    let band_color = colors::BLUE.mix(0.2);
+    add_to_legend(
+        context.draw_series(std::iter::once(Polygon::new(band, band_color.filled())))?,
+        "interquartile range",
+        band_color,
+    );
+
+    let points = get_points(function_evals, overlay.median().iter().cloned());
+    add_to_legend(
+        context.draw_series(LineSeries::new(points, &colors::BLUE))?,
+        &format!("median of {} runs", overlay.num_runs()),
+        colors::BLUE,
+    );
+
+    Ok(())
+}
+
 /// Stores parameters for configuring a drawing area
 struct DrawingAreaSetup<'a, 'b, Y, F> {
     // The drawing area to configure
     area: &'a DrawingArea<Backend<'b>, coord::Shift>,
-    // The function evals history
-    function_evals_history: &'a [usize],
+    // The first and last function evals of the history
+    function_evals_bounds: (usize, usize),
     // The caption for the drawing area
     caption: &'static str,
     // The position of the legend (disabled if `None`)
@@ -348,8 +451,7 @@ where
     /// Creates a `ChartContext` with a common style, configures it according to the stored options,
     /// and calls `draw` to draw to it
     fn configure_area(self) -> Result<(), DrawingError<'a>> {
-        let x_start = *self.function_evals_history.first().unwrap();
-        let x_end = *self.function_evals_history.last().unwrap();
+        let (x_start, x_end) = self.function_evals_bounds;
         let x_range = x_start..(x_end as f64 * 1.05) as usize;
 
         let y_label_formatter = |v: &f64| match self.y_axis.kind {
@@ -405,6 +507,18 @@ fn add_to_legend<C: Color + 'static>(annotation: &mut SeriesAnno<Backend>, label
         .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &color));
 }
 
+/// Returns the first and last function evals of a history
+fn get_function_evals_bounds<'a, I>(function_evals: I) -> (usize, usize)
+where
+    I: IntoIterator<Item = &'a usize>,
+    I::IntoIter: DoubleEndedIterator + Clone,
+{
+    let mut function_evals = function_evals.into_iter();
+    let first = *function_evals.clone().next().unwrap();
+    let last = *function_evals.next_back().unwrap();
+    (first, last)
+}
+
 /// Returns an iterator of (x, y) points with NAN y points filtered out
 fn get_points<'a, X, Y>(x: X, y: Y) -> impl Iterator<Item = (usize, f64)> + 'a
 where
@@ -476,7 +590,6 @@ fn get_linear_y_axis<I: Iterator<Item = f64> + Clone>(
 #[cfg(test)]
 mod tests {
     use assert_approx_eq::assert_approx_eq;
-    use plotters::coord::ranged1d::Ranged;
 
     use super::*;
 
@@ -1,11 +1,14 @@
-//! Types for plotting support. See [`Plot`] for usage and what is plotted.
+//! Types for plotting support. See [`Plot`] for usage and what is plotted, and
+//! [`ConvergenceOverlay`] for comparing the convergence of multiple runs.
 
 mod data;
 mod draw;
 mod options;
+mod overlay;
 mod utils;
 
 pub use options::PlotOptions;
+pub use overlay::ConvergenceOverlay;
 
 use plotters::coord;
 use plotters::drawing::{DrawingArea, DrawingAreaErrorKind, IntoDrawingArea};
@@ -36,14 +39,16 @@ pub const PLOT_WIDTH: u32 = 1200;
 /// Data plot for the algorithm. Can be obtained by calling
 /// [`CMAES::get_plot`][crate::CMAES::get_plot] or
 /// [`CMAES::get_mut_plot`][crate::CMAES::get_mut_plot] and should be saved with
-/// [`save_to_file`][`Self::save_to_file`]. Configuration is done using [`PlotOptions`]. To enable
-/// the plot, use [`CMAESOptions::enable_plot`][crate::CMAESOptions::enable_plot].
+/// [`save_to_file`][`Self::save_to_file`] or rendered in memory with
+/// [`render_to_buffer`][`Self::render_to_buffer`]. Configuration is done using [`PlotOptions`].
+/// To enable the plot, use [`CMAESOptions::enable_plot`][crate::CMAESOptions::enable_plot].
 ///
 /// Plots for each iteration the:
 /// - Distance of best value from the overall best objective function value
 /// - Absolute best objective function value
 /// - Absolute median objective function value
 /// - Distribution axis ratio
+/// - Generalized volume of the distribution (`sigma^N * sqrt(det(C))`)
 /// - Distribution mean
 /// - Scaling of each distribution axis
 /// - Standard deviation in each coordinate axis (without sigma)
@@ -81,20 +86,34 @@ pub struct Plot {
     /// Like last_data_point_evals, but tracks the generation number of the last data point
     /// recorded
     last_data_point_generation: Option<usize>,
+    /// The maximum number of data points to store, if limited
+    max_data_points: Option<usize>,
 }
 
 impl Plot {
-    /// Initializes an empty `Plot` with the provided options.
-    pub(crate) fn new(dimensions: usize, options: PlotOptions, mode: Mode) -> Self {
+    /// Initializes an empty `Plot` with the provided options that stores at most
+    /// `max_data_points` data points (if limited).
+    pub(crate) fn new(
+        dimensions: usize,
+        options: PlotOptions,
+        mode: Mode,
+        max_data_points: Option<usize>,
+    ) -> Self {
         Self {
             data: PlotData::new(dimensions),
             options,
             mode,
             last_data_point_evals: None,
             last_data_point_generation: None,
+            max_data_points,
         }
     }
 
+    /// Replaces the options of the plot, keeping the recorded data points.
+    pub(crate) fn set_options(&mut self, options: PlotOptions) {
+        self.options = options;
+    }
+
     /// Returns the next time a data point should be recorded, in function evals.
     pub(crate) fn get_next_data_point_evals(&self) -> usize {
         match self.last_data_point_evals {
@@ -116,7 +135,7 @@ impl Plot {
         };
         if !already_added {
             self.data
-                .add_data_point(current_function_evals, state, history);
+                .add_data_point(current_function_evals, state, history, self.max_data_points);
             self.last_data_point_evals = Some(current_function_evals);
             self.last_data_point_generation = Some(state.generation());
         }
@@ -129,24 +148,26 @@ impl Plot {
         path: P,
         create_dirs: bool,
     ) -> Result<(), PlotError> {
-        let path = path.as_ref();
-        if create_dirs {
-            if let Some(parent) = path.parent() {
-                DirBuilder::new().recursive(true).create(parent)?;
-            }
-        }
+        save_to_file(path.as_ref(), create_dirs, |area| self.draw_plot(area))
+    }
 
-        let plot = self.build_plot(&path)?;
-        plot.present().map_err(Into::into)
+    /// Renders the data plot to an in-memory [`PlotImage`] instead of a file. Useful for
+    /// displaying the plot in a GUI or passing its frames to a video encoder.
+    ///
+    /// Rendering draws every recorded data point, so its cost grows with [`len`][Self::len]. For
+    /// long runs, rendering every generation can take much longer than the algorithm itself; it
+    /// may be necessary to render less often, increase
+    /// [`PlotOptions::min_gap_evals`][crate::PlotOptions::min_gap_evals], or call
+    /// [`clear`][Self::clear] periodically.
+    pub fn render_to_buffer(&self) -> Result<PlotImage, PlotError<'_>> {
+        render_to_buffer(|area| self.draw_plot(area))
     }
 
-    /// Builds the data plot and returns it (does not save to a file)
-    fn build_plot<'a, P: AsRef<Path> + 'a>(
+    /// Draws the data plot to the drawing area
+    fn draw_plot<'a>(
         &self,
-        path: &'a P,
-    ) -> Result<DrawingArea<Backend<'a>, coord::Shift>, DrawingError> {
-        let root_area = Backend::new(path, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
-
+        root_area: &DrawingArea<Backend<'a>, coord::Shift>,
+    ) -> Result<(), DrawingError<'a>> {
         root_area.fill(&colors::WHITE)?;
 
         let mut child_drawing_areas = root_area.split_evenly((2, 2)).into_iter();
@@ -160,10 +181,12 @@ impl Plot {
         draw::draw_sqrt_eigenvalues(&self.data, &bottom_left)?;
         draw::draw_coord_axis_scales(&self.data, &bottom_right)?;
 
-        Ok(root_area)
+        Ok(())
     }
 
-    /// Returns the number of data points currently stored in the plot.
+    /// Returns the number of data points currently stored in the plot. This is at most
+    /// [`CMAESOptions::max_recorded_generations`][crate::CMAESOptions::max_recorded_generations]
+    /// if it is set, in which case the oldest data points are discarded first.
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -186,6 +209,61 @@ impl Plot {
     }
 }
 
+/// Saves a plot drawn by `draw` to a bitmap image file, recursively creating the necessary
+/// directories if `create_dirs` is `true`
+fn save_to_file<'a, D>(path: &Path, create_dirs: bool, draw: D) -> Result<(), PlotError<'a>>
+where
+    D: for<'b> FnOnce(&DrawingArea<Backend<'b>, coord::Shift>) -> Result<(), DrawingError<'b>>,
+{
+    if create_dirs {
+        if let Some(parent) = path.parent() {
+            DirBuilder::new().recursive(true).create(parent)?;
+        }
+    }
+
+    let root_area = Backend::new(&path, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
+    draw(&root_area)?;
+    root_area.present().map_err(Into::into)
+}
+
+/// Renders a plot drawn by `draw` to an in-memory image
+fn render_to_buffer<'a, D>(draw: D) -> Result<PlotImage, PlotError<'a>>
+where
+    D: for<'b> FnOnce(&DrawingArea<Backend<'b>, coord::Shift>) -> Result<(), DrawingError<'b>>,
+{
+    let mut rgb = vec![0; PLOT_WIDTH as usize * PLOT_HEIGHT as usize * 3];
+    {
+        let root_area =
+            Backend::with_buffer(&mut rgb, (PLOT_WIDTH, PLOT_HEIGHT)).into_drawing_area();
+        draw(&root_area)?;
+        root_area.present()?;
+    }
+
+    let pixels = rgb
+        .chunks_exact(3)
+        .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+        .collect();
+
+    Ok(PlotImage {
+        width: PLOT_WIDTH,
+        height: PLOT_HEIGHT,
+        pixels,
+    })
+}
+
+/// An image of the data plot rendered in memory. Obtained by calling
+/// [`Plot::render_to_buffer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlotImage {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The pixels of the image in row-major order, each stored as four bytes (RGBA, with the alpha
+    /// channel always fully opaque).
+    pub pixels: Vec<u8>,
+}
+
 /// An error produced while creating or saving a plot.
 #[derive(Debug)]
 pub enum PlotError<'a> {
@@ -270,6 +348,29 @@ mod tests {
         assert!(plot.save_to_file(get_plot_path("test_plot"), true).is_ok());
     }
 
+    #[test]
+    fn test_render_to_buffer() {
+        let mut state = CMAESOptions::new(vec![1.0; 4], 1.0)
+            .enable_plot(PlotOptions::new(0, false))
+            .build(|x: &DVector<f64>| x.magnitude())
+            .unwrap();
+
+        for _ in 0..10 {
+            let _ = state.next();
+        }
+
+        let image = state.get_plot().unwrap().render_to_buffer().unwrap();
+
+        assert_eq!((PLOT_WIDTH, PLOT_HEIGHT), (image.width, image.height));
+        assert_eq!(
+            PLOT_WIDTH as usize * PLOT_HEIGHT as usize * 4,
+            image.pixels.len()
+        );
+        assert!(image.pixels.chunks_exact(4).all(|p| p[3] == u8::MAX));
+        // Something other than the white background was drawn
+        assert!(image.pixels.iter().any(|c| *c != u8::MAX));
+    }
+
     #[test]
     fn test_redundant_plot() {
         let mut state = CMAESOptions::new(vec![1.0; 10], 1.0)
@@ -0,0 +1,187 @@
+//! Comparison of the convergence curves of multiple runs
+
+use std::path::Path;
+
+use super::{draw, PlotError, PlotImage};
+use crate::utils::quantile;
+
+/// A plot of the convergence curves of multiple runs (e.g. repeated runs of the same problem or
+/// runs with different configurations), overlaid as the median curve with a shaded band between
+/// the first and third quartiles. Created from the curves returned by
+/// [`CMAES::convergence_curve`][crate::CMAES::convergence_curve] and saved with
+/// [`save_to_file`][Self::save_to_file] or rendered in memory with
+/// [`render_to_buffer`][Self::render_to_buffer].
+///
+/// Each convergence curve is treated as a step function of the number of function evaluations
+/// (the overall best function value holds until the next entry). The quartiles are computed at
+/// every number of function evaluations recorded by any run, over the runs that have recorded at
+/// least one entry by then. Runs that finished earlier keep contributing their final value.
+///
+/// A log scale is used for the function values if they are all positive, and a linear scale
+/// otherwise.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cmaes::plotting::ConvergenceOverlay;
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let sphere = |x: &DVector<f64>| x.iter().map(|xi| xi.powi(2)).sum();
+/// let curves = (0..10)
+///     .map(|_| {
+///         let mut state = CMAESOptions::new(vec![1.0; 10], 1.0).build(sphere).unwrap();
+///         let _ = state.run();
+///         state.convergence_curve()
+///     })
+///     .collect::<Vec<_>>();
+///
+/// let overlay = ConvergenceOverlay::new(&curves).unwrap();
+/// overlay.save_to_file("overlay.png", true).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConvergenceOverlay {
+    /// The numbers of function evaluations at which the quartiles were computed
+    function_evals: Vec<usize>,
+    /// The first quartile of the function values at each number of function evaluations
+    lower_quartile: Vec<f64>,
+    /// The median of the function values at each number of function evaluations
+    median: Vec<f64>,
+    /// The third quartile of the function values at each number of function evaluations
+    upper_quartile: Vec<f64>,
+    /// The number of runs with at least one entry
+    num_runs: usize,
+}
+
+impl ConvergenceOverlay {
+    /// Computes the median and quartiles of the convergence curves, each of which is a list of
+    /// (function evaluations, overall best function value) pairs sorted by function evaluations
+    /// as returned by [`CMAES::convergence_curve`][crate::CMAES::convergence_curve].
+    ///
+    /// Returns `None` if none of the curves have any entries.
+    pub fn new(curves: &[Vec<(usize, f64)>]) -> Option<Self> {
+        let curves = curves
+            .iter()
+            .filter(|curve| !curve.is_empty())
+            .collect::<Vec<_>>();
+        if curves.is_empty() {
+            return None;
+        }
+
+        let mut function_evals = curves
+            .iter()
+            .flat_map(|curve| curve.iter().map(|(evals, _)| *evals))
+            .collect::<Vec<_>>();
+        function_evals.sort_unstable();
+        function_evals.dedup();
+
+        let mut lower_quartile = Vec::with_capacity(function_evals.len());
+        let mut median = Vec::with_capacity(function_evals.len());
+        let mut upper_quartile = Vec::with_capacity(function_evals.len());
+        // The index of the latest entry of each curve at the current number of function
+        // evaluations
+        let mut positions = vec![None; curves.len()];
+
+        for &evals in &function_evals {
+            let mut values = Vec::with_capacity(curves.len());
+            for (curve, position) in curves.iter().zip(positions.iter_mut()) {
+                let mut next = position.map_or(0, |p| p + 1);
+                while next < curve.len() && curve[next].0 <= evals {
+                    *position = Some(next);
+                    next += 1;
+                }
+                if let Some(p) = *position {
+                    values.push(curve[p].1);
+                }
+            }
+
+            values.sort_unstable_by(f64::total_cmp);
+            lower_quartile.push(quantile(&values, 0.25));
+            median.push(quantile(&values, 0.5));
+            upper_quartile.push(quantile(&values, 0.75));
+        }
+
+        Some(Self {
+            function_evals,
+            lower_quartile,
+            median,
+            upper_quartile,
+            num_runs: curves.len(),
+        })
+    }
+
+    /// Returns the numbers of function evaluations at which the quartiles were computed.
+    pub fn function_evals(&self) -> &[usize] {
+        &self.function_evals
+    }
+
+    /// Returns the first quartile of the function values at each number of function evaluations.
+    pub fn lower_quartile(&self) -> &[f64] {
+        &self.lower_quartile
+    }
+
+    /// Returns the median of the function values at each number of function evaluations.
+    pub fn median(&self) -> &[f64] {
+        &self.median
+    }
+
+    /// Returns the third quartile of the function values at each number of function evaluations.
+    pub fn upper_quartile(&self) -> &[f64] {
+        &self.upper_quartile
+    }
+
+    /// Returns the number of runs included in the overlay (those with at least one entry).
+    pub fn num_runs(&self) -> usize {
+        self.num_runs
+    }
+
+    /// Saves the plot to a bitmap image file. Recursively creates the necessary directories if
+    /// `create_dirs` is `true`.
+    pub fn save_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        create_dirs: bool,
+    ) -> Result<(), PlotError<'_>> {
+        super::save_to_file(path.as_ref(), create_dirs, |area| {
+            draw::draw_convergence_overlay(self, area)
+        })
+    }
+
+    /// Renders the plot to an in-memory [`PlotImage`] instead of a file.
+    pub fn render_to_buffer(&self) -> Result<PlotImage, PlotError<'_>> {
+        super::render_to_buffer(|area| draw::draw_convergence_overlay(self, area))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convergence_overlay() {
+        assert!(ConvergenceOverlay::new(&[]).is_none());
+        assert!(ConvergenceOverlay::new(&[vec![]]).is_none());
+
+        let curves = vec![
+            vec![(10, 4.0), (20, 2.0), (30, 1.0)],
+            vec![(10, 8.0), (20, 8.0)],
+            vec![(15, 6.0), (25, 3.0)],
+            vec![],
+        ];
+        let overlay = ConvergenceOverlay::new(&curves).unwrap();
+
+        assert_eq!(3, overlay.num_runs());
+        assert_eq!(&[10, 15, 20, 25, 30], overlay.function_evals());
+        // The third run starts at 15, and finished runs keep their final value
+        assert_eq!(&[6.0, 6.0, 6.0, 3.0, 3.0], overlay.median());
+        assert_eq!(&[5.0, 5.0, 4.0, 2.5, 2.0], overlay.lower_quartile());
+        assert_eq!(&[7.0, 7.0, 7.0, 5.5, 5.5], overlay.upper_quartile());
+
+        let image = overlay.render_to_buffer().unwrap();
+        assert!(image.pixels.iter().any(|c| *c != u8::MAX));
+
+        // Non-positive values use a linear scale
+        let overlay =
+            ConvergenceOverlay::new(&[vec![(10, -1.0), (20, -2.0)], vec![(10, 0.0)]]).unwrap();
+        assert!(overlay.render_to_buffer().is_ok());
+    }
+}
@@ -0,0 +1,258 @@
+//! Recording and replaying the random samples of a run, for example to track down differences
+//! between runs on different platforms. See
+//! [`CMAESOptions::record_samples`][crate::CMAESOptions::record_samples] for full documentation.
+//! Also contains the periodic recording of the covariance matrix to files enabled by
+//! [`CMAESOptions::record_covariance`][crate::CMAESOptions::record_covariance] and the export of
+//! the trajectory by [`CMAES::write_trajectory_csv`][crate::CMAES::write_trajectory_csv].
+
+use nalgebra::DVector;
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use crate::history::TrajectoryEntry;
+use crate::matrix::SquareMatrix;
+use crate::sampling::EvaluatedPoint;
+use crate::state::State;
+
+/// The placeholder in the path template of
+/// [`CMAESOptions::record_covariance`][crate::CMAESOptions::record_covariance] that is replaced
+/// with the generation number
+pub(crate) const GENERATION_PLACEHOLDER: &str = "{generation}";
+
+/// The samples of a single generation, as recorded by
+/// [`CMAESOptions::record_samples`][crate::CMAESOptions::record_samples] and replayed by
+/// [`CMAESOptions::replay_samples`][crate::CMAESOptions::replay_samples].
+///
+/// Each vector has one entry per individual, in the order in which the individuals were sampled
+/// (not sorted by function value), and each entry has one value per dimension.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationSamples {
+    /// The samples drawn from the standard normal distribution `N(0, I)`. These are the only
+    /// values that depend on the RNG; every other quantity of the run is computed from them.
+    pub standard_normal: Vec<Vec<f64>>,
+    /// The points that were evaluated (in the space of the objective function), computed from
+    /// `standard_normal` and the current mean, step size, and covariance matrix.
+    pub points: Vec<Vec<f64>>,
+}
+
+impl GenerationSamples {
+    /// Returns the recorded samples of a generation from the standard normal samples and the
+    /// evaluated points (in sampling order)
+    pub(crate) fn new(standard_normal: Vec<DVector<f64>>, points: &[EvaluatedPoint]) -> Self {
+        Self {
+            standard_normal: standard_normal
+                .into_iter()
+                .map(|z| z.as_slice().to_vec())
+                .collect(),
+            points: points
+                .iter()
+                .map(|point| point.point().as_slice().to_vec())
+                .collect(),
+        }
+    }
+
+    /// Returns the index of the first individual whose point differs from that of `other` by more
+    /// than `tol` in any coordinate (or that is missing from one of them), or `None` if all points
+    /// match. Useful for comparing recordings of the same run made on different machines.
+    pub fn first_divergence(&self, other: &Self, tol: f64) -> Option<usize> {
+        let matches = |a: &Vec<f64>, b: &Vec<f64>| {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() <= tol)
+        };
+
+        self.points
+            .iter()
+            .zip(&other.points)
+            .position(|(a, b)| !matches(a, b))
+            .or_else(|| {
+                (self.points.len() != other.points.len())
+                    .then(|| self.points.len().min(other.points.len()))
+            })
+    }
+}
+
+/// Writes the covariance matrix of the distribution to a file every `every` generations
+#[derive(Clone, Debug)]
+pub(crate) struct CovarianceRecorder {
+    every: usize,
+    path_template: String,
+    /// The generation at which the matrix is written next
+    next_generation: usize,
+    /// The error of the latest failed write, if any
+    last_error: Option<Arc<io::Error>>,
+}
+
+impl CovarianceRecorder {
+    pub fn new(every: usize, path_template: String) -> Self {
+        Self {
+            every,
+            path_template,
+            next_generation: every,
+            last_error: None,
+        }
+    }
+
+    /// Returns the error of the latest failed write, if any
+    pub fn last_error(&self) -> Option<&io::Error> {
+        self.last_error.as_deref()
+    }
+
+    /// Returns the path of the file for `generation`
+    fn path(&self, generation: usize) -> String {
+        self.path_template
+            .replace(GENERATION_PLACEHOLDER, &generation.to_string())
+    }
+
+    /// Writes the covariance matrix of `state` if it is due in the current generation, storing the
+    /// error if the file cannot be written
+    pub fn record(&mut self, state: &State) {
+        let generation = state.generation();
+        if generation < self.next_generation {
+            return;
+        }
+        self.next_generation = generation + self.every;
+
+        let path = self.path(generation);
+        let cov = state.sigma().powi(2) * state.cov();
+        if let Err(err) = write_matrix(&path, &cov) {
+            self.last_error = Some(Arc::new(err));
+        }
+    }
+}
+
+/// The header of the CSV written by [`write_trajectory_csv`]
+const TRAJECTORY_HEADER: &str =
+    "generation,function_evals,best_value,median_value,sigma,axis_ratio,mean_norm";
+
+/// Writes the header and one row per entry of `trajectory` to `writer` in CSV format
+pub(crate) fn write_trajectory_csv<W: Write>(
+    mut writer: W,
+    trajectory: &VecDeque<TrajectoryEntry>,
+) -> io::Result<()> {
+    writeln!(writer, "{}", TRAJECTORY_HEADER)?;
+    for entry in trajectory {
+        writeln!(
+            writer,
+            "{},{},{:e},{:e},{:e},{:e},{:e}",
+            entry.generation,
+            entry.function_evals,
+            entry.best_value,
+            entry.median_value,
+            entry.sigma,
+            entry.axis_ratio,
+            entry.mean_norm,
+        )?;
+    }
+    writer.flush()
+}
+
+/// Writes `matrix` to `path` with one line per row and the values of each row separated by commas
+fn write_matrix(path: &str, matrix: &SquareMatrix<f64>) -> io::Result<()> {
+    let mut contents = String::new();
+    for row in matrix.row_iter() {
+        let values = row
+            .iter()
+            .map(|value| format!("{:e}", value))
+            .collect::<Vec<_>>();
+        // Writing to a `String` cannot fail
+        writeln!(contents, "{}", values.join(",")).unwrap();
+    }
+    fs::write(path, contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_trajectory_csv() {
+        let entry = |generation, best_value| TrajectoryEntry {
+            generation,
+            function_evals: 6 * generation,
+            best_value,
+            median_value: 2.0 * best_value,
+            sigma: 0.5,
+            axis_ratio: 1.0,
+            mean_norm: 1e-3,
+        };
+        let trajectory = VecDeque::from(vec![entry(1, 4.0), entry(2, -0.25)]);
+
+        let mut csv = Vec::new();
+        write_trajectory_csv(&mut csv, &trajectory).unwrap();
+
+        assert_eq!(
+            "generation,function_evals,best_value,median_value,sigma,axis_ratio,mean_norm\n\
+             1,6,4e0,8e0,5e-1,1e0,1e-3\n\
+             2,12,-2.5e-1,-5e-1,5e-1,1e0,1e-3\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_covariance_recorder() {
+        let dir = std::env::temp_dir().join(format!("cmaes_cov_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let template = dir.join("cov_{generation}.csv");
+        let mut recorder = CovarianceRecorder::new(2, template.to_str().unwrap().to_string());
+
+        let mut state = State::new(vec![0.0; 2].into(), 2.0);
+        state
+            .set_cov(
+                SquareMatrix::from_row_slice(2, 2, &[1.0, 0.5, 0.5, 1.0]),
+                None,
+            )
+            .unwrap();
+        for generation in 1..=4 {
+            *state.mut_generation() = generation;
+            recorder.record(&state);
+        }
+
+        assert!(!dir.join("cov_1.csv").exists());
+        assert!(!dir.join("cov_3.csv").exists());
+        for generation in [2, 4] {
+            let contents = fs::read_to_string(dir.join(format!("cov_{}.csv", generation))).unwrap();
+            assert_eq!("4e0,2e0\n2e0,4e0\n", contents);
+        }
+
+        assert!(recorder.last_error().is_none());
+
+        // Errors are stored instead of stopping the run
+        fs::remove_dir_all(&dir).unwrap();
+        *state.mut_generation() = 6;
+        recorder.record(&state);
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            recorder.last_error().unwrap().kind()
+        );
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let samples = |points: Vec<Vec<f64>>| GenerationSamples {
+            standard_normal: Vec::new(),
+            points,
+        };
+        let a = samples(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+
+        assert_eq!(None, a.first_divergence(&a, 0.0));
+        assert_eq!(
+            None,
+            a.first_divergence(
+                &samples(vec![vec![1.0, 2.0], vec![3.0, 4.0 + 1e-14]]),
+                1e-12
+            )
+        );
+        assert_eq!(
+            Some(1),
+            a.first_divergence(&samples(vec![vec![1.0, 2.0], vec![3.0, 4.1]]), 1e-12)
+        );
+        assert_eq!(
+            Some(1),
+            a.first_divergence(&samples(vec![vec![1.0, 2.0]]), 1e-12)
+        );
+    }
+}
@@ -108,13 +108,18 @@ impl BIPOP {
         &mut self,
         mut options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
         run: R,
         rng: &mut ChaChaRng,
     ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl) {
         // Configure the run
-        options.initial_step_size = self.get_initial_step_size_lr(search_range_size, rng);
+        options.initial_step_size = initial_step_size
+            .unwrap_or_else(|| self.get_initial_step_size_lr(search_range_size, rng));
         options.population_size = self.get_population_size_lr(options.population_size, rng);
+        options.adaptive_population = options
+            .adaptive_population
+            .map(|adaptive_population| adaptive_population.starting_at(options.population_size));
         // Half the most recent IPOP run's function evals is used as a limit for LR (if the
         // max_function_evals option isn't stricter)
         options.max_function_evals = options
@@ -136,12 +141,17 @@ impl BIPOP {
         &mut self,
         mut options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
         run: R,
     ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl) {
         // Configure the run
-        options.initial_step_size = self.get_initial_step_size_ipop(search_range_size);
+        options.initial_step_size =
+            initial_step_size.unwrap_or_else(|| self.get_initial_step_size_ipop(search_range_size));
         options.population_size *= self.ipop_current_multiplier;
+        options.adaptive_population = options
+            .adaptive_population
+            .map(|adaptive_population| adaptive_population.starting_at(options.population_size));
 
         // Execute the run
         let mut cmaes_state = options.build(objective_function).unwrap();
@@ -175,20 +185,38 @@ impl Strategy for BIPOP {
         false
     }
 
-    fn next_run<F, R: FnOnce(&mut CMAES<F>) -> TerminationData>(
+    fn max_runs(&self) -> Option<usize> {
+        None
+    }
+
+    fn next_run<F, R: FnMut(&mut CMAES<F>) -> TerminationData>(
         &mut self,
         options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
         run: R,
         rng: &mut ChaChaRng,
     ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl) {
         if self.ipop_runs != 0 && self.lr_function_evals < self.ipop_function_evals {
             // Use LR if it has used less function evals so far and this is not the first run
-            self.next_run_lr(options, search_range_size, objective_function, run, rng)
+            self.next_run_lr(
+                options,
+                search_range_size,
+                initial_step_size,
+                objective_function,
+                run,
+                rng,
+            )
         } else {
             // Use IPOP otherwise
-            self.next_run_ipop(options, search_range_size, objective_function, run)
+            self.next_run_ipop(
+                options,
+                search_range_size,
+                initial_step_size,
+                objective_function,
+                run,
+            )
         }
     }
 
@@ -276,6 +304,7 @@ mod tests {
             let (_, _, control) = bipop.next_run_lr(
                 CMAESOptions::new(vec![1.0; 2], 0.5),
                 1.0,
+                None,
                 function,
                 |state| state.run(),
                 &mut ChaChaRng::seed_from_u64(rand::random()),
@@ -297,6 +326,7 @@ mod tests {
             let (_, _, control) = bipop.next_run_ipop(
                 CMAESOptions::new(vec![1.0; 2], 0.5),
                 1.0,
+                None,
                 function,
                 |state| state.run(),
             );
@@ -317,6 +347,7 @@ mod tests {
         let _ = bipop.next_run(
             CMAESOptions::new(vec![1.0; 2], 0.5),
             1.0,
+            None,
             function,
             |state| state.run(),
             &mut ChaChaRng::seed_from_u64(rand::random()),
@@ -333,6 +364,7 @@ mod tests {
         let _ = bipop.next_run(
             CMAESOptions::new(vec![1.0; 2], 0.5),
             1.0,
+            None,
             function,
             |state| state.run(),
             &mut ChaChaRng::seed_from_u64(rand::random()),
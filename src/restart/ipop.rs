@@ -1,10 +1,11 @@
 //! An IPOP restart strategy, increasing the population size each restart
 
+use nalgebra::DVector;
 use rand_chacha::ChaChaRng;
 
-use super::strategy::{RestartControl, Strategy};
+use super::strategy::{RestartControl, RestartMean, Strategy};
 use super::InvalidRestartStrategyOptionsError;
-use crate::{CMAESOptions, TerminationData, TerminationReason, CMAES};
+use crate::{CMAESOptions, Individual, TerminationData, TerminationReason, CMAES};
 
 /// The default population size increase factor
 const DEFAULT_INCREASE_FACTOR: usize = 2;
@@ -26,6 +27,12 @@ pub struct IPOP {
     current_multiplier: usize,
     /// The factor by which to increase the population size each restart
     increase_factor: usize,
+    /// The source of the initial mean for each run
+    restart_mean: RestartMean,
+    /// The best individual found by any run so far
+    best: Option<Individual>,
+    /// The initial mean of the first run
+    first_mean: Option<DVector<f64>>,
 }
 
 impl IPOP {
@@ -42,10 +49,20 @@ impl IPOP {
                 runs: 0,
                 current_multiplier: 1,
                 increase_factor,
+                restart_mean: RestartMean::Random,
+                best: None,
+                first_mean: None,
             })
         }
     }
 
+    /// Changes the source of the initial mean for each run from the default value of
+    /// [`RestartMean::Random`] (see [`RestartMean`]).
+    pub fn restart_mean(mut self, restart_mean: RestartMean) -> Self {
+        self.restart_mean = restart_mean;
+        self
+    }
+
     /// Returns the initial step size to use for the given search range size
     fn get_initial_step_size(&self, search_range_size: f64) -> f64 {
         search_range_size / 2.0
@@ -63,17 +80,43 @@ impl Strategy for IPOP {
         false
     }
 
-    fn next_run<F, R: FnOnce(&mut CMAES<F>) -> TerminationData>(
+    fn max_runs(&self) -> Option<usize> {
+        Some(MAX_RUNS)
+    }
+
+    fn next_run<F, R: FnMut(&mut CMAES<F>) -> TerminationData>(
         &mut self,
         mut options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
-        run: R,
+        mut run: R,
         _: &mut ChaChaRng,
     ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl) {
         // Configure the run
-        options.initial_step_size = self.get_initial_step_size(search_range_size);
+        options.initial_step_size =
+            initial_step_size.unwrap_or_else(|| self.get_initial_step_size(search_range_size));
         options.population_size *= self.current_multiplier;
+        options.adaptive_population = options
+            .adaptive_population
+            .map(|adaptive_population| adaptive_population.starting_at(options.population_size));
+
+        match self.restart_mean {
+            RestartMean::Random => (),
+            RestartMean::PreviousBest => {
+                if let Some(ref best) = self.best {
+                    options.initial_mean = best.point.clone();
+                }
+            }
+            RestartMean::FixedInitial => {
+                options.initial_mean = self
+                    .first_mean
+                    .get_or_insert_with(|| options.initial_mean.clone())
+                    .clone();
+            }
+        }
+
+        let mode = options.mode;
 
         // Execute the run
         let mut cmaes_state = options.build(objective_function).unwrap();
@@ -83,6 +126,13 @@ impl Strategy for IPOP {
         self.runs += 1;
         self.current_multiplier *= self.increase_factor;
 
+        if let Some(run_best) = cmaes_state.overall_best_individual() {
+            match self.best {
+                Some(ref best) if !mode.is_better(run_best.value, best.value) => (),
+                _ => self.best = Some(run_best.clone()),
+            }
+        }
+
         let control = if self.runs >= MAX_RUNS {
             RestartControl::MaxRunsReached
         } else {
@@ -97,10 +147,16 @@ impl Strategy for IPOP {
     }
 
     fn get_parameters_as_strings(&self) -> Vec<(String, String)> {
-        [(
-            "increase_factor".to_string(),
-            format!("{}", self.increase_factor),
-        )]
+        [
+            (
+                "increase_factor".to_string(),
+                format!("{}", self.increase_factor),
+            ),
+            (
+                "restart_mean".to_string(),
+                format!("{:?}", self.restart_mean),
+            ),
+        ]
         .into()
     }
 }
@@ -111,6 +167,7 @@ mod tests {
     use rand::SeedableRng;
 
     use super::*;
+    use crate::AdaptivePopulation;
 
     #[test]
     fn test_new() {
@@ -137,6 +194,7 @@ mod tests {
             let (_, _, control) = ipop.next_run(
                 CMAESOptions::new(vec![1.0; 2], 0.5),
                 1.0,
+                None,
                 function,
                 |state| state.run(),
                 &mut ChaChaRng::seed_from_u64(rand::random()),
@@ -150,4 +208,64 @@ mod tests {
         assert_eq!(2usize.pow(10), ipop.current_multiplier);
         assert_eq!(2, ipop.increase_factor);
     }
+
+    #[test]
+    fn test_ipop_adaptive_population() {
+        let mut ipop = IPOP::default();
+        let function = |x: &DVector<f64>| x.magnitude();
+
+        // The population size of later runs exceeds the maximum of the adaptive population
+        for _ in 0..4 {
+            let (cmaes_state, _, _) = ipop.next_run(
+                CMAESOptions::new(vec![1.0; 2], 0.5)
+                    .adaptive_population(AdaptivePopulation::new(6, 12, 2.0))
+                    .max_generations(5),
+                1.0,
+                None,
+                function,
+                |state| state.run(),
+                &mut ChaChaRng::seed_from_u64(rand::random()),
+            );
+
+            assert!(cmaes_state.population_size() >= 6);
+        }
+    }
+
+    #[test]
+    fn test_restart_mean() {
+        let function = |x: &DVector<f64>| (x - DVector::from(vec![3.0; 2])).magnitude();
+        // Executes a run with the given default initial mean and returns the initial mean that was
+        // actually used and the best point found
+        let next_run = |ipop: &mut IPOP, default_initial_mean: f64| {
+            let mut initial_mean = None;
+            let (cmaes_state, _, _) = ipop.next_run(
+                CMAESOptions::new(vec![default_initial_mean; 2], 0.5).max_generations(5),
+                1.0,
+                None,
+                function,
+                |state| {
+                    initial_mean = Some(state.mean().clone());
+                    state.run()
+                },
+                &mut ChaChaRng::seed_from_u64(rand::random()),
+            );
+            let best = cmaes_state.overall_best_individual().unwrap().point.clone();
+            (initial_mean.unwrap(), best)
+        };
+
+        let mut random = IPOP::default();
+        next_run(&mut random, 1.0);
+        let (initial_mean, _) = next_run(&mut random, 2.0);
+        assert_eq!(DVector::from(vec![2.0; 2]), initial_mean);
+
+        let mut fixed = IPOP::default().restart_mean(RestartMean::FixedInitial);
+        next_run(&mut fixed, 1.0);
+        let (initial_mean, _) = next_run(&mut fixed, 2.0);
+        assert_eq!(DVector::from(vec![1.0; 2]), initial_mean);
+
+        let mut previous_best = IPOP::default().restart_mean(RestartMean::PreviousBest);
+        let (_, best) = next_run(&mut previous_best, 1.0);
+        let (initial_mean, _) = next_run(&mut previous_best, 2.0);
+        assert_eq!(best, initial_mean);
+    }
 }
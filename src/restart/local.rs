@@ -61,16 +61,22 @@ impl Strategy for Local {
         self.max_runs == 0
     }
 
-    fn next_run<F, R: FnOnce(&mut CMAES<F>) -> TerminationData>(
+    fn max_runs(&self) -> Option<usize> {
+        Some(self.max_runs)
+    }
+
+    fn next_run<F, R: FnMut(&mut CMAES<F>) -> TerminationData>(
         &mut self,
         mut options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
-        run: R,
+        mut run: R,
         _: &mut ChaChaRng,
     ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl) {
         // Configure the run
-        options.initial_step_size = self.get_initial_step_size(search_range_size);
+        options.initial_step_size =
+            initial_step_size.unwrap_or_else(|| self.get_initial_step_size(search_range_size));
 
         // Execute the run
         let mut cmaes_state = options.build(objective_function).unwrap();
@@ -146,6 +152,7 @@ mod tests {
             let (_, _, control) = local.next_run(
                 CMAESOptions::new(vec![1.0; 2], 0.5),
                 1.0,
+                None,
                 function,
                 |state| state.run(),
                 &mut ChaChaRng::seed_from_u64(rand::random()),
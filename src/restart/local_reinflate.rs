@@ -0,0 +1,258 @@
+//! A local restart strategy that reinflates the step size instead of terminating on numerical
+//! precision criteria
+
+use rand_chacha::ChaChaRng;
+
+use super::strategy::{RestartControl, Strategy};
+use super::InvalidRestartStrategyOptionsError;
+use crate::{options, CMAESOptions, TerminationData, TerminationReason, CMAES};
+
+/// The default factor for calculating the initial step size
+const DEFAULT_INITIAL_STEP_SIZE_FACTOR: f64 = 0.5;
+/// The default maximum number of reinflations allowed per run
+const DEFAULT_MAX_REINFLATIONS: usize = 10;
+/// The default fraction of the initial step size that the step size is reset to when reinflating
+const DEFAULT_REINFLATION_FACTOR: f64 = 0.5;
+
+/// The termination reasons that trigger a reinflation instead of ending the run
+const REINFLATION_REASONS: [TerminationReason; 3] = [
+    TerminationReason::NoEffectAxis,
+    TerminationReason::NoEffectCoord,
+    TerminationReason::TolX,
+];
+
+/// A local restart strategy like [`Local`][crate::restart::Local], but when a run terminates only
+/// due to [`NoEffectAxis`][crate::TerminationReason::NoEffectAxis],
+/// [`NoEffectCoord`][crate::TerminationReason::NoEffectCoord], or
+/// [`TolX`][crate::TerminationReason::TolX], the step size is reset to a fraction of its initial
+/// value and the run continues from its current mean and covariance matrix. Each run is reinflated
+/// at most `max_reinflations` times before it is allowed to terminate.
+///
+/// These termination criteria often indicate that the distribution has contracted onto a point
+/// faster than the function value has converged (e.g. on plateaus or in narrow ridges). Continuing
+/// from the same mean with a larger step size retains what was learned about the local landscape,
+/// so this is useful when the basin of the optimum is already found and only further refinement is
+/// needed. [`IPOP`][crate::restart::IPOP] should be preferred for multimodal functions with a
+/// global structure, where searching more broadly with a larger population is more productive than
+/// searching the same basin again.
+#[derive(Clone, Debug)]
+pub struct LocalReinflate {
+    /// The number of runs performed so far
+    runs: usize,
+    /// The factor for the search range size used to calculate the initial step size
+    initial_step_size_factor: f64,
+    /// The maximum number of runs allowed
+    max_runs: usize,
+    /// The maximum number of reinflations allowed per run
+    max_reinflations: usize,
+    /// The fraction of the initial step size that the step size is reset to when reinflating
+    reinflation_factor: f64,
+    /// The number of reinflations performed so far across all runs
+    reinflations: usize,
+}
+
+impl LocalReinflate {
+    /// Returns a new `LocalReinflate` with the provided parameters.
+    ///
+    /// - `max_runs` is the maximum number of runs allowed.
+    /// - `initial_step_size_factor` is the factor multiplied by the search range size `B - A` and
+    ///   `10^-2` to calculate the initial step size. If `None`, the default value of `0.5` is used.
+    /// - `max_reinflations` is the maximum number of reinflations allowed per run. If `None`, the
+    ///   default value of `10` is used.
+    /// - `reinflation_factor` is the fraction of the initial step size of the run that the step
+    ///   size is reset to when reinflating. Must be in `(0, 1]`. If `None`, the default value of
+    ///   `0.5` is used.
+    pub fn new(
+        max_runs: usize,
+        initial_step_size_factor: Option<f64>,
+        max_reinflations: Option<usize>,
+        reinflation_factor: Option<f64>,
+    ) -> Result<Self, InvalidRestartStrategyOptionsError> {
+        if let Some(factor) = initial_step_size_factor {
+            if !options::is_initial_step_size_valid(factor) {
+                return Err(InvalidRestartStrategyOptionsError::InitialStepSize);
+            }
+        }
+        if let Some(factor) = reinflation_factor {
+            if factor.is_nan() || factor <= 0.0 || factor > 1.0 {
+                return Err(InvalidRestartStrategyOptionsError::ReinflationFactor);
+            }
+        }
+
+        Ok(Self {
+            runs: 0,
+            initial_step_size_factor: initial_step_size_factor
+                .unwrap_or(DEFAULT_INITIAL_STEP_SIZE_FACTOR),
+            max_runs,
+            max_reinflations: max_reinflations.unwrap_or(DEFAULT_MAX_REINFLATIONS),
+            reinflation_factor: reinflation_factor.unwrap_or(DEFAULT_REINFLATION_FACTOR),
+            reinflations: 0,
+        })
+    }
+
+    /// Returns the number of reinflations performed so far across all runs.
+    pub fn reinflations(&self) -> usize {
+        self.reinflations
+    }
+
+    /// Returns the initial step size to use for the given search range size
+    fn get_initial_step_size(&self, search_range_size: f64) -> f64 {
+        search_range_size * self.initial_step_size_factor * 1e-2
+    }
+}
+
+/// Returns whether a run that terminated with `reasons` should be reinflated
+fn should_reinflate(reasons: &[TerminationReason]) -> bool {
+    !reasons.is_empty() && reasons.iter().all(|r| REINFLATION_REASONS.contains(r))
+}
+
+impl Strategy for LocalReinflate {
+    fn has_zero_max_runs(&self) -> bool {
+        self.max_runs == 0
+    }
+
+    fn max_runs(&self) -> Option<usize> {
+        Some(self.max_runs)
+    }
+
+    fn next_run<F, R: FnMut(&mut CMAES<F>) -> TerminationData>(
+        &mut self,
+        mut options: CMAESOptions,
+        search_range_size: f64,
+        initial_step_size: Option<f64>,
+        objective_function: F,
+        mut run: R,
+        _: &mut ChaChaRng,
+    ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl) {
+        // Configure the run
+        options.initial_step_size =
+            initial_step_size.unwrap_or_else(|| self.get_initial_step_size(search_range_size));
+
+        // Execute the run, reinflating the step size as long as it terminates due to numerical
+        // precision
+        let mut cmaes_state = options.build(objective_function).unwrap();
+        let mut results = run(&mut cmaes_state);
+        let mut run_reinflations = 0;
+
+        while run_reinflations < self.max_reinflations && should_reinflate(&results.reasons) {
+            let sigma = self.reinflation_factor * cmaes_state.parameters().initial_sigma();
+            cmaes_state.reinflate_sigma(sigma);
+            run_reinflations += 1;
+            results = run(&mut cmaes_state);
+        }
+
+        // Update internal state
+        self.runs += 1;
+        self.reinflations += run_reinflations;
+
+        let control = if self.runs >= self.max_runs {
+            RestartControl::MaxRunsReached
+        } else {
+            RestartControl::Continue
+        };
+
+        (cmaes_state, results.reasons, control)
+    }
+
+    fn reinflations(&self) -> usize {
+        self.reinflations
+    }
+
+    fn get_algorithm_name(&self) -> &'static str {
+        "LR-aCMA-ES (reinflating)"
+    }
+
+    fn get_parameters_as_strings(&self) -> Vec<(String, String)> {
+        [
+            (
+                "sigma0_factor".to_string(),
+                format!("{}", self.initial_step_size_factor),
+            ),
+            (
+                "max_reinflations".to_string(),
+                format!("{}", self.max_reinflations),
+            ),
+            (
+                "reinflation_factor".to_string(),
+                format!("{}", self.reinflation_factor),
+            ),
+        ]
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DVector;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(LocalReinflate::new(0, None, None, None).is_ok());
+        assert!(LocalReinflate::new(5, Some(1.0), Some(0), Some(0.25)).is_ok());
+        assert!(matches!(
+            LocalReinflate::new(5, Some(0.0), None, None),
+            Err(InvalidRestartStrategyOptionsError::InitialStepSize)
+        ));
+        assert!(matches!(
+            LocalReinflate::new(5, None, None, Some(-1.0)),
+            Err(InvalidRestartStrategyOptionsError::ReinflationFactor)
+        ));
+        assert!(matches!(
+            LocalReinflate::new(5, None, None, Some(f64::NAN)),
+            Err(InvalidRestartStrategyOptionsError::ReinflationFactor)
+        ));
+        assert!(matches!(
+            LocalReinflate::new(5, None, None, Some(1.5)),
+            Err(InvalidRestartStrategyOptionsError::ReinflationFactor)
+        ));
+        assert!(LocalReinflate::new(5, None, None, Some(1.0)).is_ok());
+    }
+
+    #[test]
+    fn test_should_reinflate() {
+        assert!(!should_reinflate(&[]));
+        assert!(should_reinflate(&[TerminationReason::TolX]));
+        assert!(should_reinflate(&[
+            TerminationReason::NoEffectAxis,
+            TerminationReason::NoEffectCoord,
+        ]));
+        assert!(!should_reinflate(&[
+            TerminationReason::TolX,
+            TerminationReason::TolFun,
+        ]));
+        assert!(!should_reinflate(&[TerminationReason::MaxFunctionEvals]));
+    }
+
+    #[test]
+    fn test_local_reinflate() {
+        let mut local = LocalReinflate::new(3, None, Some(4), None).unwrap();
+        let function = |x: &DVector<f64>| x.magnitude();
+
+        for i in 0..3 {
+            // The step size is always below `tol_x`, so every run is reinflated the maximum number
+            // of times
+            let mut calls = 0;
+            let (_, reasons, control) = local.next_run(
+                CMAESOptions::new(vec![1.0; 2], 0.5).tol_x(1.0),
+                1.0,
+                None,
+                function,
+                |state| {
+                    calls += 1;
+                    state.run()
+                },
+                &mut ChaChaRng::seed_from_u64(rand::random()),
+            );
+
+            assert_eq!(5, calls);
+            assert_eq!(vec![TerminationReason::TolX], reasons);
+            assert_eq!(i + 1 == 3, control.should_terminate());
+        }
+
+        assert_eq!(3, local.runs);
+        assert_eq!(12, local.reinflations());
+    }
+}
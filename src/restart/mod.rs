@@ -12,14 +12,16 @@
 mod bipop;
 mod ipop;
 mod local;
+mod local_reinflate;
 pub mod options;
 mod strategy;
 
 pub use bipop::BIPOP;
 pub use ipop::IPOP;
 pub use local::Local;
-pub use options::RestartOptions;
-pub use strategy::RestartStrategy;
+pub use local_reinflate::LocalReinflate;
+pub use options::{RestartOptions, RestartPolicy, SigmaSchedule};
+pub use strategy::{RestartMean, RestartStrategy};
 
 use nalgebra::DVector;
 use rand::{Rng, SeedableRng};
@@ -55,6 +57,9 @@ pub enum RestartTerminationReason {
     MaxTime,
     /// The objective function returned an invalid value (`NAN` or `-NAN`).
     InvalidFunctionValue,
+    /// The best individual found so far has not improved in the number of consecutive runs set by
+    /// [`RestartOptions::stall_restarts`].
+    StallRestarts,
 }
 
 impl fmt::Display for RestartTerminationReason {
@@ -77,6 +82,16 @@ pub struct RestartResults {
     pub function_evals: usize,
     /// The number of runs performed.
     pub runs: usize,
+    /// The maximum number of runs allowed by the restart strategy, if fixed. `None` for
+    /// [`BIPOP`], which only limits its IPOP runs, so its total number of runs depends on the
+    /// function evaluations they use.
+    pub max_runs: Option<usize>,
+    /// The number of times the step size was reinflated across all runs. Always zero unless
+    /// [`LocalReinflate`] is used.
+    pub reinflations: usize,
+    /// The number of runs that were abandoned by the [`RestartPolicy`]. Always zero unless
+    /// [`RestartOptions::policy`] is set.
+    pub abandoned_runs: usize,
 }
 
 impl RestartResults {
@@ -147,10 +162,16 @@ pub struct Restarter {
     max_function_evals_per_run: Option<usize>,
     /// The maximum number of generations allowed for each run
     max_generations_per_run: Option<usize>,
+    /// The number of consecutive runs without improvement after which to terminate
+    stall_restarts: Option<usize>,
     /// Whether to print info about each run
     print_info: bool,
     /// Seed for the RNG
     seed: u64,
+    /// The schedule of initial step sizes across runs, if set
+    sigma_schedule: Option<SigmaSchedule>,
+    /// The policy for abandoning runs early, if set
+    policy: Option<RestartPolicy>,
     /// Used to generate numbers specifically relevant to performing restarts in addition to
     /// generating seeds for the runs themselves
     rng: ChaChaRng,
@@ -168,6 +189,10 @@ impl Restarter {
             Err(InvalidRestartOptionsError::Dimensions)
         } else if options.search_range.end() - options.search_range.start() == 0.0 {
             Err(InvalidRestartOptionsError::SearchRange)
+        } else if options.stall_restarts == Some(0) {
+            Err(InvalidRestartOptionsError::StallRestarts)
+        } else if matches!(options.policy, Some(policy) if !policy.is_valid()) {
+            Err(InvalidRestartOptionsError::Policy)
         } else {
             Ok(Self {
                 strategy: options.strategy,
@@ -181,8 +206,11 @@ impl Restarter {
                 max_time: options.max_time,
                 max_function_evals_per_run: options.max_function_evals_per_run,
                 max_generations_per_run: options.max_generations_per_run,
+                stall_restarts: options.stall_restarts,
                 print_info: options.enable_printing,
                 seed,
+                sigma_schedule: options.sigma_schedule,
+                policy: options.policy,
                 rng: ChaChaRng::seed_from_u64(seed),
                 overall_best: None,
             })
@@ -203,7 +231,9 @@ impl Restarter {
         F: ObjectiveFunction,
         G: FnMut() -> F,
     {
-        self.run_internal(get_objective_function, false, |state| state.run())
+        self.run_internal(get_objective_function, false, |state, abandon| {
+            state.run_until(abandon)
+        })
     }
 
     /// Like [`run`][Self::run], but reuses `objective_function` instead of initializing one for every
@@ -233,7 +263,11 @@ impl Restarter {
     /// ```
     pub fn run_with_reuse<F: ObjectiveFunction>(self, objective_function: F) -> RestartResults {
         let mut function = Some(objective_function);
-        self.run_internal(|| function.take().unwrap(), true, |state| state.run())
+        self.run_internal(
+            || function.take().unwrap(),
+            true,
+            |state, abandon| state.run_until(abandon),
+        )
     }
 
     /// Like [`run`][Self::run], but executes the objective function in parallel using multiple
@@ -246,7 +280,9 @@ impl Restarter {
         F: ParallelObjectiveFunction,
         G: FnMut() -> F,
     {
-        self.run_internal(get_objective_function, false, |state| state.run_parallel())
+        self.run_internal(get_objective_function, false, |state, abandon| {
+            state.run_parallel_until(abandon)
+        })
     }
 
     /// Like [`run_parallel`][Self::run_parallel], but reuses `objective_function` instead of
@@ -259,11 +295,13 @@ impl Restarter {
         self.run_internal(
             || function.take().unwrap(),
             true,
-            |state| state.run_parallel(),
+            |state, abandon| state.run_parallel_until(abandon),
         )
     }
 
     /// Shared logic between `Self::run_*`
+    ///
+    /// `run` runs CMA-ES until it terminates or the predicate passed to it returns `true`
     fn run_internal<F, G, R>(
         mut self,
         mut get_objective_function: G,
@@ -272,7 +310,7 @@ impl Restarter {
     ) -> RestartResults
     where
         G: FnMut() -> F,
-        R: Copy + Fn(&mut CMAES<F>) -> TerminationData,
+        R: Copy + Fn(&mut CMAES<F>, &dyn Fn(&CMAES<F>) -> bool) -> TerminationData,
     {
         // Print parameters before any runs
         if self.print_info {
@@ -284,6 +322,9 @@ impl Restarter {
         let reason;
         let mut function_evals = 0;
         let mut runs = 0;
+        let mut abandoned_runs = 0;
+        // The number of consecutive runs that did not improve the best individual
+        let mut stalled_runs = 0;
         // For storing the objective function if it's being reused
         let mut objective_function = None;
 
@@ -325,6 +366,8 @@ impl Restarter {
             options.max_function_evals = self.max_function_evals_per_run;
             options.max_generations = self.max_generations_per_run;
             options.fun_target = self.fun_target;
+            // The initial step size is chosen by the restart strategy
+            options.initial_step_size_from_bounds = false;
 
             // Respects max_function_evals more precisely, but is likely to cut runs short in
             // IPOP/BIPOP
@@ -342,6 +385,10 @@ impl Restarter {
 
             // Run CMA-ES
             let search_range_size = (self.search_range.end() - self.search_range.start()).abs();
+            let initial_step_size = self
+                .sigma_schedule
+                .as_ref()
+                .map(|schedule| schedule.initial_step_size(runs));
             // Reuse the objective function if it's stored or get a fresh one otherwise
             let function = objective_function
                 .take()
@@ -352,11 +399,16 @@ impl Restarter {
                     print_run_info(runs + 1, cmaes.parameters());
                 }
 
-                run(cmaes)
+                // Abandon the run early if required by the policy
+                run(cmaes, &|state: &CMAES<F>| match self.policy {
+                    Some(policy) => policy.should_abandon(self.mode, state),
+                    None => false,
+                })
             };
             let (final_state, reasons, control) = self.strategy.next_run(
                 options,
                 search_range_size,
+                initial_step_size,
                 function,
                 run_with_print,
                 &mut self.rng,
@@ -370,6 +422,9 @@ impl Restarter {
             // Update results
             function_evals += final_state.function_evals();
             runs += 1;
+            if reasons.contains(&TerminationReason::Predicate) {
+                abandoned_runs += 1;
+            }
 
             // Check RestartTerminationReason::InvalidFunctionValue
             if reasons
@@ -380,9 +435,10 @@ impl Restarter {
                 break;
             }
 
-            if let Some(best) = final_state.overall_best_individual().cloned() {
-                self.update_best_individual(best);
-            }
+            let improved = match final_state.overall_best_individual().cloned() {
+                Some(best) => self.update_best_individual(best),
+                None => false,
+            };
 
             // Check RestartTerminationReason::FunTarget if enabled
             if reasons.iter().any(|&r| r == TerminationReason::FunTarget) {
@@ -390,6 +446,15 @@ impl Restarter {
                 break;
             }
 
+            // Check RestartTerminationReason::StallRestarts if enabled
+            stalled_runs = if improved { 0 } else { stalled_runs + 1 };
+            if let Some(stall_restarts) = self.stall_restarts {
+                if stalled_runs >= stall_restarts {
+                    reason = RestartTerminationReason::StallRestarts;
+                    break;
+                }
+            }
+
             // Check RestartTerminationReason::MaxRuns
             match control {
                 RestartControl::Continue => (),
@@ -410,6 +475,9 @@ impl Restarter {
             reason,
             function_evals,
             runs,
+            max_runs: self.strategy.max_runs(),
+            reinflations: self.strategy.reinflations(),
+            abandoned_runs,
         };
 
         // Print overall results
@@ -420,15 +488,21 @@ impl Restarter {
         results
     }
 
-    /// Updates the overall best individual
-    fn update_best_individual(&mut self, individual: Individual) {
+    /// Updates the overall best individual, returning whether it was improved
+    fn update_best_individual(&mut self, individual: Individual) -> bool {
         match self.overall_best {
             Some(ref mut current_best) => {
                 if self.mode.is_better(individual.value, current_best.value) {
                     *current_best = individual;
+                    true
+                } else {
+                    false
                 }
             }
-            None => self.overall_best = Some(individual),
+            None => {
+                self.overall_best = Some(individual);
+                true
+            }
         }
     }
 
@@ -496,8 +570,10 @@ fn print_run_results<F>(run: usize, cmaes: &CMAES<F>, termination_reasons: &[Ter
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "statrs")]
     use assert_approx_eq::assert_approx_eq;
 
+    use std::sync::{Arc, Mutex};
     use std::thread;
 
     use super::*;
@@ -512,6 +588,7 @@ mod tests {
             RestartStrategy::Local(Local::new(10, None).unwrap()),
             RestartStrategy::IPOP(Default::default()),
             RestartStrategy::BIPOP(Default::default()),
+            RestartStrategy::LocalReinflate(LocalReinflate::new(10, None, None, None).unwrap()),
         ];
 
         for s in strategies {
@@ -582,6 +659,106 @@ mod tests {
         assert!(results.best.is_none());
     }
 
+    #[test]
+    fn test_stall_restarts() {
+        // The function is constant, so only the first run improves the best individual
+        let strategy = RestartStrategy::Local(Local::new(10, None).unwrap());
+        let results = RestartOptions::new(1, -1.0..=1.0, strategy)
+            .max_generations_per_run(5)
+            .stall_restarts(3)
+            .build()
+            .unwrap()
+            .run(|| |_: &DVector<f64>| 1.0);
+
+        assert_eq!(4, results.runs);
+        assert_eq!(Some(10), results.max_runs);
+        assert_eq!(RestartTerminationReason::StallRestarts, results.reason);
+        assert_eq!(1.0, results.best.unwrap().value);
+
+        // Without the limit, all runs are performed
+        let strategy = RestartStrategy::Local(Local::new(10, None).unwrap());
+        let results = RestartOptions::new(1, -1.0..=1.0, strategy)
+            .max_generations_per_run(5)
+            .build()
+            .unwrap()
+            .run(|| |_: &DVector<f64>| 1.0);
+
+        assert_eq!(10, results.runs);
+        assert_eq!(RestartTerminationReason::MaxRuns, results.reason);
+    }
+
+    #[test]
+    fn test_sigma_schedule() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let points = Arc::new(Mutex::new(Vec::new()));
+        let strategy = RestartStrategy::Local(Local::new(3, None).unwrap());
+        let calls_clone = calls.clone();
+        let results = RestartOptions::new(2, -1.0..=1.0, strategy)
+            .max_generations_per_run(1)
+            .sigma_schedule(move |run| {
+                calls_clone.lock().unwrap().push(run);
+                1e-12
+            })
+            .build()
+            .unwrap()
+            .run(|| {
+                let points = points.clone();
+                move |x: &DVector<f64>| {
+                    points.lock().unwrap().push(x.clone());
+                    dummy_function(x)
+                }
+            });
+
+        assert_eq!(3, results.runs);
+        assert_eq!(vec![0, 1, 2], *calls.lock().unwrap());
+
+        // Every point of a run is sampled with the tiny scheduled step size around its mean
+        let points = points.lock().unwrap();
+        let lambda = points.len() / 3;
+        for run in points.chunks(lambda) {
+            assert!(run.iter().all(|x| (x - &run[0]).magnitude() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_early_abandon() {
+        // The threshold can never be reached, so every run is abandoned once it has used 50
+        // function evaluations
+        let policy = RestartPolicy::EarlyAbandon {
+            after_evals: 50,
+            worse_than: -1.0,
+        };
+        let strategy = RestartStrategy::Local(Local::new(5, None).unwrap());
+        let results = RestartOptions::new(2, -1.0..=1.0, strategy)
+            .policy(policy)
+            .build()
+            .unwrap()
+            .run(|| dummy_function);
+
+        assert_eq!(5, results.runs);
+        assert_eq!(5, results.abandoned_runs);
+        // Each run stops in the first generation that reaches 50 function evaluations (the
+        // population size is 6)
+        assert_eq!(5 * 54, results.function_evals);
+
+        // A threshold that is reached quickly never abandons runs
+        let policy = RestartPolicy::EarlyAbandon {
+            after_evals: 50,
+            worse_than: 10.0,
+        };
+        let strategy = RestartStrategy::Local(Local::new(5, None).unwrap());
+        let results = RestartOptions::new(2, -1.0..=1.0, strategy)
+            .policy(policy)
+            .max_generations_per_run(20)
+            .build()
+            .unwrap()
+            .run_parallel(|| dummy_function);
+
+        assert_eq!(5, results.runs);
+        assert_eq!(0, results.abandoned_runs);
+        assert_eq!(5 * 20 * 6, results.function_evals);
+    }
+
     #[test]
     fn test_zero_max_function_evals() {
         let strategy = RestartStrategy::Local(Local::new(10, None).unwrap());
@@ -697,6 +874,8 @@ mod tests {
         update_and_test(&mut restarter, 2.0, 2.0);
     }
 
+    // The expected values depend on the samples drawn by the statrs sampler
+    #[cfg(feature = "statrs")]
     #[test]
     fn test_fixed_seed() {
         let function = |x: &DVector<f64>| 1e-8 + (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2);
@@ -1,12 +1,14 @@
 //! Configuration of automatic restarts at the top-level (not configuration of specific restart
 //! strategies themselves). See [`RestartOptions`] for full documentation.
 
+use std::fmt::{self, Debug};
 use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
 
 use super::{DEFAULT_INITIAL_STEP_SIZE, RestartStrategy, Restarter};
-use crate::{CMAESOptions, Mode};
+use crate::{CMAESOptions, Mode, CMAES};
 
 /// Represents invalid options for a `Restarter`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -15,6 +17,10 @@ pub enum InvalidRestartOptionsError {
     Dimensions,
     /// The search range size is zero.
     SearchRange,
+    /// The number of runs without improvement allowed is set to zero.
+    StallRestarts,
+    /// The threshold of the restart policy is `NAN`.
+    Policy,
 }
 
 /// Represents invalid options for an individual restart strategy.
@@ -24,6 +30,9 @@ pub enum InvalidRestartStrategyOptionsError {
     InitialStepSize,
     /// The population size would be set to an invalid value with the given options.
     PopulationSize,
+    /// The reinflation factor of [`LocalReinflate`][crate::restart::LocalReinflate] is not in
+    /// `(0, 1]`.
+    ReinflationFactor,
 }
 
 /// A builder for [`Restarter`][Restarter]. Configuration of individual strategies is done when
@@ -74,12 +83,24 @@ pub struct RestartOptions {
     pub max_function_evals_per_run: Option<usize>,
     /// The maximum number of generations allowed for each run. Default value is `None`.
     pub max_generations_per_run: Option<usize>,
+    /// The number of consecutive runs that fail to improve the best individual found so far
+    /// after which the restart strategy terminates with
+    /// [`StallRestarts`][super::RestartTerminationReason::StallRestarts]. This usually means that
+    /// the global optimum has already been found or that the problem is too hard for the restart
+    /// strategy. Must be at least `1`. Default value is `None`.
+    pub stall_restarts: Option<usize>,
     /// Whether to print info about each run. Default value is `false`.
     pub enable_printing: bool,
     /// The seed for the [`Restarter`] RNG. This is not the seed for the CMA-ES runs themselves, but
     /// it is used to generate them. Can be set manually for deterministic runs. By default a random
     /// seed is used if this field is `None`.
     pub seed: Option<u64>,
+    /// A schedule of initial step sizes across runs. Default value is `None`, meaning each
+    /// restart strategy chooses the initial step size itself. See
+    /// [`sigma_schedule`][Self::sigma_schedule].
+    pub sigma_schedule: Option<SigmaSchedule>,
+    /// A policy for abandoning runs early. Default value is `None`. See [`RestartPolicy`].
+    pub policy: Option<RestartPolicy>,
 }
 
 impl RestartOptions {
@@ -107,8 +128,11 @@ impl RestartOptions {
             max_generations_per_run: None,
             max_time: None,
             max_function_evals_per_run: None,
+            stall_restarts: None,
             enable_printing: false,
             seed: None,
+            sigma_schedule: None,
+            policy: None,
         }
     }
 
@@ -160,6 +184,13 @@ impl RestartOptions {
         self
     }
 
+    /// Sets the number of consecutive runs without improvement after which the restart strategy
+    /// terminates. Must be at least `1`.
+    pub fn stall_restarts(mut self, stall_restarts: usize) -> Self {
+        self.stall_restarts = Some(stall_restarts);
+        self
+    }
+
     /// Sets whether to print info about each run.
     pub fn enable_printing(mut self, enable_printing: bool) -> Self {
         self.enable_printing = enable_printing;
@@ -172,12 +203,125 @@ impl RestartOptions {
         self
     }
 
+    /// Sets a schedule of initial step sizes across runs. `schedule` is called with the index of
+    /// each run (starting from `0`) and returns the initial step size for that run, overriding the
+    /// one chosen by the restart strategy. The returned value must be positive and finite, or the
+    /// run will panic.
+    ///
+    /// Without a schedule, the initial step size is chosen by the restart strategy from the size
+    /// of the search range `B - A`:
+    ///
+    /// - [`Local`][super::Local] and [`LocalReinflate`][super::LocalReinflate]:
+    ///   `(B - A) * initial_step_size_factor * 10^-2`
+    /// - [`IPOP`][super::IPOP]: `(B - A) / 2`
+    /// - [`BIPOP`][super::BIPOP]: `(B - A) / 5` for runs with increasing population size and
+    ///   `(B - A) * lr_initial_step_size_factor * 10^(-2 * rand(0, 1))` for local runs
+    ///
+    /// The population size and all other settings are still chosen by the restart strategy, so for
+    /// [`BIPOP`][super::BIPOP] the schedule may also need to depend on whether a run is local
+    /// (e.g. by tracking the runs in the closure). The reinflated step size of
+    /// [`LocalReinflate`][super::LocalReinflate] is relative to the scheduled initial step size.
+    pub fn sigma_schedule<S>(mut self, schedule: S) -> Self
+    where
+        S: FnMut(usize) -> f64 + Send + 'static,
+    {
+        self.sigma_schedule = Some(SigmaSchedule::new(schedule));
+        self
+    }
+
+    /// Sets a policy for abandoning runs early (see [`RestartPolicy`]).
+    pub fn policy(mut self, policy: RestartPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
     /// Attempts to build the [`Restarter`] from the chosen options.
     pub fn build(self) -> Result<Restarter, InvalidRestartOptionsError> {
         Restarter::new(self)
     }
 }
 
+/// A policy for abandoning runs of a [`Restarter`] before they terminate on their own. Set using
+/// [`RestartOptions::policy`]. Abandoned runs end with
+/// [`TerminationReason::Predicate`][crate::TerminationReason::Predicate] and are followed by a
+/// fresh run as usual, and their number is reported in
+/// [`RestartResults::abandoned_runs`][super::RestartResults::abandoned_runs].
+///
+/// Unlike the convergence-based termination criteria that end runs normally, policies are meant
+/// for budget management: they cut losses on runs that are unlikely to be useful.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RestartPolicy {
+    /// Abandons a run if its best function value is still worse than `worse_than` once it has
+    /// used at least `after_evals` function evaluations (checked after each generation). Runs
+    /// that reach `worse_than` (or better) are never abandoned. `worse_than` must not be `NAN`.
+    ///
+    /// With [`LocalReinflate`][super::LocalReinflate], the function evaluations are counted
+    /// across the reinflations of a run, and an abandoned run is not reinflated.
+    EarlyAbandon {
+        /// The number of function evaluations after which the run is checked
+        after_evals: usize,
+        /// The threshold that the best function value must reach (in the optimization mode of
+        /// the restarter) to avoid being abandoned
+        worse_than: f64,
+    },
+}
+
+impl RestartPolicy {
+    /// Returns whether the policy is valid
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            RestartPolicy::EarlyAbandon { worse_than, .. } => !worse_than.is_nan(),
+        }
+    }
+
+    /// Returns whether the run of `cmaes` should be abandoned
+    pub(crate) fn should_abandon<F>(&self, mode: Mode, cmaes: &CMAES<F>) -> bool {
+        match *self {
+            RestartPolicy::EarlyAbandon {
+                after_evals,
+                worse_than,
+            } => {
+                cmaes.function_evals() >= after_evals
+                    && match cmaes.overall_best_individual() {
+                        Some(best) => mode.is_better(worse_than, best.value),
+                        None => false,
+                    }
+            }
+        }
+    }
+}
+
+/// A function returning the initial step size for each run
+type SigmaScheduleFn = dyn FnMut(usize) -> f64 + Send;
+
+/// A schedule of initial step sizes across the runs of a [`Restarter`]. Set using
+/// [`RestartOptions::sigma_schedule`].
+#[derive(Clone)]
+pub struct SigmaSchedule(Arc<Mutex<SigmaScheduleFn>>);
+
+impl SigmaSchedule {
+    /// Creates a new `SigmaSchedule` from a function receiving the index of a run (starting from
+    /// `0`) and returning its initial step size.
+    pub fn new<S>(schedule: S) -> Self
+    where
+        S: FnMut(usize) -> f64 + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(schedule)))
+    }
+
+    /// Returns the initial step size for the run with index `run`
+    pub(crate) fn initial_step_size(&self, run: usize) -> f64 {
+        let mut schedule = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        (schedule)(run)
+    }
+}
+
+impl Debug for SigmaSchedule {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SigmaSchedule").finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,5 +354,28 @@ mod tests {
             .build(),
             Err(InvalidRestartOptionsError::SearchRange)
         ));
+        assert!(matches!(
+            RestartOptions::new(
+                2,
+                0.0..=1.0,
+                RestartStrategy::Local(Local::new(10, None).unwrap())
+            )
+            .stall_restarts(0)
+            .build(),
+            Err(InvalidRestartOptionsError::StallRestarts)
+        ));
+        assert!(matches!(
+            RestartOptions::new(
+                2,
+                0.0..=1.0,
+                RestartStrategy::Local(Local::new(10, None).unwrap())
+            )
+            .policy(RestartPolicy::EarlyAbandon {
+                after_evals: 100,
+                worse_than: f64::NAN,
+            })
+            .build(),
+            Err(InvalidRestartOptionsError::Policy)
+        ));
     }
 }
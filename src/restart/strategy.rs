@@ -2,7 +2,7 @@
 
 use rand_chacha::ChaChaRng;
 
-use super::{Local, BIPOP, IPOP};
+use super::{Local, LocalReinflate, BIPOP, IPOP};
 use crate::{CMAESOptions, TerminationData, TerminationReason, CMAES};
 
 /// A type returned by restart strategies to allow them to control restart execution
@@ -26,6 +26,25 @@ impl RestartControl {
     }
 }
 
+/// The source of the initial mean for each run of a restart strategy that supports choosing it
+/// (see [`IPOP::restart_mean`]). The default value is `Random`.
+///
+/// Restarting from a random mean favors exploration and is the most robust choice for multimodal
+/// functions, while restarting from the previous best point favors exploitation and can converge
+/// faster when the previous runs have already found the right basin of attraction (e.g. when they
+/// terminated early due to a too small population size). All choices remain reproducible with a
+/// fixed seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum RestartMean {
+    /// Use a new random mean in the search range for each run.
+    #[default]
+    Random,
+    /// Use the best point found by any previous run as the mean (the first run uses a random mean).
+    PreviousBest,
+    /// Use the random mean of the first run for every run.
+    FixedInitial,
+}
+
 /// The strategy to use in performing automatic restarts. A good default choice is `BIPOP`.
 #[derive(Clone, Debug)]
 pub enum RestartStrategy {
@@ -35,6 +54,8 @@ pub enum RestartStrategy {
     IPOP(IPOP),
     /// See [`BIPOP`].
     BIPOP(BIPOP),
+    /// See [`LocalReinflate`].
+    LocalReinflate(LocalReinflate),
 }
 
 /// A trait implemented by all restart strategy types
@@ -43,6 +64,9 @@ pub trait Strategy {
     /// all. Normal termination is done with the `RestartControl` return value of
     /// `Strategy::next_run`.
     fn has_zero_max_runs(&self) -> bool;
+    /// Returns the maximum number of runs allowed by the restart strategy, or `None` if it is not
+    /// fixed.
+    fn max_runs(&self) -> Option<usize>;
     /// Configures and executes the next run (using `run`). Returns the final state of the run, the
     /// termination reasons of the run, and an instruction for how/whether to proceed with future
     /// restarts.
@@ -54,15 +78,23 @@ pub trait Strategy {
     /// options.
     /// - `search_range_size` is the size of the search range of the restart strategy and can be used
     /// to calculate other options.
+    /// - `initial_step_size` overrides the initial step size chosen by the restart strategy if
+    ///   `Some` (see [`RestartOptions::sigma_schedule`][super::RestartOptions::sigma_schedule]).
     /// - `rng` should be used to generate any random numbers used.
-    fn next_run<F, R: FnOnce(&mut CMAES<F>) -> TerminationData>(
+    fn next_run<F, R: FnMut(&mut CMAES<F>) -> TerminationData>(
         &mut self,
         default_options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
         run: R,
         rng: &mut ChaChaRng,
     ) -> (CMAES<F>, Vec<TerminationReason>, RestartControl);
+    /// Returns the number of times the step size has been reinflated across all runs (only
+    /// nonzero for strategies that reinflate the step size instead of terminating a run).
+    fn reinflations(&self) -> usize {
+        0
+    }
     /// Returns the name of the algorithm with this restart strategy, e.g. BIPOP-aCMA-ES.
     fn get_algorithm_name(&self) -> &'static str;
     /// Returns the parameters of the restart strategy in a name to value map.
@@ -75,13 +107,24 @@ impl Strategy for RestartStrategy {
             RestartStrategy::Local(ref s) => s.has_zero_max_runs(),
             RestartStrategy::IPOP(ref s) => s.has_zero_max_runs(),
             RestartStrategy::BIPOP(ref s) => s.has_zero_max_runs(),
+            RestartStrategy::LocalReinflate(ref s) => s.has_zero_max_runs(),
         }
     }
 
-    fn next_run<F, R: FnOnce(&mut CMAES<F>) -> TerminationData>(
+    fn max_runs(&self) -> Option<usize> {
+        match *self {
+            RestartStrategy::Local(ref s) => s.max_runs(),
+            RestartStrategy::IPOP(ref s) => s.max_runs(),
+            RestartStrategy::BIPOP(ref s) => s.max_runs(),
+            RestartStrategy::LocalReinflate(ref s) => s.max_runs(),
+        }
+    }
+
+    fn next_run<F, R: FnMut(&mut CMAES<F>) -> TerminationData>(
         &mut self,
         default_options: CMAESOptions,
         search_range_size: f64,
+        initial_step_size: Option<f64>,
         objective_function: F,
         run: R,
         rng: &mut ChaChaRng,
@@ -90,6 +133,7 @@ impl Strategy for RestartStrategy {
             RestartStrategy::Local(ref mut s) => s.next_run(
                 default_options,
                 search_range_size,
+                initial_step_size,
                 objective_function,
                 run,
                 rng,
@@ -97,6 +141,7 @@ impl Strategy for RestartStrategy {
             RestartStrategy::IPOP(ref mut s) => s.next_run(
                 default_options,
                 search_range_size,
+                initial_step_size,
                 objective_function,
                 run,
                 rng,
@@ -104,10 +149,26 @@ impl Strategy for RestartStrategy {
             RestartStrategy::BIPOP(ref mut s) => s.next_run(
                 default_options,
                 search_range_size,
+                initial_step_size,
                 objective_function,
                 run,
                 rng,
             ),
+            RestartStrategy::LocalReinflate(ref mut s) => s.next_run(
+                default_options,
+                search_range_size,
+                initial_step_size,
+                objective_function,
+                run,
+                rng,
+            ),
+        }
+    }
+
+    fn reinflations(&self) -> usize {
+        match *self {
+            RestartStrategy::LocalReinflate(ref s) => s.reinflations(),
+            _ => 0,
         }
     }
 
@@ -116,6 +177,7 @@ impl Strategy for RestartStrategy {
             RestartStrategy::Local(ref s) => s.get_algorithm_name(),
             RestartStrategy::IPOP(ref s) => s.get_algorithm_name(),
             RestartStrategy::BIPOP(ref s) => s.get_algorithm_name(),
+            RestartStrategy::LocalReinflate(ref s) => s.get_algorithm_name(),
         }
     }
 
@@ -124,6 +186,7 @@ impl Strategy for RestartStrategy {
             RestartStrategy::Local(ref s) => s.get_parameters_as_strings(),
             RestartStrategy::IPOP(ref s) => s.get_parameters_as_strings(),
             RestartStrategy::BIPOP(ref s) => s.get_parameters_as_strings(),
+            RestartStrategy::LocalReinflate(ref s) => s.get_parameters_as_strings(),
         }
     }
 }
@@ -1,16 +1,76 @@
 //! Types related to sampling points from the distribution
 
 use nalgebra::DVector;
+#[cfg(feature = "statrs")]
 use rand::distributions::Distribution;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha12Rng;
 use rayon::prelude::*;
+#[cfg(feature = "statrs")]
 use statrs::distribution::Normal;
 
+use std::collections::VecDeque;
+
+use crate::bounds::{BoundaryHandling, Bounds};
+use crate::cache::{CacheStats, EvaluationCache};
 use crate::mode::Mode;
+use crate::objective_function::{BatchObjectiveFunction, PartialObjectiveFunction};
+use crate::recording::GenerationSamples;
 use crate::state::State;
+use crate::termination::InvalidPolicy;
+use crate::transform::CoordinateTransform;
+use crate::utils;
 use crate::{ObjectiveFunction, ParallelObjectiveFunction};
 
+/// The offset added to the RNG stream number from which the seeds of cloned samplers are drawn
+const BRANCH_RNG_STREAM_OFFSET: u64 = 1 << 32;
+
+/// How the values of repeated evaluations of the same point are combined into a single value. Set
+/// using [`CMAESOptions::evaluation_aggregation`][crate::CMAESOptions::evaluation_aggregation].
+/// The default value is `Mean`.
+///
+/// If any of the repeated evaluations returns `NAN`, the combined value is `NAN` as well, so the
+/// point is handled according to the [`InvalidPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregation {
+    /// The arithmetic mean of the values, which is the most accurate estimate of the expected
+    /// value for well-behaved noise.
+    #[default]
+    Mean,
+    /// The median of the values, which is robust to occasional outliers (e.g. failed simulations
+    /// that return a huge value).
+    Median,
+}
+
+impl Aggregation {
+    /// Evaluates a point `evaluations` times using `evaluate` and combines the values
+    fn evaluate<E: FnMut() -> f64>(self, evaluations: usize, mut evaluate: E) -> f64 {
+        if evaluations == 1 {
+            return evaluate();
+        }
+
+        let mut values = (0..evaluations).map(|_| evaluate()).collect::<Vec<_>>();
+        self.aggregate(&mut values)
+    }
+
+    /// Combines the values of repeated evaluations of the same point, returning `NAN` if any of
+    /// them is `NAN`
+    fn aggregate(self, values: &mut [f64]) -> f64 {
+        if values.iter().any(|value| value.is_nan()) {
+            return f64::NAN;
+        }
+
+        match self {
+            Self::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Self::Median => {
+                values.sort_by(|a, b| utils::partial_cmp(*a, *b));
+                utils::quantile(values, 0.5)
+            }
+        }
+    }
+}
+
 /// A type for sampling and evaluating points from the distribution for each generation
 pub struct Sampler<F> {
     /// Number of dimensions to sample from
@@ -23,6 +83,35 @@ pub struct Sampler<F> {
     objective_function: F,
     /// The number of times the objective function has been evaluated
     function_evals: usize,
+    /// The sizes of the chunks passed to the objective function in the most recent batch
+    /// evaluation
+    chunk_sizes: Vec<usize>,
+    /// Points in the internal search space to evaluate in place of sampled points in the next
+    /// generations
+    injected: Vec<DVector<f64>>,
+    /// Cache of previously evaluated points, if enabled
+    cache: Option<EvaluationCache>,
+    /// The samples of each generation, if recording is enabled
+    recording: Option<Vec<GenerationSamples>>,
+    /// Recorded samples to use in place of the RNG for the next generations
+    replay: VecDeque<GenerationSamples>,
+    /// The number of queued replayed generations that were discarded because they did not match
+    /// the population size or dimension
+    discarded_replay_samples: usize,
+    /// How invalid function values are handled
+    invalid_policy: InvalidPolicy,
+    /// The number of sampled points for which the objective function returned an invalid value
+    invalid_evals: usize,
+    /// The number of consecutive generations in which invalid values were returned
+    invalid_generations: usize,
+    /// The fixed weights of the boundary penalty, if set (otherwise the weights are adapted)
+    penalty_weights: Option<DVector<f64>>,
+    /// The index in sampling order of the point at each rank of the latest generation
+    ranking: Vec<usize>,
+    /// The number of times each sampled point is evaluated
+    evaluations_per_candidate: usize,
+    /// How the values of the repeated evaluations of each sampled point are combined
+    aggregation: Aggregation,
 }
 
 impl<F> Sampler<F> {
@@ -33,50 +122,350 @@ impl<F> Sampler<F> {
             rng: ChaCha12Rng::seed_from_u64(rng_seed),
             objective_function,
             function_evals: 0,
+            chunk_sizes: Vec::new(),
+            injected: Vec::new(),
+            cache: None,
+            recording: None,
+            replay: VecDeque::new(),
+            discarded_replay_samples: 0,
+            invalid_policy: InvalidPolicy::default(),
+            invalid_evals: 0,
+            invalid_generations: 0,
+            penalty_weights: None,
+            ranking: Vec::new(),
+            evaluations_per_candidate: 1,
+            aggregation: Aggregation::default(),
+        }
+    }
+
+    /// Sets how invalid function values are handled
+    pub fn set_invalid_policy(&mut self, invalid_policy: InvalidPolicy) {
+        self.invalid_policy = invalid_policy;
+    }
+
+    /// Sets fixed weights for the boundary penalty in place of the adapted ones
+    pub fn set_penalty_weights(&mut self, penalty_weights: Option<DVector<f64>>) {
+        self.penalty_weights = penalty_weights;
+    }
+
+    /// Sets how many times each sampled point is evaluated and how the values are combined
+    pub fn set_repeated_evaluations(&mut self, evaluations: usize, aggregation: Aggregation) {
+        self.evaluations_per_candidate = evaluations;
+        self.aggregation = aggregation;
+    }
+
+    /// Enables recording of the samples of each generation
+    pub fn enable_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Returns the recorded samples of each generation, if recording is enabled
+    pub fn recording(&self) -> Option<&[GenerationSamples]> {
+        self.recording.as_deref()
+    }
+
+    /// Queues recorded samples to use in place of the RNG for the next generations
+    pub fn replay<I: IntoIterator<Item = GenerationSamples>>(&mut self, samples: I) {
+        self.replay.extend(samples);
+    }
+
+    /// Returns the number of queued replayed generations that were discarded because they did not
+    /// match the population size or dimension
+    pub fn discarded_replay_samples(&self) -> usize {
+        self.discarded_replay_samples
+    }
+
+    /// Enables caching of the objective function values of evaluated points
+    pub fn enable_cache(&mut self) {
+        self.cache = Some(EvaluationCache::default());
+    }
+
+    /// Returns the statistics of the evaluation cache, if enabled
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(EvaluationCache::stats)
+    }
+
+    /// Returns a copy of the sampler that evaluates points using `objective_function` and that
+    /// samples from an RNG derived deterministically from the current state of this sampler's RNG
+    pub fn clone_with<G>(&self, objective_function: G) -> Sampler<G> {
+        // Draw the seed for the new RNG from a copy of the RNG using a separate stream, so this
+        // sampler's RNG is not advanced and does not produce the same numbers later
+        let mut rng = self.rng.clone();
+        rng.set_stream(rng.get_stream().wrapping_add(BRANCH_RNG_STREAM_OFFSET));
+
+        Sampler {
+            dim: self.dim,
+            population_size: self.population_size,
+            rng: ChaCha12Rng::from_seed(rng.gen()),
+            objective_function,
+            function_evals: self.function_evals,
+            chunk_sizes: self.chunk_sizes.clone(),
+            injected: self.injected.clone(),
+            // The values of this sampler's objective function do not apply to the new one
+            cache: self.cache.as_ref().map(|_| EvaluationCache::default()),
+            recording: self.recording.as_ref().map(|_| Vec::new()),
+            replay: VecDeque::new(),
+            discarded_replay_samples: self.discarded_replay_samples,
+            invalid_policy: self.invalid_policy,
+            invalid_evals: self.invalid_evals,
+            invalid_generations: self.invalid_generations,
+            penalty_weights: self.penalty_weights.clone(),
+            ranking: self.ranking.clone(),
+            evaluations_per_candidate: self.evaluations_per_candidate,
+            aggregation: self.aggregation,
         }
     }
 
+    /// Returns the mode for which points with invalid values are marked as unrankable, or `None`
+    /// if invalid values are not skipped
+    fn skip_invalid(&self, mode: Mode) -> Option<Mode> {
+        (self.invalid_policy != InvalidPolicy::Terminate).then_some(mode)
+    }
+
     /// Shared logic between `sample` and `sample_parallel`
     fn sample_internal<
-        P: Fn(Vec<DVector<f64>>, &mut F) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError>,
+        P: FnOnce(
+            Vec<DVector<f64>>,
+            &mut F,
+            Option<&EvaluationCache>,
+        ) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError>,
     >(
         &mut self,
         state: &State,
         mode: Mode,
         parallel_update: bool,
+        mapping: PointMapping<'_>,
         evaluate_points: P,
     ) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
+        let z = self.generation_standard_normal();
+        let recorded_z = self.recording.is_some().then(|| z.clone());
+        let mut y = transform_steps(state, z, parallel_update);
+
+        // Replace the first sampled steps with the steps to the injected points, if any
+        let num_injected = self.injected.len().min(y.len());
+        for (yk, point) in y.iter_mut().zip(self.injected.drain(..num_injected)) {
+            *yk = (point - state.mean()) / state.sigma();
+        }
+
+        // Evaluate and rank points
+        let mut points = match evaluate_points(y, &mut self.objective_function, self.cache.as_ref())
+        {
+            Ok(points) => points,
+            Err(err) => {
+                self.invalid_evals += 1;
+                return Err(err);
+            }
+        };
+
+        if let Some(bounds) = mapping.bounds {
+            if bounds.handling() == BoundaryHandling::Penalty {
+                // Keep the sampled points that were repaired (before the injected steps are
+                // clipped below)
+                for point in points.iter_mut().filter(|point| point.violation > 0.0) {
+                    let sampled = state.mean() + state.sigma() * &point.unscaled_step;
+                    point.raw_point = Some(mapping.unrepaired(sampled));
+                }
+                apply_penalty(
+                    state,
+                    mode,
+                    bounds,
+                    self.penalty_weights.as_ref(),
+                    &mut points,
+                );
+            }
+        }
+
+        // The injected points are evaluated as given, but the steps used to update the
+        // distribution are shortened so that distant points do not disrupt the adaptation
+        for point in &mut points[..num_injected] {
+            point.unscaled_step = clip_injected_step(state, &point.unscaled_step);
+        }
+
+        // Each point is numbered with the last of its repeated evaluations
+        let evaluations = self.evaluations_per_candidate;
+        let first_evaluation = self.function_evals + 1;
+        for (i, point) in points.iter_mut().enumerate() {
+            point.evaluation = first_evaluation + (i + 1) * evaluations - 1;
+        }
+        self.function_evals += points.len() * evaluations;
+
+        // Skipped invalid values only terminate the run if no point could be evaluated or if the
+        // policy's limit of consecutive generations is reached
+        let num_invalid = points.iter().filter(|point| point.invalid).count();
+        self.invalid_evals += num_invalid;
+        if num_invalid > 0 {
+            self.invalid_generations += 1;
+        } else {
+            self.invalid_generations = 0;
+        }
+        let limit_reached = match self.invalid_policy {
+            InvalidPolicy::TerminateAfter(generations) => self.invalid_generations >= generations,
+            _ => false,
+        };
+        if num_invalid == points.len() || limit_reached {
+            return Err(InvalidFunctionValueError);
+        }
+
+        if let (Some(recording), Some(z)) = (&mut self.recording, recorded_z) {
+            recording.push(GenerationSamples::new(z, &points));
+        }
+
+        // Unrankable points are always placed last
+        points.sort_by(|a, b| {
+            b.rankable
+                .cmp(&a.rankable)
+                .then_with(|| mode.sort_cmp(a.value, b.value))
+        });
+        // The evaluation numbers follow the sampling order, so they identify the original indices
+        self.ranking = points
+            .iter()
+            .map(|point| (point.evaluation - first_evaluation) / evaluations)
+            .collect();
+        Ok(points)
+    }
+
+    /// Returns the samples from the distribution N(0, I) for a generation, taken from the
+    /// replayed samples if any are queued and match the population size and dimension
+    fn generation_standard_normal(&mut self) -> Vec<DVector<f64>> {
+        if let Some(samples) = self.replay.pop_front() {
+            let dim = self.dim;
+            if samples.standard_normal.len() == self.population_size
+                && samples.standard_normal.iter().all(|z| z.len() == dim)
+            {
+                return samples
+                    .standard_normal
+                    .into_iter()
+                    .map(DVector::from)
+                    .collect();
+            }
+
+            // The run has diverged from the recording, so the rest of it does not apply
+            self.discarded_replay_samples += 1 + self.replay.len();
+            self.replay.clear();
+        }
+
+        self.sample_standard_normal(self.population_size)
+    }
+
+    /// Samples `n` points from the distribution N(0, I)
+    #[cfg(feature = "statrs")]
+    fn sample_standard_normal(&mut self, n: usize) -> Vec<DVector<f64>> {
         let normal = Normal::new(0.0, 1.0).unwrap();
 
-        // Random steps in the distribution N(0, I)
-        let z = (0..self.population_size)
+        (0..n)
             .map(|_| {
                 DVector::from_iterator(
                     self.dim,
                     (0..self.dim).map(|_| normal.sample(&mut self.rng)),
                 )
             })
-            .collect::<Vec<_>>();
-        let transform = |zk| state.cov_transform() * zk;
-        let y = if parallel_update {
-            z.into_par_iter().map(transform).collect()
-        } else {
-            z.into_iter().map(transform).collect()
-        };
+            .collect()
+    }
 
-        // Evaluate and rank points
-        let mut points = evaluate_points(y, &mut self.objective_function)?;
+    /// Samples `n` points from the distribution N(0, I)
+    #[cfg(not(feature = "statrs"))]
+    fn sample_standard_normal(&mut self, n: usize) -> Vec<DVector<f64>> {
+        (0..n)
+            .map(|_| {
+                DVector::from_iterator(
+                    self.dim,
+                    (0..self.dim).map(|_| polar_standard_normal(&mut self.rng)),
+                )
+            })
+            .collect()
+    }
 
-        self.function_evals += points.len();
+    /// Samples `n` unscaled steps from the distribution N(0, cov)
+    fn sample_steps(
+        &mut self,
+        state: &State,
+        n: usize,
+        parallel_update: bool,
+    ) -> Vec<DVector<f64>> {
+        let z = self.sample_standard_normal(n);
+        transform_steps(state, z, parallel_update)
+    }
 
-        points.sort_by(|a, b| mode.sort_cmp(a.value, b.value));
-        Ok(points)
+    /// Samples a single unscaled step from the distribution N(0, cov)
+    pub fn sample_step(&mut self, state: &State) -> DVector<f64> {
+        self.sample_steps(state, 1, false).remove(0)
+    }
+
+    /// Samples `n` points from the distribution without evaluating them (mapped into the space of
+    /// the objective function using `mapping`)
+    pub fn sample_unevaluated(
+        &mut self,
+        state: &State,
+        n: usize,
+        parallel_update: bool,
+        mapping: PointMapping<'_>,
+    ) -> Vec<DVector<f64>> {
+        self.sample_steps(state, n, parallel_update)
+            .iter()
+            .map(|yk| mapping.map(&(state.mean() + state.sigma() * yk)).0)
+            .collect()
+    }
+
+    /// Evaluates a single point (in the space of the objective function) outside of sampling,
+    /// counting it as a function evaluation and using the cache if enabled
+    ///
+    /// Returns `None` if `evaluate` returns `None` or the value is invalid
+    pub fn evaluate_point<E: FnOnce(&mut F, &DVector<f64>) -> Option<f64>>(
+        &mut self,
+        point: &DVector<f64>,
+        evaluate: E,
+    ) -> Option<f64> {
+        self.function_evals += 1;
+
+        let objective_function = &mut self.objective_function;
+        let value = evaluate_cached(self.cache.as_ref(), point, |x| {
+            evaluate(objective_function, x).unwrap_or(f64::NAN)
+        });
+
+        (!value.is_nan()).then_some(value)
+    }
+
+    /// Queues points in the internal search space to be evaluated in place of sampled points in
+    /// the next generations (at most one population's worth per generation)
+    pub fn inject<I: IntoIterator<Item = DVector<f64>>>(&mut self, points: I) {
+        self.injected.extend(points);
+    }
+
+    /// Returns the number of injected points that have not been evaluated yet
+    pub fn num_injected(&self) -> usize {
+        self.injected.len()
+    }
+
+    /// Changes the number of points to sample each generation
+    pub fn set_population_size(&mut self, population_size: usize) {
+        self.population_size = population_size;
     }
 
     pub fn function_evals(&self) -> usize {
         self.function_evals
     }
 
+    /// Returns the number of function evaluations used to evaluate the sampled points of a
+    /// generation
+    pub fn generation_evals(&self) -> usize {
+        self.population_size * self.evaluations_per_candidate
+    }
+
+    /// Returns the number of sampled points for which the objective function returned an invalid
+    /// value
+    pub fn invalid_evals(&self) -> usize {
+        self.invalid_evals
+    }
+
+    /// Returns the index in sampling order of the point at each rank of the latest generation
+    pub fn ranking(&self) -> &[usize] {
+        &self.ranking
+    }
+
+    pub fn chunk_sizes(&self) -> &[usize] {
+        &self.chunk_sizes
+    }
+
     /// Consumes `self` and returns the objective function
     pub fn into_objective_function(self) -> F {
         self.objective_function
@@ -93,16 +482,27 @@ impl<F: ObjectiveFunction> Sampler<F> {
         state: &State,
         mode: Mode,
         parallel_update: bool,
+        mapping: PointMapping<'_>,
     ) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
-        self.sample_internal(state, mode, parallel_update, |y, objective_function| {
-            y.into_iter()
-                .map(|yk| {
-                    EvaluatedPoint::new(yk, state.mean(), state.sigma(), |x| {
-                        objective_function.evaluate(x)
+        let skip_invalid = self.skip_invalid(mode);
+        let (evaluations, aggregation) = (self.evaluations_per_candidate, self.aggregation);
+        self.sample_internal(
+            state,
+            mode,
+            parallel_update,
+            mapping,
+            |y, objective_function, cache| {
+                y.into_iter()
+                    .map(|yk| {
+                        let (point, violation) = mapping.map(&(state.mean() + state.sigma() * &yk));
+                        let value = evaluate_cached(cache, &point, |x| {
+                            aggregation.evaluate(evaluations, || objective_function.evaluate(x))
+                        });
+                        EvaluatedPoint::from_value(point, yk, violation, value, skip_invalid)
                     })
-                })
-                .collect::<Result<Vec<_>, _>>()
-        })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )
     }
 }
 
@@ -113,67 +513,538 @@ impl<F: ParallelObjectiveFunction> Sampler<F> {
         state: &State,
         mode: Mode,
         parallel_update: bool,
+        mapping: PointMapping<'_>,
+    ) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
+        let skip_invalid = self.skip_invalid(mode);
+        let (evaluations, aggregation) = (self.evaluations_per_candidate, self.aggregation);
+        self.sample_internal(
+            state,
+            mode,
+            parallel_update,
+            mapping,
+            |y, objective_function, cache| {
+                y.into_par_iter()
+                    .map(|yk| {
+                        let (point, violation) = mapping.map(&(state.mean() + state.sigma() * &yk));
+                        let value = evaluate_cached(cache, &point, |x| {
+                            aggregation
+                                .evaluate(evaluations, || objective_function.evaluate_parallel(x))
+                        });
+                        EvaluatedPoint::from_value(point, yk, violation, value, skip_invalid)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )
+    }
+}
+
+impl<F: BatchObjectiveFunction> Sampler<F> {
+    /// Like `sample`, but evaluates the sampled points in chunks using the objective function's
+    /// chunk size, preserving their order
+    pub fn sample_batch(
+        &mut self,
+        state: &State,
+        mode: Mode,
+        parallel_update: bool,
+        mapping: PointMapping<'_>,
+    ) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
+        let chunk_size = self
+            .objective_function
+            .chunk_size()
+            .unwrap_or(self.population_size)
+            .max(1);
+        let mut chunk_sizes = Vec::new();
+        let skip_invalid = self.skip_invalid(mode);
+        let (evaluations, aggregation) = (self.evaluations_per_candidate, self.aggregation);
+
+        let result = self.sample_internal(
+            state,
+            mode,
+            parallel_update,
+            mapping,
+            |y, objective_function, _| {
+                let (points, violations): (Vec<_>, Vec<_>) = y
+                    .iter()
+                    .map(|yk| mapping.map(&(state.mean() + state.sigma() * yk)))
+                    .unzip();
+
+                // Each chunk is passed to the objective function once per repeated evaluation
+                let mut values = Vec::with_capacity(points.len());
+                for chunk in points.chunks(chunk_size) {
+                    let mut chunk_values = vec![Vec::with_capacity(evaluations); chunk.len()];
+                    for _ in 0..evaluations {
+                        let batch_values = objective_function.evaluate_batch(chunk);
+                        // The values cannot be matched to the points, so none of them are usable
+                        if batch_values.len() != chunk.len() {
+                            return Err(InvalidFunctionValueError);
+                        }
+
+                        chunk_sizes.push(chunk.len());
+                        for (point_values, value) in chunk_values.iter_mut().zip(batch_values) {
+                            point_values.push(value);
+                        }
+                    }
+                    values.extend(
+                        chunk_values
+                            .iter_mut()
+                            .map(|point_values| aggregation.aggregate(point_values)),
+                    );
+                }
+
+                points
+                    .into_iter()
+                    .zip(violations)
+                    .zip(y)
+                    .zip(values)
+                    .map(|(((point, violation), yk), value)| {
+                        EvaluatedPoint::from_value(point, yk, violation, value, skip_invalid)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        );
+
+        self.chunk_sizes = chunk_sizes;
+        result
+    }
+}
+
+impl<F: PartialObjectiveFunction> Sampler<F> {
+    /// Like `sample`, but points for which the objective function returns `None` are marked as
+    /// unrankable and placed last
+    pub fn sample_partial(
+        &mut self,
+        state: &State,
+        mode: Mode,
+        parallel_update: bool,
+        mapping: PointMapping<'_>,
     ) -> Result<Vec<EvaluatedPoint>, InvalidFunctionValueError> {
-        self.sample_internal(state, mode, parallel_update, |y, objective_function| {
-            y.into_par_iter()
-                .map(|yk| {
-                    EvaluatedPoint::new(yk, state.mean(), state.sigma(), |x| {
-                        objective_function.evaluate_parallel(x)
+        let skip_invalid = self.skip_invalid(mode);
+        let (evaluations, aggregation) = (self.evaluations_per_candidate, self.aggregation);
+        self.sample_internal(
+            state,
+            mode,
+            parallel_update,
+            mapping,
+            |y, objective_function, _| {
+                y.into_iter()
+                    .map(|yk| {
+                        let (point, violation) = mapping.map(&(state.mean() + state.sigma() * &yk));
+                        // The point is unrankable if any of its evaluations fails
+                        let values = (0..evaluations)
+                            .map(|_| objective_function.evaluate_partial(&point))
+                            .collect::<Option<Vec<_>>>();
+                        match values {
+                            Some(mut values) => EvaluatedPoint::from_value(
+                                point,
+                                yk,
+                                violation,
+                                aggregation.aggregate(&mut values),
+                                skip_invalid,
+                            ),
+                            None => Ok(EvaluatedPoint::unrankable(point, yk, violation, mode)),
+                        }
                     })
-                })
-                .collect::<Result<Vec<_>, _>>()
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )
+    }
+}
+
+/// Transforms samples from the distribution N(0, I) into unscaled steps from the distribution
+/// N(0, cov)
+fn transform_steps(
+    state: &State,
+    z: Vec<DVector<f64>>,
+    parallel_update: bool,
+) -> Vec<DVector<f64>> {
+    let transform = |zk| state.cov_transform() * zk;
+    if parallel_update {
+        z.into_par_iter().map(transform).collect()
+    } else {
+        z.into_iter().map(transform).collect()
+    }
+}
+
+/// Evaluates `point` using `evaluate`, or returns its cached value if the cache is enabled and
+/// contains the point
+fn evaluate_cached<E: FnOnce(&DVector<f64>) -> f64>(
+    cache: Option<&EvaluationCache>,
+    point: &DVector<f64>,
+    evaluate: E,
+) -> f64 {
+    match cache {
+        Some(cache) => cache.get_or_evaluate(point, evaluate),
+        None => evaluate(point),
+    }
+}
+
+/// Adds the boundary penalty to the function values of the points that were sampled outside of the
+/// bounds, using `weights` if set and weights adapted to the distribution and the spread of the
+/// function values otherwise
+fn apply_penalty(
+    state: &State,
+    mode: Mode,
+    bounds: &Bounds,
+    weights: Option<&DVector<f64>>,
+    points: &mut [EvaluatedPoint],
+) {
+    if points.iter().all(|point| point.violation == 0.0) {
+        return;
+    }
+
+    let weights = match weights {
+        Some(weights) => weights.clone(),
+        None => {
+            let mut values = points
+                .iter()
+                .filter(|point| point.rankable)
+                .map(|point| point.value)
+                .collect::<Vec<_>>();
+            values.sort_by(|a, b| utils::partial_cmp(*a, *b));
+            let iqr = match values.len() {
+                0 => 0.0,
+                _ => utils::quantile(&values, 0.75) - utils::quantile(&values, 0.25),
+            };
+            let scale = if iqr.is_normal() { iqr } else { 1.0 };
+
+            state
+                .cov()
+                .diagonal()
+                .map(|variance| scale / (state.sigma().powi(2) * variance))
+        }
+    };
+
+    let sign = match mode {
+        Mode::Minimize => 1.0,
+        Mode::Maximize => -1.0,
+    };
+    for point in points
+        .iter_mut()
+        .filter(|point| point.rankable && point.violation > 0.0)
+    {
+        let sampled = state.mean() + state.sigma() * &point.unscaled_step;
+        point.value += sign * bounds.penalty(&sampled, &weights);
+    }
+}
+
+/// Shortens the unscaled step to an injected point if its Mahalanobis norm exceeds the typical
+/// length of a sampled step
+fn clip_injected_step(state: &State, step: &DVector<f64>) -> DVector<f64> {
+    let dim = step.len() as f64;
+    let max_norm = dim.sqrt() + 2.0 * dim / (dim + 2.0);
+    let norm = (state.cov_sqrt_inv() * step).magnitude();
+
+    if norm > max_norm {
+        step * (max_norm / norm)
+    } else {
+        step.clone()
+    }
+}
+
+/// Samples a value from N(0, 1) using the Marsaglia polar method (only one of the two generated
+/// values is used). Used in place of the statrs sampler when the `statrs` feature is disabled.
+#[cfg(not(feature = "statrs"))]
+fn polar_standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    loop {
+        let u = 2.0 * rng.gen::<f64>() - 1.0;
+        let v = 2.0 * rng.gen::<f64>() - 1.0;
+        let s = u * u + v * v;
+        if s > 0.0 && s < 1.0 {
+            return u * (-2.0 * s.ln() / s).sqrt();
+        }
+    }
+}
+
+/// Maps points from the internal search space of the algorithm into the space of the objective
+/// function
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PointMapping<'a> {
+    /// The bounds used to map points into the feasible space, if enabled
+    pub bounds: Option<&'a Bounds>,
+    /// The transform used to decode feasible points, if enabled
+    pub transform: Option<&'a CoordinateTransform>,
+}
+
+impl PointMapping<'_> {
+    /// Maps a point from the internal search space into the space of the objective function,
+    /// returning it along with the total distance by which it lies outside of the bounds (measured
+    /// in the encoded space)
+    pub fn map(&self, point: &DVector<f64>) -> (DVector<f64>, f64) {
+        let (point, violation) = match self.bounds {
+            Some(bounds) => {
+                let feasible = bounds.to_feasible(point);
+                // Penalized points are repaired, so their violation is measured before repair
+                let violation = match bounds.handling() {
+                    BoundaryHandling::Transform => bounds.violation(&feasible),
+                    BoundaryHandling::Penalty => bounds.violation(point),
+                };
+                (feasible, violation)
+            }
+            None => (point.clone(), 0.0),
+        };
+
+        match self.transform {
+            Some(transform) => (transform.decode(&point), violation),
+            None => (point, violation),
+        }
+    }
+
+    /// Clamps a point in the space of the objective function into the bounds (which apply in the
+    /// encoded space if a transform is enabled). Returns `None` if the point already lies inside
+    /// the bounds or bounds are disabled.
+    pub fn clamp_to_bounds(&self, point: &DVector<f64>) -> Option<DVector<f64>> {
+        let bounds = self.bounds?;
+        let encoded = match self.transform {
+            Some(transform) => transform.encode(point),
+            None => point.clone(),
+        };
+        if bounds.violation(&encoded) == 0.0 {
+            return None;
+        }
+
+        let clamped = bounds.clamp(&encoded);
+        Some(match self.transform {
+            Some(transform) => transform.decode(&clamped),
+            None => clamped,
         })
     }
+
+    /// Maps a point from the internal search space into the space of the objective function like
+    /// [`map`][Self::map], but without repairing it (only valid with
+    /// [`BoundaryHandling::Penalty`], for which the internal search space is not transformed)
+    fn unrepaired(&self, point: DVector<f64>) -> DVector<f64> {
+        match self.transform {
+            Some(transform) => transform.decode(&point),
+            None => point,
+        }
+    }
+
+    /// Maps a point from the space of the objective function into the internal search space (the
+    /// inverse of [`map`][Self::map] for feasible points)
+    pub fn to_internal(self, point: &DVector<f64>) -> DVector<f64> {
+        let point = match self.transform {
+            Some(transform) => transform.encode(point),
+            None => point.clone(),
+        };
+
+        match self.bounds {
+            Some(bounds) => bounds.to_internal(&point),
+            None => point,
+        }
+    }
 }
 
-/// A point from the distribution that has been evaluated by the objective function
+/// A point from the distribution that has been evaluated by the objective function. The points
+/// of the latest generation can be obtained from
+/// [`CMAES::current_population`][crate::CMAES::current_population].
 #[derive(Clone, Debug)]
 pub struct EvaluatedPoint {
-    /// The evaluated point
+    /// The evaluated point (mapped into the feasible space if bounds are enabled and decoded if a
+    /// transform is enabled)
     point: DVector<f64>,
+    /// The sampled point before it was repaired, if it was (decoded if a transform is enabled)
+    raw_point: Option<DVector<f64>>,
     /// The step from the mean of the point before scaling by sigma
     /// In the distribution N(0, cov)
     unscaled_step: DVector<f64>,
     /// The objective value at the point
     value: f64,
+    /// The total distance by which the evaluated point lies outside of the bounds (always `0.0`
+    /// if bounds are disabled)
+    violation: f64,
+    /// Whether the objective function was able to evaluate the point
+    rankable: bool,
+    /// The number of the function evaluation of the point over the whole run (counted from 1)
+    evaluation: usize,
+    /// Whether the objective function returned an invalid value at the point (only possible if
+    /// invalid values are skipped)
+    invalid: bool,
 }
 
 impl EvaluatedPoint {
-    /// Returns a new `EvaluatedPoint` from the unscaled step from the mean, the mean, and the step
-    /// size
+    /// Returns a new `EvaluatedPoint` from the unscaled step from the mean, the mean, the step
+    /// size, and the mapping used to map the point into the space of the objective function
     ///
     /// Returns `Err` if the objective function returned an invalid value
     pub fn new<F: FnMut(&DVector<f64>) -> f64>(
         unscaled_step: DVector<f64>,
         mean: &DVector<f64>,
         sigma: f64,
+        mapping: PointMapping<'_>,
         mut objective_function: F,
     ) -> Result<Self, InvalidFunctionValueError> {
-        let point = mean + sigma * &unscaled_step;
+        let (point, violation) = mapping.map(&(mean + sigma * &unscaled_step));
         let value = objective_function(&point);
 
-        if value.is_nan() {
-            Err(InvalidFunctionValueError)
-        } else {
-            Ok(Self {
+        Self::from_value(point, unscaled_step, violation, value, None)
+    }
+
+    /// Returns a new `EvaluatedPoint` from an already evaluated point and its violation of the
+    /// bounds
+    ///
+    /// Returns `Err` if the value is invalid, unless `skip_invalid` is `Some`, in which case the
+    /// point is marked as unrankable with the worst possible value for the mode
+    fn from_value(
+        point: DVector<f64>,
+        unscaled_step: DVector<f64>,
+        violation: f64,
+        value: f64,
+        skip_invalid: Option<Mode>,
+    ) -> Result<Self, InvalidFunctionValueError> {
+        match (value.is_nan(), skip_invalid) {
+            (false, _) => Ok(Self {
                 point,
+                raw_point: None,
                 unscaled_step,
                 value,
-            })
+                violation,
+                rankable: true,
+                evaluation: 0,
+                invalid: false,
+            }),
+            (true, Some(mode)) => Ok(Self {
+                invalid: true,
+                ..Self::unrankable(point, unscaled_step, violation, mode)
+            }),
+            (true, None) => Err(InvalidFunctionValueError),
+        }
+    }
+
+    /// Returns a new `EvaluatedPoint` for a point that the objective function could not evaluate,
+    /// with the worst possible value for `mode`
+    fn unrankable(
+        point: DVector<f64>,
+        unscaled_step: DVector<f64>,
+        violation: f64,
+        mode: Mode,
+    ) -> Self {
+        let value = match mode {
+            Mode::Minimize => f64::INFINITY,
+            Mode::Maximize => f64::NEG_INFINITY,
+        };
+
+        Self {
+            point,
+            raw_point: None,
+            unscaled_step,
+            value,
+            violation,
+            rankable: false,
+            evaluation: 0,
+            invalid: false,
         }
     }
 
+    /// Returns the point that was evaluated (in the feasible space if bounds are enabled and in
+    /// natural coordinates if a transform is enabled).
     pub fn point(&self) -> &DVector<f64> {
         &self.point
     }
 
+    /// Returns the point that was sampled from the distribution, before it was repaired (in
+    /// natural coordinates if a transform is enabled). This is the same as
+    /// [`repaired_point`][Self::repaired_point] unless the point was repaired, which only happens
+    /// with [`BoundaryHandling::Penalty`] for points sampled outside of the bounds (with
+    /// [`BoundaryHandling::Transform`], every sampled point is mapped smoothly into the bounds
+    /// rather than repaired, so the evaluated point is the sampled one).
+    ///
+    /// Comparing the two shows how much repair is happening: a search that is constantly pressing
+    /// against a bound has many points whose raw point lies well outside of it. See also
+    /// [`violation`][Self::violation], which summarizes the distance between them.
+    ///
+    /// The raw point is only stored separately for the points that were repaired, so the memory
+    /// cost is one additional vector of length `N` per repaired point (and nothing for points that
+    /// were not repaired or if bounds are disabled). This applies to every stored
+    /// `EvaluatedPoint`, such as those of
+    /// [`CMAES::current_population`][crate::CMAES::current_population].
+    pub fn raw_point(&self) -> &DVector<f64> {
+        self.raw_point.as_ref().unwrap_or(&self.point)
+    }
+
+    /// Returns the point that was evaluated after repair. This is the same as
+    /// [`point`][Self::point] and is provided for symmetry with [`raw_point`][Self::raw_point].
+    pub fn repaired_point(&self) -> &DVector<f64> {
+        &self.point
+    }
+
+    /// Returns whether the sampled point was repaired before it was evaluated (i.e. whether
+    /// [`raw_point`][Self::raw_point] differs from [`repaired_point`][Self::repaired_point]).
+    pub fn repaired(&self) -> bool {
+        self.raw_point.is_some()
+    }
+
+    /// Returns the step from the mean to the point in the internal search space, before scaling
+    /// by the step size.
     pub fn unscaled_step(&self) -> &DVector<f64> {
         &self.unscaled_step
     }
 
+    /// Returns the objective function value at the point.
     pub fn value(&self) -> f64 {
         self.value
     }
+
+    /// Returns whether the evaluated point lies within the bounds. Always `true` if bounds are
+    /// disabled.
+    pub fn feasible(&self) -> bool {
+        self.violation == 0.0
+    }
+
+    /// Returns the total distance by which the evaluated point lies outside of the bounds, summed
+    /// over all coordinates. Always `0.0` if the point is feasible.
+    ///
+    /// With [`BoundaryHandling::Transform`], every evaluated point is mapped into the bounds and is
+    /// therefore feasible. With [`BoundaryHandling::Penalty`], this is the distance of the sampled
+    /// point before it was repaired, and the [value][Self::value] of a point with a violation
+    /// includes the boundary penalty.
+    pub fn violation(&self) -> f64 {
+        self.violation
+    }
+
+    /// Returns the number of the function evaluation that produced this point over the whole run,
+    /// counted from `1` in the order in which the points of each generation were sampled. If each
+    /// point is evaluated several times (see
+    /// [`evaluations_per_candidate`][crate::CMAESOptions::evaluations_per_candidate]), this is the
+    /// number of its last evaluation.
+    pub fn evaluation(&self) -> usize {
+        self.evaluation
+    }
+
+    /// Returns whether the objective function was able to evaluate the point. Only `false` for
+    /// points for which a [`PartialObjectiveFunction`] returned `None` or for which an invalid
+    /// value was skipped (see [`InvalidPolicy`]), in which case
+    /// [`value`][Self::value] is the worst possible value (infinite) and the point does not
+    /// contribute to the update of the distribution.
+    pub fn rankable(&self) -> bool {
+        self.rankable
+    }
+}
+
+/// Returns the rankable individuals of a sorted generation (unrankable individuals are always
+/// sorted last)
+pub fn rankable_individuals(individuals: &[EvaluatedPoint]) -> &[EvaluatedPoint] {
+    let num_rankable = individuals.iter().take_while(|p| p.rankable()).count();
+    &individuals[..num_rankable]
+}
+
+/// Returns the mean Euclidean distance between all pairs of evaluated points, or `0.0` if there
+/// are fewer than two points
+///
+/// Takes O(n^2 * dim) time for n points
+pub fn mean_pairwise_distance(points: &[EvaluatedPoint]) -> f64 {
+    let n = points.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mut sum = 0.0;
+    for (i, a) in points.iter().enumerate() {
+        for b in &points[i + 1..] {
+            sum += (a.point() - b.point()).magnitude();
+        }
+    }
+
+    sum / (n * (n - 1) / 2) as f64
 }
 
 /// The objective function returned an invalid value
@@ -182,7 +1053,25 @@ pub struct InvalidFunctionValueError;
 
 #[cfg(test)]
 mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
     use super::*;
+    use crate::objective_function::Chunked;
+
+    #[cfg(not(feature = "statrs"))]
+    #[test]
+    fn test_polar_standard_normal() {
+        let mut rng = ChaCha12Rng::seed_from_u64(1);
+        let n = 100000;
+        let values = (0..n)
+            .map(|_| polar_standard_normal(&mut rng))
+            .collect::<Vec<_>>();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+
+        assert!(mean.abs() < 0.01);
+        assert!((variance - 1.0).abs() < 0.02);
+    }
 
     #[test]
     fn test_evaluated_point() {
@@ -192,14 +1081,48 @@ mod tests {
         let sigma = 3.0;
         let mut function = |x: &DVector<f64>| x.iter().sum();
 
-        let point = EvaluatedPoint::new(step.clone(), &mean, sigma, &mut function).unwrap();
+        let point = EvaluatedPoint::new(
+            step.clone(),
+            &mean,
+            sigma,
+            PointMapping::default(),
+            &mut function,
+        )
+        .unwrap();
 
         assert_eq!(point.unscaled_step, DVector::from(vec![1.0; dim]));
         assert_eq!(point.point, DVector::from(vec![5.0; dim]));
         assert_eq!(point.value, 5.0 * dim as f64);
 
         let mut function_nan = |_: &DVector<f64>| f64::NAN;
-        assert!(EvaluatedPoint::new(step, &mean, sigma, &mut function_nan).is_err());
+        assert!(EvaluatedPoint::new(
+            step,
+            &mean,
+            sigma,
+            PointMapping::default(),
+            &mut function_nan
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_mean_pairwise_distance() {
+        let point = |x: f64, y: f64| {
+            EvaluatedPoint::from_value(vec![x, y].into(), vec![0.0; 2].into(), 0.0, 0.0, None)
+                .unwrap()
+        };
+
+        assert_eq!(0.0, mean_pairwise_distance(&[]));
+        assert_eq!(0.0, mean_pairwise_distance(&[point(1.0, 2.0)]));
+        assert_eq!(
+            5.0,
+            mean_pairwise_distance(&[point(0.0, 0.0), point(3.0, 4.0)])
+        );
+        // Distances are 1, 1, and sqrt(2)
+        assert_eq!(
+            (2.0 + 2f64.sqrt()) / 3.0,
+            mean_pairwise_distance(&[point(0.0, 0.0), point(1.0, 0.0), point(0.0, 1.0)])
+        );
     }
 
     #[test]
@@ -211,7 +1134,9 @@ mod tests {
 
         let n = 5;
         for _ in 0..n {
-            let individuals = sampler.sample(&state, Mode::Minimize, false).unwrap();
+            let individuals = sampler
+                .sample(&state, Mode::Minimize, false, PointMapping::default())
+                .unwrap();
 
             assert_eq!(individuals.len(), population_size);
         }
@@ -225,7 +1150,147 @@ mod tests {
             1,
         );
 
-        assert!(sampler_nan.sample(&state, Mode::Minimize, false).is_err());
+        assert!(sampler_nan
+            .sample(&state, Mode::Minimize, false, PointMapping::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_repeated_evaluations() {
+        let dim = 3;
+        let population_size = 4;
+        let state = State::new(vec![0.0; dim].into(), 2.0);
+
+        // Every third evaluation of a point returns an outlier
+        let mut count = 0;
+        let function = move |x: &DVector<f64>| {
+            let outlier = count % 3 == 2;
+            count += 1;
+            x.sum() + if outlier { 100.0 } else { 0.0 }
+        };
+
+        for (aggregation, outlier_weight) in
+            [(Aggregation::Mean, 1.0 / 3.0), (Aggregation::Median, 0.0)]
+        {
+            let mut sampler = Sampler::new(dim, population_size, function, 1);
+            sampler.set_repeated_evaluations(3, aggregation);
+            let individuals = sampler
+                .sample(&state, Mode::Minimize, false, PointMapping::default())
+                .unwrap();
+
+            assert_eq!(3 * population_size, sampler.function_evals());
+            for (rank, individual) in individuals.iter().enumerate() {
+                let expected = individual.point.sum() + outlier_weight * 100.0;
+                assert_approx_eq!(expected, individual.value, 1e-12);
+                // Each point is numbered with its last evaluation
+                assert_eq!(3 * (sampler.ranking()[rank] + 1), individual.evaluation());
+            }
+        }
+
+        assert_eq!(2.0, Aggregation::Median.aggregate(&mut [3.0, 1.0, 2.0]));
+        assert_eq!(
+            2.5,
+            Aggregation::Median.aggregate(&mut [4.0, 1.0, 2.0, 3.0])
+        );
+        assert!(Aggregation::Mean.aggregate(&mut [1.0, f64::NAN]).is_nan());
+        assert!(Aggregation::Median
+            .aggregate(&mut [1.0, f64::NAN, 2.0])
+            .is_nan());
+    }
+
+    #[test]
+    fn test_sample_unevaluated() {
+        let dim = 4;
+        let mut sampler = Sampler::new(dim, 6, Box::new(|_: &DVector<f64>| 0.0), 1);
+        let state = State::new(vec![1.0; dim].into(), 2.0);
+
+        let points = sampler.sample_unevaluated(&state, 20, false, PointMapping::default());
+
+        assert_eq!(20, points.len());
+        assert!(points.iter().all(|p| p.len() == dim));
+        assert_eq!(0, sampler.function_evals());
+
+        // The points are mapped into the bounds if enabled
+        let bounds = Bounds::new(
+            vec![0.0; dim],
+            vec![1.5; dim],
+            crate::BoundaryHandling::Transform,
+        );
+        let points = sampler.sample_unevaluated(
+            &state,
+            20,
+            true,
+            PointMapping {
+                bounds: Some(&bounds),
+                transform: None,
+            },
+        );
+
+        assert!(points.iter().flatten().all(|x| (0.0..=1.5).contains(x)));
+    }
+
+    #[test]
+    fn test_sample_batch() {
+        let dim = 4;
+        let population_size = 10;
+        let state = State::new(vec![0.0; dim].into(), 2.0);
+        let function = |xs: &[DVector<f64>]| xs.iter().map(|x| x.sum()).collect::<Vec<_>>();
+
+        for (chunk_size, expected) in [
+            (None, vec![10]),
+            (Some(4), vec![4, 4, 2]),
+            (Some(5), vec![5, 5]),
+            (Some(20), vec![10]),
+        ] {
+            let mut sampler = match chunk_size {
+                Some(size) => Sampler::new(
+                    dim,
+                    population_size,
+                    Box::new(Chunked::new(function, size)) as Box<dyn BatchObjectiveFunction>,
+                    1,
+                ),
+                None => Sampler::new(dim, population_size, Box::new(function) as _, 1),
+            };
+            let individuals = sampler
+                .sample_batch(&state, Mode::Minimize, false, PointMapping::default())
+                .unwrap();
+
+            assert_eq!(individuals.len(), population_size);
+            assert_eq!(sampler.chunk_sizes(), expected.as_slice());
+            assert_eq!(sampler.function_evals(), population_size);
+            // Each value corresponds to its own point
+            assert!(individuals.iter().all(|ind| ind.value == ind.point.sum()));
+        }
+
+        let mut sampler_nan = Sampler::new(
+            dim,
+            population_size,
+            Chunked::new(
+                |xs: &[DVector<f64>]| {
+                    let mut values = vec![0.0; xs.len()];
+                    values[0] = f64::NAN;
+                    values
+                },
+                3,
+            ),
+            1,
+        );
+
+        assert!(sampler_nan
+            .sample_batch(&state, Mode::Minimize, false, PointMapping::default())
+            .is_err());
+
+        // Returning the wrong number of values is treated as an invalid function value
+        let mut sampler_short = Sampler::new(
+            dim,
+            population_size,
+            |xs: &[DVector<f64>]| vec![0.0; xs.len() - 1],
+            1,
+        );
+
+        assert!(sampler_short
+            .sample_batch(&state, Mode::Minimize, false, PointMapping::default())
+            .is_err());
     }
 
     fn sample_sort(mode: Mode, expected: [f64; 5]) {
@@ -246,7 +1311,9 @@ mod tests {
         let mut sampler = Sampler::new(dim, population_size, function, 1);
         let state = State::new(vec![0.0; dim].into(), 2.0);
 
-        let individuals = sampler.sample(&state, mode, false).unwrap();
+        let individuals = sampler
+            .sample(&state, mode, false, PointMapping::default())
+            .unwrap();
         let values = individuals
             .into_iter()
             .map(|ind| ind.value)
@@ -0,0 +1,108 @@
+//! Checks of the determinism of the algorithm on the current platform. See
+//! [`check_reproducibility`] for full documentation.
+
+use nalgebra::DVector;
+
+use crate::options::InvalidOptionsError;
+use crate::{CMAESOptions, ObjectiveFunction, CMAES};
+
+/// Runs two identical seeded optimizations and returns whether their trajectories are
+/// bit-identical, to check that a seed fully determines a run on the current platform.
+///
+/// After every generation, the mean, the step size, and the best function value of the two runs
+/// are compared, as well as the termination reasons and number of function evaluations at the end.
+/// No tolerance is used: every value must have exactly the same bit pattern, because any
+/// difference (e.g. caused by platform-dependent floating point behavior, a non-deterministic
+/// objective function, or a change in the random number generation) would grow over the course of
+/// a run. The runs stop at the first mismatch.
+///
+/// Each run uses its own clone of `objective_function`, so the function must give the same results
+/// for both clones. If `options` has no seed, a random one is chosen and used for both runs. The
+/// runs use [`CMAES::next`][crate::CMAES::next] until termination, so `options` should include a
+/// limit such as [`max_generations`][CMAESOptions::max_generations] to keep the check short.
+///
+/// Returns `Err` if the options are invalid.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let function = |x: &DVector<f64>| x.magnitude();
+/// let options = CMAESOptions::new(vec![1.0; 4], 1.0)
+///     .seed(1)
+///     .max_generations(100);
+///
+/// assert!(cmaes::selftest::check_reproducibility(options, function).unwrap());
+/// ```
+pub fn check_reproducibility<F: ObjectiveFunction + Clone>(
+    mut options: CMAESOptions,
+    objective_function: F,
+) -> Result<bool, InvalidOptionsError> {
+    options.seed = Some(options.seed.unwrap_or_else(rand::random));
+
+    let mut first = options.clone().build(objective_function.clone())?;
+    let mut second = options.build(objective_function)?;
+
+    loop {
+        let (first_result, second_result) = (first.next(), second.next());
+
+        let best_value = |cmaes: &CMAES<F>| {
+            cmaes
+                .current_best_individual()
+                .map(|individual| individual.value.to_bits())
+        };
+        if !same_bits(first.mean(), second.mean())
+            || first.sigma().to_bits() != second.sigma().to_bits()
+            || best_value(&first) != best_value(&second)
+        {
+            return Ok(false);
+        }
+
+        match (first_result, second_result) {
+            (None, None) => {}
+            (Some(first_result), Some(second_result)) => {
+                return Ok(first_result.reasons == second_result.reasons
+                    && first_result.function_evals == second_result.function_evals);
+            }
+            _ => return Ok(false),
+        }
+    }
+}
+
+/// Returns whether `a` and `b` have the same length and bit-identical elements
+fn same_bits(a: &DVector<f64>, b: &DVector<f64>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(x, y)| x.to_bits() == y.to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_check_reproducibility() {
+        let options = CMAESOptions::new(vec![1.0; 3], 1.0).max_generations(50);
+
+        let function = |x: &DVector<f64>| x.magnitude();
+        assert!(check_reproducibility(options.clone(), function).unwrap());
+        assert!(check_reproducibility(options.clone().seed(5), function).unwrap());
+
+        // The clones share a counter, so the second run sees different function values
+        let counter = Arc::new(AtomicUsize::new(0));
+        let shared = move |x: &DVector<f64>| {
+            x.magnitude() + 1e-9 * counter.fetch_add(1, Ordering::Relaxed) as f64
+        };
+        assert!(!check_reproducibility(options.clone(), shared).unwrap());
+
+        assert!(matches!(
+            check_reproducibility(options.population_size(1), function),
+            Err(InvalidOptionsError::PopulationSize),
+        ));
+    }
+}
@@ -0,0 +1,152 @@
+//! A JSON-serializable snapshot of the parameters and scalar state of a [`CMAES`]. See
+//! [`CMAES::to_json`] for the schema.
+
+use nalgebra::DVector;
+use serde::Serialize;
+
+use crate::parameters::{Parameters, RecombinationWeighting, Weights};
+use crate::{Individual, Mode, CMAES};
+
+/// The root of the snapshot
+#[derive(Serialize)]
+pub(crate) struct Snapshot {
+    parameters: ParametersSnapshot,
+    state: StateSnapshot,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the current parameters and state of `cmaes`
+    pub fn new<F>(cmaes: &CMAES<F>) -> Self {
+        let state = &cmaes.state;
+        Self {
+            parameters: ParametersSnapshot::new(cmaes.parameters()),
+            state: StateSnapshot {
+                generation: cmaes.generation(),
+                function_evals: cmaes.function_evals(),
+                sigma: cmaes.sigma(),
+                mean: to_vec(cmaes.mean()),
+                axis_ratio: cmaes.axis_ratio(),
+                eigenvalues: to_vec(&cmaes.eigenvalues()),
+                covariance: cmaes
+                    .covariance_matrix()
+                    .row_iter()
+                    .map(|row| row.iter().cloned().collect())
+                    .collect(),
+                path_c: to_vec(state.path_c()),
+                path_sigma: to_vec(state.path_sigma()),
+                current_best: cmaes.current_best_individual().map(IndividualSnapshot::new),
+                overall_best: cmaes.overall_best_individual().map(IndividualSnapshot::new),
+            },
+        }
+    }
+}
+
+/// The resolved parameters of the algorithm
+#[derive(Serialize)]
+struct ParametersSnapshot {
+    mode: Mode,
+    dimensions: usize,
+    lambda: usize,
+    mu: usize,
+    mu_eff: f64,
+    weights_setting: Weights,
+    weights: Vec<f64>,
+    recombination_weighting: RecombinationWeighting,
+    initial_sigma: f64,
+    cc: f64,
+    c1: f64,
+    cs: f64,
+    cmu: f64,
+    cm: f64,
+    damp_s: f64,
+    seed: u64,
+    termination: TerminationSnapshot,
+}
+
+impl ParametersSnapshot {
+    fn new(parameters: &Parameters) -> Self {
+        Self {
+            mode: parameters.mode(),
+            dimensions: parameters.dim(),
+            lambda: parameters.lambda(),
+            mu: parameters.mu(),
+            mu_eff: parameters.mu_eff(),
+            weights_setting: parameters.weights_setting(),
+            weights: to_vec(parameters.weights()),
+            recombination_weighting: parameters.recombination_weighting(),
+            initial_sigma: parameters.initial_sigma(),
+            cc: parameters.cc(),
+            c1: parameters.c1(),
+            cs: parameters.cs(),
+            cmu: parameters.cmu(),
+            cm: parameters.cm(),
+            damp_s: parameters.damp_s(),
+            seed: parameters.seed(),
+            termination: TerminationSnapshot {
+                max_function_evals: parameters.max_function_evals(),
+                max_generations: parameters.max_generations(),
+                max_time: parameters.max_time().map(|t| t.as_secs_f64()),
+                fun_target: parameters.fun_target(),
+                tol_fun: parameters.tol_fun(),
+                tol_fun_rel: parameters.tol_fun_rel(),
+                tol_fun_hist: parameters.tol_fun_hist(),
+                tol_x: parameters.tol_x(),
+                tol_x_up: parameters.tol_x_up(),
+                tol_stagnation: parameters.tol_stagnation(),
+                tol_condition_cov: parameters.tol_condition_cov(),
+            },
+        }
+    }
+}
+
+/// The values of the main termination criteria
+#[derive(Serialize)]
+struct TerminationSnapshot {
+    max_function_evals: Option<usize>,
+    max_generations: Option<usize>,
+    max_time: Option<f64>,
+    fun_target: Option<f64>,
+    tol_fun: f64,
+    tol_fun_rel: f64,
+    tol_fun_hist: f64,
+    tol_x: f64,
+    tol_x_up: f64,
+    tol_stagnation: usize,
+    tol_condition_cov: f64,
+}
+
+/// The current state of the algorithm
+#[derive(Serialize)]
+struct StateSnapshot {
+    generation: usize,
+    function_evals: usize,
+    sigma: f64,
+    mean: Vec<f64>,
+    axis_ratio: f64,
+    eigenvalues: Vec<f64>,
+    covariance: Vec<Vec<f64>>,
+    path_c: Vec<f64>,
+    path_sigma: Vec<f64>,
+    current_best: Option<IndividualSnapshot>,
+    overall_best: Option<IndividualSnapshot>,
+}
+
+/// A point and its objective function value
+#[derive(Serialize)]
+struct IndividualSnapshot {
+    point: Vec<f64>,
+    value: f64,
+}
+
+impl IndividualSnapshot {
+    fn new(individual: &Individual) -> Self {
+        Self {
+            point: to_vec(&individual.point),
+            value: individual.value,
+        }
+    }
+}
+
+fn to_vec(vector: &DVector<f64>) -> Vec<f64> {
+    vector.iter().cloned().collect()
+}
@@ -1,13 +1,50 @@
-//! Variable state of the algorithm and updating of that state.
+//! Variable state of the algorithm and updating of that state. Only public with the `unstable`
+//! feature (see [`State`]).
 
 use nalgebra::DVector;
 
-use crate::matrix::{CovarianceMatrix, PosDefCovError, SquareMatrix};
-use crate::parameters::Parameters;
+use crate::matrix::{CovarianceMatrix, SquareMatrix};
+use crate::parameters::{self, Parameters};
 use crate::sampling::EvaluatedPoint;
 use rayon::prelude::*;
 
+pub use crate::matrix::PosDefCovError;
+pub use crate::termination::StateComponent;
+
+/// An error produced by [`State::update`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateError {
+    /// The covariance matrix is not positive-definite
+    PosDefCov,
+    /// A component of the state became non-finite
+    NonFinite(StateComponent),
+}
+
+impl From<PosDefCovError> for UpdateError {
+    fn from(_: PosDefCovError) -> Self {
+        Self::PosDefCov
+    }
+}
+
+/// An error produced by [`State::from_parts`]
+#[cfg(feature = "unstable")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidStateError {
+    /// The length of a path or the size of the covariance matrix does not match the length of
+    /// the mean
+    Dimensions,
+    /// The step size is not finite or not positive
+    Sigma,
+    /// The covariance matrix is not positive-definite
+    PosDefCov,
+}
+
 /// Stores the variable state of the algorithm and handles updating it
+///
+/// Only public with the `unstable` feature, for constructing hand-crafted states with
+/// [`State::from_parts`] and `CMAES::from_parts`. Its contents and methods may change in any
+/// release.
+#[derive(Clone)]
 pub struct State {
     /// The number of generations that have been fully completed
     generation: usize,
@@ -23,6 +60,20 @@ pub struct State {
     path_sigma: DVector<f64>,
     /// The last time the eigendecomposition was updated, in function evals
     last_eigen_update_evals: usize,
+    /// Whether the covariance matrix has changed since the eigendecomposition was last updated
+    eigen_outdated: bool,
+    /// The weighted recombination of the steps of the latest generation (the change of the mean
+    /// before applying the learning rate `cm`), scaled by the inverse of the covariance matrix
+    /// `sigma^2 * C` that the generation was sampled from
+    search_direction: DVector<f64>,
+    /// The change of the mean in the latest update (`None` before the first update)
+    last_mean_shift: Option<DVector<f64>>,
+    /// Whether the latest covariance matrix update included the negative weights
+    active_update_applied: bool,
+    /// Multiplier applied to the learning rates `c1` and `cmu` of the covariance matrix update
+    covariance_learning_scale: f64,
+    /// Whether the covariance matrix update is skipped
+    covariance_frozen: bool,
 }
 
 impl State {
@@ -43,7 +94,53 @@ impl State {
             path_c,
             path_sigma,
             last_eigen_update_evals: 0,
+            eigen_outdated: false,
+            search_direction: DVector::zeros(dim),
+            last_mean_shift: None,
+            active_update_applied: false,
+            covariance_learning_scale: 1.0,
+            covariance_frozen: false,
+        }
+    }
+
+    /// Returns a state with the given distribution and evolution paths, as if `generation`
+    /// generations had been completed. The mean and step size are in the internal search space of
+    /// the algorithm (see [`BoundaryHandling`][crate::BoundaryHandling] and
+    /// [`CoordinateTransform`][crate::CoordinateTransform]). `cov` is symmetrized using its upper
+    /// triangle. Covariance matrices can be created with nalgebra's `DMatrix`.
+    ///
+    /// Returns `Err` if the lengths of the vectors and the size of `cov` do not match, if `sigma`
+    /// is not finite or not positive, or if `cov` is not positive-definite.
+    #[cfg(feature = "unstable")]
+    pub fn from_parts(
+        mean: DVector<f64>,
+        sigma: f64,
+        cov: SquareMatrix<f64>,
+        path_c: DVector<f64>,
+        path_sigma: DVector<f64>,
+        generation: usize,
+    ) -> Result<Self, InvalidStateError> {
+        let dim = mean.len();
+        if cov.nrows() != dim
+            || cov.ncols() != dim
+            || path_c.len() != dim
+            || path_sigma.len() != dim
+        {
+            return Err(InvalidStateError::Dimensions);
         }
+        if !sigma.is_finite() || sigma <= 0.0 {
+            return Err(InvalidStateError::Sigma);
+        }
+
+        let mut state = Self::new(mean, sigma);
+        state
+            .cov
+            .set_cov(cov, true)
+            .map_err(|_| InvalidStateError::PosDefCov)?;
+        state.path_c = path_c;
+        state.path_sigma = path_sigma;
+        state.generation = generation;
+        Ok(state)
     }
 
     /// Updates the variable state using the provided sampled individuals
@@ -52,16 +149,38 @@ impl State {
         current_function_evals: usize,
         params: &Parameters,
         individuals: &[EvaluatedPoint],
-    ) -> Result<(), PosDefCovError> {
+    ) -> Result<(), UpdateError> {
         let dim = params.dim();
         let mu = params.mu();
         let mu_eff = params.mu_eff();
         let cc = params.cc();
-        let c1 = params.c1();
         let cs = params.cs();
-        let cmu = params.cmu();
+        // Scale the covariance learning rates, capping the scale so that the weight of the old
+        // covariance matrix stays non-negative
+        let cov_scale = self
+            .covariance_learning_scale
+            .min(1.0 / (params.c1() + params.cmu()));
+        let c1 = cov_scale * params.c1();
+        let cmu = cov_scale * params.cmu();
         let cm = params.cm();
         let damp_s = params.damp_s();
+        // Unrankable individuals are sorted last and do not contribute to the update
+        // The fitness transform only affects the weights, not the ranking
+        let values = individuals
+            .iter()
+            .take_while(|p| p.rankable())
+            .map(|p| match params.fitness_transform() {
+                Some(transform) => transform.apply(p.value()),
+                None => p.value(),
+            })
+            .collect::<Vec<_>>();
+        let mut weights = parameters::recombination_weights(
+            params.recombination_weighting(),
+            params.weights(),
+            mu,
+            &values,
+        );
+        parameters::discard_unrankable_weights(&mut weights, values.len());
 
         // Calculate new mean through weighted recombination
         // Only the mu best individuals are used even if there are lambda weights
@@ -69,12 +188,17 @@ impl State {
             .iter()
             .take(mu)
             .enumerate()
-            .map(|(i, p)| p.unscaled_step() * params.weights()[i])
+            .map(|(i, p)| p.unscaled_step() * weights[i])
             .sum::<DVector<f64>>();
-        self.mean = &self.mean + &(cm * self.sigma * &yw);
+        let mean_shift = cm * self.sigma * &yw;
+        self.mean = &self.mean + &mean_shift;
+        self.last_mean_shift = Some(mean_shift);
 
-        // Update evolution paths
+        // The change of the mean scaled by (sigma^2 * C)^-1, which simplifies to C^-1 * yw / sigma
         let sqrt_inv_c = self.cov.sqrt_inv();
+        self.search_direction = sqrt_inv_c * (sqrt_inv_c * &yw) / self.sigma;
+
+        // Update evolution paths
 
         self.path_sigma =
             (1.0 - cs) * &self.path_sigma + (cs * (2.0 - cs) * mu_eff).sqrt() * sqrt_inv_c * &yw;
@@ -94,51 +218,120 @@ impl State {
 
         self.path_c = (1.0 - cc) * &self.path_c + hs * (cc * (2.0 - cc) * mu_eff).sqrt() * &yw;
 
-        // Update step size
-        self.sigma *= ((cs / damp_s) * ((self.path_sigma.magnitude() / chi_n) - 1.0)).exp();
+        // Update step size, limiting the change if enabled
+        let mut sigma_factor =
+            ((cs / damp_s) * ((self.path_sigma.magnitude() / chi_n) - 1.0)).exp();
+        if let Some(max_sigma_change) = params.max_sigma_change() {
+            sigma_factor = sigma_factor.clamp(1.0 / max_sigma_change, max_sigma_change);
+        }
+        self.sigma *= sigma_factor;
 
-        // Update covariance matrix
+        // Check the sigma first because a non-finite sigma also makes the mean non-finite
+        if !self.sigma.is_finite() {
+            return Err(UpdateError::NonFinite(StateComponent::Sigma));
+        }
+        if self.mean.iter().any(|x| !x.is_finite()) {
+            return Err(UpdateError::NonFinite(StateComponent::Mean));
+        }
 
-        // Calculates the weighted contribution of each individual to the rank-mu update
-        let map_weights = |(i, w): (usize, f64)| {
-            // Scale negative weights to maintain positive definiteness of cov
-            let mut wc = w;
-            if w < 0.0 {
-                wc *= dim as f64
-                    / (sqrt_inv_c * individuals[i].unscaled_step())
-                        .magnitude()
-                        .powi(2);
+        if self.covariance_frozen {
+            self.active_update_applied = false;
+
+            // Bring the eigendecomposition up to date once so that the frozen covariance matrix is
+            // the one that is sampled from
+            if self.eigen_outdated {
+                let cov = self.cov.cov().clone();
+                self.update_cov(cov, true, current_function_evals, params)?;
             }
 
-            wc * individuals[i].unscaled_step() * individuals[i].unscaled_step().transpose()
-        };
-        let rank_mu_update = if params.parallel_update() {
-            rank_mu_update_parallel(params.weights().as_slice(), map_weights, || {
-                SquareMatrix::zeros(dim, dim)
+            self.generation += 1;
+            return Ok(());
+        }
+
+        // Update covariance matrix
+        let delta_hs = (1.0 - hs) * cc * (2.0 - cc);
+
+        // Each negative contribution to the rank-mu update is scaled below to a size of `dim * |w|`
+        // in the metric of the covariance matrix, so the updated matrix stays positive-definite as
+        // long as the weight of the old matrix exceeds their total. The default weights satisfy
+        // this, but otherwise fall back to a positive-only update for numerical safety
+        let sum_negative_weights = weights.iter().filter(|w| **w < 0.0).sum::<f64>().abs();
+        let old_cov_weight = 1.0 + c1 * delta_hs - c1 - cmu * weights.iter().sum::<f64>();
+        self.active_update_applied = sum_negative_weights > 0.0
+            && cmu > 0.0
+            && old_cov_weight > cmu * dim as f64 * sum_negative_weights;
+        if !self.active_update_applied {
+            weights
+                .iter_mut()
+                .filter(|w| **w < 0.0)
+                .for_each(|w| *w = 0.0);
+        }
+
+        // Calculates the weight of each individual in the rank-mu update
+        let steps = individuals
+            .iter()
+            .take(weights.len())
+            .map(|p| p.unscaled_step())
+            .collect::<Vec<_>>();
+        let scaled_weights = weights
+            .iter()
+            .zip(&steps)
+            .map(|(&w, y)| {
+                // Scale negative weights to maintain positive definiteness of cov
+                if w < 0.0 {
+                    w * dim as f64 / (sqrt_inv_c * *y).magnitude().powi(2)
+                } else {
+                    w
+                }
             })
+            .collect::<Vec<_>>();
+        let rank_mu_update = if params.parallel_update() {
+            rank_mu_update_parallel(&scaled_weights, &steps)
         } else {
-            rank_mu_update(params.weights().as_slice(), map_weights)
+            rank_mu_update(&scaled_weights, &steps)
         };
 
-        let delta_hs = (1.0 - hs) * cc * (2.0 - cc);
-        let cov_new = (1.0 + c1 * delta_hs - c1 - cmu * params.weights().iter().sum::<f64>())
+        let cov_new = (1.0 + c1 * delta_hs - c1 - cmu * weights.iter().sum::<f64>())
             * self.cov.cov()
             + c1 * &self.path_c * self.path_c.transpose()
             + cmu * rank_mu_update;
 
+        if cov_new.iter().any(|x| !x.is_finite()) {
+            return Err(UpdateError::NonFinite(StateComponent::Covariance));
+        }
+
         // Update eigendecomposition occasionally (updating every generation is unnecessary and
         // inefficient for high dim)
         let evals_per_eigen = self.evals_per_eigen_update(params);
         let do_eigen_update =
             current_function_evals >= self.last_eigen_update_evals + evals_per_eigen;
 
-        self.cov.set_cov(cov_new, do_eigen_update)?;
+        self.update_cov(cov_new, do_eigen_update, current_function_evals, params)?;
+
+        self.generation += 1;
 
-        if do_eigen_update {
+        Ok(())
+    }
+
+    /// Sets the covariance matrix, also updating its eigendecomposition if `update_eigen` is set
+    fn update_cov(
+        &mut self,
+        cov: SquareMatrix<f64>,
+        update_eigen: bool,
+        current_function_evals: usize,
+        params: &Parameters,
+    ) -> Result<(), PosDefCovError> {
+        self.cov.set_cov(cov, update_eigen)?;
+        self.eigen_outdated = !update_eigen;
+
+        if update_eigen {
             self.last_eigen_update_evals = current_function_evals;
-        }
 
-        self.generation += 1;
+            // Prevent the smallest eigenvalues from collapsing if enabled
+            if let Some(min_eigenvalue_ratio) = params.min_eigenvalue_ratio() {
+                self.cov.floor_eigenvalues(min_eigenvalue_ratio);
+            }
+        }
 
         Ok(())
     }
@@ -151,6 +344,22 @@ impl State {
         &self.mean
     }
 
+    /// Returns the change of the mean in the latest update before applying the learning rate,
+    /// scaled by the inverse covariance matrix
+    pub fn search_direction(&self) -> &DVector<f64> {
+        &self.search_direction
+    }
+
+    /// Returns the change of the mean in the latest update
+    pub fn last_mean_shift(&self) -> Option<&DVector<f64>> {
+        self.last_mean_shift.as_ref()
+    }
+
+    /// Returns whether the latest covariance matrix update included the negative weights
+    pub fn active_update_applied(&self) -> bool {
+        self.active_update_applied
+    }
+
     pub fn cov(&self) -> &SquareMatrix<f64> {
         self.cov.cov()
     }
@@ -163,11 +372,49 @@ impl State {
         self.cov.sqrt_eigenvalues()
     }
 
+    /// Returns the inverse square root of the covariance matrix (`C^(-1/2)`)
+    pub fn cov_sqrt_inv(&self) -> &SquareMatrix<f64> {
+        self.cov.sqrt_inv()
+    }
+
     /// Returns the transform of the covariance matrix (`B * D`)
     pub fn cov_transform(&self) -> &SquareMatrix<f64> {
         self.cov.transform()
     }
 
+    /// Returns the logarithm of the probability density of `point` (in the search space) under the
+    /// distribution `N(mean, sigma^2 * C)`, using the cached eigendecomposition of `C` (which is
+    /// also used for sampling)
+    pub fn log_density(&self, point: &DVector<f64>) -> f64 {
+        let dim = self.mean.len() as f64;
+        let log_det_cov = 2.0 * dim * self.sigma.ln()
+            + 2.0
+                * self
+                    .cov
+                    .sqrt_eigenvalues()
+                    .diagonal()
+                    .iter()
+                    .map(|d| d.ln())
+                    .sum::<f64>();
+        let squared_distance =
+            (self.cov.sqrt_inv() * (point - &self.mean)).magnitude_squared() / self.sigma.powi(2);
+
+        -0.5 * (dim * (2.0 * std::f64::consts::PI).ln() + log_det_cov + squared_distance)
+    }
+
+    /// Returns the generalized volume of the distribution, `sigma^N * sqrt(det(C))`, using the
+    /// cached eigendecomposition of `C` (computed in log space to avoid intermediate overflow)
+    pub fn distribution_volume(&self) -> f64 {
+        let log_sqrt_det_cov = self
+            .cov
+            .sqrt_eigenvalues()
+            .diagonal()
+            .iter()
+            .map(|d| d.ln())
+            .sum::<f64>();
+        (self.mean.len() as f64 * self.sigma.ln() + log_sqrt_det_cov).exp()
+    }
+
     /// Returns the current axis ratio of the distribution
     pub fn axis_ratio(&self) -> f64 {
         let diag = self.cov.sqrt_eigenvalues().diagonal();
@@ -178,10 +425,67 @@ impl State {
         self.sigma
     }
 
+    /// Moves the mean by `step` (in the search space) and multiplies the step size by
+    /// `sigma_factor`, leaving the covariance matrix and evolution paths unchanged
+    pub fn kick(&mut self, step: &DVector<f64>, sigma_factor: f64) {
+        self.mean += step;
+        self.sigma *= sigma_factor;
+    }
+
+    /// Replaces the covariance matrix (symmetrized using its upper triangle) and updates its
+    /// eigendecomposition, flooring the eigenvalues if `min_eigenvalue_ratio` is set. The state is
+    /// left unchanged if the matrix is not positive-definite.
+    pub fn set_cov(
+        &mut self,
+        cov: SquareMatrix<f64>,
+        min_eigenvalue_ratio: Option<f64>,
+    ) -> Result<(), PosDefCovError> {
+        let mut new_cov = CovarianceMatrix::new(cov.nrows());
+        new_cov.set_cov(cov, true)?;
+        if let Some(min_eigenvalue_ratio) = min_eigenvalue_ratio {
+            new_cov.floor_eigenvalues(min_eigenvalue_ratio);
+        }
+        self.cov = new_cov;
+        self.eigen_outdated = false;
+        Ok(())
+    }
+
+    /// Sets the step size, leaving the rest of the distribution unchanged
+    pub fn set_sigma(&mut self, sigma: f64) {
+        self.sigma = sigma;
+    }
+
+    pub fn covariance_learning_scale(&self) -> f64 {
+        self.covariance_learning_scale
+    }
+
+    /// Sets the multiplier of the covariance matrix learning rates used by subsequent updates
+    pub fn set_covariance_learning_scale(&mut self, scale: f64) {
+        self.covariance_learning_scale = scale;
+    }
+
+    pub fn covariance_frozen(&self) -> bool {
+        self.covariance_frozen
+    }
+
+    /// Sets whether subsequent updates skip the covariance matrix update
+    pub fn freeze_covariance(&mut self, frozen: bool) {
+        self.covariance_frozen = frozen;
+    }
+
     pub fn path_c(&self) -> &DVector<f64> {
         &self.path_c
     }
 
+    pub fn path_sigma(&self) -> &DVector<f64> {
+        &self.path_sigma
+    }
+
+    pub fn set_paths(&mut self, path_c: DVector<f64>, path_sigma: DVector<f64>) {
+        self.path_c = path_c;
+        self.path_sigma = path_sigma;
+    }
+
     /// Returns how many function evals should pass before updating the eigendecomposition
     pub fn evals_per_eigen_update(&self, params: &Parameters) -> usize {
         (0.5 * params.dim() as f64 * params.lambda() as f64
@@ -211,24 +515,41 @@ impl State {
     }
 }
 
-/// Calculates the rank-mu update term (ignoring cmu)
-fn rank_mu_update<F>(weights: &[f64], map_weights: F) -> SquareMatrix<f64>
-where
-    F: Fn((usize, f64)) -> SquareMatrix<f64>,
-{
-    weights.iter().cloned().enumerate().map(map_weights).sum()
+/// Calculates the rank-mu update term (ignoring cmu), `sum(w_i * y_i * y_i^T)`
+fn rank_mu_update(weights: &[f64], steps: &[&DVector<f64>]) -> SquareMatrix<f64> {
+    weights
+        .iter()
+        .zip(steps)
+        .map(|(w, y)| *w * *y * y.transpose())
+        .sum()
 }
 
-/// Like `rank_mu_update`, but uses parallel iterators instead
-fn rank_mu_update_parallel<F, I>(weights: &[f64], map_weights: F, identity: I) -> SquareMatrix<f64>
-where
-    F: Send + Sync + Fn((usize, f64)) -> SquareMatrix<f64>,
-    I: Send + Sync + Fn() -> SquareMatrix<f64>,
-{
-    weights
-        .par_iter()
-        .cloned()
-        .enumerate()
-        .map(map_weights)
-        .reduce(identity, |a, b| a + b)
+/// Like `rank_mu_update`, but computes the columns of the result in parallel. Each column is
+/// accumulated over the individuals sequentially in the same order as by `rank_mu_update`, so the
+/// result is bit-identical to it regardless of how the work is scheduled across threads (a
+/// parallel reduction over the individuals would group the floating point additions differently
+/// depending on the scheduling). This uses O(dim^2) memory for the columns instead of one matrix
+/// per individual.
+fn rank_mu_update_parallel(weights: &[f64], steps: &[&DVector<f64>]) -> SquareMatrix<f64> {
+    let dim = steps[0].len();
+    let weighted_steps = weights
+        .iter()
+        .zip(steps)
+        .map(|(w, y)| *w * *y)
+        .collect::<Vec<_>>();
+
+    let columns = (0..dim)
+        .into_par_iter()
+        .map(|j| {
+            let mut column = DVector::zeros(dim);
+            for (wy, y) in weighted_steps.iter().zip(steps) {
+                for (c, wy_i) in column.iter_mut().zip(wy.iter()) {
+                    *c += wy_i * y[j];
+                }
+            }
+            column
+        })
+        .collect::<Vec<_>>();
+
+    SquareMatrix::from_columns(&columns)
 }
@@ -0,0 +1,115 @@
+//! The in-place updating status line enabled by
+//! [`CMAESOptions::live_status`][crate::CMAESOptions::live_status].
+
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// The minimum time between updates of the status line when stdout is a terminal
+const TERMINAL_INTERVAL: Duration = Duration::from_millis(250);
+/// The minimum time between status lines when stdout is not a terminal
+const NON_TERMINAL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A rate-limited status line, which is updated in place using a carriage return if stdout is a
+/// terminal and printed as separate lines otherwise
+#[derive(Clone, Debug)]
+pub struct LiveStatus {
+    /// Whether stdout is a terminal
+    is_terminal: bool,
+    /// The time of the latest update
+    last_update: Option<Instant>,
+    /// The length of the latest status line printed in place, used to overwrite it fully
+    line_len: usize,
+}
+
+impl LiveStatus {
+    /// Returns a new `LiveStatus` for the current stdout
+    pub fn new() -> Self {
+        Self::with_terminal(io::stdout().is_terminal())
+    }
+
+    fn with_terminal(is_terminal: bool) -> Self {
+        Self {
+            is_terminal,
+            last_update: None,
+            line_len: 0,
+        }
+    }
+
+    /// Returns the minimum time between updates
+    fn interval(&self) -> Duration {
+        if self.is_terminal {
+            TERMINAL_INTERVAL
+        } else {
+            NON_TERMINAL_INTERVAL
+        }
+    }
+
+    /// Returns whether an update is due at `now`
+    fn is_due(&self, now: Instant) -> bool {
+        match self.last_update {
+            Some(last_update) => now.duration_since(last_update) >= self.interval(),
+            None => true,
+        }
+    }
+
+    /// Prints the status produced by `status` if an update is due
+    pub fn update<S: FnOnce() -> String>(&mut self, status: S) {
+        let now = Instant::now();
+        if self.is_due(now) {
+            self.print(&status());
+            self.last_update = Some(now);
+        }
+    }
+
+    /// Prints the final status and ends the status line
+    pub fn finish(&mut self, status: &str) {
+        self.print(status);
+        if self.is_terminal {
+            println!();
+            self.line_len = 0;
+        }
+        self.last_update = Some(Instant::now());
+    }
+
+    fn print(&mut self, status: &str) {
+        if self.is_terminal {
+            // Pad with spaces to overwrite the rest of a longer previous line
+            let len = status.chars().count();
+            print!(
+                "\r{}{}",
+                status,
+                " ".repeat(self.line_len.saturating_sub(len))
+            );
+            let _ = io::stdout().flush();
+            self.line_len = len;
+        } else {
+            println!("{}", status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_due() {
+        let start = Instant::now();
+        for is_terminal in [true, false] {
+            let mut status = LiveStatus::with_terminal(is_terminal);
+            let interval = status.interval();
+            assert!(status.is_due(start));
+
+            status.last_update = Some(start);
+            assert!(!status.is_due(start));
+            assert!(!status.is_due(start + interval / 2));
+            assert!(status.is_due(start + interval));
+        }
+
+        // Non-terminal outputs are updated much less often
+        assert!(
+            LiveStatus::with_terminal(false).interval()
+                > LiveStatus::with_terminal(true).interval() * 10
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Running a grid of option variations for studying the algorithm itself. See [`sweep`] for full
+//! documentation.
+
+use rayon::prelude::*;
+
+use crate::options::InvalidOptionsError;
+use crate::{CMAESOptions, ObjectiveFunction, RecombinationWeighting, TerminationData, Weights};
+
+/// The value of a single option in a variation of a [`sweep`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OptionValue {
+    /// See [`CMAESOptions::population_size`].
+    PopulationSize(usize),
+    /// See [`CMAESOptions::weights`].
+    Weights(Weights),
+    /// See [`CMAESOptions::recombination_weighting`].
+    RecombinationWeighting(RecombinationWeighting),
+    /// See [`CMAESOptions::cm`].
+    Cm(f64),
+    /// See [`CMAESOptions::initial_step_size`].
+    InitialStepSize(f64),
+}
+
+impl OptionValue {
+    /// Sets the option to this value
+    fn apply(self, options: CMAESOptions) -> CMAESOptions {
+        match self {
+            Self::PopulationSize(population_size) => options.population_size(population_size),
+            Self::Weights(weights) => options.weights(weights),
+            Self::RecombinationWeighting(weighting) => options.recombination_weighting(weighting),
+            Self::Cm(cm) => options.cm(cm),
+            Self::InitialStepSize(initial_step_size) => {
+                options.initial_step_size(initial_step_size)
+            }
+        }
+    }
+}
+
+/// Describes which options to vary in a [`sweep`]. Each call to one of the builder methods adds
+/// an axis of the grid, and every combination of the values of all axes is run.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::sweep::Variations;
+/// use cmaes::Weights;
+///
+/// // 3 * 2 = 6 configurations, each run twice
+/// let variations = Variations::new()
+///     .population_size([6, 12, 24])
+///     .weights([Weights::Negative, Weights::Positive])
+///     .repetitions(2);
+///
+/// assert_eq!(12, variations.len());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Variations {
+    axes: Vec<Vec<OptionValue>>,
+    repetitions: usize,
+}
+
+impl Variations {
+    /// Creates a new `Variations` that does not vary any options and runs each configuration once
+    /// (i.e. a sweep only runs the base options).
+    pub fn new() -> Self {
+        Self {
+            axes: Vec::new(),
+            repetitions: 1,
+        }
+    }
+
+    /// Varies the population size over the given values.
+    pub fn population_size<I: IntoIterator<Item = usize>>(self, values: I) -> Self {
+        self.axis(values.into_iter().map(OptionValue::PopulationSize))
+    }
+
+    /// Varies the weights over the given values.
+    pub fn weights<I: IntoIterator<Item = Weights>>(self, values: I) -> Self {
+        self.axis(values.into_iter().map(OptionValue::Weights))
+    }
+
+    /// Varies the recombination weighting over the given values.
+    pub fn recombination_weighting<I>(self, values: I) -> Self
+    where
+        I: IntoIterator<Item = RecombinationWeighting>,
+    {
+        self.axis(values.into_iter().map(OptionValue::RecombinationWeighting))
+    }
+
+    /// Varies the learning rate for the mean over the given values.
+    pub fn cm<I: IntoIterator<Item = f64>>(self, values: I) -> Self {
+        self.axis(values.into_iter().map(OptionValue::Cm))
+    }
+
+    /// Varies the initial step size over the given values.
+    pub fn initial_step_size<I: IntoIterator<Item = f64>>(self, values: I) -> Self {
+        self.axis(values.into_iter().map(OptionValue::InitialStepSize))
+    }
+
+    /// Varies any options over the given values. If several values of an axis set the same
+    /// option, the last one is used.
+    pub fn values<I: IntoIterator<Item = OptionValue>>(self, values: I) -> Self {
+        self.axis(values)
+    }
+
+    /// Changes the number of times each configuration is run (with distinct seeds) from the
+    /// default value of `1`.
+    pub fn repetitions(mut self, repetitions: usize) -> Self {
+        self.repetitions = repetitions;
+        self
+    }
+
+    /// Returns the total number of runs, which is the product of the number of values of every
+    /// axis and the number of repetitions.
+    pub fn len(&self) -> usize {
+        self.axes.iter().map(Vec::len).product::<usize>() * self.repetitions
+    }
+
+    /// Returns whether no runs would be performed (i.e. an axis has no values or the number of
+    /// repetitions is zero).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds an axis with the given values
+    fn axis<I: IntoIterator<Item = OptionValue>>(mut self, values: I) -> Self {
+        self.axes.push(values.into_iter().collect());
+        self
+    }
+
+    /// Returns every combination of the values of all axes, with the first axis varying the
+    /// slowest
+    fn combinations(&self) -> Vec<Vec<OptionValue>> {
+        self.axes
+            .iter()
+            .fold(vec![Vec::new()], |combinations, axis| {
+                combinations
+                    .iter()
+                    .flat_map(|combination| {
+                        axis.iter().map(move |value| {
+                            let mut combination = combination.clone();
+                            combination.push(*value);
+                            combination
+                        })
+                    })
+                    .collect()
+            })
+    }
+}
+
+impl Default for Variations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A single row of the table returned by [`sweep`], identifying the configuration of a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionsVariant {
+    /// The values that were applied to the base options, one for each axis of the
+    /// [`Variations`] in the order in which the axes were added.
+    pub values: Vec<OptionValue>,
+    /// The index of the repetition of this configuration, in `0..repetitions`.
+    pub repetition: usize,
+    /// The seed that the run used.
+    pub seed: u64,
+}
+
+impl OptionsVariant {
+    /// Returns the options that were used for this run given the base options passed to
+    /// [`sweep`]. Building a [`CMAES`][crate::CMAES] from them reproduces the run.
+    pub fn options(&self, base_options: &CMAESOptions) -> CMAESOptions {
+        self.values
+            .iter()
+            .fold(base_options.clone(), |options, value| value.apply(options))
+            .seed(self.seed)
+    }
+}
+
+/// Runs every combination of the option `variations` applied on top of `base_options` and returns
+/// a table of the configurations and their results, for studying the effect of the options of
+/// the algorithm itself (e.g. in hyperparameter studies).
+///
+/// Each run uses its own clone of `objective_function`, and the runs are distributed across the
+/// threads of the global [rayon][rayon] thread pool like in
+/// [`parallel_restarts`][crate::parallel_restarts]. The rows are returned in a fixed order: the
+/// first axis of `variations` varies the slowest and the repetitions of each configuration are
+/// adjacent.
+///
+/// Every run uses a distinct seed. The base seed is `base_options.seed` if set and random
+/// otherwise, and row `i` is run with the seed `base_seed + i` (wrapping on overflow). Each row
+/// can be reproduced with [`OptionsVariant::options`].
+///
+/// Returns `Err` without running anything if the options of any configuration are invalid.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::sweep::{OptionValue, Variations};
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// let function = |x: &DVector<f64>| x.magnitude();
+/// let options = CMAESOptions::new(vec![1.0; 4], 1.0).max_generations(200);
+/// let variations = Variations::new().cm([1.0, 0.5]).repetitions(3);
+///
+/// let table = cmaes::sweep(options, variations, function).unwrap();
+///
+/// assert_eq!(6, table.len());
+/// assert_eq!(vec![OptionValue::Cm(0.5)], table[3].0.values);
+/// for (variant, result) in &table {
+///     println!("{:?}: {} evals", variant.values, result.function_evals);
+/// }
+/// ```
+pub fn sweep<F: ObjectiveFunction + Clone + Send>(
+    base_options: CMAESOptions,
+    variations: Variations,
+    objective_function: F,
+) -> Result<Vec<(OptionsVariant, TerminationData)>, InvalidOptionsError> {
+    let seed = base_options.seed.unwrap_or_else(rand::random);
+
+    let variants = variations
+        .combinations()
+        .into_iter()
+        .flat_map(|values| {
+            (0..variations.repetitions).map(move |repetition| (values.clone(), repetition))
+        })
+        .enumerate()
+        .map(|(i, (values, repetition))| OptionsVariant {
+            values,
+            repetition,
+            seed: seed.wrapping_add(i as u64),
+        })
+        .collect::<Vec<_>>();
+
+    // Validate everything up front so that no work is wasted on an invalid configuration, and
+    // clone everything so that `F` does not need to be `Sync`
+    let instances = variants
+        .iter()
+        .map(|variant| {
+            let options = variant.options(&base_options);
+            options.validate()?;
+            Ok((options, objective_function.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let results = instances
+        .into_par_iter()
+        .map(|(options, objective_function)| {
+            options
+                .build(objective_function)
+                .map(|mut cmaes| cmaes.run())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(variants.into_iter().zip(results).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DVector;
+
+    use super::*;
+
+    #[test]
+    fn test_combinations() {
+        let variations = Variations::new()
+            .population_size([6, 8])
+            .cm([1.0, 0.5, 0.25]);
+        let combinations = variations.combinations();
+
+        assert_eq!(6, variations.len());
+        assert_eq!(6, combinations.len());
+        assert_eq!(
+            vec![OptionValue::PopulationSize(6), OptionValue::Cm(1.0)],
+            combinations[0]
+        );
+        assert_eq!(
+            vec![OptionValue::PopulationSize(6), OptionValue::Cm(0.5)],
+            combinations[1]
+        );
+        assert_eq!(
+            vec![OptionValue::PopulationSize(8), OptionValue::Cm(0.25)],
+            combinations[5]
+        );
+
+        assert_eq!(
+            vec![Vec::<OptionValue>::new()],
+            Variations::new().combinations()
+        );
+        assert!(Variations::new().cm([]).is_empty());
+        assert!(Variations::new().repetitions(0).is_empty());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let function = |x: &DVector<f64>| (x.magnitude() - 1.0).abs();
+        let options = CMAESOptions::new(vec![2.0; 3], 1.0)
+            .max_generations(20)
+            .seed(10);
+        let variations = Variations::new()
+            .population_size([6, 10])
+            .weights([Weights::Negative, Weights::Positive])
+            .repetitions(2);
+        let table = sweep(options.clone(), variations, function).unwrap();
+
+        assert_eq!(8, table.len());
+        for (i, (variant, result)) in table.iter().enumerate() {
+            assert_eq!(10 + i as u64, variant.seed);
+            assert_eq!(i % 2, variant.repetition);
+
+            // Each run is reproducible from its variant
+            let run_options = variant.options(&options);
+            assert_eq!(if i < 4 { 6 } else { 10 }, run_options.population_size);
+            let expected = run_options.build(function).unwrap().run();
+            assert_eq!(
+                expected.overall_best.unwrap().value,
+                result.overall_best.as_ref().unwrap().value
+            );
+        }
+        assert_eq!(
+            vec![
+                OptionValue::PopulationSize(6),
+                OptionValue::Weights(Weights::Positive)
+            ],
+            table[2].0.values
+        );
+
+        // Invalid configurations are rejected before running anything
+        assert!(matches!(
+            sweep(options, Variations::new().cm([0.5, 2.0]), function),
+            Err(InvalidOptionsError::Cm),
+        ));
+    }
+}
@@ -1,14 +1,12 @@
 //! Algorithm termination handling. See [`TerminationReason`] for full documentation.
 
-use statrs::statistics::{Data, Median};
-
 use std::collections::VecDeque;
 use std::fmt::{self, Debug};
 use std::time::Instant;
 
 use crate::history::History;
 use crate::parameters::Parameters;
-use crate::sampling::EvaluatedPoint;
+use crate::sampling::{self, EvaluatedPoint};
 use crate::state::State;
 use crate::{utils, MAX_HISTORY_LENGTH};
 
@@ -23,12 +21,21 @@ pub enum TerminationReason {
     MaxGenerations,
     /// The algorithm has been running for longer than the time limit.
     MaxTime,
+    /// The overall best function value has not improved for longer than `improvement_timeout`
+    /// (measured in wall-clock time, starting from the creation of the [`CMAES`][crate::CMAES]
+    /// until the first generation is evaluated). Complements generation-based criteria such as
+    /// `TolStagnation` when the cost of each generation varies greatly. Disabled by default.
+    ImprovementTimeout,
+    /// The predicate passed to [`CMAES::run_until`][crate::CMAES::run_until] returned `true`.
+    Predicate,
     /// The target objective function value has been reached.
     FunTarget,
     /// The range of function values of the latest generation and the range of the best function
     /// values of many consecutive generations lie below `tol_fun`. Indicates that the function
     /// value has stopped changing significantly and that the function value spread of each
-    /// generation is equally insignificant.
+    /// generation is equally insignificant. If `adaptive_tol_fun` is enabled, the threshold is
+    /// raised to `1e-10` times the largest improvement achieved earlier in the run (see
+    /// [`CMAESOptions::adaptive_tol_fun`][crate::CMAESOptions::adaptive_tol_fun]).
     TolFun,
     /// Like `TolFun`, but the range is `tol_fun_rel * (first_median - best_median)` (i.e. it is
     /// relative to the overall improvement in the median objective function value).
@@ -39,6 +46,61 @@ pub enum TerminationReason {
     /// The standard deviation of the distribution is smaller than `tol_x` in every coordinate and
     /// the mean has not moved much recently. Indicates that the algorithm has converged.
     TolX,
+    /// The largest standard deviation of the distribution (`sigma * sqrt(max eigenvalue)`, the
+    /// length of the longest principal axis of the distribution's one-sigma ellipsoid) is smaller
+    /// than `target_sigma`. This means that samples deviate from the mean by less than
+    /// `target_sigma` along every direction (not only along the coordinate axes), so the solution
+    /// is known to roughly the requested absolute precision. Unlike `TolX`, this ignores the
+    /// evolution path. Disabled by default.
+    TargetSigma,
+    /// The population diversity (see
+    /// [`CMAES::population_diversity`][crate::CMAES::population_diversity]) has been below
+    /// `tol_diversity` for `tol_diversity_generations` consecutive generations. Indicates that the
+    /// population has collapsed onto a small region, which may be a sign of premature convergence
+    /// on deceptive functions (in which case a restart strategy from [`restart`][crate::restart]
+    /// may be useful). Disabled by default because computing the diversity takes O(lambda^2 * N)
+    /// time per generation.
+    TolDiversity,
+    /// Every individual of the generation has had exactly the same objective function value for
+    /// `flat_function_generations` consecutive generations. Indicates that the distribution is on
+    /// a plateau of the objective function (or that the objective function ignores its input), in
+    /// which case the algorithm has no information to follow and would otherwise wander aimlessly.
+    /// Unlike `TolFun`, this requires the range of values to be exactly zero and does not look at
+    /// the history of best values. Disabled by default.
+    FlatFunction,
+    /// Every individual of the first generation evaluated by the [`CMAES`][crate::CMAES] has the
+    /// same objective function value. This almost always means that the objective function
+    /// accidentally ignores its input (e.g. returns a constant), so the run is aborted before the
+    /// evaluation budget is wasted. Only checked if
+    /// [`CMAESOptions::validate_objective`][crate::CMAESOptions::validate_objective] is enabled.
+    ConstantFunction,
+    /// The rate at which the best function value improves per function evaluation has fallen below
+    /// `tol_improvement_rate`. The rate is estimated as the absolute slope of a least squares
+    /// linear fit of the overall best function value versus the number of function evaluations
+    /// performed by the end of each generation, over the same window of recent generations used by
+    /// `TolFun` (`10 + 30 * N / lambda` generations). Function values are divided by `value_scale`
+    /// first. Unlike `TolFun`, this accounts for how many evaluations were spent, so it can detect
+    /// slow progress on smooth problems whose function values still vary significantly between
+    /// generations. Disabled by default.
+    TolImprovementRate,
+    /// The step size has decayed more than `max_sigma_decay_ratio` times faster than the best
+    /// function value improved, indicating that the distribution is collapsing without finding
+    /// better solutions (e.g. onto a saddle point or the edge of a plateau). Both rates are
+    /// averaged over the same window of recent generations used by `TolFun` (`10 + 30 * N /
+    /// lambda` generations) on a logarithmic scale, and the improvement is relative to the
+    /// magnitude of the best function value, so the criterion assumes that the optimal function
+    /// value is near zero (see [`CMAES::sigma_decay_ratio`][crate::CMAES::sigma_decay_ratio] for
+    /// details). Unlike `TolX`, it does not depend on the absolute size of the distribution, so
+    /// it can catch a collapse long before `TolX` is reached. Disabled by default.
+    PrematureConvergence,
+    /// The mean of the distribution (in the space of the objective function, as returned by
+    /// [`CMAES::mean`][crate::CMAES::mean]) lies outside of the region set by
+    /// [`CMAESOptions::mean_region`][crate::CMAESOptions::mean_region]. A guardrail for aborting
+    /// runs that wander into regions that are known to be unreasonable. Unlike
+    /// [`Bounds`][crate::Bounds], which keep the sampled points feasible, the region does not
+    /// affect sampling at all; it is only checked after each update of the mean. Disabled by
+    /// default.
+    MeanOutOfRegion,
     /// The best and median function values have not improved over the past 20% of all generations,
     /// clamped to the range `[tol_stagnation, MAX_HISTORY_LENGTH]`. Setting `tol_stagnation` to be
     /// greater than `MAX_HISTORY_LENGTH` effectively disables this termination criterion.
@@ -55,8 +117,14 @@ pub enum TerminationReason {
     NoEffectCoord,
     /// The condition number of the covariance matrix exceeds `tol_condition_cov` or is non-normal.
     TolConditionCov,
-    /// The objective function has returned an invalid value (`NAN` or `-NAN`).
+    /// The objective function has returned an invalid value (`NAN` or `-NAN`). Depending on the
+    /// [`InvalidPolicy`], invalid values may be skipped instead, in which case this is only
+    /// returned if they persist.
     InvalidFunctionValue,
+    /// The mean, step size, or covariance matrix of the distribution became non-finite (`NAN` or
+    /// infinite) during an update, e.g. because the objective function returned extreme values.
+    /// [`TerminationDetail::component`] contains the component that was non-finite.
+    NonFiniteState,
     /// The covariance matrix is not positive definite. If this is returned frequently, it probably
     /// indicates a bug in the library and can be reported [here][0]. Using
     /// [`Weights::Positive`][crate::parameters::Weights::Positive] should prevent this entirely in
@@ -72,29 +140,280 @@ impl fmt::Display for TerminationReason {
     }
 }
 
+/// A component of the distribution state that was found to be non-finite (see
+/// [`NonFiniteState`][TerminationReason::NonFiniteState]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StateComponent {
+    /// The distribution mean.
+    Mean,
+    /// The step size.
+    Sigma,
+    /// The covariance matrix.
+    Covariance,
+}
+
+impl fmt::Display for StateComponent {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        Debug::fmt(self, fmt)
+    }
+}
+
+/// How the [`FunTarget`][TerminationReason::FunTarget] termination criterion is handled. Set using
+/// [`CMAESOptions::fun_target_mode`][crate::CMAESOptions::fun_target_mode]. The default value is
+/// `Terminate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetMode {
+    /// Terminate with `FunTarget` as soon as the target value is reached.
+    #[default]
+    Terminate,
+    /// Only record the generation in which the target value was first reached (see
+    /// [`CMAES::fun_target_generation`][crate::CMAES::fun_target_generation]) and continue
+    /// running until another termination criterion is met. Useful for benchmarking how fast the
+    /// function value keeps decreasing after reaching the target.
+    ///
+    /// Because `FunTarget` is never reported in this mode, a
+    /// [`Restarter`][crate::restart::Restarter] whose default options use it will not stop early
+    /// when the target is reached and will instead keep restarting until its other limits (e.g.
+    /// `max_runs` or `max_function_evals`) are reached.
+    RecordOnly,
+}
+
+/// How invalid (`NAN`) objective function values are handled. Set using
+/// [`CMAESOptions::invalid_value_policy`][crate::CMAESOptions::invalid_value_policy]. The default
+/// value is `Terminate`.
+///
+/// When an invalid value is skipped, the individual is treated like a point that a
+/// [`PartialObjectiveFunction`][crate::PartialObjectiveFunction] could not evaluate: it is ranked
+/// last with the worst possible value and does not contribute to the update of the distribution.
+/// A generation in which every individual has an invalid value always terminates the run with
+/// [`InvalidFunctionValue`][TerminationReason::InvalidFunctionValue], because it provides no
+/// information to update the distribution with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvalidPolicy {
+    /// Terminate with `InvalidFunctionValue` as soon as an invalid value is returned.
+    #[default]
+    Terminate,
+    /// Skip individuals with invalid values and continue running.
+    SkipIndividual,
+    /// Skip individuals with invalid values, but terminate with `InvalidFunctionValue` once
+    /// invalid values have been returned in this many consecutive generations. Must be at least
+    /// `1`.
+    TerminateAfter(usize),
+}
+
+/// A bundle of termination tolerances and limits that can be applied at once using
+/// [`CMAESOptions::termination_preset`][crate::CMAESOptions::termination_preset]. Each preset
+/// trades off the accuracy of the result against the number of function evaluations used.
+///
+/// | Option | `Fast` | `Balanced` | `Thorough` |
+/// |---|---|---|---|
+/// | `tol_fun` | `1e-8` | `1e-12` | `1e-15` |
+/// | `tol_fun_hist` | `1e-10` | `1e-12` | `1e-15` |
+/// | `relative_tol_x` | `1e-8` | `1e-12` | `1e-15` |
+/// | `tol_stagnation` | default / 2 | default | default * 2 |
+/// | `tol_x_up` | `1e4` | `1e8` | `1e12` |
+/// | `tol_condition_cov` | `1e12` | `1e14` | `1e15` |
+/// | `max_generations` | base / 10 | base | base * 10 |
+///
+/// where `tol_x` is reset so that it defaults to `relative_tol_x * initial_step_size`, the default
+/// `tol_stagnation` is `100 + 100 * dimensions^1.5 / population_size`, and the base generation
+/// limit is `100 + 150 * (dimensions + 3)^2 / sqrt(population_size)`.
+/// `Balanced` therefore uses the default tolerances, only adding a generation limit. The
+/// `tol_condition_cov` of `Thorough` stays below `1e16`, beyond which the smallest eigenvalues of
+/// the covariance matrix are lost to rounding errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TerminationPreset {
+    /// Looser tolerances and a small generation limit for quick, approximate results.
+    Fast,
+    /// The default tolerances with a moderate generation limit.
+    Balanced,
+    /// Tighter tolerances and a large generation limit for results that are as accurate as
+    /// possible.
+    Thorough,
+}
+
+impl TerminationPreset {
+    /// Returns the `(tol_fun, tol_fun_hist, relative tol_x, tol_x_up, tol_condition_cov)` values
+    /// used by this preset
+    pub(crate) fn tolerances(self) -> (f64, f64, f64, f64, f64) {
+        match self {
+            TerminationPreset::Fast => (1e-8, 1e-10, 1e-8, 1e4, 1e12),
+            TerminationPreset::Balanced => (1e-12, 1e-12, 1e-12, 1e8, 1e14),
+            TerminationPreset::Thorough => (1e-15, 1e-15, 1e-15, 1e12, 1e15),
+        }
+    }
+
+    /// Returns the `tol_stagnation` value used by this preset for the given problem dimension and
+    /// population size
+    pub(crate) fn tol_stagnation(self, dim: usize, lambda: usize) -> usize {
+        let default = get_default_tol_stagnation_option(dim, lambda);
+        match self {
+            TerminationPreset::Fast => default / 2,
+            TerminationPreset::Balanced => default,
+            TerminationPreset::Thorough => default * 2,
+        }
+    }
+
+    /// Returns the `max_generations` value used by this preset for the given problem dimension
+    /// and population size
+    pub(crate) fn max_generations(self, dim: usize, lambda: usize) -> usize {
+        let base = 100 + (150.0 * (dim as f64 + 3.0).powi(2) / (lambda as f64).sqrt()) as usize;
+        match self {
+            TerminationPreset::Fast => base / 10,
+            TerminationPreset::Balanced => base,
+            TerminationPreset::Thorough => base * 10,
+        }
+    }
+}
+
+/// A [`TerminationReason`] along with the measured quantity that met the termination criterion
+/// and the threshold it was compared against. Obtained from
+/// [`TerminationData::details`][crate::TerminationData::details].
+///
+/// The quantities are reported in the same units as the corresponding option:
+///
+/// - `MaxFunctionEvals`, `MaxGenerations`: the number of function evaluations or generations
+/// - `MaxTime`: the elapsed time in seconds
+/// - `ImprovementTimeout`: the time since the last improvement in seconds
+/// - `FunTarget`: the best function value of the latest generation
+/// - `TolFun`, `TolFunRel`, `TolFunHist`: the largest of the checked function value ranges (the
+///   threshold of `TolFunRel` is the absolute range, `tol_fun_rel` times the overall improvement)
+/// - `TolImprovementRate`: the estimated improvement per function evaluation
+/// - `PrematureConvergence`: the ratio between the decay rate of the step size and the
+///   improvement rate of the best function value
+/// - `TolX`: the largest of the checked standard deviations and evolution path components
+/// - `TargetSigma`: the largest standard deviation of the distribution
+/// - `TolDiversity`: the number of consecutive low-diversity generations
+/// - `FlatFunction`: the number of consecutive generations with equal function values
+/// - `MeanOutOfRegion`: the total distance of the mean outside of the region, summed over all
+///   coordinates (the threshold is `0.0`)
+/// - `TolConditionCov`: the condition number of the covariance matrix
+/// - `TolXUp`: the increase of the largest standard deviation relative to the initial step size
+///
+/// The remaining reasons do not compare a quantity against a threshold, so `measured` and
+/// `threshold` are `None` for them.
+///
+/// For `NoEffectCoord` and `NoEffectAxis`, `indices` contains the coordinates (in ascending order)
+/// or the principal axis (the index of its eigenvalue in
+/// [`CMAES::eigenvalues`][crate::CMAES::eigenvalues]) along which adding a step no longer changes
+/// the mean. For `MeanOutOfRegion`, it contains the coordinates in which the mean lies outside of
+/// the region. It is empty for all other reasons.
+///
+/// For `NonFiniteState`, `component` contains the part of the state that was non-finite. It is
+/// `None` for all other reasons.
+///
+/// Fields may be added in future versions, so this struct can only be constructed by this crate.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TerminationDetail {
+    /// The termination criterion that was met.
+    pub reason: TerminationReason,
+    /// The measured quantity that met the criterion.
+    pub measured: Option<f64>,
+    /// The threshold that the measured quantity was compared against.
+    pub threshold: Option<f64>,
+    /// The coordinates or principal axes that met the criterion.
+    pub indices: Vec<usize>,
+    /// The component of the state that was non-finite.
+    pub component: Option<StateComponent>,
+}
+
+impl TerminationDetail {
+    /// Returns a new `TerminationDetail` for a criterion that compares a measured quantity
+    /// against a threshold
+    fn new(reason: TerminationReason, measured: f64, threshold: f64) -> Self {
+        Self {
+            reason,
+            measured: Some(measured),
+            threshold: Some(threshold),
+            indices: Vec::new(),
+            component: None,
+        }
+    }
+
+    /// Returns a new `TerminationDetail` for a criterion that is met along the given coordinates
+    /// or principal axes
+    fn with_indices(reason: TerminationReason, indices: Vec<usize>) -> Self {
+        Self {
+            indices,
+            ..reason.into()
+        }
+    }
+
+    /// Returns a new `TerminationDetail` for `NonFiniteState` with the given non-finite component
+    pub(crate) fn non_finite_state(component: StateComponent) -> Self {
+        Self {
+            component: Some(component),
+            ..TerminationReason::NonFiniteState.into()
+        }
+    }
+}
+
+impl fmt::Display for TerminationDetail {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match (self.measured, self.threshold) {
+            (Some(measured), Some(threshold)) => write!(
+                fmt,
+                "{} (measured: {:e}, threshold: {:e})",
+                self.reason, measured, threshold,
+            ),
+            _ if !self.indices.is_empty() => {
+                write!(fmt, "{} (indices: {:?})", self.reason, self.indices)
+            }
+            _ if self.component.is_some() => write!(
+                fmt,
+                "{} (component: {})",
+                self.reason,
+                self.component.unwrap(),
+            ),
+            _ => write!(fmt, "{}", self.reason),
+        }
+    }
+}
+
+impl From<TerminationReason> for TerminationDetail {
+    fn from(reason: TerminationReason) -> Self {
+        Self {
+            reason,
+            measured: None,
+            threshold: None,
+            indices: Vec::new(),
+            component: None,
+        }
+    }
+}
+
 /// Stores parameters of the termination check
 #[cfg_attr(test, derive(Clone))]
 pub(crate) struct TerminationCheck<'a> {
     pub current_function_evals: usize,
     /// The time at which the `CMAES` was created
     pub time_created: Instant,
+    /// The time at which the overall best individual last improved
+    pub time_last_improvement: Instant,
     pub parameters: &'a Parameters,
     pub state: &'a State,
     pub history: &'a History,
     /// The current generation of individuals
     pub individuals: &'a [EvaluatedPoint],
+    /// Whether `individuals` is the first generation evaluated by this `CMAES` (which is not
+    /// generation `1` if the `CMAES` was created from an existing state)
+    pub first_generation: bool,
 }
 
 impl<'a> TerminationCheck<'a> {
-    /// Checks whether any termination criteria are met based on the stored parameters
-    pub(crate) fn check_termination_criteria(self) -> Vec<TerminationReason> {
+    /// Checks whether any termination criteria are met based on the stored parameters, returning
+    /// the measured quantities and thresholds of the criteria that are met
+    pub(crate) fn check_termination_details(self) -> Vec<TerminationDetail> {
         let mut result = Vec::new();
 
         let mode = self.parameters.mode();
         let dim = self.parameters.dim();
         let lambda = self.parameters.lambda();
         let initial_sigma = self.parameters.initial_sigma();
-        let tol_fun = self.parameters.tol_fun();
         let tol_fun_rel_option = self.parameters.tol_fun_rel();
         let tol_fun_hist = self.parameters.tol_fun_hist();
         let tol_x = self.parameters.tol_x();
@@ -112,37 +431,68 @@ impl<'a> TerminationCheck<'a> {
         // Check TerminationReason::MaxFunctionEvals
         if let Some(max_function_evals) = self.parameters.max_function_evals() {
             if self.current_function_evals >= max_function_evals {
-                result.push(TerminationReason::MaxFunctionEvals);
+                result.push(TerminationDetail::new(
+                    TerminationReason::MaxFunctionEvals,
+                    self.current_function_evals as f64,
+                    max_function_evals as f64,
+                ));
             }
         }
 
         // Check TerminationReason::MaxGenerations
         if let Some(max_generations) = self.parameters.max_generations() {
             if self.state.generation() >= max_generations {
-                result.push(TerminationReason::MaxGenerations);
+                result.push(TerminationDetail::new(
+                    TerminationReason::MaxGenerations,
+                    self.state.generation() as f64,
+                    max_generations as f64,
+                ));
             }
         }
 
         // Check TerminationReason::MaxTime
         if let Some(max_time) = self.parameters.max_time() {
-            if self.time_created.elapsed() >= max_time {
-                result.push(TerminationReason::MaxTime);
+            let elapsed = self.time_created.elapsed();
+            if elapsed >= max_time {
+                result.push(TerminationDetail::new(
+                    TerminationReason::MaxTime,
+                    elapsed.as_secs_f64(),
+                    max_time.as_secs_f64(),
+                ));
+            }
+        }
+
+        // Check TerminationReason::ImprovementTimeout
+        if let Some(improvement_timeout) = self.parameters.improvement_timeout() {
+            let elapsed = self.time_last_improvement.elapsed();
+            if elapsed >= improvement_timeout {
+                result.push(TerminationDetail::new(
+                    TerminationReason::ImprovementTimeout,
+                    elapsed.as_secs_f64(),
+                    improvement_timeout.as_secs_f64(),
+                ));
             }
         }
 
         // Check TerminationReason::FunTarget
-        if let Some(fun_target) = self.parameters.fun_target() {
-            if self
-                .individuals
-                .iter()
-                .any(|ind| mode.is_better(ind.value(), fun_target))
-            {
-                result.push(TerminationReason::FunTarget);
+        let fun_target = match self.parameters.fun_target_mode() {
+            TargetMode::Terminate => self.parameters.fun_target(),
+            TargetMode::RecordOnly => None,
+        };
+        if let Some(fun_target) = fun_target {
+            // The individuals are sorted, so the first one is the best
+            let best_value = self.individuals[0].value();
+            if mode.is_better(best_value, fun_target) {
+                result.push(TerminationDetail::new(
+                    TerminationReason::FunTarget,
+                    best_value,
+                    fun_target,
+                ));
             }
         }
 
         // Check TerminationReason::TolFun*
-        let past_generations_a = 10 + (30.0 * dim as f64 / lambda as f64).ceil() as usize;
+        let past_generations_a = tol_fun_window(dim, lambda);
 
         if self.history.best_function_values().len() >= past_generations_a {
             let range_history = utils::range(
@@ -154,14 +504,33 @@ impl<'a> TerminationCheck<'a> {
             )
             .unwrap();
 
-            let range_current = utils::range(self.individuals.iter().map(|p| p.value())).unwrap();
+            // A generation without rankable individuals has no range, so it never satisfies the
+            // criteria below
+            let range_current = utils::range(
+                sampling::rankable_individuals(self.individuals)
+                    .iter()
+                    .map(|p| p.value() / self.parameters.value_scale()),
+            )
+            .unwrap_or(f64::INFINITY);
+
+            let range_max = range_history.max(range_current);
 
             if range_history < tol_fun_hist {
-                result.push(TerminationReason::TolFunHist);
+                result.push(TerminationDetail::new(
+                    TerminationReason::TolFunHist,
+                    range_history,
+                    tol_fun_hist,
+                ));
             }
 
+            let tol_fun = effective_tol_fun(self.parameters, self.history);
+
             if range_history < tol_fun && range_current < tol_fun {
-                result.push(TerminationReason::TolFun);
+                result.push(TerminationDetail::new(
+                    TerminationReason::TolFun,
+                    range_max,
+                    tol_fun,
+                ));
             }
 
             if let (Some(first_median_value), Some(best_median_value)) = (
@@ -172,7 +541,46 @@ impl<'a> TerminationCheck<'a> {
                     tol_fun_rel_option * (first_median_value - best_median_value).abs();
 
                 if range_history < tol_fun_rel_range && range_current < tol_fun_rel_range {
-                    result.push(TerminationReason::TolFunRel);
+                    result.push(TerminationDetail::new(
+                        TerminationReason::TolFunRel,
+                        range_max,
+                        tol_fun_rel_range,
+                    ));
+                }
+            }
+
+            // Check TerminationReason::TolImprovementRate
+            if let Some(tol_improvement_rate) = self.parameters.tol_improvement_rate() {
+                // Fit the overall best value of each generation in the window against the
+                // function evals recorded for it
+                let points = self
+                    .history
+                    .best_value_evals()
+                    .iter()
+                    .take(past_generations_a)
+                    .map(|&(function_evals, best_value)| (function_evals as f64, best_value));
+
+                if let Some(slope) = utils::linear_fit_slope(points) {
+                    if slope.abs() < tol_improvement_rate {
+                        result.push(TerminationDetail::new(
+                            TerminationReason::TolImprovementRate,
+                            slope.abs(),
+                            tol_improvement_rate,
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Check TerminationReason::PrematureConvergence
+        if let Some(max_sigma_decay_ratio) = self.parameters.max_sigma_decay_ratio() {
+            if let Some(ratio) = self.history.sigma_decay_ratio(mode, past_generations_a) {
+                if ratio > max_sigma_decay_ratio {
+                    result.push(TerminationDetail::new(
+                        TerminationReason::PrematureConvergence,
+                        ratio,
+                        max_sigma_decay_ratio,
+                    ));
                 }
             }
         }
@@ -181,14 +589,94 @@ impl<'a> TerminationCheck<'a> {
         if (0..dim).all(|i| (sigma * cov[(i, i)]).abs() < tol_x)
             && path_c.iter().all(|x| (sigma * *x).abs() < tol_x)
         {
-            result.push(TerminationReason::TolX);
+            let max_deviation = (0..dim)
+                .map(|i| (sigma * cov[(i, i)]).abs().max((sigma * path_c[i]).abs()))
+                .fold(0.0, f64::max);
+            result.push(TerminationDetail::new(
+                TerminationReason::TolX,
+                max_deviation,
+                tol_x,
+            ));
+        }
+
+        // Check TerminationReason::TargetSigma
+        let max_standard_deviation = sigma
+            * cov_sqrt_eigenvalues
+                .diagonal()
+                .iter()
+                .max_by(|a, b| utils::partial_cmp(**a, **b))
+                .unwrap();
+
+        if let Some(target_sigma) = self.parameters.target_sigma() {
+            if max_standard_deviation < target_sigma {
+                result.push(TerminationDetail::new(
+                    TerminationReason::TargetSigma,
+                    max_standard_deviation,
+                    target_sigma,
+                ));
+            }
+        }
+
+        // Check TerminationReason::TolDiversity
+        if self.parameters.tol_diversity().is_some()
+            && self.history.low_diversity_generations()
+                >= self.parameters.tol_diversity_generations()
+        {
+            result.push(TerminationDetail::new(
+                TerminationReason::TolDiversity,
+                self.history.low_diversity_generations() as f64,
+                self.parameters.tol_diversity_generations() as f64,
+            ));
+        }
+
+        // Check TerminationReason::FlatFunction
+        if let Some(flat_function_generations) = self.parameters.flat_function_generations() {
+            if self.history.flat_generations() >= flat_function_generations {
+                result.push(TerminationDetail::new(
+                    TerminationReason::FlatFunction,
+                    self.history.flat_generations() as f64,
+                    flat_function_generations as f64,
+                ));
+            }
+        }
+
+        // Check TerminationReason::ConstantFunction
+        if self.parameters.validate_objective() && self.first_generation {
+            // A single rankable individual is not enough to tell whether the function is constant
+            let rankable = sampling::rankable_individuals(self.individuals);
+            if rankable.len() > 1 && rankable.iter().all(|p| p.value() == rankable[0].value()) {
+                result.push(TerminationReason::ConstantFunction.into());
+            }
+        }
+
+        // Check TerminationReason::MeanOutOfRegion
+        if let Some((lower, upper)) = self.parameters.mean_region() {
+            let mapped_mean = self.parameters.point_mapping().map(mean).0;
+            let outside = (0..dim)
+                .filter(|&i| !(lower[i]..=upper[i]).contains(&mapped_mean[i]))
+                .collect::<Vec<_>>();
+
+            if !outside.is_empty() {
+                let distance = outside
+                    .iter()
+                    .map(|&i| (lower[i] - mapped_mean[i]).max(mapped_mean[i] - upper[i]))
+                    .sum();
+                result.push(TerminationDetail {
+                    indices: outside,
+                    ..TerminationDetail::new(TerminationReason::MeanOutOfRegion, distance, 0.0)
+                });
+            }
         }
 
         // Check TerminationReason::TolConditionCov
         let cond = self.state.axis_ratio().powi(2);
 
         if !cond.is_normal() || cond > tol_condition_cov {
-            result.push(TerminationReason::TolConditionCov);
+            result.push(TerminationDetail::new(
+                TerminationReason::TolConditionCov,
+                cond,
+                tol_condition_cov,
+            ));
         }
 
         // Check TerminationReason::NoEffectAxis
@@ -201,12 +689,21 @@ impl<'a> TerminationCheck<'a> {
             * cov_eigenvectors.column(index_to_check);
 
         if mean == &(mean + no_effect_axis_check) {
-            result.push(TerminationReason::NoEffectAxis);
+            result.push(TerminationDetail::with_indices(
+                TerminationReason::NoEffectAxis,
+                vec![index_to_check],
+            ));
         }
 
         // Check TerminationReason::NoEffectCoord
-        if (0..dim).any(|i| mean[i] == mean[i] + 0.2 * sigma * cov[(i, i)]) {
-            result.push(TerminationReason::NoEffectCoord);
+        let no_effect_coords = (0..dim)
+            .filter(|&i| mean[i] == mean[i] + 0.2 * sigma * cov[(i, i)])
+            .collect::<Vec<_>>();
+        if !no_effect_coords.is_empty() {
+            result.push(TerminationDetail::with_indices(
+                TerminationReason::NoEffectCoord,
+                no_effect_coords,
+            ));
         }
 
         // Check TerminationReason::TolStagnation
@@ -227,7 +724,7 @@ impl<'a> TerminationCheck<'a> {
 
                     // Most recent `subrange_length `values within the past
                     // `tol_stagnation_generations` generations
-                    let first_values = values
+                    let mut first_values = values
                         .iter()
                         .take(tol_stagnation_generations)
                         .take(subrange_length)
@@ -236,43 +733,178 @@ impl<'a> TerminationCheck<'a> {
 
                     // Least recent `subrange_length` values within the past
                     // tol_stagnation_generations` generations
-                    let last_values = values
+                    let mut last_values = values
                         .iter()
                         .take(tol_stagnation_generations)
                         .skip(tol_stagnation_generations - subrange_length)
                         .cloned()
                         .collect::<Vec<_>>();
 
+                    // The medians are `NAN` (so the values do not count as regressed) if
+                    // `subrange_length` is `0`
+                    first_values.sort_unstable_by(f64::total_cmp);
+                    last_values.sort_unstable_by(f64::total_cmp);
                     mode.is_better(
-                        Data::new(last_values).median(),
-                        Data::new(first_values).median(),
+                        utils::quantile(&last_values, 0.5),
+                        utils::quantile(&first_values, 0.5),
                     )
                 };
 
                 if did_values_regress(self.history.best_function_values())
                     && did_values_regress(self.history.median_function_values())
                 {
-                    result.push(TerminationReason::TolStagnation);
+                    result.push(TerminationReason::TolStagnation.into());
                 }
             }
         }
 
         // Check TerminationReason::TolXUp
-        let max_standard_deviation = sigma
-            * cov_sqrt_eigenvalues
-                .diagonal()
-                .iter()
-                .max_by(|a, b| utils::partial_cmp(**a, **b))
-                .unwrap();
+        let sigma_up = max_standard_deviation / initial_sigma;
+        if sigma_up > tol_x_up {
+            result.push(TerminationDetail::new(
+                TerminationReason::TolXUp,
+                sigma_up,
+                tol_x_up,
+            ));
+        }
 
-        if max_standard_deviation / initial_sigma > tol_x_up {
-            result.push(TerminationReason::TolXUp);
+        // Suppress the convergence-based criteria until enough generations have been completed
+        if self.state.generation() < self.parameters.min_generations() {
+            result.retain(|detail| !is_convergence_criterion(detail.reason));
         }
 
         result
     }
 }
 
+/// Returns whether `reason` is one of the criteria suppressed by
+/// [`CMAESOptions::min_generations`][crate::CMAESOptions::min_generations]
+fn is_convergence_criterion(reason: TerminationReason) -> bool {
+    use TerminationReason::*;
+
+    matches!(
+        reason,
+        TolFun
+            | TolFunRel
+            | TolFunHist
+            | TolX
+            | TolDiversity
+            | TolImprovementRate
+            | PrematureConvergence
+            | TolStagnation
+            | TolXUp
+            | TolConditionCov
+            | NoEffectAxis
+            | NoEffectCoord
+    )
+}
+
+/// Returns the termination criteria that are enabled by `parameters` along with the thresholds in
+/// effect for `history` (see [`CMAES::active_criteria`][crate::CMAES::active_criteria])
+pub(crate) fn active_criteria(
+    parameters: &Parameters,
+    history: &History,
+) -> Vec<(TerminationReason, Option<f64>)> {
+    use TerminationReason::*;
+
+    let fun_target = match parameters.fun_target_mode() {
+        TargetMode::Terminate => parameters.fun_target(),
+        TargetMode::RecordOnly => None,
+    };
+    let tol_stagnation = parameters.tol_stagnation();
+    // TolStagnation is never checked if its lower bound exceeds the history length
+    let tol_stagnation = if tol_stagnation <= MAX_HISTORY_LENGTH {
+        Some(tol_stagnation as f64)
+    } else {
+        None
+    };
+
+    let criteria = [
+        (
+            MaxFunctionEvals,
+            parameters
+                .max_function_evals()
+                .map(|evals| Some(evals as f64)),
+        ),
+        (
+            MaxGenerations,
+            parameters
+                .max_generations()
+                .map(|generations| Some(generations as f64)),
+        ),
+        (
+            MaxTime,
+            parameters.max_time().map(|time| Some(time.as_secs_f64())),
+        ),
+        (
+            ImprovementTimeout,
+            parameters
+                .improvement_timeout()
+                .map(|timeout| Some(timeout.as_secs_f64())),
+        ),
+        (FunTarget, fun_target.map(Some)),
+        (TolFun, Some(Some(effective_tol_fun(parameters, history)))),
+        (TolFunRel, Some(Some(parameters.tol_fun_rel()))),
+        (TolFunHist, Some(Some(parameters.tol_fun_hist()))),
+        (TolX, Some(Some(parameters.tol_x()))),
+        (TargetSigma, parameters.target_sigma().map(Some)),
+        (TolDiversity, parameters.tol_diversity().map(Some)),
+        (
+            FlatFunction,
+            parameters
+                .flat_function_generations()
+                .map(|generations| Some(generations as f64)),
+        ),
+        (
+            ConstantFunction,
+            parameters.validate_objective().then_some(None),
+        ),
+        (
+            TolImprovementRate,
+            parameters.tol_improvement_rate().map(Some),
+        ),
+        (
+            PrematureConvergence,
+            parameters.max_sigma_decay_ratio().map(Some),
+        ),
+        (MeanOutOfRegion, parameters.mean_region().map(|_| None)),
+        (TolStagnation, tol_stagnation.map(Some)),
+        (TolXUp, Some(Some(parameters.tol_x_up()))),
+        (NoEffectAxis, Some(None)),
+        (NoEffectCoord, Some(None)),
+        (TolConditionCov, Some(Some(parameters.tol_condition_cov()))),
+        (InvalidFunctionValue, Some(None)),
+        (NonFiniteState, Some(None)),
+        (PosDefCov, Some(None)),
+    ];
+
+    criteria
+        .into_iter()
+        .filter_map(|(reason, threshold)| threshold.map(|threshold| (reason, threshold)))
+        .collect()
+}
+
+/// The factor by which the peak improvement is multiplied to get the threshold of `TolFun` when
+/// `adaptive_tol_fun` is enabled
+const ADAPTIVE_TOL_FUN_FACTOR: f64 = 1e-10;
+
+/// Returns the number of recent generations whose best function values are compared by the
+/// `TolFun` family of criteria
+pub(crate) fn tol_fun_window(dim: usize, lambda: usize) -> usize {
+    10 + (30.0 * dim as f64 / lambda as f64).ceil() as usize
+}
+
+/// Returns the threshold of `TolFun` in effect for `history`, which is raised above `tol_fun` if
+/// `adaptive_tol_fun` is enabled
+fn effective_tol_fun(parameters: &Parameters, history: &History) -> f64 {
+    let tol_fun = parameters.tol_fun();
+    if parameters.adaptive_tol_fun() {
+        tol_fun.max(ADAPTIVE_TOL_FUN_FACTOR * history.peak_improvement())
+    } else {
+        tol_fun
+    }
+}
+
 /// Returns the default value for the `tol_stagnation` option (which is the lower bound for
 /// `TolStagnation`)
 pub(crate) fn get_default_tol_stagnation_option(dim: usize, lambda: usize) -> usize {
@@ -306,12 +938,31 @@ mod tests {
     use std::time::Duration;
 
     use super::*;
+    use crate::history::TrajectoryEntry;
     use crate::matrix::SquareMatrix;
     use crate::mode::Mode;
     use crate::parameters::TerminationParameters;
+    use crate::sampling::PointMapping;
     use crate::state::State;
     use crate::CMAESOptions;
 
+    #[test]
+    fn test_termination_detail_display() {
+        assert_eq!(
+            "TolConditionCov (measured: 2e14, threshold: 1e14)",
+            TerminationDetail::new(TerminationReason::TolConditionCov, 2e14, 1e14).to_string(),
+        );
+        assert_eq!(
+            "NoEffectAxis",
+            TerminationDetail::from(TerminationReason::NoEffectAxis).to_string(),
+        );
+        assert_eq!(
+            "NoEffectCoord (indices: [0, 2])",
+            TerminationDetail::with_indices(TerminationReason::NoEffectCoord, vec![0, 2])
+                .to_string(),
+        );
+    }
+
     #[test]
     fn test_get_default_tol_stagnation_option() {
         assert_eq!(180, get_default_tol_stagnation_option(4, 10));
@@ -340,6 +991,7 @@ mod tests {
                     DVector::zeros(DIM),
                     &DVector::zeros(DIM),
                     1.0,
+                    PointMapping::default(),
                     &mut |_: &DVector<f64>| function_value,
                 )
                 .unwrap()
@@ -364,6 +1016,37 @@ mod tests {
         H: FnOnce(&mut History),
         P: FnOnce(&mut TerminationParameters),
         R: FnOnce(Vec<TerminationReason>),
+    {
+        run_termination_detail_test(
+            mode,
+            time_created,
+            initial_sigma,
+            current_function_evals,
+            current_generation_function_value,
+            map_state,
+            map_history,
+            map_parameters,
+            |details| map_results(details.into_iter().map(|d| d.reason).collect()),
+        );
+    }
+
+    /// Like `run_termination_test`, but passes the full termination details to `map_results`
+    #[allow(clippy::too_many_arguments)]
+    fn run_termination_detail_test<S, H, P, R>(
+        mode: Mode,
+        time_created: Option<Instant>,
+        initial_sigma: Option<f64>,
+        current_function_evals: usize,
+        current_generation_function_value: f64,
+        map_state: S,
+        map_history: H,
+        map_parameters: P,
+        map_results: R,
+    ) where
+        S: FnOnce(&mut State),
+        H: FnOnce(&mut History),
+        P: FnOnce(&mut TerminationParameters),
+        R: FnOnce(Vec<TerminationDetail>),
     {
         let initial_sigma = initial_sigma.unwrap_or(DEFAULT_INITIAL_SIGMA);
         let initial_mean = DVector::from(vec![0.0; DIM]);
@@ -371,7 +1054,7 @@ mod tests {
         let mut state = State::new(initial_mean.clone(), initial_sigma);
         map_state(&mut state);
 
-        let mut history = History::new();
+        let mut history = History::new(1.0);
         map_history(&mut history);
 
         // Take most default parameters from default CMAESOptions
@@ -384,15 +1067,19 @@ mod tests {
 
         let parameters = Parameters::new(&options, 0, termination_parameters);
 
+        let time_created = time_created.unwrap_or_else(Instant::now);
         let results = TerminationCheck {
             current_function_evals,
-            time_created: time_created.unwrap_or_else(Instant::now),
+            time_created,
+            time_last_improvement: time_created,
             parameters: &parameters,
             state: &state,
             history: &history,
             individuals: &get_dummy_generation(current_generation_function_value),
+            // Like in a `CMAES` created from options
+            first_generation: state.generation() == 1,
         }
-        .check_termination_criteria();
+        .check_termination_details();
 
         map_results(results);
     }
@@ -493,6 +1180,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_check_termination_criteria_improvement_timeout() {
+        // The creation time is used as the time of the last improvement here
+        let time_last_improvement = Instant::now() - Duration::from_secs(5);
+
+        run_termination_test(
+            Mode::Minimize,
+            Some(time_last_improvement),
+            None,
+            400,
+            1.0,
+            |_| {},
+            |_| {},
+            |params| params.improvement_timeout = Some(Duration::from_secs(4)),
+            |results| assert_eq!(results, &[TerminationReason::ImprovementTimeout]),
+        );
+
+        run_termination_test(
+            Mode::Minimize,
+            Some(time_last_improvement),
+            None,
+            400,
+            1.0,
+            |_| {},
+            |_| {},
+            |params| params.improvement_timeout = Some(Duration::from_secs(10)),
+            |results| assert!(results.is_empty()),
+        );
+    }
+
     #[test]
     fn test_check_termination_criteria_fun_target() {
         // A best function value better than the threshold produces FunTarget
@@ -548,6 +1265,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_check_termination_criteria_adaptive_tol_fun() {
+        // A small range of recent function values compared to an earlier large improvement
+        // produces TolFun only if adaptive_tol_fun is enabled
+        let historical_best = 1.0;
+        // Outside the range of TolFun/TolFunHist
+        let most_recent_best = historical_best - 1e-9;
+        let set_history = |history: &mut History| {
+            history
+                .mut_best_function_values()
+                .extend(vec![historical_best; 100]);
+            history
+                .mut_best_function_values()
+                .push_front(most_recent_best);
+            *history.mut_peak_improvement() = 1e3;
+        };
+
+        for (adaptive_tol_fun, expected) in
+            [(false, &[][..]), (true, &[TerminationReason::TolFun][..])]
+        {
+            run_termination_test(
+                Mode::Minimize,
+                None,
+                None,
+                400,
+                most_recent_best,
+                |_| {},
+                set_history,
+                |params| params.adaptive_tol_fun = adaptive_tol_fun,
+                |results| assert_eq!(results, expected),
+            );
+        }
+    }
+
     #[test]
     fn test_check_termination_criteria_tol_fun_rel() {
         // Small ranges of current and historical function values relative to the overall
@@ -580,6 +1331,159 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_check_termination_criteria_fun_target_record_only() {
+        // FunTarget is not produced in RecordOnly mode
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            |_| {},
+            |params| {
+                params.fun_target = Some(2.0);
+                params.fun_target_mode = TargetMode::RecordOnly;
+            },
+            |results| assert!(results.is_empty()),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_tol_improvement_rate() {
+        // The best value steadily improves by 1e-3 per generation, using the given number of
+        // function evals per generation and recording at most the given number of generations
+        let map_history = |evals_per_generation: usize, max_recorded_generations: Option<usize>| {
+            move |history: &mut History| {
+                history
+                    .mut_best_function_values()
+                    .extend((0..100).map(|i| 1.0 + i as f64 * 1e-3));
+                for i in 0..100 {
+                    let entry = TrajectoryEntry {
+                        generation: i + 1,
+                        function_evals: (i + 1) * evals_per_generation,
+                        best_value: 1.0 + (99 - i) as f64 * 1e-3,
+                        median_value: 2.0,
+                        sigma: 1.0,
+                        axis_ratio: 1.0,
+                        mean_norm: 1.0,
+                    };
+                    history.update_trajectory(entry, max_recorded_generations);
+                }
+            }
+        };
+
+        // An improvement rate below the threshold produces TolImprovementRate
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history(400, None),
+            |params| params.tol_improvement_rate = Some(1e-3),
+            |results| assert_eq!(results, &[TerminationReason::TolImprovementRate]),
+        );
+
+        // An improvement rate above the threshold does not
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history(400, None),
+            |params| params.tol_improvement_rate = Some(1e-6),
+            |results| assert!(results.is_empty()),
+        );
+
+        // The recorded function evals are used instead of the population size (e.g. when each
+        // candidate is evaluated multiple times)
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history(400_000, None),
+            |params| params.tol_improvement_rate = Some(1e-6),
+            |results| assert_eq!(results, &[TerminationReason::TolImprovementRate]),
+        );
+
+        // The window is not limited by the number of recorded generations
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history(400, Some(1)),
+            |params| params.tol_improvement_rate = Some(1e-3),
+            |results| assert_eq!(results, &[TerminationReason::TolImprovementRate]),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_premature_convergence() {
+        // The step size halves every 10 generations
+        let map_history = |best_values: Vec<f64>| {
+            move |history: &mut History| {
+                history
+                    .mut_sigma_values()
+                    .extend((0..100).map(|i| 0.5f64.powf(-i as f64 / 10.0)));
+                history.mut_best_function_values().extend(best_values);
+            }
+        };
+
+        // The best value barely improves, so the step size decays much faster
+        run_termination_detail_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history((0..100).map(|i| 1.0 + i as f64 * 1e-6).collect()),
+            |params| params.max_sigma_decay_ratio = Some(10.0),
+            |details| {
+                assert_eq!(1, details.len());
+                assert_eq!(TerminationReason::PrematureConvergence, details[0].reason);
+                assert!(details[0].measured.unwrap() > 1000.0);
+            },
+        );
+
+        // The best value shrinks with the square of the step size like on a converging run
+        run_termination_detail_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history((0..100).map(|i| 0.25f64.powf(-i as f64 / 10.0)).collect()),
+            |params| params.max_sigma_decay_ratio = Some(10.0),
+            |details| assert!(details.is_empty()),
+        );
+
+        // Worse values do not count as progress
+        run_termination_test(
+            Mode::Maximize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history((0..100).map(|i| 0.25f64.powf(-i as f64 / 10.0)).collect()),
+            |params| params.max_sigma_decay_ratio = Some(10.0),
+            |results| assert!(results.contains(&TerminationReason::PrematureConvergence)),
+        );
+    }
+
     #[test]
     fn test_check_termination_criteria_tol_fun_hist() {
         // A small range of historical best values produces TolFunHist
@@ -623,6 +1527,266 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_check_termination_criteria_min_generations() {
+        // TolX is suppressed before `min_generations` generations have been completed
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| {
+                *state.mut_sigma() = 1e-13;
+                *state.mut_generation() = 9;
+            },
+            |_| {},
+            |params| {
+                params.min_generations = 10;
+                params.max_function_evals = Some(400);
+            },
+            |results| assert_eq!(results, &[TerminationReason::MaxFunctionEvals]),
+        );
+
+        // And applies again afterwards
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| {
+                *state.mut_sigma() = 1e-13;
+                *state.mut_generation() = 10;
+            },
+            |_| {},
+            |params| params.min_generations = 10,
+            |results| assert_eq!(results, &[TerminationReason::TolX]),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_tol_diversity() {
+        let map_history = |history: &mut History| {
+            for _ in 0..5 {
+                history.update_diversity(true);
+            }
+        };
+
+        // Enough consecutive low-diversity generations produce TolDiversity
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history,
+            |params| {
+                params.tol_diversity = Some(1e-3);
+                params.tol_diversity_generations = 5;
+            },
+            |results| assert_eq!(results, &[TerminationReason::TolDiversity]),
+        );
+
+        // Too few consecutive generations do not
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history,
+            |params| {
+                params.tol_diversity = Some(1e-3);
+                params.tol_diversity_generations = 6;
+            },
+            |results| assert!(results.is_empty()),
+        );
+
+        // The criterion is disabled by default
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history,
+            |params| params.tol_diversity_generations = 1,
+            |results| assert!(results.is_empty()),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_constant_function() {
+        // A first generation with equal function values produces ConstantFunction
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| *state.mut_generation() = 1,
+            |_| {},
+            |params| params.validate_objective = true,
+            |results| assert_eq!(results, &[TerminationReason::ConstantFunction]),
+        );
+
+        // Later generations are not checked
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| *state.mut_generation() = 2,
+            |_| {},
+            |params| params.validate_objective = true,
+            |results| assert!(results.is_empty()),
+        );
+
+        // The check is disabled by default
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| *state.mut_generation() = 1,
+            |_| {},
+            |_| {},
+            |results| assert!(results.is_empty()),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_flat_function() {
+        let map_history = |history: &mut History| *history.mut_flat_generations() = 5;
+
+        // Enough consecutive flat generations produce FlatFunction
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history,
+            |params| params.flat_function_generations = Some(5),
+            |results| assert_eq!(results, &[TerminationReason::FlatFunction]),
+        );
+
+        // Too few consecutive generations do not
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history,
+            |params| params.flat_function_generations = Some(6),
+            |results| assert!(results.is_empty()),
+        );
+
+        // The criterion is disabled by default
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |_| {},
+            map_history,
+            |_| {},
+            |results| assert!(results.is_empty()),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_mean_out_of_region() {
+        let map_parameters = |params: &mut TerminationParameters| {
+            params.mean_region = Some((vec![-1.0, -1.0].into(), vec![1.0, 0.5].into()))
+        };
+
+        // A mean outside of the region produces MeanOutOfRegion
+        run_termination_detail_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| *state.mut_mean() = vec![0.0, 2.0].into(),
+            |_| {},
+            map_parameters,
+            |results| {
+                assert_eq!(
+                    results,
+                    &[TerminationDetail {
+                        indices: vec![1],
+                        ..TerminationDetail::new(TerminationReason::MeanOutOfRegion, 1.5, 0.0)
+                    }],
+                )
+            },
+        );
+
+        // A mean on the boundary does not
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            |state| *state.mut_mean() = vec![-1.0, 0.5].into(),
+            |_| {},
+            map_parameters,
+            |results| assert!(results.is_empty()),
+        );
+    }
+
+    #[test]
+    fn test_check_termination_criteria_target_sigma() {
+        // The largest standard deviation is sigma * sqrt(max eigenvalue) = 1e-3 * 2.0
+        let map_state = |state: &mut State| {
+            *state.mut_sigma() = 1e-3;
+            state
+                .mut_cov()
+                .set_cov(
+                    SquareMatrix::from_iterator(DIM, DIM, [1.0, 0.0, 0.0, 4.0]),
+                    true,
+                )
+                .unwrap();
+        };
+
+        // A largest standard deviation below the target produces TargetSigma
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            map_state,
+            |_| {},
+            |params| params.target_sigma = Some(2.5e-3),
+            |results| assert_eq!(results, &[TerminationReason::TargetSigma]),
+        );
+
+        // Only the largest standard deviation is considered
+        run_termination_test(
+            Mode::Minimize,
+            None,
+            None,
+            400,
+            1.0,
+            map_state,
+            |_| {},
+            |params| params.target_sigma = Some(1.5e-3),
+            |results| assert!(results.is_empty()),
+        );
+    }
+
     fn check_termination_criteria_tol_stagnation(mode: Mode, historical_values: [f64; 4]) {
         // Median/best function values that worsen or don't improve over many generations produces
         // TolStagnation
@@ -690,7 +1854,7 @@ mod tests {
         // NoEffectAxis
         let mut terminated_count = 0;
         for g in 0..DIM {
-            run_termination_test(
+            run_termination_detail_test(
                 Mode::Minimize,
                 None,
                 None,
@@ -718,7 +1882,13 @@ mod tests {
                 |_| {},
                 |results| {
                     if !results.is_empty() {
-                        assert_eq!(results, &[TerminationReason::NoEffectAxis]);
+                        assert_eq!(
+                            results,
+                            &[TerminationDetail::with_indices(
+                                TerminationReason::NoEffectAxis,
+                                vec![g],
+                            )],
+                        );
                         terminated_count += 1;
                     }
                 },
@@ -733,7 +1903,7 @@ mod tests {
     fn test_check_termination_criteria_no_effect_coord() {
         // A lack of available precision along a coordinate axis in the distribution produces
         // NoEffectCoord
-        run_termination_test(
+        run_termination_detail_test(
             Mode::Minimize,
             None,
             None,
@@ -752,7 +1922,15 @@ mod tests {
             },
             |_| {},
             |_| {},
-            |results| assert_eq!(results, &[TerminationReason::NoEffectCoord]),
+            |results| {
+                assert_eq!(
+                    results,
+                    &[TerminationDetail::with_indices(
+                        TerminationReason::NoEffectCoord,
+                        vec![1],
+                    )],
+                )
+            },
         );
     }
 
@@ -0,0 +1,322 @@
+//! Types related to optimizing in a transformed coordinate space or with transformed objective
+//! function values. See [`CoordinateTransform`] and [`FitnessTransform`] for full documentation.
+
+use nalgebra::{DMatrix, DVector};
+
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+/// A function mapping a point from one coordinate space to another
+type TransformFn = dyn Fn(&DVector<f64>) -> DVector<f64> + Send + Sync;
+
+/// A function mapping a raw objective function value to a transformed value
+type FitnessFn = dyn Fn(f64) -> f64 + Send + Sync;
+
+/// A pair of functions for converting between the natural coordinates of the objective function
+/// and the encoded coordinates that the algorithm operates in. Set using
+/// [`CMAESOptions::transform`][crate::CMAESOptions::transform].
+///
+/// The algorithm samples points and adapts the distribution (mean, step size, and covariance
+/// matrix) entirely in the encoded space. Each point is decoded before being passed to the
+/// objective function, and all points and means reported by [`CMAES`][crate::CMAES] and
+/// [`TerminationData`][crate::TerminationData] are decoded as well. The initial mean is given in
+/// natural coordinates and encoded once when the `CMAES` is created, while the initial step size is
+/// measured in the encoded space.
+///
+/// Because the covariance matrix adapts in the encoded space, the transform determines which
+/// changes the algorithm considers to be of equal size. This is usually what is wanted: for
+/// example, encoding a positive quantity with `ln` and decoding it with `exp` makes the algorithm
+/// search over orders of magnitude instead of absolute differences.
+///
+/// `decode(encode(x))` should equal `x` for any point `x` that the objective function accepts.
+/// If [`Bounds`][crate::Bounds] are also set, they are applied in the encoded space before
+/// decoding.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::{CMAESOptions, DVector};
+///
+/// // Both parameters must be positive and span several orders of magnitude
+/// let function = |x: &DVector<f64>| (x[0] / 1e-3).ln().powi(2) + (x[1] / 1e4).ln().powi(2);
+/// let mut cmaes_state = CMAESOptions::new(vec![1.0; 2], 1.0)
+///     .transform(|x| x.map(f64::ln), |x| x.map(f64::exp))
+///     .build(function)
+///     .unwrap();
+///
+/// let result = cmaes_state.run();
+/// let best = result.overall_best.unwrap().point;
+///
+/// assert!((best[0] / 1e-3 - 1.0).abs() < 1e-3);
+/// assert!((best[1] / 1e4 - 1.0).abs() < 1e-3);
+/// ```
+#[derive(Clone)]
+pub struct CoordinateTransform {
+    /// Maps natural coordinates to encoded coordinates
+    encode: Arc<TransformFn>,
+    /// Maps encoded coordinates to natural coordinates
+    decode: Arc<TransformFn>,
+}
+
+impl CoordinateTransform {
+    /// Creates a new `CoordinateTransform` from the functions mapping natural coordinates to
+    /// encoded coordinates and back.
+    pub fn new<E, D>(encode: E, decode: D) -> Self
+    where
+        E: Fn(&DVector<f64>) -> DVector<f64> + Send + Sync + 'static,
+        D: Fn(&DVector<f64>) -> DVector<f64> + Send + Sync + 'static,
+    {
+        Self {
+            encode: Arc::new(encode),
+            decode: Arc::new(decode),
+        }
+    }
+
+    /// Creates an affine `CoordinateTransform` that encodes `x` as `matrix * x + offset` and
+    /// decodes `y` as `matrix^-1 * (y - offset)`. Returns `None` if `matrix` is not square, is not
+    /// invertible, or does not have the same number of rows as `offset`.
+    ///
+    /// CMA-ES is invariant to affine transformations of the search space: with this transform,
+    /// optimizing `g(x) = f(matrix * x + offset)` from the initial mean `x0` produces exactly the
+    /// same sequence of generations (up to floating point error) as optimizing `f` from
+    /// `matrix * x0 + offset` without a transform and the same seed and options, with every point
+    /// mapped through the decoding function. Equivalently, the distribution in natural
+    /// coordinates starts with the covariance matrix `sigma^2 * matrix^-1 * matrix^-T`, which is
+    /// the initial covariance matrix that compensates for the transformation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cmaes::{CMAESOptions, CoordinateTransform, DVector};
+    /// use nalgebra::DMatrix;
+    ///
+    /// // A badly scaled and rotated quadratic
+    /// let matrix = DMatrix::from_row_slice(2, 2, &[1e3, 1e3, -1.0, 1.0]);
+    /// let offset = DVector::from(vec![1.0, 2.0]);
+    /// let (matrix_clone, offset_clone) = (matrix.clone(), offset.clone());
+    /// let function = move |x: &DVector<f64>| (&matrix_clone * x + &offset_clone).norm_squared();
+    ///
+    /// let mut cmaes_state = CMAESOptions::new(vec![0.0; 2], 1.0)
+    ///     .coordinate_transform(CoordinateTransform::affine(matrix, offset).unwrap())
+    ///     .build(function)
+    ///     .unwrap();
+    ///
+    /// let result = cmaes_state.run();
+    ///
+    /// assert!(result.overall_best.unwrap().value < 1e-10);
+    /// ```
+    pub fn affine(matrix: DMatrix<f64>, offset: DVector<f64>) -> Option<Self> {
+        if !matrix.is_square() || matrix.nrows() != offset.len() {
+            return None;
+        }
+        let inverse = matrix.clone().try_inverse()?;
+        let encode_offset = offset.clone();
+
+        Some(Self::new(
+            move |x| &matrix * x + &encode_offset,
+            move |y| &inverse * (y - &offset),
+        ))
+    }
+
+    /// Maps a point in natural coordinates to encoded coordinates.
+    pub fn encode(&self, point: &DVector<f64>) -> DVector<f64> {
+        (self.encode)(point)
+    }
+
+    /// Maps a point in encoded coordinates to natural coordinates.
+    pub fn decode(&self, point: &DVector<f64>) -> DVector<f64> {
+        (self.decode)(point)
+    }
+}
+
+impl Debug for CoordinateTransform {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("CoordinateTransform")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A function applied to the raw objective function values of each generation before they are
+/// used to compute value-dependent quantities. Set using
+/// [`CMAESOptions::fitness_transform`][crate::CMAESOptions::fitness_transform].
+///
+/// Ranking is already invariant to order-preserving transformations, but
+/// [`RecombinationWeighting::Value`][crate::RecombinationWeighting::Value] weights the selected
+/// individuals by their raw function values, so a single outlier (e.g. a very noisy evaluation)
+/// can take almost all of the weight. A bounded transform such as [`clip`][Self::clip] or
+/// [`sigmoid`][Self::sigmoid] limits the influence of such outliers.
+///
+/// Transformed values are only used for the value-based recombination weights (the transform has
+/// no effect with [`RecombinationWeighting::Rank`][crate::RecombinationWeighting::Rank]).
+/// Everything else sees the raw values: ranking the individuals, the function value histories and
+/// termination criteria, the best individuals, and all values reported to the user (printing,
+/// plots, callbacks, and [`TerminationData`][crate::TerminationData]).
+///
+/// The function should be non-decreasing so that the transformed values do not contradict the
+/// ranking, and it should return finite values for all finite inputs.
+///
+/// # Examples
+///
+/// ```
+/// use cmaes::{CMAESOptions, DVector, FitnessTransform, RecombinationWeighting};
+///
+/// let function = |x: &DVector<f64>| x.magnitude();
+/// let mut cmaes_state = CMAESOptions::new(vec![1.0; 4], 1.0)
+///     .recombination_weighting(RecombinationWeighting::Value)
+///     .fitness_transform(FitnessTransform::clip(0.0, 10.0))
+///     .build(function)
+///     .unwrap();
+///
+/// let result = cmaes_state.run();
+///
+/// assert!(result.overall_best.unwrap().value < 1e-6);
+/// ```
+#[derive(Clone)]
+pub struct FitnessTransform {
+    /// Maps raw values to transformed values
+    function: Arc<FitnessFn>,
+}
+
+impl FitnessTransform {
+    /// Creates a new `FitnessTransform` from a function mapping raw objective function values to
+    /// transformed values.
+    pub fn new<T>(function: T) -> Self
+    where
+        T: Fn(f64) -> f64 + Send + Sync + 'static,
+    {
+        Self {
+            function: Arc::new(function),
+        }
+    }
+
+    /// Returns a `FitnessTransform` that clips values into the window `[lower, upper]`.
+    pub fn clip(lower: f64, upper: f64) -> Self {
+        Self::new(move |value| value.max(lower).min(upper))
+    }
+
+    /// Returns a `FitnessTransform` that maps values through the logistic function
+    /// `1 / (1 + exp(-(value - center) / scale))`, which is bounded to `(0, 1)`. Values within a
+    /// few multiples of `scale` of `center` are mapped almost linearly, while more extreme values
+    /// are compressed. `scale` should be positive.
+    pub fn sigmoid(center: f64, scale: f64) -> Self {
+        Self::new(move |value| 1.0 / (1.0 + (-(value - center) / scale).exp()))
+    }
+
+    /// Returns the transformed value of a raw objective function value.
+    pub fn apply(&self, value: f64) -> f64 {
+        (self.function)(value)
+    }
+}
+
+impl Debug for FitnessTransform {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("FitnessTransform").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CMAESOptions;
+
+    #[test]
+    fn test_coordinate_transform() {
+        let transform = CoordinateTransform::new(|x| x.map(f64::ln), |x| x.map(f64::exp));
+        let point = DVector::from(vec![1.0, 10.0]);
+
+        let encoded = transform.encode(&point);
+        assert_eq!(DVector::from(vec![0.0, 10f64.ln()]), encoded);
+
+        let decoded = transform.clone().decode(&encoded);
+        assert!((decoded - point).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn test_affine_transform() {
+        let matrix = DMatrix::from_row_slice(2, 2, &[2.0, 1.0, 0.0, 3.0]);
+        let offset = DVector::from(vec![1.0, -1.0]);
+        let transform = CoordinateTransform::affine(matrix, offset).unwrap();
+
+        let point = DVector::from(vec![1.0, 2.0]);
+        let encoded = transform.encode(&point);
+        assert_eq!(DVector::from(vec![5.0, 5.0]), encoded);
+        assert!((transform.decode(&encoded) - point).magnitude() < 1e-12);
+
+        // Singular or mismatched matrices are rejected
+        let singular = DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 2.0, 4.0]);
+        assert!(CoordinateTransform::affine(singular, DVector::zeros(2)).is_none());
+        assert!(CoordinateTransform::affine(DMatrix::identity(2, 3), DVector::zeros(2)).is_none());
+        assert!(CoordinateTransform::affine(DMatrix::identity(2, 2), DVector::zeros(3)).is_none());
+    }
+
+    #[test]
+    fn test_affine_invariance() {
+        let quadratic = |y: &DVector<f64>| {
+            y.iter()
+                .enumerate()
+                .map(|(i, yi)| 10f64.powi(i as i32) * yi * yi)
+                .sum::<f64>()
+        };
+        let matrix = DMatrix::from_row_slice(3, 3, &[1.0, 2.0, 0.0, 0.5, -1.0, 3.0, 0.0, 1.0, 4.0]);
+        let offset = DVector::from(vec![1.0, -2.0, 0.5]);
+        let initial_mean = DVector::from(vec![3.0, -1.0, 2.0]);
+        let options = CMAESOptions::new(initial_mean.clone(), 0.5).seed(17);
+
+        // Records the generations produced by running for at most 50 generations
+        let run = |options: CMAESOptions, function: &dyn Fn(&DVector<f64>) -> f64| {
+            let mut generations = Vec::new();
+            let mut cmaes = options.build(function).unwrap();
+            for _ in 0..50 {
+                let terminated = cmaes.next().is_some();
+                generations.push(
+                    cmaes
+                        .current_population()
+                        .iter()
+                        .map(|p| (p.point().clone(), p.value()))
+                        .collect::<Vec<_>>(),
+                );
+                if terminated {
+                    break;
+                }
+            }
+            generations
+        };
+
+        // Optimize `quadratic` directly and `quadratic(Ax + b)` through the affine transform,
+        // starting from the corresponding initial mean
+        let direct = run(options.clone(), &quadratic);
+        let transform = CoordinateTransform::affine(matrix.clone(), offset.clone()).unwrap();
+        let transformed_options = CMAESOptions {
+            initial_mean: transform.decode(&initial_mean),
+            ..options
+        }
+        .coordinate_transform(transform);
+        let transformed = run(transformed_options, &|x| {
+            quadratic(&(&matrix * x + &offset))
+        });
+
+        assert_eq!(direct.len(), transformed.len());
+        for (direct, transformed) in direct.iter().zip(&transformed) {
+            for ((y, direct_value), (x, transformed_value)) in direct.iter().zip(transformed) {
+                assert!((&matrix * x + &offset - y).magnitude() < 1e-9 * (1.0 + y.magnitude()));
+                assert!((direct_value - transformed_value).abs() < 1e-9 * (1.0 + direct_value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fitness_transform() {
+        let clip = FitnessTransform::clip(-1.0, 2.0);
+        assert_eq!(-1.0, clip.apply(-5.0));
+        assert_eq!(0.5, clip.apply(0.5));
+        assert_eq!(2.0, clip.apply(1e300));
+
+        let sigmoid = FitnessTransform::sigmoid(1.0, 2.0);
+        assert_eq!(0.5, sigmoid.apply(1.0));
+        assert!(sigmoid.apply(-1e300) >= 0.0 && sigmoid.apply(1e300) <= 1.0);
+        assert!(sigmoid.apply(0.0) < sigmoid.apply(2.0));
+
+        let custom = FitnessTransform::new(f64::sqrt).clone();
+        assert_eq!(3.0, custom.apply(9.0));
+    }
+}
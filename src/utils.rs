@@ -53,6 +53,44 @@ pub fn range<I: IntoIterator<Item = f64>>(iter: I) -> Option<f64> {
     Some(max - min)
 }
 
+/// Returns the slope of the least squares linear fit of the `(x, y)` points
+///
+/// Returns `None` if there are fewer than two distinct `x` values
+pub fn linear_fit_slope<I: IntoIterator<Item = (f64, f64)>>(points: I) -> Option<f64> {
+    let points = points.into_iter().collect::<Vec<_>>();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let covariance = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = points
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+
+    if variance > 0.0 {
+        Some(covariance / variance)
+    } else {
+        None
+    }
+}
+
+/// Returns the `q`-quantile of `sorted` (which must be sorted), linearly interpolating between the
+/// closest values, or `NAN` if `sorted` is empty
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (position - lower as f64) * (sorted[upper] - sorted[lower])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,6 +111,21 @@ mod tests {
         assert_eq!("        -inf", &format_num(-f64::INFINITY, 12));
     }
 
+    #[test]
+    fn test_linear_fit_slope() {
+        assert_eq!(None, linear_fit_slope([]));
+        assert_eq!(None, linear_fit_slope([(1.0, 2.0), (1.0, 3.0)]));
+        assert_eq!(
+            Some(-2.0),
+            linear_fit_slope([(0.0, 1.0), (1.0, -1.0), (2.0, -3.0)])
+        );
+        // Points that do not lie on a line are fit in the least squares sense
+        assert_eq!(
+            Some(0.8),
+            linear_fit_slope([(0.0, 0.5), (1.0, 0.5), (2.0, 2.5), (3.0, 2.5)])
+        );
+    }
+
     #[test]
     fn test_range() {
         assert_eq!(1.0, range([1.0, 1.25, 1.5, 1.75, 2.0]).unwrap());
@@ -82,4 +135,14 @@ mod tests {
         assert!(range([-1.0, 1.0, f64::NAN, 5.0]).is_none());
         assert!(range([]).is_none());
     }
+
+    #[test]
+    fn test_quantile() {
+        assert_eq!(3.0, quantile(&[3.0], 0.25));
+        assert_eq!(2.0, quantile(&[1.0, 2.0, 3.0], 0.5));
+        assert_eq!(1.5, quantile(&[1.0, 2.0, 3.0], 0.25));
+        assert_eq!(2.5, quantile(&[1.0, 2.0, 3.0, 4.0], 0.5));
+        assert_eq!(4.0, quantile(&[1.0, 2.0, 3.0, 4.0], 1.0));
+        assert!(quantile(&[], 0.5).is_nan());
+    }
 }
@@ -1,7 +1,8 @@
 //! General tests
 
-use assert_approx_eq::assert_approx_eq;
-use cmaes::{CMAESOptions, Mode, ObjectiveFunction, TerminationReason, Weights, CMAES};
+use cmaes::{
+    CMAESOptions, Mode, ObjectiveFunction, RecombinationWeighting, TerminationReason, Weights,
+};
 use nalgebra::DVector;
 
 use std::collections::HashMap;
@@ -101,6 +102,20 @@ fn test_sphere() {
     run_test_sphere(30, 1, 5730, 0, Weights::Negative);
 }
 
+#[test]
+fn test_value_weighting() {
+    let run_test_value_weighting = |function: fn(&DVector<f64>) -> f64, mode, max_avg_evals| {
+        let options = CMAESOptions::new(vec![0.1; 10], 0.1)
+            .recombination_weighting(RecombinationWeighting::Value);
+
+        run_test(function, mode, options, max_avg_evals, 0);
+    };
+
+    run_test_value_weighting(sphere, Mode::Minimize, 1650);
+    run_test_value_weighting(sphere, Mode::Maximize, 1670);
+    run_test_value_weighting(ellipsoid, Mode::Minimize, 4300);
+}
+
 #[test]
 fn test_ellipsoid() {
     let run_test_ellipsoid = |dim, pop_size_mult, max_avg_evals, max_failures, weights| {
@@ -198,8 +213,56 @@ fn test_cigar() {
     run_test_cigar(30, 1, 14500, 1, Weights::Negative);
 }
 
-/// For tests with consistent results
+// Checks that parallel evaluation and state updates produce bit-identical trajectories to the
+// serial path, regardless of how the work is scheduled across threads
+#[test]
+fn test_parallel_determinism() {
+    let options = CMAESOptions::new(vec![1.0; 8], 0.5)
+        .population_size(200)
+        .max_generations(100)
+        .seed(5);
+
+    let run = |parallel: bool| {
+        let mut cmaes_state = options
+            .clone()
+            .parallel_update(parallel)
+            .build(rosenbrock)
+            .unwrap();
+        let mut trajectory = Vec::new();
+        let result = loop {
+            let data = if parallel {
+                cmaes_state.next_parallel()
+            } else {
+                cmaes_state.next()
+            };
+            trajectory.push((
+                cmaes_state.mean().clone(),
+                cmaes_state.sigma(),
+                cmaes_state.covariance_matrix().clone(),
+                cmaes_state.current_best_individual().map(|x| x.value),
+            ));
+            if let Some(data) = data {
+                break data;
+            }
+        };
+        (trajectory, result)
+    };
+
+    let (serial_trajectory, serial_result) = run(false);
+    for _ in 0..3 {
+        let (parallel_trajectory, parallel_result) = run(true);
+        assert_eq!(serial_trajectory, parallel_trajectory);
+        assert_eq!(serial_result.reasons, parallel_result.reasons);
+        assert_eq!(serial_result.final_mean, parallel_result.final_mean);
+    }
+}
+
+/// For tests with consistent results (which depend on the samples drawn by the statrs sampler)
+#[cfg(feature = "statrs")]
 mod consistent {
+    use assert_approx_eq::assert_approx_eq;
+    use cmaes::CMAES;
+
     use super::*;
 
     // Must be updated after every change to the algorithm (after thorough testing)
@@ -339,8 +402,7 @@ mod consistent {
         fixed_seed(Mode::Minimize, true, false);
     }
 
-    // Check that parallel state updates don't affect the results too much (they do after some
-    // number of iterations, but the results shouldn't differ much at first)
+    // Check that parallel state updates don't affect the results
     #[test]
     fn test_fixed_seed_minimize_parallel_update() {
         fixed_seed(Mode::Minimize, true, true);
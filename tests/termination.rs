@@ -1,6 +1,6 @@
 //! Tests for certain termination criteria being reached
 
-use cmaes::{CMAESOptions, Mode, ObjectiveFunction, TerminationReason};
+use cmaes::{CMAESOptions, InvalidPolicy, Mode, ObjectiveFunction, TerminationReason};
 use nalgebra::DVector;
 
 use std::thread;
@@ -76,6 +76,21 @@ fn test_max_time() {
     );
 }
 
+#[test]
+fn test_improvement_timeout() {
+    // The function is constant, so only the first generation improves the best function value
+    let function = |_: &DVector<f64>| {
+        thread::sleep(Duration::from_millis(1));
+        1.0
+    };
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0).improvement_timeout(Duration::from_millis(5)),
+        |r| matches!(r, TerminationReason::ImprovementTimeout),
+        0,
+    );
+}
+
 #[test]
 fn test_fun_target() {
     // The function reaches `fun_target` more quickly than the algorithm converges
@@ -137,6 +152,117 @@ fn test_tol_x() {
     );
 }
 
+#[test]
+fn test_constant_function() {
+    // The function ignores its input, so the first generation is already flat
+    let function = |_: &DVector<f64>| 1.0;
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0).validate_objective(true),
+        |r| matches!(r, TerminationReason::ConstantFunction),
+        0,
+    );
+}
+
+#[test]
+fn test_invalid_value_policy() {
+    // The function cannot be evaluated near the initial mean, but invalid values stop occurring
+    // once the mean moves toward the optimum
+    let function = |x: &DVector<f64>| {
+        if x[0] > 6.0 {
+            f64::NAN
+        } else {
+            x.magnitude().powi(2)
+        }
+    };
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0)
+            .invalid_value_policy(InvalidPolicy::TerminateAfter(50)),
+        |r| !matches!(r, TerminationReason::InvalidFunctionValue),
+        0,
+    );
+}
+
+#[test]
+fn test_min_generations() {
+    // The function is constant, so `TolFun` would be reached long before `max_generations` if it
+    // were not suppressed
+    let function = |_: &DVector<f64>| 1.0;
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0)
+            .min_generations(100)
+            .max_generations(50),
+        |r| matches!(r, TerminationReason::MaxGenerations),
+        0,
+    );
+}
+
+#[test]
+fn test_tol_fun_value_scale() {
+    // The function values are too large for `tol_fun` to ever be reached without scaling
+    let function = |x: &DVector<f64>| 1e20 * (1.0 + x.magnitude().powi(2));
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0)
+            .value_scale(1e20)
+            .tol_fun_hist(0.0),
+        |r| matches!(r, TerminationReason::TolFun),
+        0,
+    );
+}
+
+#[test]
+fn test_target_sigma() {
+    // The distribution shrinks to the target scale long before the function value converges
+    let function = |x: &DVector<f64>| x.magnitude();
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0).target_sigma(1e-3),
+        |r| matches!(r, TerminationReason::TargetSigma),
+        0,
+    );
+}
+
+#[test]
+fn test_tol_diversity() {
+    // The population contracts onto the optimum long before the function value converges
+    let function = |x: &DVector<f64>| x.magnitude();
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0).tol_diversity(1e-3),
+        |r| matches!(r, TerminationReason::TolDiversity),
+        0,
+    );
+}
+
+#[test]
+fn test_tol_improvement_rate() {
+    // The improvement per function evaluation slows down long before the function value
+    // converges
+    let function = |x: &DVector<f64>| x.magnitude();
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0).tol_improvement_rate(1e-4),
+        |r| matches!(r, TerminationReason::TolImprovementRate),
+        0,
+    );
+}
+
+#[test]
+fn test_premature_convergence() {
+    // The function value converges to 1 instead of 0, so the best value stops improving relative
+    // to its magnitude while the step size keeps decaying
+    let function = |x: &DVector<f64>| x.magnitude_squared() + 1.0;
+    run_test(
+        function,
+        CMAESOptions::new(vec![5.0; 2], 1.0).max_sigma_decay_ratio(100.0),
+        |r| matches!(r, TerminationReason::PrematureConvergence),
+        0,
+    );
+}
+
 #[test]
 fn test_tol_fun_hist() {
     // The function bottoms out before convergence